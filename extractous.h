@@ -53,12 +53,105 @@
 
 #define ERR_OCR_FAILED -10
 
+#define ERR_BUFFER_TOO_SMALL -11
+
+#define ERR_MIME_NOT_ALLOWED -12
+
+#define ERR_TIMEOUT -13
+
+#define ERR_ABORTED -14
+
+#define ERR_METADATA_ABSENT -15
+
+#define ERR_NO_IMAGE -16
+
+#define ERR_INDEX_OUT_OF_RANGE -17
+
+#define ERR_UNSUPPORTED_FEATURE -18
+
+#define ERR_NOT_SEEKABLE -19
+
+#define ERR_BUSY -20
+
 #define CHARSET_UTF_8 0
 
 #define CHARSET_US_ASCII 1
 
 #define CHARSET_UTF_16BE 3
 
+#define OUTPUT_TEXT 0
+
+#define OUTPUT_XML 1
+
+#define HASH_SHA256 0
+
+#define HASH_BLAKE3 1
+
+#define NORM_NONE 0
+
+#define NORM_NFC 1
+
+#define NORM_NFD 2
+
+#define NORM_NFKC 3
+
+#define NORM_NFKD 4
+
+#define LINE_ENDING_PRESERVE 0
+
+#define LINE_ENDING_LF 1
+
+#define LINE_ENDING_CRLF 2
+
+#define BIDI_PRESERVE 0
+
+#define BIDI_LOGICAL 1
+
+#define BIDI_VISUAL 2
+
+#define CHAR_FILTER_DENY 0
+
+#define CHAR_FILTER_ALLOW 1
+
+#define EMPTY_NONE 0
+
+#define EMPTY_NO_TEXT_LAYER 1
+
+#define EMPTY_UNSUPPORTED_BODY 2
+
+#define EMPTY_OCR_DISABLED 3
+
+#define DOC_KIND_UNKNOWN 0
+
+#define DOC_KIND_DIGITAL 1
+
+#define DOC_KIND_SCANNED 2
+
+#define DOC_KIND_MIXED 3
+
+#define TRUNC_NONE 0
+
+#define TRUNC_MAX_CHARS 1
+
+#define TRUNC_MAX_BYTES 2
+
+#define TRUNC_NUL 3
+
+/*
+ The reading speed behind `CContentAnalysis::reading_minutes`.
+ */
+#define READING_WORDS_PER_MINUTE 200
+
+#define NUL_POLICY_ERROR 0
+
+#define NUL_POLICY_REPLACE 1
+
+#define NUL_POLICY_TRUNCATE 2
+
+#define LOG_INFO 1
+
+#define LOG_WARN 2
+
 #define PDF_OCR_STRATEGY_NO_OCR 0
 
 #define PDF_OCR_STRATEGY_OCR_ONLY 1
@@ -102,6 +195,263 @@ typedef struct CStreamReader {
   uint8_t _private[0];
 } CStreamReader;
 
+typedef struct CStreamCursor {
+  uint8_t _private[0];
+} CStreamCursor;
+
+/*
+ A borrowed, read-only segment of a scatter/gather input.
+ */
+typedef struct CIoSlice {
+  /*
+   Pointer to the first byte of the segment
+   */
+  const uint8_t *ptr;
+  /*
+   The number of bytes in the segment
+   */
+  size_t len;
+} CIoSlice;
+
+typedef struct CCancelToken {
+  uint8_t _private[0];
+} CCancelToken;
+
+/*
+ The content of one document within a segmented extraction.
+ */
+typedef struct CSegment {
+  /*
+   The document's file or resource name, or NULL if it has none
+   */
+  char *name;
+  /*
+   The document's text content
+   */
+  char *content;
+  /*
+   The document's metadata
+   */
+  struct CMetadata *metadata;
+} CSegment;
+
+/*
+ The text of one page of a document.
+ */
+typedef struct CPageResult {
+  /*
+   The 1-based page number
+   */
+  int page;
+  /*
+   The page's text content
+   */
+  char *content;
+  /*
+   The document's metadata
+   */
+  struct CMetadata *metadata;
+} CPageResult;
+
+/*
+ An entry of a document's outline, such as a PDF bookmark or a heading.
+ */
+typedef struct COutlineEntry {
+  /*
+   The entry's title
+   */
+  char *title;
+  /*
+   The 1-based nesting depth of a bookmark, or the rank of a heading
+   */
+  int level;
+  /*
+   The 1-based page the entry is on, or 0 if it is not known
+   */
+  int page;
+} COutlineEntry;
+
+/*
+ A table found in a document, as a grid of cell strings.
+ */
+typedef struct CTable {
+  /*
+   The heading before the table, such as a spreadsheet's sheet name, or
+   NULL if it has none
+   */
+  char *name;
+  /*
+   Row-major array of `rows * columns` null-terminated cell strings
+   */
+  char **cells;
+  /*
+   The number of rows
+   */
+  size_t rows;
+  /*
+   The number of columns, the length of the longest row
+   */
+  size_t columns;
+} CTable;
+
+/*
+ An inclusive range of Unicode code points.
+ */
+typedef struct CCharRange {
+  uint32_t start;
+  uint32_t end;
+} CCharRange;
+
+/*
+ Statistics of extracted text.
+ */
+typedef struct CContentAnalysis {
+  /*
+   The number of runs of non-whitespace characters
+   */
+  size_t word_count;
+  /*
+   The number of Unicode scalar values
+   */
+  size_t char_count;
+  /*
+   The number of sentences, counting trailing text without final
+   punctuation as one
+   */
+  size_t sentence_count;
+  /*
+   `word_count` divided by `READING_WORDS_PER_MINUTE`
+   */
+  double reading_minutes;
+} CContentAnalysis;
+
+/*
+ A run of text with its bounding box on the page, in points from the
+ page's top-left corner.
+ */
+typedef struct CTextRun {
+  /*
+   The run's text
+   */
+  char *text;
+  /*
+   The 1-based page number
+   */
+  int page;
+  double x;
+  double y;
+  double width;
+  double height;
+} CTextRun;
+
+/*
+ Properties of a PDF document.
+ */
+typedef struct CPdfInfo {
+  /*
+   The number of pages, or -1 if unknown
+   */
+  int page_count;
+  /*
+   The PDF version, such as `1.7`, or NULL if unknown
+   */
+  char *pdf_version;
+  /*
+   1 if the document is encrypted, 0 otherwise
+   */
+  int encrypted;
+  /*
+   1 if the document is linearized for fast web view, 0 otherwise
+   */
+  int linearized;
+} CPdfInfo;
+
+/*
+ An image embedded in a document and referenced from its extracted HTML.
+ */
+typedef struct CImage {
+  /*
+   The reference used in the HTML's `src` attributes, such as `cid:image0`
+   */
+  char *cid;
+  /*
+   The image's file name within the document
+   */
+  char *name;
+  /*
+   The image's MIME type
+   */
+  char *mime;
+  /*
+   The image bytes
+   */
+  uint8_t *data;
+  /*
+   The number of bytes in `data`
+   */
+  size_t len;
+} CImage;
+
+/*
+ Called once per metadata entry with its key and value, which are only
+ valid for the duration of the call. Returning anything but `ERR_OK` stops
+ the enumeration.
+ */
+typedef int (*CMetadataCallback)(void *ctx, const char *key, const char *value);
+
+/*
+ Resizes a block of the caller's memory with the contract of C `realloc`:
+ called with NULL to allocate, and returning NULL if the block cannot be
+ grown, in which case the old block is left as it was.
+ */
+typedef void *(*CReallocCallback)(void *ptr, size_t new_size);
+
+/*
+ Turns the raw bytes of an input into the bytes to parse, for instance by
+ decrypting or decompressing a custom container. On success it sets
+ `*out_ptr` to a buffer allocated with `malloc` and `*out_len` to its
+ length, and returns `ERR_OK`. `in_ptr` is only valid for the duration of
+ the call.
+ */
+typedef int (*CInputTransformCallback)(void *ctx,
+                                       const uint8_t *in_ptr,
+                                       size_t in_len,
+                                       uint8_t **out_ptr,
+                                       size_t *out_len);
+
+/*
+ Called for each opening tag with the element name and its attributes as
+ parallel arrays of `attr_count` names and values.
+ */
+typedef void (*CStartElementCallback)(void *ctx,
+                                      const char *name,
+                                      const char *const *attr_names,
+                                      const char *const *attr_values,
+                                      size_t attr_count);
+
+/*
+ Called for each closing tag with the element name.
+ */
+typedef void (*CEndElementCallback)(void *ctx, const char *name);
+
+/*
+ Called for each run of character data. `text` is null-terminated and
+ `len` is its length in bytes.
+ */
+typedef void (*CCharactersCallback)(void *ctx, const char *text, size_t len);
+
+/*
+ Called with each chunk of streamed output. Returning anything but `ERR_OK`
+ stops the extraction.
+ */
+typedef int (*CWriteCallback)(void *ctx, const uint8_t *data, size_t len);
+
+/*
+ Called with a `LOG_*` level and a null-terminated message, which is only
+ valid for the duration of the call.
+ */
+typedef void (*CLogCallback)(void *ctx, int level, const char *message);
+
 /*
  Returns the FFI wrapper version as a null-terminated UTF-8 string.
  The returned pointer is to a static string and must not be freed.
@@ -114,6 +464,50 @@ const char *extractous_ffi_version(void);
  */
 const char *extractous_core_version(void);
 
+/*
+ Returns the version of Apache Tika the bundled `libtika_native` was built
+ from, or `"unknown"` if the build could not determine it.
+
+ The version is captured by `build.rs` from the native build output, or
+ from the `EXTRACTOUS_TIKA_VERSION` environment variable when set at build
+ time. The returned pointer is to a static string and must not be freed.
+ */
+const char *extractous_tika_version(void);
+
+/*
+ Sets the directory this library uses for its own intermediate files: the
+ filtered copies of archives parsed under the embedded-file limits, and the
+ copies of unidentified inputs retried under a fallback MIME type.
+
+ The directory must exist and be writable; otherwise `ERR_IO_ERROR` is
+ returned and nothing changes. Call this during startup: once the first
+ extraction has started, `ERR_INVALID_CONFIG` is returned. The parser
+ runtime's own temporary files, such as the images it passes to Tesseract
+ for OCR, go to its `java.io.tmpdir`, which the core fixes when it starts
+ the runtime and which cannot be changed from here. The process
+ environment is left alone.
+ */
+int extractous_set_temp_dir(const char *path);
+
+/*
+ Creates a new, uncancelled token.
+ The returned handle must be freed with `extractous_cancel_token_free`.
+ */
+struct CCancelToken *extractous_cancel_token_new(void);
+
+/*
+ Requests cancellation of every extraction using this token.
+
+ Unlike other handles, a token may be cancelled from any thread while an
+ extraction on another thread is using it. Cancellation cannot be undone.
+ */
+void extractous_cancel_token_cancel(const struct CCancelToken *token);
+
+/*
+ Frees the memory associated with a cancellation token.
+ */
+void extractous_cancel_token_free(struct CCancelToken *token);
+
 /*
  Creates a new PDF parser configuration with default settings.
  The returned handle must be freed with `extractous_pdf_config_free()`
@@ -155,6 +549,9 @@ void extractous_pdf_config_set_extract_annotation_text(struct CPdfParserConfig *
 
 /*
  Creates a new Office parser configuration with default settings.
+
+ Spreadsheet output always starts each sheet with its name, as Tika emits
+ it; the core has no setting to leave sheet names out or change the marker.
  */
 struct COfficeParserConfig *extractous_office_config_new(void);
 
@@ -186,6 +583,17 @@ void extractous_office_config_set_include_move_from_content(struct COfficeParser
 void extractous_office_config_set_include_shape_based_content(struct COfficeParserConfig *handle,
                                                               bool value);
 
+/*
+ Enables or disables inclusion of header and footer text (default enabled).
+
+ Tika takes this text from the header and footer parts that Office files
+ declare, so it is exact for those, but text that merely repeats on every
+ page inside the body is still extracted. PDFs have no such parts, so there
+ is no PDF equivalent and their running headers are always extracted.
+ */
+void extractous_office_config_set_include_headers_and_footers(struct COfficeParserConfig *handle,
+                                                              bool value);
+
 /*
  Creates a new Tesseract OCR configuration with default settings.
  */
@@ -217,19 +625,160 @@ void extractous_ocr_config_set_depth(struct CTesseractOcrConfig *handle, int32_t
 void extractous_ocr_config_set_enable_image_preprocessing(struct CTesseractOcrConfig *handle,
                                                           bool value);
 
+/*
+ Enables or disables automatic rotation correction before recognition.
+
+ When enabled, Tesseract runs orientation and script detection (OSD) on each
+ image and rotates it upright first, which helps with rotated or skewed scans.
+ OSD is an extra Tesseract pass per image, so expect OCR to take noticeably
+ longer; leave this off for scans that are known to be upright.
+ */
+void extractous_ocr_config_set_apply_rotation(struct CTesseractOcrConfig *handle, bool value);
+
 /*
  Sets the timeout for the Tesseract process in seconds.
  */
 void extractous_ocr_config_set_timeout_seconds(struct CTesseractOcrConfig *handle, int32_t seconds);
 
+/*
+ Detects the MIME type of a local file from its leading bytes, without
+ parsing it, and rates the detection from 0 to 100.
+
+ The core library only reports a type after a full parse, so this uses the
+ same magic-byte sniffing as the MIME allow-list. A distinctive signature
+ such as that of a PDF or PNG scores 95, HTML or XML markup 70, a ZIP or OLE
+ container whose inner format was not recognised 60, plain text 40, and an
+ unrecognised file, reported as `application/octet-stream`, 0.
+
+ The handle's root directory and symlink settings apply to `path`.
+ The output string must be freed with `extractous_string_free`.
+ */
+int extractous_extractor_detect_mime_type_file_ex(struct CExtractor *handle,
+                                                  const char *path,
+                                                  char **out_mime,
+                                                  int *out_confidence);
+
+/*
+ Returns the supertype chain of a MIME type, nearest first and ending at
+ `application/octet-stream`, for coarse routing such as "anything
+ office-like".
+
+ The core library does not expose Tika's MIME registry, so the chain comes
+ from a copy of the relevant part of its hierarchy: legacy Office formats
+ are `application/x-tika-msoffice`, Office Open XML formats
+ `application/x-tika-ooxml` and then `application/zip`, and OpenDocument
+ and EPUB files `application/zip`. Otherwise `+xml` types are
+ `application/xml`, `+zip` types `application/zip`, and textual types,
+ XML included, `text/plain`. Parameters and case are ignored. The type
+ itself is not part of the chain, which is empty for
+ `application/octet-stream`.
+
+ The array must be freed with `extractous_string_array_free`.
+ */
+int extractous_mime_supertypes(const char *mime, char ***out_chain, size_t *out_count);
+
+/*
+ Guesses the MIME type of a file from its name alone, without opening it,
+ for routing files before reading them when their extensions can be
+ trusted. Returns `application/octet-stream` for a name without a known
+ extension, and NULL if `filename` is NULL or not valid UTF-8.
+
+ The core library does not expose Tika's name-based detection, so the
+ guess comes from a copy of the globs Tika's `tika-mimetypes.xml` gives
+ common document, image, archive and text formats. Like Tika's, the match
+ ignores case and any directory part of `filename`.
+
+ The output string must be freed with `extractous_string_free`.
+ */
+char *extractous_detect_mime_by_filename(const char *filename);
+
+/*
+ Reports whether a local file is password-protected, by inspecting its
+ container without parsing it, so callers can ask for a password before
+ extracting.
+
+ `*out_encrypted` is set to 1 for a PDF whose trailer has an `/Encrypt`
+ entry, an Office Open XML file saved with a password, which is stored as
+ an OLE container holding an `EncryptedPackage` stream, a ZIP archive with
+ encrypted entries, and an OpenDocument file whose manifest lists
+ encryption data. It is 0 otherwise, including for formats not covered
+ here such as legacy `.doc` and `.xls` files. Either way `ERR_OK` is
+ returned; `ERR_IO_ERROR` means the file could not be read.
+
+ The handle's root directory and symlink settings apply to `path`.
+ */
+int extractous_extractor_is_encrypted_file(struct CExtractor *handle,
+                                           const char *path,
+                                           int *out_encrypted);
+
+/*
+ Returns the raw bytes and metadata of the embedded resource at `index` in
+ a local file, such as one attachment of an email, without parsing it.
+
+ Resources are numbered from 0 in document order. For a ZIP archive they
+ are its file entries, directories excluded; for an email message (RFC 822)
+ they are its attachments, including attached messages, decoded from their
+ transfer encoding. These are the resources that
+ `extractous_extractor_list_embedded_file` names for such files, in the
+ same order, and the handle's embedded-file filters apply as they do
+ there. Other formats, including Office documents, return
+ `ERR_UNSUPPORTED_FORMAT`, and an index past the last resource returns
+ `ERR_INDEX_OUT_OF_RANGE`.
+
+ `*out_bytes` is NULL if the resource is empty. `*out_mime` is the
+ resource's declared type, or one guessed from its bytes. `*out_metadata`
+ holds `resourceName` when the resource is named, `Content-Type` and
+ `Content-Length`.
+ The bytes must be freed with `extractous_buffer_free`, the MIME type with
+ `extractous_string_free` and the metadata with `extractous_metadata_free`.
+ */
+int extractous_extractor_extract_embedded_at(struct CExtractor *handle,
+                                             const char *path,
+                                             size_t index,
+                                             uint8_t **out_bytes,
+                                             size_t *out_len,
+                                             char **out_mime,
+                                             struct CMetadata **out_metadata);
+
 char *extractous_error_message(int code);
 
+/*
+ Returns the same message as `extractous_error_message` without allocating.
+ The returned pointer is to a static string and must not be freed.
+ */
+const char *extractous_error_message_static(int code);
+
+/*
+ Returns a stable, machine-readable category for an error code, such as
+ `"io_error"`. Unknown codes map to `"unknown"`.
+ The returned pointer is to a static string and must not be freed.
+ */
+const char *extractous_error_category(int code);
+
+/*
+ Exposes every error code and its category as two static parallel arrays
+ of `*out_count` entries, so bindings can build their mapping up front.
+ These are the categories `extractous_error_category` returns; codes not
+ listed map to `"unknown"`. Nothing is allocated and nothing must be freed.
+ */
+int extractous_error_category_table(const int **out_codes,
+                                    const char *const **out_categories,
+                                    size_t *out_count);
+
 /*
  Retrieves a detailed debug report for the last error on this thread
  full error chain and a backtrace if RUST_BACKTRACE=1
  */
 char *extractous_error_get_last_debug(void);
 
+/*
+ Returns the category of the last error stored on this thread, as
+ `extractous_error_category` would for its code, or `"none"` if there is
+ none. Retrieving the debug report clears the stored error.
+ The returned pointer is to a static string and must not be freed.
+ */
+const char *extractous_error_get_last_category(void);
+
 /*
  Checks if debug information is available for the current thread
  */
@@ -283,44 +832,987 @@ void extractous_extractor_set_ocr_config_mut(struct CExtractor *handle,
 void extractous_extractor_set_xml_output_mut(struct CExtractor *handle, bool xml_output);
 
 /*
- Extracts content and metadata from a local file path into a string.
-
- Output strings must be freed with `extractous_string_free`.
- Output metadata must be freed with `extractous_metadata_free`.
+ Restricts extraction to documents whose MIME type is in `types`.
+
+ The type is sniffed from the leading bytes of file and byte inputs before
+ the parser runs, and other documents are rejected with
+ `ERR_MIME_NOT_ALLOWED`. An entry with a `*` subtype allows a whole
+ top-level type. Passing an empty list allows everything again. URL inputs
+ are not checked. The strings are copied; entries that are NULL or not valid
+ UTF-8 are skipped.
  */
-int extractous_extractor_extract_file_to_string(struct CExtractor *handle,
-                                                const char *path,
-                                                char **out_content,
-                                                struct CMetadata **out_metadata);
+void extractous_extractor_set_allowed_mime_types_mut(struct CExtractor *handle,
+                                                     const char *const *types,
+                                                     size_t count);
 
 /*
- Extracts content and metadata from a local file path into a stream.
+ Sets whether file inputs may be symbolic links. When disabled, a path
+ that is itself a symbolic link is rejected with `ERR_IO_ERROR`; links in
+ its parent directories are not checked. Enabled by default.
  */
-int extractous_extractor_extract_file(struct CExtractor *handle,
-                                      const char *path,
-                                      struct CStreamReader **out_reader,
-                                      struct CMetadata **out_metadata);
+void extractous_extractor_set_follow_symlinks_mut(struct CExtractor *handle, bool follow);
 
 /*
- Extracts content and metadata from a byte slice into a string.
+ Restricts file inputs to paths inside the directory `root`.
+
+ Each input path is resolved, following `..` components and symbolic links,
+ and rejected with `ERR_IO_ERROR` if it ends up outside `root`; the debug
+ report then reads "path escapes root". Paths that cannot be resolved, such
+ as missing files, are rejected too. Passing NULL removes the restriction,
+ which is the default. URL and byte inputs are not affected.
  */
-int extractous_extractor_extract_bytes_to_string(struct CExtractor *handle,
-                                                 const uint8_t *data,
-                                                 size_t data_len,
-                                                 char **out_content,
-                                                 struct CMetadata **out_metadata);
+void extractous_extractor_set_root_dir_mut(struct CExtractor *handle, const char *root);
 
 /*
- Extracts content and metadata from a byte slice into a stream.
+ Sets whether control characters are stripped from string output.
+
+ When enabled, the C0 control characters U+0000–U+0008, U+000B (vertical
+ tab), U+000C (form feed) and U+000E–U+001F are removed from content
+ returned as a string. Tab, line feed and carriage return are kept, as are
+ DEL and the C1 controls. Stream output is not affected. Disabled by default.
  */
-int extractous_extractor_extract_bytes(struct CExtractor *handle,
-                                       const uint8_t *data,
-                                       size_t data_len,
-                                       struct CStreamReader **out_reader,
-                                       struct CMetadata **out_metadata);
+void extractous_extractor_set_sanitize_output_mut(struct CExtractor *handle, bool enabled);
 
 /*
- Extracts content and metadata from a URL into a string.
+ Sets whether whitespace in string output is collapsed, for search indexing
+ where layout spacing only bloats the index.
+
+ When enabled, each run of whitespace becomes a single newline if it
+ contains a line break, keeping paragraph boundaries, or a single space
+ otherwise, and leading and trailing whitespace is removed. This happens
+ after sanitization and before normalization. Stream output is not affected.
+ Disabled by default.
+ */
+void extractous_extractor_set_collapse_whitespace_mut(struct CExtractor *handle, bool enabled);
+
+/*
+ Sets the largest uncompressed size in bytes of a file embedded in an
+ archive, to guard against decompression bombs. 0, the default, means no
+ limit.
+
+ Before any file extraction parses a plain ZIP archive, each entry is
+ decompressed up to the limit, and entries over it are left out of a
+ temporary copy that is parsed instead, so a header that understates the
+ size does not let an entry through. A `LOG_WARN` message naming each
+ skipped entry goes to the log callback. ZIP-based document formats such
+ as DOCX and ODT are not checked, since every part is needed to read them,
+ nor are entries of nested archives. If the archive cannot be read for the
+ check, extraction fails with `ERR_IO_ERROR` rather than going ahead
+ unchecked.
+ */
+void extractous_extractor_set_max_embedded_bytes_mut(struct CExtractor *handle, size_t max_bytes);
+
+/*
+ Sets MIME types of embedded files that are listed but not parsed for
+ content, to speed up recursion over archives full of media such as images
+ and videos. An entry with a `*` subtype skips a whole top-level type.
+
+ Before any file extraction parses a plain ZIP archive, the type of each
+ entry is sniffed from its leading bytes as for
+ `extractous_extractor_set_allowed_mime_types_mut`, and matching entries are
+ emptied in a temporary copy that is parsed instead. They keep their names,
+ so the parser still reports them. A `LOG_INFO` message naming each skipped
+ entry goes to the log callback. As for
+ `extractous_extractor_set_max_embedded_bytes_mut`, ZIP-based document
+ formats such as DOCX are left alone, only the archive's own entries are
+ checked, and an archive that cannot be read for the check fails with
+ `ERR_IO_ERROR`. Passing an empty list parses everything again.
+ The strings are copied; entries that are NULL or not valid UTF-8 are
+ skipped.
+ */
+void extractous_extractor_set_skip_embedded_mime_types_mut(struct CExtractor *handle,
+                                                           const char *const *types,
+                                                           size_t count);
+
+/*
+ Sets whether archive entries byte-for-byte identical to an earlier entry
+ are parsed, so archives holding copies of the same file do not repeat its
+ content. Disabled by default.
+
+ Before any file extraction parses a plain ZIP archive, each entry's
+ decompressed bytes are hashed, and entries with the same bytes as an
+ earlier one are emptied in a temporary copy that is parsed instead. They
+ keep their names, so the parser still reports them. Entries are compared
+ by their bytes, not by the text extracted from them, so files that differ
+ in any byte are all parsed even when they give the same text. The number
+ of duplicates goes to the log callback as a `LOG_INFO` message. As for
+ `extractous_extractor_set_max_embedded_bytes_mut`, ZIP-based document
+ formats such as DOCX are left alone and only the archive's own entries
+ are checked.
+ */
+void extractous_extractor_set_dedupe_embedded_mut(struct CExtractor *handle, bool enabled);
+
+/*
+ Sets how many times an extraction is retried when calling into the parser
+ runtime fails transiently, for example because the thread could not be
+ attached to the JVM. Failures raised by the parser itself, such as an
+ unreadable document, are never retried.
+
+ Retries wait 10 ms longer each time: 10 ms, 20 ms and so on. They apply to
+ every call that parses a file, bytes or a URL, including those that run
+ on a worker thread. The default is 0, no retries; negative values are
+ treated as 0.
+ */
+void extractous_extractor_set_jni_retry_mut(struct CExtractor *handle, int attempts);
+
+/*
+ Sets the most metadata entries an extraction returns, to keep documents
+ with thousands of entries from bloating `CMetadata` and derived JSON.
+
+ Keys are sorted as in every `CMetadata` and the first `max` are kept, so
+ the subset is the same each time. The number of entries dropped goes to
+ the log callback as a `LOG_WARN` message. 0 or a negative value, the
+ default, means no limit.
+ */
+void extractous_extractor_set_max_metadata_entries_mut(struct CExtractor *handle, int max);
+
+/*
+ Sets whether `extractous_extractor_extract_file_positioned` may run, which
+ returns text runs with their coordinates on the page. Disabled by default,
+ in which case that call returns `ERR_INVALID_CONFIG`.
+ */
+void extractous_extractor_set_include_coordinates_mut(struct CExtractor *handle, bool enabled);
+
+/*
+ Sets the Unicode normalization form applied to string output, using one of
+ the `NORM_*` constants. The default is `NORM_NONE`, which leaves the text as
+ the parser produced it. Unknown forms are ignored. Stream output is not
+ affected.
+ */
+void extractous_extractor_set_unicode_normalization_mut(struct CExtractor *handle, int form);
+
+/*
+ Sets whether the handle must work without network access, for sandboxed
+ and air-gapped deployments.
+
+ When offline, the URL extraction functions return `ERR_INVALID_CONFIG`
+ without connecting, and so does extracting a text document (XML, HTML,
+ SVG or any other text file or buffer) that refers to a resource on the
+ network: a doctype or entity declaration whose system identifier is an
+ `http:`, `ftp:` or other non-`file:` URL, an XInclude `href`, or an XML
+ Schema location. Such documents are refused before parsing rather than
+ relying on the parser not to resolve them, so a common XHTML doctype
+ naming its DTD by URL is refused too. Text inputs, and the text entries
+ of a plain ZIP file, are read in full for the check; entries of nested
+ archives and of ZIP buffers are not checked. Disabled by default for
+ compatibility; enabling it is recommended wherever inputs are untrusted.
+ */
+void extractous_extractor_set_offline_mut(struct CExtractor *handle, bool offline);
+
+/*
+ Sets code point ranges that filter string output, such as U+E000–U+F8FF
+ to drop the private-use glyphs some PDFs' fonts leave in their text.
+
+ With `CHAR_FILTER_DENY`, characters in any of the `count` ranges are
+ removed; with `CHAR_FILTER_ALLOW`, only those characters are kept, so the
+ ranges must include any whitespace wanted. Ranges are inclusive, and those
+ with `start` after `end` are skipped. Passing no ranges turns filtering
+ off, as it is by default. Unknown modes are ignored. The ranges are
+ copied. Filtering runs after control characters are stripped and before
+ whitespace is collapsed. Stream output is not affected.
+ */
+void extractous_extractor_set_char_filter_mut(struct CExtractor *handle,
+                                              const struct CCharRange *ranges,
+                                              size_t count,
+                                              int mode);
+
+/*
+ Sets whether byte inputs that the parser decodes as UTF-8 text must be
+ valid UTF-8. Disabled by default.
+
+ The parser replaces bytes it cannot decode with U+FFFD. When strict, the
+ `extractous_extractor_extract_bytes*` functions instead fail with
+ `ERR_INVALID_UTF8` if the input was decoded as UTF-8, according to its
+ `Content-Encoding` or `Content-Type` charset, and is not valid UTF-8. The
+ debug report gives the offset of the first invalid byte. Text the parser
+ decodes in another charset, such as Windows-1252, is not affected.
+ */
+void extractous_extractor_set_strict_utf8_mut(struct CExtractor *handle, bool strict);
+
+/*
+ Sets whether `extractous_extractor_extract_file_segmented` guesses the
+ language of each segment on its own. Disabled by default.
+
+ When enabled, the metadata of every segment whose text can be classified,
+ the top-level document and each embedded resource alike, gets a
+ `language` key with the code
+ `extractous_extractor_extract_file_to_string_lang` would report for that
+ text alone, so the parts of a multilingual archive keep their own
+ languages.
+ */
+void extractous_extractor_set_segment_language_mut(struct CExtractor *handle, bool enabled);
+
+/*
+ Sets a transform applied to the bytes of every byte input before it is
+ checked and parsed, for formats the parser cannot read as they are, such
+ as encrypted or custom-compressed containers. Pass NULL to remove it,
+ which is the default.
+
+ The transform gets `ctx` and the input, and hands back the bytes to
+ parse in a buffer allocated with `malloc`, which the library frees after
+ copying it. A NULL buffer with a length of 0 is an empty document. If the
+ transform returns anything but `ERR_OK`, the extraction fails with
+ `ERR_ABORTED`, after freeing any buffer it set. The
+ `extractous_extractor_extract_bytes*` and
+ `extractous_extractor_extract_iovec_to_string` functions apply it; file
+ and URL inputs are parsed as they are. The transform runs on the
+ extracting thread, and `ctx` must stay valid while it is set.
+ */
+void extractous_extractor_set_input_transform_mut(struct CExtractor *handle,
+                                                  CInputTransformCallback transform_cb,
+                                                  void *ctx);
+
+/*
+ Sets how line endings in string output are normalized, using one of the
+ `LINE_ENDING_*` constants: `LINE_ENDING_LF` and `LINE_ENDING_CRLF` rewrite
+ every `\r\n`, `\r` and `\n` to that ending. The default is
+ `LINE_ENDING_PRESERVE`, which leaves them as the parser produced them.
+ Unknown modes are ignored. Stream output is not affected.
+ */
+void extractous_extractor_set_line_ending_mut(struct CExtractor *handle, int mode);
+
+/*
+ Sets how right-to-left text such as Arabic and Hebrew is ordered in
+ string output, using one of the `BIDI_*` constants. The default is
+ `BIDI_PRESERVE`, which leaves it in the order the parser produced.
+
+ Some PDFs yield right-to-left text in visual order, reversed from the
+ order it is read in. `BIDI_LOGICAL` takes each line holding right-to-left
+ text from visual order back to logical order, undoing the Unicode
+ Bidirectional Algorithm, with the line's direction taken from its last
+ strong character. Visual order does not record everything the algorithm
+ used, so a few lines, such as a number next to a right-to-left word in a
+ left-to-right line, may not come back exactly as written. `BIDI_VISUAL`
+ takes logical text to visual order with the algorithm, for displays
+ without bidirectional support, with the line's direction taken from its
+ first strong character. The parser does not report which order it
+ produced, so choosing the wrong mode scrambles text that was already in
+ the wanted order. Lines without right-to-left characters are left as they
+ are. Unknown modes are ignored. Stream output is not affected.
+ */
+void extractous_extractor_set_bidi_mode_mut(struct CExtractor *handle, int mode);
+
+/*
+ Sets the maximum size in bytes of string output, not counting the null
+ terminator. Longer content is cut at the last UTF-8 character boundary
+ within the limit, after sanitization and normalization. Unlike
+ `extractous_extractor_set_extract_string_max_length_mut`, which counts
+ characters, this bounds the size of the returned buffer. 0, the default,
+ means no limit. Stream output is not affected.
+ */
+void extractous_extractor_set_max_output_bytes_mut(struct CExtractor *handle, size_t max_bytes);
+
+/*
+ Sets whether XML output is reduced to the inner markup of the `<body>`
+ element, dropping the XHTML `<head>` with its metadata and the surrounding
+ scaffolding. Disabled by default, which returns the full document. Text and
+ stream output are not affected.
+ */
+void extractous_extractor_set_xml_body_only_mut(struct CExtractor *handle, bool body_only);
+
+/*
+ Sets a MIME type to fall back on when the parser cannot identify a
+ document and reports it as `application/octet-stream`.
+
+ Every file extraction that parses into a string or JSON, and every string
+ extraction from bytes or slices, then retries once with the document
+ treated as `mime`, which suits pipelines that know what their inputs are
+ even when detection fails.
+ The type is forced through the file extension the parser sees, using a
+ copy of the input in the temp directory, so only the types that
+ `extractous_detect_mime_by_filename` gives an extension, such as
+ `text/csv`, `text/plain` or `application/pdf`, are accepted; others are
+ ignored, as is invalid UTF-8. Every extraction also reports `mime` as the
+ `Content-Type` of such a document, including streamed ones, whose content
+ is not parsed again. Passing NULL clears the fallback, which is the
+ default.
+ */
+void extractous_extractor_set_fallback_mime_mut(struct CExtractor *handle, const char *mime);
+
+/*
+ Sets the output format using one of the `OUTPUT_*` constants.
+ Unknown formats are ignored and leave the current format unchanged.
+ */
+void extractous_extractor_set_output_format_mut(struct CExtractor *handle, int format);
+
+/*
+ Returns the current output format as one of the `OUTPUT_*` constants,
+ or `ERR_NULL_POINTER` if the handle is null.
+ */
+int extractous_extractor_get_output_format(const struct CExtractor *handle);
+
+/*
+ Returns true only if the output format is `OUTPUT_XML`.
+
+ Kept for compatibility; prefer `extractous_extractor_get_output_format`.
+ */
+bool extractous_extractor_get_xml_output(const struct CExtractor *handle);
+
+/*
+ Describes the extractor's effective settings as a JSON object, so bug
+ reports can include the exact configuration.
+
+ Each setting is included under the name of its setter, such as
+ `"output_format"` or `"max_output_bytes"`, with enumerations given as the
+ value of their C constant, a setting that is off or unlimited as `false`,
+ `0` or `null`, and lists as arrays. The PDF, Office and OCR
+ configurations are left out: the core library does not expose their
+ fields once set. `*out_json` must be freed with `extractous_string_free`.
+ */
+int extractous_extractor_config_to_json(const struct CExtractor *handle, char **out_json);
+
+/*
+ Extracts content and metadata from a local file path into a string.
+
+ An empty file is not parsed: it gives `ERR_OK` with empty content and
+ empty metadata, whatever its extension.
+ Output strings must be freed with `extractous_string_free`.
+ Output metadata must be freed with `extractous_metadata_free`.
+ */
+int extractous_extractor_extract_file_to_string(struct CExtractor *handle,
+                                                const char *path,
+                                                char **out_content,
+                                                struct CMetadata **out_metadata);
+
+/*
+ Extracts content and metadata from a local file path as UTF-16, for callers
+ such as .NET and Windows APIs that work in UTF-16 natively.
+
+ `*out_content` is a NUL-terminated array of UTF-16 code units in native
+ byte order, and `*out_len_units` its length in code units without the
+ terminator. The content is always well-formed UTF-16, without lone
+ surrogates. Since the length is reported, NUL characters in the content
+ are kept and the NUL policy does not apply. An empty file gives empty
+ content and metadata, as for `extractous_extractor_extract_file_to_string`.
+ Output content must be freed with `extractous_utf16_free`.
+ Output metadata must be freed with `extractous_metadata_free`.
+ */
+int extractous_extractor_extract_file_to_utf16(struct CExtractor *handle,
+                                               const char *path,
+                                               uint16_t **out_content,
+                                               size_t *out_len_units,
+                                               struct CMetadata **out_metadata);
+
+/*
+ Extracts content and metadata from a local file path into a string in the
+ first charset of a preference list that can represent the content.
+
+ `accept` is a comma-separated list of charset labels, as in HTTP
+ `Accept-Charset`, such as `"windows-1252, utf-8"`. The supported charsets
+ are UTF-8 (`utf-8`, or `*`), US-ASCII (`us-ascii`), ISO-8859-1
+ (`iso-8859-1`, `latin1`) and Windows-1252 (`windows-1252`, `cp1252`).
+ Labels are matched without regard to case, parameters such as `;q=0.5`
+ are ignored and other charsets are skipped. UTF-16 is not offered because
+ it cannot be returned as a null-terminated string; use
+ `extractous_extractor_extract_file_to_utf16` for it.
+
+ `*out_encoding` receives the IANA name of the charset chosen: `UTF-8`,
+ `US-ASCII`, `ISO-8859-1` or `windows-1252`. Returns `ERR_INVALID_CONFIG`
+ if `accept` names no supported charset, or if none of those it names can
+ represent the content, in which case no output is set. Listing `utf-8`
+ last always avoids the latter. The NUL policy applies as for
+ `extractous_extractor_extract_file_to_string`.
+ Output strings must be freed with `extractous_string_free`.
+ Output metadata must be freed with `extractous_metadata_free`.
+ */
+int extractous_extractor_extract_file_negotiated(struct CExtractor *handle,
+                                                 const char *path,
+                                                 const char *accept,
+                                                 char **out_content,
+                                                 char **out_encoding,
+                                                 struct CMetadata **out_metadata);
+
+/*
+ Extracts content and metadata from a local file path into a string in the
+ given `OUTPUT_*` format, leaving the handle's own format unchanged.
+
+ Returns `ERR_INVALID_ENUM` for an unknown format.
+ Output strings must be freed with `extractous_string_free`.
+ Output metadata must be freed with `extractous_metadata_free`.
+ */
+int extractous_extractor_extract_file_as_format(struct CExtractor *handle,
+                                                const char *path,
+                                                int format,
+                                                char **out_content,
+                                                struct CMetadata **out_metadata);
+
+/*
+ Extracts content and metadata from a local file path into a string, running
+ OCR with the Tesseract language `ocr_lang`, such as `"fra"` or `"eng+deu"`,
+ for this call only.
+
+ The rest of the handle's OCR configuration applies unchanged, and the stored
+ configuration is not modified. NULL keeps the configured language.
+ Output strings must be freed with `extractous_string_free`.
+ Output metadata must be freed with `extractous_metadata_free`.
+ */
+int extractous_extractor_extract_file_ocr_lang(struct CExtractor *handle,
+                                               const char *path,
+                                               const char *ocr_lang,
+                                               char **out_content,
+                                               struct CMetadata **out_metadata);
+
+/*
+ Extracts content and metadata from a local file path into a string, running
+ OCR at `dpi` dots per inch for this call only.
+
+ The rest of the handle's OCR configuration applies unchanged, and the stored
+ configuration is not modified. `dpi` must be positive, otherwise
+ `ERR_INVALID_CONFIG` is returned.
+ Output strings must be freed with `extractous_string_free`.
+ Output metadata must be freed with `extractous_metadata_free`.
+ */
+int extractous_extractor_extract_file_ocr_dpi(struct CExtractor *handle,
+                                              const char *path,
+                                              int dpi,
+                                              char **out_content,
+                                              struct CMetadata **out_metadata);
+
+/*
+ Extracts only the metadata of a local file, such as its properties,
+ author and dates, without returning the body text.
+
+ The core has no metadata-only parse, so this runs the usual parse with the
+ maximum string length capped at 0 for this call. The parser stops at the
+ first body text it writes, which skips most of the work for large
+ documents, and no content string is allocated. Metadata that a format only
+ records after its body, which is rare, may be missing as a result.
+ Output metadata must be freed with `extractous_metadata_free`.
+ */
+int extractous_extractor_extract_file_metadata_only(struct CExtractor *handle,
+                                                    const char *path,
+                                                    struct CMetadata **out_metadata);
+
+/*
+ Extracts content from a local file path into a string, passing each
+ metadata entry to `meta_cb` instead of returning a `CMetadata`, so the
+ parallel key and value arrays are never allocated.
+
+ Entries arrive in the order, and with the values joined and capped, as
+ `CMetadata` would hold them. They are passed before the content is set.
+ If the callback returns anything but `ERR_OK`, no further entries are
+ passed and `ERR_ABORTED` is returned with `*out_content` left unset.
+ Output strings must be freed with `extractous_string_free`.
+ */
+int extractous_extractor_extract_file_meta_cb(struct CExtractor *handle,
+                                              const char *path,
+                                              CMetadataCallback meta_cb,
+                                              void *ctx,
+                                              char **out_content);
+
+/*
+ Extracts content and metadata from a local file path into a string,
+ reporting whether the content was cut off.
+
+ `*out_truncated` is set as for
+ `extractous_extractor_extract_bytes_to_string_ex`.
+ Output strings must be freed with `extractous_string_free`.
+ Output metadata must be freed with `extractous_metadata_free`.
+ */
+int extractous_extractor_extract_file_to_string_ex(struct CExtractor *handle,
+                                                   const char *path,
+                                                   char **out_content,
+                                                   struct CMetadata **out_metadata,
+                                                   int *out_truncated);
+
+/*
+ Extracts content and metadata from a local file path into a string,
+ reporting whether the parser had to work around damage in the document,
+ so questionable extractions can be flagged for another look.
+
+ `*out_repaired` is set to 1 when Tika recorded a problem it recovered
+ from under an `X-TIKA:EXCEPTION:` metadata key, such as a warning or an
+ embedded document that could not be parsed, or when a PDF's `startxref`
+ does not lead to its cross-reference table, which PDFBox then rebuilds
+ without reporting it. Content cut short by the length limit does not
+ count. It is 0 otherwise. Content and metadata are returned either way.
+ Output strings must be freed with `extractous_string_free`.
+ Output metadata must be freed with `extractous_metadata_free`.
+ */
+int extractous_extractor_extract_file_to_string_repaired(struct CExtractor *handle,
+                                                         const char *path,
+                                                         char **out_content,
+                                                         struct CMetadata **out_metadata,
+                                                         int *out_repaired);
+
+/*
+ Extracts content and metadata from a local file path into a string,
+ reporting which limit, if any, cut the content short.
+
+ `*out_truncation_reason` is set to one of the `TRUNC_*` constants:
+ `TRUNC_MAX_CHARS` when the content exceeded the core's maximum length set
+ with `extractous_extractor_set_extract_string_max_length_mut`,
+ `TRUNC_MAX_BYTES` when it was cut to the size set with
+ `extractous_extractor_set_max_output_bytes_mut`, `TRUNC_NUL` when
+ `NUL_POLICY_TRUNCATE` cut it at a NUL character, and `TRUNC_NONE`
+ otherwise. These limits apply in that order, and when several cut the
+ content the last one, which set its final length, is reported. As for
+ `extractous_extractor_extract_file_to_string_ex`, content exactly as long
+ as the maximum length is not truncated.
+ Output strings must be freed with `extractous_string_free`.
+ Output metadata must be freed with `extractous_metadata_free`.
+ */
+int extractous_extractor_extract_file_to_string_with_truncation_reason(struct CExtractor *handle,
+                                                                       const char *path,
+                                                                       char **out_content,
+                                                                       struct CMetadata **out_metadata,
+                                                                       int *out_truncation_reason);
+
+/*
+ Extracts content and metadata from a local file path into a string, along
+ with a hash of the content's UTF-8 bytes.
+
+ `algo` is one of the `HASH_*` constants; unknown values return
+ `ERR_INVALID_CONFIG`. The hash is written as a lowercase hex string.
+ Output strings must be freed with `extractous_string_free`.
+ Output metadata must be freed with `extractous_metadata_free`.
+ */
+int extractous_extractor_extract_file_to_string_hashed(struct CExtractor *handle,
+                                                       const char *path,
+                                                       int algo,
+                                                       char **out_content,
+                                                       char **out_hash_hex,
+                                                       struct CMetadata **out_metadata);
+
+/*
+ Returns an identifier for the file at `path`: the SHA-256 of its raw bytes
+ as a lowercase hex string, for use as a cache key before deciding whether
+ to extract at all.
+
+ The file is hashed as-is without being parsed, so the id is cheap to
+ compute and changes only when the bytes do. The handle's path
+ restrictions apply, and `handle` may be NULL to skip them; its other
+ settings do not affect the id. Returns NULL
+ if the file cannot be read, with the reason available from the last error;
+ use `extractous_extractor_compute_doc_id_checked` to get the error code.
+ The returned string must be freed with `extractous_string_free`.
+ */
+char *extractous_extractor_compute_doc_id(struct CExtractor *handle, const char *path);
+
+/*
+ Like `extractous_extractor_compute_doc_id`, returning an error code and
+ writing the id to `*out_id`.
+
+ Returns `ERR_IO_ERROR` if the file cannot be read.
+ The output string must be freed with `extractous_string_free`.
+ */
+int extractous_extractor_compute_doc_id_checked(struct CExtractor *handle,
+                                                const char *path,
+                                                char **out_id);
+
+/*
+ Extracts content and metadata from a local file path into a string and
+ reports how long the extraction took.
+
+ `*out_elapsed_ms` is set to the wall-clock milliseconds spent in the
+ parser, on failure as well as on success, counting any retries of
+ transient runtime errors (see `extractous_extractor_set_jni_retry_mut`)
+ and the waits between them. It is 0 if the file is refused before
+ parsing.
+ Output strings must be freed with `extractous_string_free`.
+ Output metadata must be freed with `extractous_metadata_free`.
+ */
+int extractous_extractor_extract_file_to_string_timed(struct CExtractor *handle,
+                                                      const char *path,
+                                                      char **out_content,
+                                                      struct CMetadata **out_metadata,
+                                                      uint64_t *out_elapsed_ms);
+
+/*
+ Extracts content and metadata from a local file path into a string and
+ explains why the content is empty, if it is.
+
+ `*out_content_empty_reason` is set to one of the `EMPTY_*` constants:
+ `EMPTY_NONE` when the content has non-whitespace text, `EMPTY_OCR_DISABLED`
+ for a PDF or image that OCR did not run on, `EMPTY_NO_TEXT_LAYER` for a PDF
+ or image that OCR ran on without recognising any text, and
+ `EMPTY_UNSUPPORTED_BODY` for other formats, whose parser produced no body
+ text. The format is taken from the parser's `Content-Type`, and OCR counts
+ as having run if the Tesseract parser appears in `X-TIKA:Parsed-By`.
+
+ Output strings must be freed with `extractous_string_free`.
+ Output metadata must be freed with `extractous_metadata_free`.
+ */
+int extractous_extractor_extract_file_to_string_with_empty_reason(struct CExtractor *handle,
+                                                                  const char *path,
+                                                                  char **out_content,
+                                                                  struct CMetadata **out_metadata,
+                                                                  int *out_content_empty_reason);
+
+/*
+ Extracts content and metadata from a local file path into a string and
+ reports whether OCR was run.
+
+ `*out_ocr_used` is set to 1 if the Tesseract parser appears in
+ `X-TIKA:Parsed-By`, which happens when the PDF OCR strategy or an image
+ input sent any page to OCR and Tesseract is installed, and to 0 otherwise.
+
+ Output strings must be freed with `extractous_string_free`.
+ Output metadata must be freed with `extractous_metadata_free`.
+ */
+int extractous_extractor_extract_file_to_string_ocrinfo(struct CExtractor *handle,
+                                                        const char *path,
+                                                        char **out_content,
+                                                        struct CMetadata **out_metadata,
+                                                        int *out_ocr_used);
+
+/*
+ Extracts content and metadata from a local file path into a string, along
+ with character and word counts of the returned content.
+
+ `*out_char_count` is the number of Unicode scalar values, so a multi-byte
+ character counts once. `*out_word_count` is the number of runs of
+ non-whitespace characters, where whitespace is any character with the
+ Unicode `White_Space` property.
+ Output strings must be freed with `extractous_string_free`.
+ Output metadata must be freed with `extractous_metadata_free`.
+ */
+int extractous_extractor_extract_file_to_string_stats(struct CExtractor *handle,
+                                                      const char *path,
+                                                      char **out_content,
+                                                      struct CMetadata **out_metadata,
+                                                      size_t *out_char_count,
+                                                      size_t *out_word_count);
+
+/*
+ Extracts content and metadata from a local file path into a string, along
+ with statistics of the returned content for display in content-management
+ interfaces.
+
+ Words and characters are counted as for
+ `extractous_extractor_extract_file_to_string_stats`. A sentence ends at
+ `.`, `!`, `?` or an ellipsis followed by whitespace, so abbreviations such
+ as "e.g." are counted as sentence ends. Reading time assumes
+ `READING_WORDS_PER_MINUTE`.
+ Output strings must be freed with `extractous_string_free`.
+ Output metadata must be freed with `extractous_metadata_free`.
+ */
+int extractous_extractor_extract_file_to_string_analysis(struct CExtractor *handle,
+                                                         const char *path,
+                                                         char **out_content,
+                                                         struct CContentAnalysis *out_analysis,
+                                                         struct CMetadata **out_metadata);
+
+/*
+ Extracts content and metadata from a local file path into a string, along
+ with a guess at the language of the returned content.
+
+ `*out_language` is set to an ISO 639-1 code such as `"en"`, or an ISO 639-3
+ code for languages without one, and `*out_language_confidence` to how far
+ the guess can be trusted, from 0 to 100; callers may want to ignore guesses
+ below about 50. Content with fewer than ten letters is too short to
+ classify, and then, as when no language matches, the language is NULL and
+ the confidence 0.
+ Output strings must be freed with `extractous_string_free`.
+ Output metadata must be freed with `extractous_metadata_free`.
+ */
+int extractous_extractor_extract_file_to_string_lang(struct CExtractor *handle,
+                                                     const char *path,
+                                                     char **out_content,
+                                                     struct CMetadata **out_metadata,
+                                                     char **out_language,
+                                                     int *out_language_confidence);
+
+/*
+ Extracts content and metadata from a local file path and returns the
+ window of at most `char_limit` characters starting at `char_offset`, for
+ paging through a large document.
+
+ Characters are Unicode scalar values, counted after the handle's
+ post-processing. `*out_total_len` is set to the length of the whole content
+ in characters, so callers know how many windows there are. A window
+ reaching past the end is cut short, and one starting past it is empty.
+ Each call extracts the whole document again, so paging through it costs
+ one extraction per page; callers paging repeatedly should cache the result
+ of `extractous_extractor_extract_file_to_string` instead. The handle's
+ maximum string length still bounds the content being windowed.
+ Output strings must be freed with `extractous_string_free`.
+ Output metadata must be freed with `extractous_metadata_free`.
+ */
+int extractous_extractor_extract_file_window(struct CExtractor *handle,
+                                             const char *path,
+                                             size_t char_offset,
+                                             size_t char_limit,
+                                             char **out_content,
+                                             size_t *out_total_len,
+                                             struct CMetadata **out_metadata);
+
+/*
+ Extracts content and metadata from a local file path into a string, giving
+ up once the absolute deadline `deadline_unix_ms` (milliseconds since the
+ Unix epoch) has passed.
+
+ The extraction runs on a worker thread. If the deadline passes first,
+ `ERR_TIMEOUT` is returned; a deadline already in the past returns it without
+ starting. The deadline also bounds the wait for a slot of the limit set
+ with `extractous_set_max_concurrent_extractions`, which returns
+ `ERR_TIMEOUT` too when it runs out, or `ERR_BUSY` at once if waiting is
+ turned off. The core parser cannot be interrupted, so a timed-out worker is
+ abandoned and keeps running in the background until the parse finishes,
+ still holding its JVM thread, its memory and its slot of the limit set
+ with `extractous_set_max_concurrent_extractions`; its result is discarded.
+
+ Output strings must be freed with `extractous_string_free`.
+ Output metadata must be freed with `extractous_metadata_free`.
+ */
+int extractous_extractor_extract_file_with_deadline(struct CExtractor *handle,
+                                                    const char *path,
+                                                    uint64_t deadline_unix_ms,
+                                                    char **out_content,
+                                                    struct CMetadata **out_metadata);
+
+/*
+ Extracts content and metadata from a local file path into a string,
+ retrying once with a smaller output limit if the first attempt runs out of
+ memory.
+
+ When the first attempt fails with `ERR_OUT_OF_MEMORY`, the extraction is
+ repeated with the maximum string length capped at `hard_cap` characters and
+ `*out_capped` is set to 1; otherwise it is set to 0. The handle's own
+ configuration is not changed. Returns `ERR_INVALID_CONFIG` if `hard_cap`
+ is not positive.
+
+ Output strings must be freed with `extractous_string_free`.
+ Output metadata must be freed with `extractous_metadata_free`.
+ */
+int extractous_extractor_extract_file_to_string_bounded(struct CExtractor *handle,
+                                                        const char *path,
+                                                        int hard_cap,
+                                                        char **out_content,
+                                                        struct CMetadata **out_metadata,
+                                                        int *out_capped);
+
+/*
+ Extracts content and metadata from a local file path into a string,
+ stopping with `ERR_ABORTED` once `token` is cancelled.
+
+ The extraction streams on a worker thread that checks the token between
+ chunks, so a cancelled call returns promptly and the worker stops at the
+ next chunk boundary. Until it does, the worker keeps its slot of the limit
+ set with `extractous_set_max_concurrent_extractions`, and a call still
+ waiting for a slot stops waiting when cancelled. Because the content is
+ streamed, it is not limited by
+ `extractous_extractor_set_extract_string_max_length_mut`.
+
+ Output strings must be freed with `extractous_string_free`.
+ Output metadata must be freed with `extractous_metadata_free`.
+ */
+int extractous_extractor_extract_file_cancellable(struct CExtractor *handle,
+                                                  const char *path,
+                                                  const struct CCancelToken *token,
+                                                  char **out_content,
+                                                  struct CMetadata **out_metadata);
+
+/*
+ Extracts content from a local file path into a caller-provided buffer.
+
+ The content is written as UTF-8 without a null terminator and `*out_len` is
+ set to its size in bytes. If `buf_cap` is too small nothing is written,
+ `*out_len` is set to the required size and `ERR_BUFFER_TOO_SMALL` is
+ returned so the caller can grow the buffer and retry. `buf` may be NULL when
+ `buf_cap` is 0 to query the size.
+
+ `*out_metadata` is set to NULL whenever the result is not `ERR_OK`.
+ Output metadata must be freed with `extractous_metadata_free`.
+ */
+int extractous_extractor_extract_file_into(struct CExtractor *handle,
+                                           const char *path,
+                                           uint8_t *buf,
+                                           size_t buf_cap,
+                                           size_t *out_len,
+                                           struct CMetadata **out_metadata);
+
+/*
+ Extracts content from a local file path into memory from the caller's own
+ allocator, growing it through `realloc_cb` as the content streams in, so
+ no second copy of the content is made.
+
+ The content is read as from `extractous_extractor_extract_file`: in the
+ handle's encoding and output format, without the string post-processing
+ options. It is followed by a null terminator that `*out_len` does not
+ count.
+
+ Ownership of the block passes to the caller, who frees it with the
+ allocator behind `realloc_cb`, never with a function of this library.
+ `*out_ptr` is set on failure too, to the block as far as it was filled,
+ or NULL if nothing was allocated, as for an empty file. `ERR_OUT_OF_MEMORY`
+ means the callback returned NULL.
+
+ Output metadata must be freed with `extractous_metadata_free`; it is NULL
+ on failure.
+ */
+int extractous_extractor_extract_file_realloc(struct CExtractor *handle,
+                                              const char *path,
+                                              CReallocCallback realloc_cb,
+                                              uint8_t **out_ptr,
+                                              size_t *out_len,
+                                              struct CMetadata **out_metadata);
+
+/*
+ Extracts content and metadata from a local file path into a single JSON
+ document of the form `{"content": "...", "metadata": {"key": ["value"]}}`.
+
+ `*out_len` is set to the length of the JSON in bytes, excluding the null
+ terminator. The output string must be freed with `extractous_string_free`.
+ */
+int extractous_extractor_extract_file_to_json(struct CExtractor *handle,
+                                              const char *path,
+                                              char **out_json,
+                                              size_t *out_len);
+
+/*
+ Extracts content and metadata from a local file path into a stream.
+
+ An empty file is not parsed: it gives `ERR_OK` with a stream that is
+ already at its end and empty metadata.
+ */
+int extractous_extractor_extract_file(struct CExtractor *handle,
+                                      const char *path,
+                                      struct CStreamReader **out_reader,
+                                      struct CMetadata **out_metadata);
+
+/*
+ Extracts content and metadata from a byte slice into a string.
+
+ An empty slice is not parsed: it gives `ERR_OK` with empty content and
+ empty metadata.
+ */
+int extractous_extractor_extract_bytes_to_string(struct CExtractor *handle,
+                                                 const uint8_t *data,
+                                                 size_t data_len,
+                                                 char **out_content,
+                                                 struct CMetadata **out_metadata);
+
+/*
+ Extracts content and metadata from a byte slice into a string, reporting
+ `name` as the document's `resourceName` metadata.
+
+ The name replaces any `resourceName` the parser reports, so callers can
+ correlate results with their source. It does not affect format detection.
+ Returns `ERR_INVALID_UTF8` if `name` is not valid UTF-8.
+ Output strings must be freed with `extractous_string_free`.
+ Output metadata must be freed with `extractous_metadata_free`.
+ */
+int extractous_extractor_extract_bytes_to_string_named(struct CExtractor *handle,
+                                                       const uint8_t *data,
+                                                       size_t data_len,
+                                                       const char *name,
+                                                       char **out_content,
+                                                       struct CMetadata **out_metadata);
+
+/*
+ Extracts content and metadata from a byte slice into a string without
+ copying the input on the Rust side, for callers passing large or
+ memory-mapped buffers.
+
+ The slice is borrowed for the duration of the call and handed straight to
+ the core, as `extractous_extractor_extract_bytes_to_string` also does; this
+ entry point states that guarantee explicitly. The core still copies the
+ bytes once into the parser runtime, which cannot read native memory.
+ Output strings must be freed with `extractous_string_free`.
+ Output metadata must be freed with `extractous_metadata_free`.
+ */
+int extractous_extractor_extract_bytes_zero_copy(struct CExtractor *handle,
+                                                 const uint8_t *data,
+                                                 size_t data_len,
+                                                 char **out_content,
+                                                 struct CMetadata **out_metadata);
+
+/*
+ Extracts content and metadata from a byte slice into a string, reporting
+ whether the content was cut off by the configured maximum length.
+
+ `*out_truncated` is set to 1 when the content was longer than the limit
+ set with `extractous_extractor_set_extract_string_max_length_mut` or was
+ cut to the one set with `extractous_extractor_set_max_output_bytes_mut`,
+ 0 otherwise. The length limit is counted in UTF-16 code units like the
+ core does. The parser reads one character past it, so content exactly as
+ long as the limit is not reported as truncated.
+
+ An empty slice is not parsed: it gives `ERR_OK` with empty content, empty
+ metadata and `*out_truncated` set to 0.
+ */
+int extractous_extractor_extract_bytes_to_string_ex(struct CExtractor *handle,
+                                                    const uint8_t *data,
+                                                    size_t data_len,
+                                                    char **out_content,
+                                                    struct CMetadata **out_metadata,
+                                                    int *out_truncated);
+
+/*
+ Extracts content and metadata from a byte slice into a string, also
+ returning the MIME type the parser detected.
+
+ `*out_mime` is set to the first `Content-Type` value in the metadata, or
+ NULL if the parser did not report one. Pass NULL for `out_mime` to skip it.
+ An empty slice is not parsed: it gives `ERR_OK` with empty content, empty
+ metadata and `*out_mime` set to NULL.
+ Output strings must be freed with `extractous_string_free`.
+ Output metadata must be freed with `extractous_metadata_free`.
+ */
+int extractous_extractor_extract_bytes_to_string_detect(struct CExtractor *handle,
+                                                        const uint8_t *data,
+                                                        size_t data_len,
+                                                        char **out_content,
+                                                        struct CMetadata **out_metadata,
+                                                        char **out_mime);
+
+/*
+ Extracts content and metadata from a document split across several
+ non-contiguous buffers, read in order.
+
+ A single non-empty slice is parsed in place like
+ `extractous_extractor_extract_bytes_to_string` does. Several slices are
+ streamed in order into a file in the temp directory that the parser reads,
+ as the core only parses contiguous buffers and files; the file is removed
+ afterwards. An input transform takes one buffer, so with one set the
+ slices are read into a buffer for it instead.
+
+ Returns `ERR_NULL_POINTER` if any slice has a NULL pointer and a nonzero
+ length. Slices that hold no bytes between them are not parsed: they give
+ `ERR_OK` with empty content and empty metadata.
+ */
+int extractous_extractor_extract_iovec_to_string(struct CExtractor *handle,
+                                                 const struct CIoSlice *iov,
+                                                 size_t iov_count,
+                                                 char **out_content,
+                                                 struct CMetadata **out_metadata);
+
+/*
+ Extracts content and metadata from a byte slice into a stream.
+
+ An empty slice is not parsed: it gives `ERR_OK` with a stream that is
+ already at its end and empty metadata.
+ */
+int extractous_extractor_extract_bytes(struct CExtractor *handle,
+                                       const uint8_t *data,
+                                       size_t data_len,
+                                       struct CStreamReader **out_reader,
+                                       struct CMetadata **out_metadata);
+
+/*
+ Extracts content and metadata from a byte slice into a stream, reporting
+ `name` as the document's `resourceName` metadata, as
+ `extractous_extractor_extract_bytes_to_string_named` does.
+ */
+int extractous_extractor_extract_bytes_named(struct CExtractor *handle,
+                                             const uint8_t *data,
+                                             size_t data_len,
+                                             const char *name,
+                                             struct CStreamReader **out_reader,
+                                             struct CMetadata **out_metadata);
+
+/*
+ Extracts content and metadata from a URL into a string.
+
+ Network failures (unresolvable host, refused connection, HTTP 404) are
+ returned as `ERR_IO_ERROR`; the debug report names the specific reason.
+ An offline handle returns `ERR_INVALID_CONFIG`.
+
+ `Content-Length` is the length the server declared for the response. A
+ response that declares none, such as a chunked one, has no
+ `Content-Length` entry: the parser fetches and reads the body itself, so,
+ unlike for files and bytes, the size of the input is never known here.
  */
 int extractous_extractor_extract_url_to_string(struct CExtractor *handle,
                                                const char *url,
@@ -329,6 +1821,10 @@ int extractous_extractor_extract_url_to_string(struct CExtractor *handle,
 
 /*
  Extracts content and metadata from a URL into a stream.
+
+ Network failures and `Content-Length` are reported as for
+ `extractous_extractor_extract_url_to_string`. An offline handle returns
+ `ERR_INVALID_CONFIG`.
  */
 int extractous_extractor_extract_url(struct CExtractor *handle,
                                      const char *url,
@@ -340,11 +1836,247 @@ int extractous_extractor_extract_url(struct CExtractor *handle,
  */
 void extractous_string_free(char *s);
 
+/*
+ Frees UTF-16 content returned by `extractous_extractor_extract_file_to_utf16`.
+ `len_units` is the length it reported, without the terminator.
+ */
+void extractous_utf16_free(uint16_t *content, size_t len_units);
+
+/*
+ Frees the content string and metadata returned by one extraction.
+
+ This is the preferred cleanup for the `_to_string` functions, replacing
+ separate calls to `extractous_string_free` and `extractous_metadata_free`.
+ Either pointer may be NULL.
+ */
+void extractous_extraction_result_free(char *content, struct CMetadata *metadata);
+
+/*
+ Frees an array of `count` strings returned by this library, along with
+ each string in it.
+ */
+void extractous_string_array_free(char **values, size_t count);
+
+#if defined(EXTRACTOUS_FAULT_INJECTION)
+/*
+ Makes the next `count` extraction attempts fail with a transient JNI error
+ before reaching the parser.
+ */
+void extractous_debug_fail_next_jni_calls(int count);
+#endif
+
+/*
+ Returns the first image embedded in a local file, for document previews.
+
+ Images are read from the document's container, so Office Open XML,
+ OpenDocument and EPUB files are supported, whatever the parser
+ configuration; the first image entry in the archive is returned, which may
+ be a saved thumbnail. An image file is returned as-is. Returns
+ `ERR_NO_IMAGE` if the document holds no image, or one larger than 64 MiB,
+ and `ERR_UNSUPPORTED_FORMAT` for other formats, such as PDF, whose images
+ cannot be read this way.
+
+ `*out_mime` is set to the image's MIME type, taken from its leading bytes
+ or else its file extension. The handle's path restrictions and MIME
+ allow-list apply to `path`, and its embedded-file filters to the entries
+ searched.
+ The image must be freed with `extractous_buffer_free`.
+ The output string must be freed with `extractous_string_free`.
+ */
+int extractous_extractor_extract_first_image(struct CExtractor *handle,
+                                             const char *path,
+                                             uint8_t **out_image,
+                                             size_t *out_len,
+                                             char **out_mime);
+
+/*
+ Extracts a local file as XHTML in which the images it shows are linked to
+ image data returned alongside, for rendering the document with its
+ pictures.
+
+ Tika refers to an embedded image as `src="embedded:<name>"`. Each such
+ reference whose image can be read from the document's container, as for
+ `extractous_extractor_extract_first_image`, is rewritten to
+ `src="cid:image<i>"`, where `i` is the image's index in `*out_images`.
+ Images are numbered in order of first reference, and an image referenced
+ several times is returned once. References that cannot be resolved, such
+ as those to images inside a PDF, are left as they are.
+
+ The extraction runs in XML mode regardless of the handle's output format,
+ and the handle's post-processing applies to the XHTML.
+ The output string must be freed with `extractous_string_free`.
+ The images must be freed with `extractous_images_free`.
+ Output metadata must be freed with `extractous_metadata_free`.
+ */
+int extractous_extractor_extract_file_html_with_images(struct CExtractor *handle,
+                                                       const char *path,
+                                                       char **out_html,
+                                                       struct CImage **out_images,
+                                                       size_t *out_image_count,
+                                                       struct CMetadata **out_metadata);
+
+/*
+ Frees an array of `count` images returned by
+ `extractous_extractor_extract_file_html_with_images`, along with their
+ strings and data.
+ */
+void extractous_images_free(struct CImage *images, size_t count);
+
+/*
+ Sets the callback that receives the library's log messages, replacing any
+ previous one. Pass NULL to stop logging.
+
+ The callback runs on the thread that raised the message, possibly several
+ threads at once, so it and `ctx` must be thread-safe. It must not call
+ `extractous_set_log_callback` itself.
+ */
+void extractous_set_log_callback(CLogCallback callback, void *ctx);
+
+/*
+ Sets the separator used to join the values of multi-valued metadata keys
+ in `CMetadata`. The setting is global and applies to later extractions.
+
+ The default is the ASCII unit separator `"\x1f"`, which does not occur in
+ real metadata values the way a comma does.
+ */
+int extractous_set_metadata_value_separator(const char *sep);
+
+/*
+ Returns the separator currently used to join the values of multi-valued
+ metadata keys, so bindings can split them without assuming the default.
+ Metadata returned before a change was joined with the old separator, so
+ set it once, before extracting.
+
+ The returned string must be freed with `extractous_string_free`.
+ */
+char *extractous_get_metadata_value_separator(void);
+
+/*
+ Returns the values of `key` as a separate string for each value.
+
+ Values are split on the current separator (see
+ `extractous_set_metadata_value_separator`), so the separator should not be
+ changed between extraction and this call. An absent key returns `ERR_OK`
+ with `*out_count` set to 0 and `*out_values` set to NULL.
+ The array must be freed with `extractous_string_array_free`.
+ */
+int extractous_metadata_get_all_values(const struct CMetadata *metadata,
+                                       const char *key,
+                                       char ***out_values,
+                                       size_t *out_count);
+
+/*
+ Returns the chain of parser classes that handled the document, outermost
+ first, from the `X-TIKA:Parsed-By` metadata key.
+
+ Returns `ERR_METADATA_ABSENT` if the key is missing.
+ The array must be freed with `extractous_string_array_free`.
+ */
+int extractous_metadata_get_parsed_by(const struct CMetadata *metadata,
+                                      char ***out_parsers,
+                                      size_t *out_count);
+
+/*
+ Returns the document's `resourceName`: the file's name for file inputs,
+ or the name given with a `_named` byte extraction.
+
+ The string belongs to `metadata` and stays valid until it is freed; do not
+ free it separately. Returns NULL if the key is missing.
+ */
+const char *extractous_metadata_get_resource_name(const struct CMetadata *metadata);
+
+/*
+ Reads the document's page count from whichever metadata key the parser
+ used for it.
+
+ Returns `ERR_METADATA_ABSENT` if no page count was reported, as is the case
+ for single images and plain text, or if it is not a valid number.
+ */
+int extractous_metadata_get_page_count(const struct CMetadata *metadata, int *out_count);
+
+/*
+ Reads the document's creation date as seconds since the Unix epoch.
+
+ The date is taken from the first of `dcterms:created`,
+ `meta:creation-date`, `Creation-Date` and `pdf:docinfo:created` that is
+ present. ISO 8601 dates with or without fractional seconds, a UTC offset or
+ a time of day are accepted; dates without an offset are taken as UTC.
+ Returns `ERR_METADATA_ABSENT` if no date was reported or it cannot be parsed.
+ */
+int extractous_metadata_get_created_epoch(const struct CMetadata *metadata, int64_t *out);
+
+/*
+ Reads the document's last modification date as seconds since the Unix
+ epoch, from the first of `dcterms:modified`, `Last-Modified`,
+ `meta:save-date` and `pdf:docinfo:modified` that is present.
+
+ Dates are parsed as for `extractous_metadata_get_created_epoch`.
+ Returns `ERR_METADATA_ABSENT` if no date was reported or it cannot be parsed.
+ */
+int extractous_metadata_get_modified_epoch(const struct CMetadata *metadata, int64_t *out);
+
+/*
+ Returns a deep copy of `metadata` whose strings are allocated separately,
+ so the original can be freed while the copy is kept. Returns NULL if
+ `metadata` is NULL.
+ The copy must be freed with `extractous_metadata_free`.
+ */
+struct CMetadata *extractous_metadata_clone(const struct CMetadata *metadata);
+
+/*
+ Returns a new metadata structure holding a copy of each entry of
+ `metadata` whose key starts with `prefix`, such as `"dc:"` for the Dublin
+ Core fields. Matching is case-sensitive. Entries keep their order, and an
+ empty prefix copies every entry. Returns NULL if `metadata` or `prefix` is
+ NULL or `prefix` is not valid UTF-8.
+ The result must be freed with `extractous_metadata_free`.
+ */
+struct CMetadata *extractous_metadata_filter_by_prefix(const struct CMetadata *metadata,
+                                                       const char *prefix);
+
 /*
  Frees a metadata structure and all associated memory.
  */
 void extractous_metadata_free(struct CMetadata *metadata);
 
+/*
+ Fills `*out_info` with the page count, version, encryption flag and
+ linearized flag of the PDF at `path`.
+
+ The page count comes from Tika's `xmpTPg:NPages`, the version from
+ `pdf:PDFVersion` or else the file header, and the encryption flag from
+ `pdf:encrypted`. Tika does not report linearization, so the flag is set
+ when the file starts with a linearization dictionary. Files that are not
+ PDFs return `ERR_UNSUPPORTED_FORMAT`.
+
+ `*out_info` is the caller's; the strings it holds must be released with
+ `extractous_pdf_info_free`.
+ */
+int extractous_extractor_extract_pdf_info(struct CExtractor *handle,
+                                          const char *path,
+                                          struct CPdfInfo *out_info);
+
+/*
+ Frees the strings held by a `CPdfInfo` filled by
+ `extractous_extractor_extract_pdf_info` and sets them to NULL. The struct
+ itself is not freed.
+ */
+void extractous_pdf_info_free(struct CPdfInfo *info);
+
+/*
+ Copies up to `buffer_size` upcoming bytes of the stream into `buffer`
+ without consuming them, so the next reads return the same bytes again.
+
+ `*out_peeked` is set to the number of bytes copied, which is less than
+ `buffer_size` only when the stream ends first. Peeked bytes are held in
+ memory until read, so peeking far ahead costs as much memory as it reads.
+ Peeking can be mixed freely with reads at any point in the stream.
+ */
+int extractous_stream_peek(struct CStreamReader *handle,
+                           uint8_t *buffer,
+                           size_t buffer_size,
+                           size_t *out_peeked);
+
 /*
  Reads data from a stream into a user-provided buffer.
 
@@ -384,4 +2116,328 @@ void extractous_buffer_free(uint8_t *buffer, size_t size);
  */
 void extractous_stream_free(struct CStreamReader *handle);
 
+/*
+ Saves the current position of a stream, so that reading can go back to it
+ later with `extractous_stream_restore_cursor`.
+
+ The core streams cannot seek, so once a cursor is saved, the stream keeps
+ every byte read from the first saved cursor on in memory, until it is
+ freed. The cursor records the byte offset. Returns NULL if `handle` is
+ NULL. The cursor must be freed with `extractous_stream_cursor_free`.
+ */
+struct CStreamCursor *extractous_stream_save_cursor(struct CStreamReader *handle);
+
+/*
+ Moves a stream back, or forward, to a position saved with
+ `extractous_stream_save_cursor`, so the following reads return the same
+ bytes as they did from there.
+
+ Returns `ERR_NOT_SEEKABLE` if the cursor was saved from a different
+ stream. The cursor stays valid and can be restored again.
+ */
+int extractous_stream_restore_cursor(struct CStreamReader *handle,
+                                     const struct CStreamCursor *cursor);
+
+/*
+ Frees a cursor returned by `extractous_stream_save_cursor`.
+ */
+void extractous_stream_cursor_free(struct CStreamCursor *cursor);
+
+/*
+ Extracts a local file and reports its XHTML structure through SAX-style
+ callbacks, so callers get headings, paragraphs and tables without writing
+ an XML parser.
+
+ The extraction runs in XML mode regardless of the handle's output format.
+ Any callback may be NULL to skip those events. Strings passed to callbacks
+ are only valid for the duration of the call.
+
+ Output metadata must be freed with `extractous_metadata_free`.
+ */
+int extractous_extractor_extract_file_sax(struct CExtractor *handle,
+                                          const char *path,
+                                          CStartElementCallback start_elem_cb,
+                                          CEndElementCallback end_elem_cb,
+                                          CCharactersCallback chars_cb,
+                                          void *ctx,
+                                          struct CMetadata **out_metadata);
+
+/*
+ Extracts a local file in XML mode and passes the XML to `write_cb` in
+ chunks as the parser produces it, so documents too large to hold as one
+ string can be written out or parsed incrementally.
+
+ The extraction runs in XML mode regardless of the handle's output format,
+ and the string post-processing options do not apply. Chunks end just
+ after a `>` so that they break at element edges, except when a run of
+ text is too long to buffer, when they end at a UTF-8 character boundary.
+ Concatenated, the chunks form the complete document. If the callback
+ returns anything but `ERR_OK`, extraction stops with `ERR_ABORTED`. An
+ empty file gives no chunks and empty metadata.
+
+ Output metadata must be freed with `extractous_metadata_free`; it is NULL
+ on failure.
+ */
+int extractous_extractor_extract_file_xml_stream(struct CExtractor *handle,
+                                                 const char *path,
+                                                 CWriteCallback write_cb,
+                                                 void *ctx,
+                                                 struct CMetadata **out_metadata);
+
+/*
+ Extracts only pages `start` to `end` (1-based, inclusive) of a local file
+ into a string.
+
+ The core has no page selection, so the whole document is parsed in XML mode
+ and the text of the `<div class="page">` elements Tika emits for paged
+ formats such as PDF is kept for the selected pages. A range reaching past
+ the last page is clamped to it, and `start` below 1 is treated as 1.
+ Documents without page markup count as a single page. Returns
+ `ERR_INVALID_CONFIG` if `end < start`.
+
+ Output strings must be freed with `extractous_string_free`.
+ Output metadata must be freed with `extractous_metadata_free`.
+ */
+int extractous_extractor_extract_file_range(struct CExtractor *handle,
+                                            const char *path,
+                                            int start,
+                                            int end,
+                                            char **out_content,
+                                            struct CMetadata **out_metadata);
+
+/*
+ Extracts a local file into one result per page, for page-level indexing
+ and search highlighting.
+
+ The whole document is parsed in XML mode and split at the
+ `<div class="page">` elements Tika emits for paged formats such as PDF.
+ A document without page markup is a single page. The core reports
+ metadata for the whole document only, so each page's metadata is a copy
+ of the document's. `*out_count` is at least 1 on success. The array must
+ be freed with `extractous_pages_free`.
+ */
+int extractous_extractor_extract_file_per_page(struct CExtractor *handle,
+                                               const char *path,
+                                               struct CPageResult **out_pages,
+                                               size_t *out_count);
+
+/*
+ Frees an array of `count` pages returned by
+ `extractous_extractor_extract_file_per_page`, along with their strings and
+ metadata.
+ */
+void extractous_pages_free(struct CPageResult *pages, size_t count);
+
+/*
+ Classifies a local file as a digital document with a text layer, a scan
+ without one, or a mix of both, so callers can choose an OCR strategy
+ before extracting.
+
+ `*out_kind` is one of the `DOC_KIND_*` constants. Image files are
+ `DOC_KIND_SCANNED` without being parsed. Other files are parsed in XML mode
+ with PDF OCR turned off for this call, and each page of Tika's page markup
+ with fewer than 10 non-whitespace characters counts as scanned: a document
+ is `DOC_KIND_SCANNED` if every page is, `DOC_KIND_DIGITAL` if none is and
+ `DOC_KIND_MIXED` otherwise. This is a heuristic: a blank page counts as
+ scanned, and a scan with a hidden OCR text layer counts as digital.
+ Documents without page markup are `DOC_KIND_DIGITAL` if they have any
+ text and `DOC_KIND_UNKNOWN` otherwise, as are empty files.
+ */
+int extractous_extractor_classify_file(struct CExtractor *handle, const char *path, int *out_kind);
+
+/*
+ Extracts a local file into its body text and its footnote and endnote
+ text, as two strings.
+
+ The whole document is parsed in XML mode and the text inside elements
+ whose `class` marks a footnote or endnote, such as `footnote` or
+ `endnotes`, goes to `*out_footnotes`; the rest of the body goes to
+ `*out_body`. Only some of Tika's parsers mark notes this way. When a
+ document has no such markup, all of its text is in `*out_body`, including
+ any note text the parser wrote inline, and `*out_footnotes` is empty.
+
+ Output strings must be freed with `extractous_string_free`.
+ Output metadata must be freed with `extractous_metadata_free`.
+ */
+int extractous_extractor_extract_file_structured_parts(struct CExtractor *handle,
+                                                       const char *path,
+                                                       char **out_body,
+                                                       char **out_footnotes,
+                                                       struct CMetadata **out_metadata);
+
+/*
+ Extracts a local file once in XML mode and returns both the XHTML and a
+ plain-text rendition of it, for callers that want the text for display
+ and the markup for structure without extracting twice.
+
+ The text is the character data of the XHTML body, which matches what
+ text mode returns apart from, at most, differences in whitespace. The
+ string post-processing options apply to each output as for its format,
+ whatever the handle's output format.
+
+ Output strings must be freed with `extractous_string_free`.
+ Output metadata must be freed with `extractous_metadata_free`.
+ */
+int extractous_extractor_extract_file_dual(struct CExtractor *handle,
+                                           const char *path,
+                                           char **out_text,
+                                           char **out_xhtml,
+                                           struct CMetadata **out_metadata);
+
+/*
+ Lists the names of the embedded resources, such as email attachments and
+ OLE objects, that the parser found in a local file.
+
+ Names come from the markers Tika leaves in its XHTML for each embedded
+ document: the heading of a `package-entry` div or the `id` of an `embedded`
+ div. Unnamed resources are skipped, and only resources the core parses
+ recursively are seen. The array must be freed with
+ `extractous_string_array_free`.
+ */
+int extractous_extractor_list_embedded_file(struct CExtractor *handle,
+                                            const char *path,
+                                            char ***out_names,
+                                            size_t *out_count);
+
+/*
+ Extracts a local file into one segment per document: the file itself
+ first, followed by each embedded resource, such as an archive entry or an
+ email attachment, in document order.
+
+ Embedded resources are found from the markers Tika leaves in its XHTML, as
+ for `extractous_extractor_list_embedded_file`, and the text inside a
+ resource belongs to its segment only; text nested in a deeper resource goes
+ to that resource. The first segment is named after the file and carries
+ the extraction's metadata. The core reports metadata for the whole
+ extraction only, so an embedded segment's metadata holds just its
+ `resourceName` when the resource is named, and the `language` of its text
+ when `extractous_extractor_set_segment_language_mut` is enabled.
+
+ `*out_count` is at least 1 on success. The array must be freed with
+ `extractous_segments_free`.
+ */
+int extractous_extractor_extract_file_segmented(struct CExtractor *handle,
+                                                const char *path,
+                                                struct CSegment **out_segments,
+                                                size_t *out_count);
+
+/*
+ Frees an array of `count` segments returned by
+ `extractous_extractor_extract_file_segmented`, along with their strings and
+ metadata.
+ */
+void extractous_segments_free(struct CSegment *segments, size_t count);
+
+/*
+ Extracts the tables of a local file, such as the sheets of a spreadsheet,
+ as grids of cell strings.
+
+ The tables are read from the `<table>` elements of Tika's XHTML, in
+ document order; a table nested in a cell is kept as text of that cell. Each
+ table is named after the closest `<h1>` heading before it, which for
+ spreadsheets is the sheet name. Short rows are padded with empty cells to
+ the width of the longest. A merged cell's text appears once, in its top-left
+ position, and the other positions it covers are empty: spreadsheets are
+ written that way by Tika, and `colspan` and `rowspan` are expanded the same
+ way for other formats. Cell text is trimmed.
+
+ The array must be freed with `extractous_tables_free`.
+ */
+int extractous_extractor_extract_file_tables(struct CExtractor *handle,
+                                             const char *path,
+                                             struct CTable **out_tables,
+                                             size_t *out_count);
+
+/*
+ Frees an array of `count` tables returned by
+ `extractous_extractor_extract_file_tables`, along with their strings.
+ */
+void extractous_tables_free(struct CTable *tables, size_t count);
+
+/*
+ Extracts the outline of a local file, for navigation such as a table of
+ contents.
+
+ The outline is read from Tika's XHTML. For a PDF it is the bookmark tree,
+ which Tika writes as nested lists after the pages, with each bookmark's
+ nesting depth as its level. Tika does not report where a bookmark points,
+ so its page is the first page, no earlier than the previous bookmark's,
+ whose text contains its title, or 0 if there is none. Other documents are
+ outlined by their `<h1>` to `<h6>` headings, with the heading's rank as
+ its level and its page only known for paged formats. Headings of embedded
+ resources are not included. A document without bookmarks or headings has
+ an empty outline: `*out_count` is 0.
+
+ The array must be freed with `extractous_outline_free`.
+ */
+int extractous_extractor_extract_file_outline(struct CExtractor *handle,
+                                              const char *path,
+                                              struct COutlineEntry **out_entries,
+                                              size_t *out_count);
+
+/*
+ Frees an array of `count` entries returned by
+ `extractous_extractor_extract_file_outline`, along with their titles.
+ */
+void extractous_outline_free(struct COutlineEntry *entries, size_t count);
+
+/*
+ Extracts the text runs of a local file with their bounding boxes, for
+ layout-aware processing such as reading invoices.
+
+ Requires `extractous_extractor_set_include_coordinates_mut`, without which
+ `ERR_INVALID_CONFIG` is returned. The core extractor reports text and its
+ structure but not where it sits on the page, for PDFs and OCR alike, so
+ this currently returns `ERR_UNSUPPORTED_FEATURE` for every document
+ rather than runs without positions. `*out_runs` is then NULL and
+ `*out_count` 0.
+
+ The array must be freed with `extractous_text_runs_free`.
+ */
+int extractous_extractor_extract_file_positioned(struct CExtractor *handle,
+                                                 const char *path,
+                                                 struct CTextRun **out_runs,
+                                                 size_t *out_count);
+
+/*
+ Frees an array of `count` text runs returned by
+ `extractous_extractor_extract_file_positioned`, along with their strings.
+ */
+void extractous_text_runs_free(struct CTextRun *runs, size_t count);
+
+/*
+ Sets how string output containing NUL characters, which cannot be passed
+ to C as a null-terminated string, is handled. The setting is global and
+ applies to later extractions.
+
+ - `NUL_POLICY_ERROR` (the default) returns NULL content, as earlier
+   versions did.
+ - `NUL_POLICY_REPLACE` replaces each NUL with U+FFFD.
+ - `NUL_POLICY_TRUNCATE` cuts the content at the first NUL.
+
+ Returns `ERR_INVALID_ENUM` for an unknown policy.
+ */
+int extractous_set_nul_byte_policy(int policy);
+
+/*
+ Sets the most extractions that may run at once across all handles and
+ threads. 0, the default, means no limit; negative values are ignored.
+
+ An extraction over the limit waits for another to finish, or fails with
+ `ERR_BUSY` when `extractous_set_wait_for_extraction_slot` has turned
+ waiting off. Lowering the limit does not interrupt extractions already
+ running. For stream outputs the limit covers starting the extraction,
+ not reading the stream. An extraction abandoned by its caller, after a
+ deadline passed or a token was cancelled, keeps its slot until its worker
+ finishes.
+ */
+void extractous_set_max_concurrent_extractions(int limit);
+
+/*
+ Sets whether an extraction over the limit set with
+ `extractous_set_max_concurrent_extractions` waits for a slot, the
+ default, or fails at once with `ERR_BUSY`.
+ */
+void extractous_set_wait_for_extraction_slot(bool wait);
+
 #endif  /* EXTRACTOUS_H */
@@ -24,7 +24,10 @@ fn main() {
     // 3. Ensure extractous dependency built libraries are discoverable
     setup_extractous_libs(&target, &profile);
 
-    // 4. Configure rerun triggers
+    // 4. Record the Tika version the native libraries were built from
+    export_tika_version();
+
+    // 5. Configure rerun triggers
     configure_rerun_triggers();
 }
 
@@ -97,6 +100,57 @@ fn setup_extractous_libs(_target: &str, _profile: &str) {
     }
 }
 
+fn export_tika_version() {
+    // An explicit override wins, e.g. for libraries built outside cargo
+    println!("cargo:rerun-if-env-changed=EXTRACTOUS_TIKA_VERSION");
+    if let Ok(version) = env::var("EXTRACTOUS_TIKA_VERSION") {
+        println!("cargo:rustc-env=EXTRACTOUS_TIKA_VERSION={}", version.trim());
+        return;
+    }
+
+    let out_dir = env::var("OUT_DIR").unwrap();
+    let build_dir = PathBuf::from(&out_dir)
+        .parent().unwrap()
+        .parent().unwrap()
+        .to_path_buf();
+
+    let Ok(entries) = fs::read_dir(&build_dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let name = entry.file_name();
+        if !name.to_string_lossy().starts_with("extractous-") {
+            continue;
+        }
+        let out = entry.path().join("out");
+        if let Some(version) = find_tika_version(&out) {
+            println!("cargo:rustc-env=EXTRACTOUS_TIKA_VERSION={}", version);
+            println!("cargo:warning=Bundled Tika version: {}", version);
+            return;
+        }
+    }
+}
+
+/// Looks for the Tika version in an extractous build output directory: first
+/// a `tika-version.txt` shipped next to the native libs, then the
+/// `tika-core` dependency of the copied `tika-native` Gradle project.
+fn find_tika_version(out: &std::path::Path) -> Option<String> {
+    if let Ok(text) = fs::read_to_string(out.join("libs").join("tika-version.txt")) {
+        let version = text.trim();
+        if !version.is_empty() {
+            return Some(version.to_string());
+        }
+    }
+
+    let gradle = fs::read_to_string(out.join("tika-native").join("build.gradle")).ok()?;
+    let start = gradle.find("org.apache.tika:tika-core:")? + "org.apache.tika:tika-core:".len();
+    let version: String = gradle[start..]
+        .chars()
+        .take_while(|c| c.is_ascii_alphanumeric() || *c == '.' || *c == '-')
+        .collect();
+    (!version.is_empty()).then_some(version)
+}
+
 fn configure_rerun_triggers() {
     println!("cargo:rerun-if-changed=src");
     println!("cargo:rerun-if-changed=build.rs");
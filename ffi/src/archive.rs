@@ -0,0 +1,275 @@
+//! Guards applied to archives before the core parser recurses into them.
+
+use crate::detect;
+use crate::log;
+use crate::types::LOG_INFO;
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use zip::CompressionMethod;
+use zip::write::SimpleFileOptions;
+
+/// A temporary copy of an input, removed when dropped.
+pub(crate) struct TempCopy(PathBuf);
+
+impl TempCopy {
+    /// Takes over the file at `path`, which is removed when this is dropped.
+    pub(crate) fn new(path: PathBuf) -> Self {
+        TempCopy(path)
+    }
+
+    pub(crate) fn path(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempCopy {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+/// What happens to an archive entry in the copy that is parsed.
+#[derive(PartialEq)]
+enum Action {
+    Keep,
+    /// Left out of the copy.
+    Drop,
+    /// Kept under its name with no content, so it is listed but not parsed.
+    Empty,
+}
+
+/// The changes made to an archive's own entries before it is parsed.
+pub(crate) struct EntryFilter<'a> {
+    /// Entries that decompress to more bytes than this are left out; 0 means
+    /// no limit.
+    pub(crate) max_bytes: u64,
+    /// Entries whose sniffed MIME type matches one of these are emptied.
+    pub(crate) skip_mime_types: &'a [String],
+    /// Whether entries with the same bytes as an earlier one are emptied.
+    pub(crate) dedupe: bool,
+}
+
+impl EntryFilter<'_> {
+    /// Returns true if the filter can change anything.
+    pub(crate) fn is_active(&self) -> bool {
+        self.max_bytes > 0 || !self.skip_mime_types.is_empty() || self.dedupe
+    }
+}
+
+/// Copies the plain ZIP archive at `path` for parsing with `filter` applied.
+///
+/// Oversized entries are left out, with a warning logged for each. Entries of
+/// skipped types are emptied, with an info message logged for each, and so
+/// are duplicate entries, with one info message giving their number. An
+/// emptied entry keeps its name, so the parser still reports it. An entry's
+/// size is measured by decompressing it, so a header that understates the
+/// size does not let it through.
+///
+/// Returns `None` if the file is not a plain ZIP archive or nothing needs
+/// changing, in which case the original should be used. ZIP-based formats
+/// such as OOXML and ODF are left alone, since every part is needed to read
+/// the document. Only the archive's own entries are checked: entries inside
+/// nested archives are left to the parser.
+pub(crate) fn filter_entries(path: &str, filter: &EntryFilter) -> io::Result<Option<TempCopy>> {
+    static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+    let head = detect::read_head(path)?;
+    if detect::sniff_mime(&head) != "application/zip" {
+        return Ok(None);
+    }
+    let mut archive = zip::ZipArchive::new(File::open(path)?).map_err(io::Error::other)?;
+    if is_document_package(&archive) {
+        return Ok(None);
+    }
+
+    let mut actions = Vec::with_capacity(archive.len());
+    let mut seen = HashSet::new();
+    let mut duplicates = 0;
+    for i in 0..archive.len() {
+        let entry = archive.by_index_raw(i).map_err(io::Error::other)?;
+        let (name, size, is_dir) = (entry.name().to_string(), entry.size(), entry.is_dir());
+        drop(entry);
+        let action = if is_dir {
+            Action::Keep
+        } else if filter.max_bytes > 0 && is_oversized(&mut archive, i, size, filter.max_bytes) {
+            log::warn(&format!(
+                "skipping embedded file {:?} in {}: it is over the {} byte limit",
+                name, path, filter.max_bytes
+            ));
+            Action::Drop
+        } else if let Some(mime) = skipped_mime(&mut archive, i, filter.skip_mime_types) {
+            log::log(
+                LOG_INFO,
+                &format!(
+                    "not parsing embedded file {:?} in {}: {} is skipped",
+                    name, path, mime
+                ),
+            );
+            Action::Empty
+        } else if filter.dedupe
+            && size > 0
+            && entry_sha256(&mut archive, i).is_some_and(|hash| !seen.insert(hash))
+        {
+            duplicates += 1;
+            Action::Empty
+        } else {
+            Action::Keep
+        };
+        actions.push(action);
+    }
+    if duplicates > 0 {
+        log::log(
+            LOG_INFO,
+            &format!(
+                "not parsing {} duplicate embedded files in {}",
+                duplicates, path
+            ),
+        );
+    }
+    if actions.iter().all(|a| *a == Action::Keep) {
+        return Ok(None);
+    }
+
+    let ext = Path::new(path)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or("zip");
+    let copy = TempCopy(crate::temp_dir().join(format!(
+        "extractous-embedded-{}-{}.{}",
+        std::process::id(),
+        NEXT_ID.fetch_add(1, Ordering::Relaxed),
+        ext
+    )));
+    let mut writer = zip::ZipWriter::new(File::create(copy.path())?);
+    for (i, action) in actions.iter().enumerate() {
+        let entry = archive.by_index_raw(i).map_err(io::Error::other)?;
+        match action {
+            Action::Keep => writer.raw_copy_file(entry).map_err(io::Error::other)?,
+            Action::Drop => {}
+            Action::Empty => {
+                let options =
+                    SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+                writer
+                    .start_file(entry.name(), options)
+                    .map_err(io::Error::other)?;
+            }
+        }
+    }
+    writer.finish().map_err(io::Error::other)?;
+    Ok(Some(copy))
+}
+
+/// Returns the name of the first entry of the plain ZIP archive at `path`
+/// that refers to a resource on the network, with the URL it refers to, as
+/// `detect::network_reference` finds them. Only text entries are read, and
+/// as with `filter_entries`, document packages and nested archives are left
+/// alone. An entry that cannot be decompressed is passed over.
+pub(crate) fn network_reference(path: &str) -> io::Result<Option<(String, String)>> {
+    let head = detect::read_head(path)?;
+    if detect::sniff_mime(&head) != "application/zip" {
+        return Ok(None);
+    }
+    let mut archive = zip::ZipArchive::new(File::open(path)?).map_err(io::Error::other)?;
+    if is_document_package(&archive) {
+        return Ok(None);
+    }
+    for i in 0..archive.len() {
+        let Ok(mut entry) = archive.by_index(i) else {
+            continue;
+        };
+        let mut doc = Vec::with_capacity(detect::SNIFF_LEN);
+        if entry.is_dir()
+            || (&mut entry)
+                .take(detect::SNIFF_LEN as u64)
+                .read_to_end(&mut doc)
+                .is_err()
+            || !detect::looks_like_text(&doc)
+            || entry.read_to_end(&mut doc).is_err()
+        {
+            continue;
+        }
+        if let Some(url) = detect::network_reference(&doc) {
+            return Ok(Some((entry.name().to_string(), url)));
+        }
+    }
+    Ok(None)
+}
+
+/// Returns true if the archive is a ZIP-based document whose leading bytes
+/// did not give it away: OOXML packages list their parts in
+/// `[Content_Types].xml`, and ODF and EPUB files name their type in a
+/// `mimetype` entry.
+fn is_document_package(archive: &zip::ZipArchive<File>) -> bool {
+    archive.index_for_name("[Content_Types].xml").is_some()
+        || archive.index_for_name("mimetype").is_some()
+}
+
+/// Returns true if archive entry `index` declares or decompresses to more than
+/// `max_bytes`. At most one byte over the limit is decompressed. An entry
+/// that cannot be decompressed, as when it is encrypted, is judged by its
+/// declared size alone.
+fn is_oversized(
+    archive: &mut zip::ZipArchive<File>,
+    index: usize,
+    declared: u64,
+    max_bytes: u64,
+) -> bool {
+    if declared > max_bytes {
+        return true;
+    }
+    let Ok(entry) = archive.by_index(index) else {
+        return false;
+    };
+    let mut limited = entry.take(max_bytes + 1);
+    let mut buf = vec![0u8; 64 * 1024];
+    let mut read = 0;
+    // Bytes read before a decompression error still count.
+    while let Ok(n @ 1..) = limited.read(&mut buf) {
+        read += n as u64;
+    }
+    read > max_bytes
+}
+
+/// Returns the sniffed MIME type of archive entry `index` if it matches one of
+/// `skip_mime_types`. An entry that cannot be decompressed, as when it is
+/// encrypted, is not skipped.
+fn skipped_mime(
+    archive: &mut zip::ZipArchive<File>,
+    index: usize,
+    skip_mime_types: &[String],
+) -> Option<&'static str> {
+    if skip_mime_types.is_empty() {
+        return None;
+    }
+    let entry = archive.by_index(index).ok()?;
+    let mut head = Vec::with_capacity(detect::SNIFF_LEN);
+    entry
+        .take(detect::SNIFF_LEN as u64)
+        .read_to_end(&mut head)
+        .ok()?;
+    let mime = detect::sniff_mime(&head);
+    skip_mime_types
+        .iter()
+        .any(|skip| detect::mime_matches(skip, mime))
+        .then_some(mime)
+}
+
+/// Hashes the decompressed bytes of archive entry `index`, or returns `None`
+/// if they cannot be read.
+fn entry_sha256(archive: &mut zip::ZipArchive<File>, index: usize) -> Option<Vec<u8>> {
+    let mut entry = archive.by_index(index).ok()?;
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0u8; 64 * 1024];
+    loop {
+        let n = entry.read(&mut buf).ok()?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    Some(hasher.finalize().to_vec())
+}
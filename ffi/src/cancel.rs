@@ -0,0 +1,44 @@
+//! Cancellation tokens for long-running extractions.
+
+use crate::types::CCancelToken;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// The state behind a `CCancelToken` handle.
+///
+/// The flag is shared with any worker thread using the token, so freeing the
+/// handle while an extraction is still winding down is safe.
+pub(crate) struct CancelToken {
+    pub(crate) flag: Arc<AtomicBool>,
+}
+
+/// Creates a new, uncancelled token.
+/// The returned handle must be freed with `extractous_cancel_token_free`.
+#[unsafe(no_mangle)]
+pub extern "C" fn extractous_cancel_token_new() -> *mut CCancelToken {
+    let token = Box::new(CancelToken {
+        flag: Arc::new(AtomicBool::new(false)),
+    });
+    Box::into_raw(token) as *mut CCancelToken
+}
+
+/// Requests cancellation of every extraction using this token.
+///
+/// Unlike other handles, a token may be cancelled from any thread while an
+/// extraction on another thread is using it. Cancellation cannot be undone.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_cancel_token_cancel(token: *const CCancelToken) {
+    if !token.is_null() {
+        unsafe { &*(token as *const CancelToken) }
+            .flag
+            .store(true, Ordering::Release);
+    }
+}
+
+/// Frees the memory associated with a cancellation token.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_cancel_token_free(token: *mut CCancelToken) {
+    if !token.is_null() {
+        drop(unsafe { Box::from_raw(token as *mut CancelToken) });
+    }
+}
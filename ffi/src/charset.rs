@@ -0,0 +1,83 @@
+//! Single-byte and UTF-8 encodings extracted text can be handed back in, and
+//! the negotiation between a caller's preference list and the content.
+
+/// An encoding content can be returned in as a null-terminated string.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Charset {
+    Utf8,
+    UsAscii,
+    Latin1,
+    Windows1252,
+}
+
+/// The characters of Windows-1252 bytes 0x80–0x9F. The five bytes the code
+/// page leaves undefined map to the C1 control of the same value, as in the
+/// WHATWG Encoding Standard.
+const WINDOWS_1252_HIGH: [char; 32] = [
+    '\u{20AC}', '\u{0081}', '\u{201A}', '\u{0192}', '\u{201E}', '\u{2026}', '\u{2020}', '\u{2021}',
+    '\u{02C6}', '\u{2030}', '\u{0160}', '\u{2039}', '\u{0152}', '\u{008D}', '\u{017D}', '\u{008F}',
+    '\u{0090}', '\u{2018}', '\u{2019}', '\u{201C}', '\u{201D}', '\u{2022}', '\u{2013}', '\u{2014}',
+    '\u{02DC}', '\u{2122}', '\u{0161}', '\u{203A}', '\u{0153}', '\u{009D}', '\u{017E}', '\u{0178}',
+];
+
+impl Charset {
+    /// Looks up a charset label, ignoring case and surrounding whitespace.
+    /// `*` stands for UTF-8.
+    pub(crate) fn from_label(label: &str) -> Option<Self> {
+        match label.trim().to_ascii_lowercase().as_str() {
+            "utf-8" | "utf8" | "*" => Some(Self::Utf8),
+            "us-ascii" | "ascii" => Some(Self::UsAscii),
+            "iso-8859-1" | "iso8859-1" | "latin1" | "l1" => Some(Self::Latin1),
+            "windows-1252" | "cp1252" => Some(Self::Windows1252),
+            _ => None,
+        }
+    }
+
+    /// Returns the charset's IANA name.
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            Self::Utf8 => "UTF-8",
+            Self::UsAscii => "US-ASCII",
+            Self::Latin1 => "ISO-8859-1",
+            Self::Windows1252 => "windows-1252",
+        }
+    }
+
+    /// Encodes `text`, or returns `None` if it has a character the charset
+    /// cannot represent.
+    pub(crate) fn encode(self, text: &str) -> Option<Vec<u8>> {
+        match self {
+            Self::Utf8 => Some(text.as_bytes().to_vec()),
+            Self::UsAscii => text.is_ascii().then(|| text.as_bytes().to_vec()),
+            Self::Latin1 => text.chars().map(|c| u8::try_from(c).ok()).collect(),
+            Self::Windows1252 => text.chars().map(windows_1252_byte).collect(),
+        }
+    }
+}
+
+fn windows_1252_byte(c: char) -> Option<u8> {
+    match c as u32 {
+        0..=0x7F | 0xA0..=0xFF => Some(c as u8),
+        _ => WINDOWS_1252_HIGH
+            .iter()
+            .position(|&h| h == c)
+            .map(|i| 0x80 + i as u8),
+    }
+}
+
+/// Parses a comma-separated preference list such as `"windows-1252, utf-8"`
+/// into the charsets it names, in order. Parameters such as `;q=0.5` are
+/// ignored, as are labels that are not supported.
+pub(crate) fn parse_accept(accept: &str) -> Vec<Charset> {
+    accept
+        .split(',')
+        .filter_map(|item| Charset::from_label(item.split(';').next().unwrap_or("")))
+        .collect()
+}
+
+/// Encodes `text` in the first of `accepted` that can represent it.
+pub(crate) fn negotiate(accepted: &[Charset], text: &str) -> Option<(Charset, Vec<u8>)> {
+    accepted
+        .iter()
+        .find_map(|&charset| charset.encode(text).map(|bytes| (charset, bytes)))
+}
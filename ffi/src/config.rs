@@ -107,6 +107,9 @@ pub unsafe extern "C" fn extractous_pdf_config_set_extract_annotation_text(
 }
 
 /// Creates a new Office parser configuration with default settings.
+///
+/// Spreadsheet output always starts each sheet with its name, as Tika emits
+/// it; the core has no setting to leave sheet names out or change the marker.
 // #[must_use]
 #[unsafe(no_mangle)]
 pub extern "C" fn extractous_office_config_new() -> *mut COfficeParserConfig {
@@ -166,6 +169,22 @@ pub unsafe extern "C" fn extractous_office_config_set_include_shape_based_conten
     });
 }
 
+/// Enables or disables inclusion of header and footer text (default enabled).
+///
+/// Tika takes this text from the header and footer parts that Office files
+/// declare, so it is exact for those, but text that merely repeats on every
+/// page inside the body is still extracted. PDFs have no such parts, so there
+/// is no PDF equivalent and their running headers are always extracted.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_office_config_set_include_headers_and_footers(
+    handle: *mut COfficeParserConfig,
+    value: bool,
+) {
+    update_config!(handle, CoreOfficeConfig, |config| {
+        config.set_include_headers_and_footers(value)
+    });
+}
+
 /// Creates a new Tesseract OCR configuration with default settings.
 // #[must_use]
 #[unsafe(no_mangle)]
@@ -231,6 +250,22 @@ pub unsafe extern "C" fn extractous_ocr_config_set_enable_image_preprocessing(
     });
 }
 
+/// Enables or disables automatic rotation correction before recognition.
+///
+/// When enabled, Tesseract runs orientation and script detection (OSD) on each
+/// image and rotates it upright first, which helps with rotated or skewed scans.
+/// OSD is an extra Tesseract pass per image, so expect OCR to take noticeably
+/// longer; leave this off for scans that are known to be upright.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_ocr_config_set_apply_rotation(
+    handle: *mut CTesseractOcrConfig,
+    value: bool,
+) {
+    update_config!(handle, CoreOcrConfig, |config| {
+        config.set_apply_rotation(value)
+    });
+}
+
 /// Sets the timeout for the Tesseract process in seconds.
 #[unsafe(no_mangle)]
 pub unsafe extern "C" fn extractous_ocr_config_set_timeout_seconds(
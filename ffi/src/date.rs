@@ -0,0 +1,106 @@
+//! Parsing of the ISO 8601 timestamps Tika writes into date metadata.
+//!
+//! Tika normalizes dates to ISO 8601, but depending on the parser the value
+//! may carry fractional seconds, a numeric UTC offset, no zone at all or no
+//! time of day. Values without a zone are taken as UTC.
+
+/// Parses a Tika date value into seconds since the Unix epoch.
+///
+/// Accepts `YYYY-MM-DD`, optionally followed by `T` or a space and
+/// `hh:mm[:ss[.fff]]`, optionally followed by `Z` or an offset of the form
+/// `+hh:mm`, `+hhmm` or `+hh`.
+pub(crate) fn parse_epoch_seconds(value: &str) -> Option<i64> {
+    let value = value.trim();
+    let (date, rest) = value.split_at(value.find(['T', 't', ' ']).unwrap_or(value.len()));
+
+    let mut fields = date.split('-');
+    let year = digits(fields.next()?, 4)?;
+    let month = digits(fields.next()?, 2)?;
+    let day = digits(fields.next()?, 2)?;
+    if fields.next().is_some()
+        || !(1..=12).contains(&month)
+        || day < 1
+        || day > days_in_month(year, month)
+    {
+        return None;
+    }
+    let mut seconds = days_from_civil(year, month, day) * 86_400;
+    if rest.is_empty() {
+        return Some(seconds);
+    }
+
+    let rest = &rest[1..];
+    let (time, zone) = rest.split_at(rest.find(['Z', 'z', '+', '-']).unwrap_or(rest.len()));
+    let mut fields = time.split(':');
+    let hour = digits(fields.next()?, 2)?;
+    let minute = digits(fields.next()?, 2)?;
+    let second = match fields.next() {
+        Some(s) => {
+            let (whole, fraction) = s.split_once('.').unwrap_or((s, "0"));
+            if fraction.is_empty() || !fraction.bytes().all(|b| b.is_ascii_digit()) {
+                return None;
+            }
+            digits(whole, 2)?
+        }
+        None => 0,
+    };
+    // A second of 60 allows for leap seconds.
+    if fields.next().is_some() || hour > 23 || minute > 59 || second > 60 {
+        return None;
+    }
+    seconds += hour * 3600 + minute * 60 + second;
+
+    Some(seconds - utc_offset_seconds(zone)?)
+}
+
+/// Parses an empty zone, `Z`, or an offset of the form `+hh:mm`, `+hhmm` or
+/// `+hh` into seconds east of UTC.
+fn utc_offset_seconds(zone: &str) -> Option<i64> {
+    let sign = match zone.chars().next() {
+        None => return Some(0),
+        Some('Z' | 'z') if zone.len() == 1 => return Some(0),
+        Some('+') => 1,
+        Some('-') => -1,
+        _ => return None,
+    };
+    let offset = &zone[1..];
+    let (hours, minutes) = match offset.len() {
+        2 => (offset, "00"),
+        4 => offset.split_at(2),
+        5 if offset.as_bytes()[2] == b':' => (&offset[..2], &offset[3..]),
+        _ => return None,
+    };
+    let (hours, minutes) = (digits(hours, 2)?, digits(minutes, 2)?);
+    if hours > 23 || minutes > 59 {
+        return None;
+    }
+    Some(sign * (hours * 3600 + minutes * 60))
+}
+
+/// Parses exactly `len` ASCII digits.
+fn digits(s: &str, len: usize) -> Option<i64> {
+    if s.len() != len || !s.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    s.parse().ok()
+}
+
+fn days_in_month(year: i64, month: i64) -> i64 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Returns the number of days from 1970-01-01 to the given proleptic
+/// Gregorian date.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
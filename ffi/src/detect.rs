@@ -0,0 +1,609 @@
+//! Lightweight MIME type sniffing from leading bytes.
+//!
+//! The core library only reports the detected type after a full parse, so
+//! checks that must run before parsing use these magic-byte signatures instead.
+//! They cover the common document, image and archive formats; anything else is
+//! reported as `text/plain` if it looks like text, or `application/octet-stream`.
+
+use crate::errors::*;
+use crate::extractor::{ExtractorState, strings_to_c};
+use crate::types::*;
+use crate::xhtml;
+use std::ffi::{CStr, CString};
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::os::raw::{c_char, c_int};
+
+/// The number of leading bytes inspected when sniffing a file.
+pub(crate) const SNIFF_LEN: usize = 8192;
+
+pub(crate) const OCTET_STREAM: &str = "application/octet-stream";
+
+/// The signature of an OLE compound file, used by legacy Office formats.
+const OLE_MAGIC: &[u8] = b"\xD0\xCF\x11\xE0\xA1\xB1\x1A\xE1";
+
+/// Reads up to `SNIFF_LEN` leading bytes of the file at `path`.
+pub(crate) fn read_head(path: &str) -> std::io::Result<Vec<u8>> {
+    let mut head = Vec::with_capacity(SNIFF_LEN);
+    File::open(path)?
+        .take(SNIFF_LEN as u64)
+        .read_to_end(&mut head)?;
+    Ok(head)
+}
+
+/// Guesses the MIME type of a document from its leading bytes.
+pub(crate) fn sniff_mime(head: &[u8]) -> &'static str {
+    if head.starts_with(b"%PDF-") {
+        "application/pdf"
+    } else if head.starts_with(b"PK\x03\x04") {
+        sniff_zip(head)
+    } else if head.starts_with(OLE_MAGIC) {
+        "application/x-tika-msoffice"
+    } else if head.starts_with(b"\x89PNG\r\n\x1a\n") {
+        "image/png"
+    } else if head.starts_with(b"\xFF\xD8\xFF") {
+        "image/jpeg"
+    } else if head.starts_with(b"GIF87a") || head.starts_with(b"GIF89a") {
+        "image/gif"
+    } else if head.starts_with(b"II*\0") || head.starts_with(b"MM\0*") {
+        "image/tiff"
+    } else if head.len() >= 12 && head.starts_with(b"RIFF") && &head[8..12] == b"WEBP" {
+        "image/webp"
+    } else if head.starts_with(b"{\\rtf") {
+        "application/rtf"
+    } else if head.starts_with(b"\x1F\x8B") {
+        "application/gzip"
+    } else if head.starts_with(b"7z\xBC\xAF\x27\x1C") {
+        "application/x-7z-compressed"
+    } else if head.starts_with(b"Rar!\x1A\x07") {
+        "application/x-rar-compressed"
+    } else if head.starts_with(b"%!PS") {
+        "application/postscript"
+    } else if looks_like_text(head) {
+        sniff_text(head)
+    } else {
+        OCTET_STREAM
+    }
+}
+
+/// Rates how much a result of `sniff_mime` can be trusted, from 0 to 100.
+///
+/// A distinctive magic number scores highest and markup found in text lower.
+/// A bare ZIP or OLE container scores lower still because the format inside
+/// was not identified, and plain text, which is only the absence of binary
+/// bytes, lowest of the positive matches. `application/octet-stream` means
+/// nothing matched and scores 0.
+pub(crate) fn confidence(mime: &str) -> c_int {
+    match mime {
+        OCTET_STREAM => 0,
+        "text/plain" => 40,
+        "application/zip" | "application/x-tika-msoffice" => 60,
+        "text/html" | "application/xhtml+xml" | "application/xml" | "image/svg+xml" => 70,
+        _ => 95,
+    }
+}
+
+/// Detects the MIME type of a local file from its leading bytes, without
+/// parsing it, and rates the detection from 0 to 100.
+///
+/// The core library only reports a type after a full parse, so this uses the
+/// same magic-byte sniffing as the MIME allow-list. A distinctive signature
+/// such as that of a PDF or PNG scores 95, HTML or XML markup 70, a ZIP or OLE
+/// container whose inner format was not recognised 60, plain text 40, and an
+/// unrecognised file, reported as `application/octet-stream`, 0.
+///
+/// The handle's root directory and symlink settings apply to `path`.
+/// The output string must be freed with `extractous_string_free`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_extractor_detect_mime_type_file_ex(
+    handle: *mut CExtractor,
+    path: *const c_char,
+    out_mime: *mut *mut c_char,
+    out_confidence: *mut c_int,
+) -> c_int {
+    if handle.is_null() || path.is_null() || out_mime.is_null() || out_confidence.is_null() {
+        return ERR_NULL_POINTER;
+    }
+    let path_str = match unsafe { CStr::from_ptr(path).to_str() } {
+        Ok(s) => s,
+        Err(_) => return ERR_INVALID_UTF8,
+    };
+    let path_ok = unsafe { &*(handle as *const ExtractorState) }.check_path(path_str);
+    if path_ok != ERR_OK {
+        return path_ok;
+    }
+
+    let head = match read_head(path_str) {
+        Ok(head) => head,
+        Err(e) => {
+            return record_ffi_error(ERR_IO_ERROR, format!("cannot read {}: {}", path_str, e));
+        }
+    };
+    let mime = sniff_mime(&head);
+    unsafe {
+        *out_mime = CString::new(mime).map_or(std::ptr::null_mut(), |s| s.into_raw());
+        *out_confidence = confidence(mime);
+    }
+    ERR_OK
+}
+
+/// Returns the supertype chain of a MIME type, nearest first and ending at
+/// `application/octet-stream`, for coarse routing such as "anything
+/// office-like".
+///
+/// The core library does not expose Tika's MIME registry, so the chain comes
+/// from a copy of the relevant part of its hierarchy: legacy Office formats
+/// are `application/x-tika-msoffice`, Office Open XML formats
+/// `application/x-tika-ooxml` and then `application/zip`, and OpenDocument
+/// and EPUB files `application/zip`. Otherwise `+xml` types are
+/// `application/xml`, `+zip` types `application/zip`, and textual types,
+/// XML included, `text/plain`. Parameters and case are ignored. The type
+/// itself is not part of the chain, which is empty for
+/// `application/octet-stream`.
+///
+/// The array must be freed with `extractous_string_array_free`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_mime_supertypes(
+    mime: *const c_char,
+    out_chain: *mut *mut *mut c_char,
+    out_count: *mut libc::size_t,
+) -> c_int {
+    if mime.is_null() || out_chain.is_null() || out_count.is_null() {
+        return ERR_NULL_POINTER;
+    }
+    let mime = match unsafe { CStr::from_ptr(mime).to_str() } {
+        Ok(s) => s,
+        Err(_) => return ERR_INVALID_UTF8,
+    };
+    let mut mime = mime
+        .split(';')
+        .next()
+        .unwrap_or("")
+        .trim()
+        .to_ascii_lowercase();
+    let mut chain = Vec::new();
+    while let Some(parent) = supertype(&mime) {
+        chain.push(parent.to_string());
+        mime = parent.to_string();
+    }
+    unsafe { strings_to_c(chain, out_chain, out_count) };
+    ERR_OK
+}
+
+/// Returns the direct supertype of a lowercased base MIME type, following
+/// Tika's `tika-mimetypes.xml`, or None for `application/octet-stream`.
+fn supertype(mime: &str) -> Option<&'static str> {
+    let parent = match mime {
+        OCTET_STREAM => return None,
+        "application/msword"
+        | "application/vnd.ms-excel"
+        | "application/vnd.ms-powerpoint"
+        | "application/vnd.ms-outlook"
+        | "application/vnd.visio" => "application/x-tika-msoffice",
+        "application/x-tika-msoffice" => "application/x-ole-storage",
+        "application/x-tika-ooxml"
+        | "application/vnd.oasis.opendocument.text"
+        | "application/vnd.oasis.opendocument.spreadsheet"
+        | "application/vnd.oasis.opendocument.presentation"
+        | "application/java-archive" => "application/zip",
+        "application/json" => "application/javascript",
+        "text/xml"
+        | "application/xml"
+        | "application/javascript"
+        | "application/rtf"
+        | "message/rfc822" => "text/plain",
+        "text/plain" => OCTET_STREAM,
+        m if m.starts_with("application/vnd.openxmlformats-officedocument.")
+            || m.starts_with("application/vnd.ms-excel.")
+            || m.starts_with("application/vnd.ms-word.")
+            || m.starts_with("application/vnd.ms-powerpoint.") =>
+        {
+            "application/x-tika-ooxml"
+        }
+        m if m.ends_with("+xml") => "application/xml",
+        m if m.ends_with("+zip") => "application/zip",
+        m if m.starts_with("text/") => "text/plain",
+        _ => OCTET_STREAM,
+    };
+    Some(parent)
+}
+
+/// Guesses the MIME type of a file from its name alone, without opening it,
+/// for routing files before reading them when their extensions can be
+/// trusted. Returns `application/octet-stream` for a name without a known
+/// extension, and NULL if `filename` is NULL or not valid UTF-8.
+///
+/// The core library does not expose Tika's name-based detection, so the
+/// guess comes from a copy of the globs Tika's `tika-mimetypes.xml` gives
+/// common document, image, archive and text formats. Like Tika's, the match
+/// ignores case and any directory part of `filename`.
+///
+/// The output string must be freed with `extractous_string_free`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_detect_mime_by_filename(
+    filename: *const c_char,
+) -> *mut c_char {
+    if filename.is_null() {
+        return std::ptr::null_mut();
+    }
+    let Ok(filename) = (unsafe { CStr::from_ptr(filename).to_str() }) else {
+        return std::ptr::null_mut();
+    };
+    let mime = std::path::Path::new(filename)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .and_then(|ext| mime_for_extension(&ext.to_ascii_lowercase()))
+        .unwrap_or(OCTET_STREAM);
+    CString::new(mime).map_or(std::ptr::null_mut(), |s| s.into_raw())
+}
+
+/// File extensions and the MIME types Tika's globs give them, used both to
+/// guess a type from a file name and to force a type through the extension
+/// the parser sees. A type's first extension is its usual one.
+const EXTENSION_MIME_TYPES: &[(&str, &str)] = &[
+    ("txt", "text/plain"),
+    ("text", "text/plain"),
+    ("log", "text/plain"),
+    ("csv", "text/csv"),
+    ("tsv", "text/tab-separated-values"),
+    ("html", "text/html"),
+    ("htm", "text/html"),
+    ("xhtml", "application/xhtml+xml"),
+    ("md", "text/x-web-markdown"),
+    ("markdown", "text/x-web-markdown"),
+    ("xml", "application/xml"),
+    ("json", "application/json"),
+    ("js", "text/javascript"),
+    ("css", "text/css"),
+    ("pdf", "application/pdf"),
+    ("rtf", "application/rtf"),
+    ("doc", "application/msword"),
+    ("dot", "application/msword"),
+    ("xls", "application/vnd.ms-excel"),
+    ("xlt", "application/vnd.ms-excel"),
+    ("ppt", "application/vnd.ms-powerpoint"),
+    ("pps", "application/vnd.ms-powerpoint"),
+    ("pot", "application/vnd.ms-powerpoint"),
+    ("msg", "application/vnd.ms-outlook"),
+    ("vsd", "application/vnd.visio"),
+    (
+        "docx",
+        "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+    ),
+    ("docm", "application/vnd.ms-word.document.macroenabled.12"),
+    (
+        "xlsx",
+        "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+    ),
+    ("xlsm", "application/vnd.ms-excel.sheet.macroenabled.12"),
+    (
+        "pptx",
+        "application/vnd.openxmlformats-officedocument.presentationml.presentation",
+    ),
+    (
+        "pptm",
+        "application/vnd.ms-powerpoint.presentation.macroenabled.12",
+    ),
+    ("odt", "application/vnd.oasis.opendocument.text"),
+    ("ods", "application/vnd.oasis.opendocument.spreadsheet"),
+    ("odp", "application/vnd.oasis.opendocument.presentation"),
+    ("epub", "application/epub+zip"),
+    ("zip", "application/zip"),
+    ("jar", "application/java-archive"),
+    ("gz", "application/gzip"),
+    ("tgz", "application/gzip"),
+    ("tar", "application/x-tar"),
+    ("7z", "application/x-7z-compressed"),
+    ("rar", "application/x-rar-compressed"),
+    ("eml", "message/rfc822"),
+    ("png", "image/png"),
+    ("jpg", "image/jpeg"),
+    ("jpeg", "image/jpeg"),
+    ("jpe", "image/jpeg"),
+    ("gif", "image/gif"),
+    ("bmp", "image/bmp"),
+    ("tiff", "image/tiff"),
+    ("tif", "image/tiff"),
+    ("webp", "image/webp"),
+    ("svg", "image/svg+xml"),
+];
+
+/// Returns the MIME type Tika's globs give a lowercased file extension.
+fn mime_for_extension(ext: &str) -> Option<&'static str> {
+    EXTENSION_MIME_TYPES
+        .iter()
+        .find(|(known, _)| *known == ext)
+        .map(|(_, mime)| *mime)
+}
+
+/// Returns the usual file extension for a MIME type, without the dot. It is
+/// the extension `extractous_detect_mime_by_filename` maps back to the type.
+///
+/// The core parser cannot be told a document's type directly, but it takes
+/// the file name into account when the content alone is inconclusive, so an
+/// extension is how a type is forced.
+pub(crate) fn extension_for(mime: &str) -> Option<&'static str> {
+    let mime = mime.split(';').next()?.trim().to_ascii_lowercase();
+    EXTENSION_MIME_TYPES
+        .iter()
+        .find(|(_, known)| *known == mime)
+        .map(|(ext, _)| *ext)
+}
+
+/// Reports whether a local file is password-protected, by inspecting its
+/// container without parsing it, so callers can ask for a password before
+/// extracting.
+///
+/// `*out_encrypted` is set to 1 for a PDF whose trailer has an `/Encrypt`
+/// entry, an Office Open XML file saved with a password, which is stored as
+/// an OLE container holding an `EncryptedPackage` stream, a ZIP archive with
+/// encrypted entries, and an OpenDocument file whose manifest lists
+/// encryption data. It is 0 otherwise, including for formats not covered
+/// here such as legacy `.doc` and `.xls` files. Either way `ERR_OK` is
+/// returned; `ERR_IO_ERROR` means the file could not be read.
+///
+/// The handle's root directory and symlink settings apply to `path`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_extractor_is_encrypted_file(
+    handle: *mut CExtractor,
+    path: *const c_char,
+    out_encrypted: *mut c_int,
+) -> c_int {
+    if handle.is_null() || path.is_null() || out_encrypted.is_null() {
+        return ERR_NULL_POINTER;
+    }
+    let path_str = match unsafe { CStr::from_ptr(path).to_str() } {
+        Ok(s) => s,
+        Err(_) => return ERR_INVALID_UTF8,
+    };
+    let path_ok = unsafe { &*(handle as *const ExtractorState) }.check_path(path_str);
+    if path_ok != ERR_OK {
+        return path_ok;
+    }
+
+    let encrypted = read_head(path_str).and_then(|head| {
+        if head.starts_with(b"%PDF-") {
+            pdf_is_encrypted(path_str)
+        } else if head.starts_with(OLE_MAGIC) {
+            ole_is_encrypted(path_str)
+        } else if head.starts_with(b"PK\x03\x04") {
+            zip_is_encrypted(path_str)
+        } else {
+            Ok(false)
+        }
+    });
+    match encrypted {
+        Ok(encrypted) => {
+            unsafe { *out_encrypted = encrypted as c_int };
+            ERR_OK
+        }
+        Err(e) => record_ffi_error(ERR_IO_ERROR, format!("cannot read {}: {}", path_str, e)),
+    }
+}
+
+/// How many bytes at each end of a PDF are searched for the trailer. The
+/// trailer is normally at the end, or at the start of a linearized file.
+const PDF_TRAILER_SCAN: u64 = 64 * 1024;
+
+/// Returns true if the trailer of a PDF refers to an encryption dictionary.
+fn pdf_is_encrypted(path: &str) -> std::io::Result<bool> {
+    let mut file = File::open(path)?;
+    let len = file.metadata()?.len();
+    let mut start = Vec::new();
+    file.by_ref()
+        .take(PDF_TRAILER_SCAN)
+        .read_to_end(&mut start)?;
+    if contains(&start, b"/Encrypt") {
+        return Ok(true);
+    }
+    if len <= PDF_TRAILER_SCAN {
+        return Ok(false);
+    }
+    let mut end = Vec::new();
+    file.seek(SeekFrom::Start(len - PDF_TRAILER_SCAN))?;
+    file.read_to_end(&mut end)?;
+    Ok(contains(&end, b"/Encrypt"))
+}
+
+/// Returns true if an OLE container holds the `EncryptedPackage` stream in
+/// which Office stores a password-protected OOXML file. The stream's name is
+/// searched for in UTF-16LE, as directory entries store it.
+fn ole_is_encrypted(path: &str) -> std::io::Result<bool> {
+    let needle: Vec<u8> = "EncryptedPackage"
+        .encode_utf16()
+        .flat_map(u16::to_le_bytes)
+        .collect();
+    let mut file = File::open(path)?;
+    let mut buf = vec![0u8; 64 * 1024];
+    // Bytes kept from the previous chunk so a name split across chunks matches.
+    let mut kept = 0;
+    loop {
+        let n = file.read(&mut buf[kept..])?;
+        if n == 0 {
+            return Ok(false);
+        }
+        let filled = kept + n;
+        if contains(&buf[..filled], &needle) {
+            return Ok(true);
+        }
+        kept = (needle.len() - 1).min(filled);
+        buf.copy_within(filled - kept..filled, 0);
+    }
+}
+
+/// Returns true if a ZIP archive has encrypted entries, or is an OpenDocument
+/// file whose manifest describes encrypted parts.
+fn zip_is_encrypted(path: &str) -> std::io::Result<bool> {
+    let mut archive = zip::ZipArchive::new(File::open(path)?).map_err(std::io::Error::other)?;
+    for i in 0..archive.len() {
+        if archive
+            .by_index_raw(i)
+            .map_err(std::io::Error::other)?
+            .encrypted()
+        {
+            return Ok(true);
+        }
+    }
+    let Ok(mut manifest) = archive.by_name("META-INF/manifest.xml") else {
+        return Ok(false);
+    };
+    let mut xml = Vec::new();
+    manifest.by_ref().take(1024 * 1024).read_to_end(&mut xml)?;
+    Ok(contains(&xml, b"encryption-data"))
+}
+
+fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+    haystack.windows(needle.len()).any(|w| w == needle)
+}
+
+/// Distinguishes ZIP-based office and e-book formats by their entry names,
+/// which appear in the local file headers near the start of the archive.
+fn sniff_zip(head: &[u8]) -> &'static str {
+    let contains = |needle: &[u8]| head.windows(needle.len()).any(|w| w == needle);
+    if contains(b"mimetypeapplication/vnd.oasis.opendocument.text") {
+        "application/vnd.oasis.opendocument.text"
+    } else if contains(b"mimetypeapplication/vnd.oasis.opendocument.spreadsheet") {
+        "application/vnd.oasis.opendocument.spreadsheet"
+    } else if contains(b"mimetypeapplication/vnd.oasis.opendocument.presentation") {
+        "application/vnd.oasis.opendocument.presentation"
+    } else if contains(b"mimetypeapplication/epub+zip") {
+        "application/epub+zip"
+    } else if contains(b"word/") {
+        "application/vnd.openxmlformats-officedocument.wordprocessingml.document"
+    } else if contains(b"xl/") {
+        "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet"
+    } else if contains(b"ppt/") {
+        "application/vnd.openxmlformats-officedocument.presentationml.presentation"
+    } else {
+        "application/zip"
+    }
+}
+
+/// Returns true if `head` has no NUL bytes and is valid UTF-8, allowing for a
+/// multi-byte sequence cut off at the end.
+pub(crate) fn looks_like_text(head: &[u8]) -> bool {
+    if head.is_empty() || head.contains(&0) {
+        return false;
+    }
+    match std::str::from_utf8(head) {
+        Ok(_) => true,
+        Err(e) => e.error_len().is_none(),
+    }
+}
+
+/// Recognises markup in text content.
+fn sniff_text(head: &[u8]) -> &'static str {
+    let start = String::from_utf8_lossy(&head[..head.len().min(512)]).to_ascii_lowercase();
+    let start = start.trim_start_matches('\u{feff}').trim_start();
+    if start.starts_with("<!doctype html") || start.starts_with("<html") {
+        "text/html"
+    } else if start.starts_with("<?xml") || start.starts_with("<svg") {
+        if start.contains("<svg") {
+            "image/svg+xml"
+        } else if start.contains("<html") {
+            "application/xhtml+xml"
+        } else {
+            "application/xml"
+        }
+    } else {
+        "text/plain"
+    }
+}
+
+/// Returns the first URL a parser resolving external resources would fetch
+/// over the network while reading the text document `doc`: the system
+/// identifier of a doctype or entity declaration, an XInclude `href`, or an
+/// XML Schema location hint. Binary documents have none.
+pub(crate) fn network_reference(doc: &[u8]) -> Option<String> {
+    if !looks_like_text(&doc[..doc.len().min(SNIFF_LEN)]) {
+        return None;
+    }
+    let doc = String::from_utf8_lossy(doc);
+    let lower = doc.to_ascii_lowercase();
+    for keyword in ["<!doctype", "<!entity"] {
+        let mut from = 0;
+        while let Some(i) = lower[from..].find(keyword) {
+            from += i + keyword.len();
+            let decl = &lower[from..];
+            let end = decl.find(['>', '[']).unwrap_or(decl.len());
+            let ids = [" system", " public"]
+                .iter()
+                .filter_map(|k| decl[..end].find(k))
+                .min();
+            if let Some(ids) = ids {
+                let literals = quoted_literals(&doc[from + ids..from + end]);
+                if let Some(url) = literals.into_iter().find(|id| is_network_url(id)) {
+                    return Some(url.to_string());
+                }
+            }
+        }
+    }
+    for event in xhtml::Reader::new(&doc) {
+        let xhtml::Event::Start { name, attrs } = event else {
+            continue;
+        };
+        let local = |n: &str| n.rsplit(':').next().unwrap_or(n).to_string();
+        for (attr, value) in attrs {
+            let refs: Vec<&str> = match (local(name).as_str(), local(attr).as_str()) {
+                ("include", "href") => vec![value.as_str()],
+                // Namespace names and schema locations alternate here.
+                (_, "schemaLocation") => value.split_whitespace().skip(1).step_by(2).collect(),
+                (_, "noNamespaceSchemaLocation") => value.split_whitespace().collect(),
+                _ => continue,
+            };
+            if let Some(url) = refs.into_iter().find(|r| is_network_url(r)) {
+                return Some(url.to_string());
+            }
+        }
+    }
+    None
+}
+
+/// Returns the contents of the quoted literals in a declaration.
+fn quoted_literals(decl: &str) -> Vec<&str> {
+    let mut literals = Vec::new();
+    let mut rest = decl;
+    while let Some(open) = rest.find(['"', '\'']) {
+        let quote = rest[open..].chars().next().unwrap_or('"');
+        let body = &rest[open + 1..];
+        match body.find(quote) {
+            Some(close) => {
+                literals.push(&body[..close]);
+                rest = &body[close + 1..];
+            }
+            None => break,
+        }
+    }
+    literals
+}
+
+/// Returns true if `reference` is an absolute URL with a scheme other than
+/// `file`. A Windows drive letter is not taken for a scheme.
+fn is_network_url(reference: &str) -> bool {
+    let Some((scheme, _)) = reference.trim().split_once(':') else {
+        return false;
+    };
+    scheme.len() > 1
+        && scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+        && scheme
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+        && !scheme.eq_ignore_ascii_case("file")
+}
+
+/// Returns true if `mime` matches `pattern`, comparing the base types
+/// case-insensitively. A pattern ending in `/*` matches any subtype.
+pub(crate) fn mime_matches(pattern: &str, mime: &str) -> bool {
+    let base = |m: &str| {
+        m.split(';')
+            .next()
+            .unwrap_or("")
+            .trim()
+            .to_ascii_lowercase()
+    };
+    let (pattern, mime) = (base(pattern), base(mime));
+    match pattern.strip_suffix("/*") {
+        Some(major) => mime.split('/').next() == Some(major),
+        None => pattern == mime,
+    }
+}
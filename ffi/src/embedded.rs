@@ -0,0 +1,200 @@
+//! Retrieval of the raw bytes of a document's embedded resources.
+//!
+//! The core extractor parses embedded resources for their text but never
+//! returns their bytes, so they are read from the container directly: the
+//! entries of a ZIP archive or the attachments of an email message.
+
+use crate::detect;
+use crate::errors::*;
+use crate::extractor::FileInput;
+use crate::metadata::metadata_to_c;
+use crate::mime;
+use crate::types::*;
+use std::collections::HashMap;
+use std::ffi::CString;
+use std::fs::File;
+use std::io::Read;
+use std::os::raw::{c_char, c_int};
+use std::ptr;
+
+/// Embedded resources larger than this are not read into memory.
+const MAX_EMBEDDED_LEN: u64 = 256 * 1024 * 1024;
+
+/// An embedded resource read from its container.
+struct Embedded {
+    name: Option<String>,
+    mime: String,
+    data: Vec<u8>,
+}
+
+/// Returns the raw bytes and metadata of the embedded resource at `index` in
+/// a local file, such as one attachment of an email, without parsing it.
+///
+/// Resources are numbered from 0 in document order. For a ZIP archive they
+/// are its file entries, directories excluded; for an email message (RFC 822)
+/// they are its attachments, including attached messages, decoded from their
+/// transfer encoding. These are the resources that
+/// `extractous_extractor_list_embedded_file` names for such files, in the
+/// same order, and the handle's embedded-file filters apply as they do
+/// there. Other formats, including Office documents, return
+/// `ERR_UNSUPPORTED_FORMAT`, and an index past the last resource returns
+/// `ERR_INDEX_OUT_OF_RANGE`.
+///
+/// `*out_bytes` is NULL if the resource is empty. `*out_mime` is the
+/// resource's declared type, or one guessed from its bytes. `*out_metadata`
+/// holds `resourceName` when the resource is named, `Content-Type` and
+/// `Content-Length`.
+/// The bytes must be freed with `extractous_buffer_free`, the MIME type with
+/// `extractous_string_free` and the metadata with `extractous_metadata_free`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_extractor_extract_embedded_at(
+    handle: *mut CExtractor,
+    path: *const c_char,
+    index: libc::size_t,
+    out_bytes: *mut *mut u8,
+    out_len: *mut libc::size_t,
+    out_mime: *mut *mut c_char,
+    out_metadata: *mut *mut CMetadata,
+) -> c_int {
+    if handle.is_null()
+        || path.is_null()
+        || out_bytes.is_null()
+        || out_len.is_null()
+        || out_mime.is_null()
+        || out_metadata.is_null()
+    {
+        return ERR_NULL_POINTER;
+    }
+    let input = match unsafe { FileInput::open(handle, path) } {
+        Ok(input) => input,
+        Err(code) => return code,
+    };
+    let path_str = input.path;
+
+    let head = match detect::read_head(input.parse_path()) {
+        Ok(head) => head,
+        Err(e) => {
+            return record_ffi_error(ERR_IO_ERROR, format!("cannot read {}: {}", path_str, e));
+        }
+    };
+    let found = if head.starts_with(b"PK\x03\x04") {
+        zip_entry_at(input.parse_path(), index)
+    } else if mime::looks_like_message(&head) {
+        message_attachment_at(input.parse_path(), index)
+    } else {
+        return record_ffi_error(
+            ERR_UNSUPPORTED_FORMAT,
+            format!(
+                "embedded resources cannot be read from {} documents",
+                detect::sniff_mime(&head)
+            ),
+        );
+    };
+    let embedded = match found {
+        Ok(Some(embedded)) => embedded,
+        Ok(None) => {
+            return record_ffi_error(
+                ERR_INDEX_OUT_OF_RANGE,
+                format!("{} has no embedded resource {}", path_str, index),
+            );
+        }
+        Err((code, message)) => return record_ffi_error(code, message),
+    };
+
+    let mut metadata: HashMap<String, Vec<String>> = HashMap::new();
+    if let Some(name) = &embedded.name {
+        metadata.insert("resourceName".to_string(), vec![name.clone()]);
+    }
+    metadata.insert("Content-Type".to_string(), vec![embedded.mime.clone()]);
+    metadata.insert(
+        "Content-Length".to_string(),
+        vec![embedded.data.len().to_string()],
+    );
+    let data = embedded.data.into_boxed_slice();
+    unsafe {
+        *out_len = data.len();
+        *out_bytes = match data.len() {
+            0 => ptr::null_mut(),
+            _ => Box::into_raw(data) as *mut u8,
+        };
+        *out_mime = CString::new(embedded.mime).map_or(ptr::null_mut(), |s| s.into_raw());
+        *out_metadata = metadata_to_c(metadata);
+    }
+    ERR_OK
+}
+
+/// Reads the file entry at `index` of a ZIP archive.
+fn zip_entry_at(path: &str, index: usize) -> Result<Option<Embedded>, (c_int, String)> {
+    let archive_error = |e: zip::result::ZipError| {
+        (
+            ERR_EXTRACTION_FAILED,
+            format!("cannot read archive {}: {}", path, e),
+        )
+    };
+    let file =
+        File::open(path).map_err(|e| (ERR_IO_ERROR, format!("cannot open {}: {}", path, e)))?;
+    let mut archive = zip::ZipArchive::new(file).map_err(archive_error)?;
+    let mut files = 0;
+    for i in 0..archive.len() {
+        let mut entry = archive.by_index(i).map_err(archive_error)?;
+        if !entry.is_file() {
+            continue;
+        }
+        if files < index {
+            files += 1;
+            continue;
+        }
+        if entry.size() > MAX_EMBEDDED_LEN {
+            return Err((
+                ERR_OUT_OF_MEMORY,
+                format!(
+                    "{} is too large to read: {} bytes",
+                    entry.name(),
+                    entry.size()
+                ),
+            ));
+        }
+        let mut data = Vec::with_capacity(entry.size() as usize);
+        entry
+            .by_ref()
+            .take(MAX_EMBEDDED_LEN)
+            .read_to_end(&mut data)
+            .map_err(|e| {
+                (
+                    ERR_EXTRACTION_FAILED,
+                    format!("cannot read {}: {}", entry.name(), e),
+                )
+            })?;
+        let name = entry.name().to_string();
+        let mime = sniffed_mime(&data);
+        return Ok(Some(Embedded {
+            name: Some(name),
+            mime,
+            data,
+        }));
+    }
+    Ok(None)
+}
+
+/// Reads the attachment at `index` of an email message.
+fn message_attachment_at(path: &str, index: usize) -> Result<Option<Embedded>, (c_int, String)> {
+    let message =
+        std::fs::read(path).map_err(|e| (ERR_IO_ERROR, format!("cannot read {}: {}", path, e)))?;
+    let Some(attachment) = mime::attachments(&message).into_iter().nth(index) else {
+        return Ok(None);
+    };
+    let mime = match attachment.mime.as_str() {
+        "" | detect::OCTET_STREAM => sniffed_mime(&attachment.data),
+        _ => attachment.mime,
+    };
+    Ok(Some(Embedded {
+        name: attachment.name,
+        mime,
+        data: attachment.data,
+    }))
+}
+
+/// Guesses the MIME type of embedded bytes.
+fn sniffed_mime(data: &[u8]) -> String {
+    detect::sniff_mime(&data[..data.len().min(detect::SNIFF_LEN)]).to_string()
+}
@@ -1,7 +1,8 @@
 use crate::ecore::Error;
 use std::cell::RefCell;
 use std::error::Error as StdError;
-use std::ffi::CString;
+use std::ffi::{CStr, CString};
+use std::fmt;
 use std::os::raw::{c_char, c_int};
 use std::ptr;
 
@@ -16,8 +17,21 @@ pub const ERR_INVALID_ENUM: c_int = -7;
 pub const ERR_UNSUPPORTED_FORMAT: c_int = -8;
 pub const ERR_OUT_OF_MEMORY: c_int = -9;
 pub const ERR_OCR_FAILED: c_int = -10;
+pub const ERR_BUFFER_TOO_SMALL: c_int = -11;
+pub const ERR_MIME_NOT_ALLOWED: c_int = -12;
+pub const ERR_TIMEOUT: c_int = -13;
+pub const ERR_ABORTED: c_int = -14;
+pub const ERR_METADATA_ABSENT: c_int = -15;
+pub const ERR_NO_IMAGE: c_int = -16;
+pub const ERR_INDEX_OUT_OF_RANGE: c_int = -17;
+pub const ERR_UNSUPPORTED_FEATURE: c_int = -18;
+pub const ERR_NOT_SEEKABLE: c_int = -19;
+pub const ERR_BUSY: c_int = -20;
 
 pub(crate) fn extractous_error_to_code(err: &Error) -> c_int {
+    if java_exception(err).is_some_and(|(class, _)| class == "java.lang.OutOfMemoryError") {
+        return ERR_OUT_OF_MEMORY;
+    }
     match err {
         Error::IoError(_) => ERR_IO_ERROR,
         Error::Utf8Error(_) => ERR_INVALID_UTF8,
@@ -57,36 +71,333 @@ pub(crate) fn extractous_error_to_code(err: &Error) -> c_int {
     }
 }
 
+/// Returns true if `err` is a failure to call into the parser runtime that
+/// may succeed on retry, such as a thread that could not be attached, as
+/// opposed to an exception thrown while parsing.
+pub(crate) fn is_transient_jni_error(err: &Error) -> bool {
+    if java_exception(err).is_some() {
+        return false;
+    }
+    match err {
+        Error::JniEnvCall(_) => true,
+        Error::JniError(jni_err) => {
+            let lower = jni_err.to_string().to_lowercase();
+            lower.contains("not attached") || lower.contains("detached")
+        }
+        _ => false,
+    }
+}
+
+/// Records `err` as the last error on this thread and returns its code.
+///
+/// When the error carries a Java exception from the parser, the debug report
+/// starts with `java_exception: <class>: <message>`.
+pub(crate) fn record_error(err: Error) -> c_int {
+    let code = extractous_error_to_code(&err);
+    match java_exception(&err) {
+        Some((class, message)) => set_last_error(
+            code,
+            JavaException {
+                class,
+                message,
+                source: err,
+            },
+        ),
+        None => set_last_error(code, err),
+    }
+    code
+}
+
+/// An extraction failure caused by an exception thrown in the parser runtime.
+#[derive(Debug)]
+struct JavaException {
+    class: String,
+    message: String,
+    source: Error,
+}
+
+impl fmt::Display for JavaException {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "java_exception: {}", self.class)?;
+        if !self.message.is_empty() {
+            write!(f, ": {}", self.message)?;
+        }
+        Ok(())
+    }
+}
+
+impl StdError for JavaException {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        Some(&self.source)
+    }
+}
+
+/// Finds the class name and message of a Java exception in the text of a core
+/// error, such as `org.apache.tika.exception.TikaException: Bad header`.
+///
+/// The core passes exceptions on as text, so this looks for the first fully
+/// qualified class name ending in `Exception` or `Error`. The message is the
+/// rest of that line after a `: ` separator, if any.
+fn java_exception(err: &Error) -> Option<(String, String)> {
+    [err.to_string(), format!("{:?}", err)]
+        .iter()
+        .find_map(|text| find_java_exception(text))
+}
+
+fn find_java_exception(text: &str) -> Option<(String, String)> {
+    let is_name_char = |c: char| c.is_ascii_alphanumeric() || c == '_' || c == '$' || c == '.';
+    let mut rest = text;
+    while let Some(start) = rest.find(is_name_char) {
+        let candidate = &rest[start..];
+        let end = candidate
+            .find(|c: char| !is_name_char(c))
+            .unwrap_or(candidate.len());
+        let class = candidate[..end].trim_end_matches('.');
+        if is_java_exception_class(class) {
+            let message = candidate[end..]
+                .strip_prefix(": ")
+                .map_or("", |m| m.lines().next().unwrap_or(""))
+                .trim();
+            return Some((class.to_string(), message.to_string()));
+        }
+        rest = &candidate[end..];
+    }
+    None
+}
+
+/// Returns true for a name like `java.io.IOException`: at least one package
+/// segment, then a capitalised simple name ending in `Exception` or `Error`.
+fn is_java_exception_class(name: &str) -> bool {
+    let Some((package, simple)) = name.rsplit_once('.') else {
+        return false;
+    };
+    !package.split('.').any(str::is_empty)
+        && simple.starts_with(|c: char| c.is_ascii_uppercase())
+        && (simple.ends_with("Exception") || simple.ends_with("Error"))
+}
+
+/// An error raised by this library itself rather than by the core extractor.
+#[derive(Debug)]
+pub(crate) struct FfiError(pub(crate) String);
+
+impl fmt::Display for FfiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl StdError for FfiError {}
+
+/// Records an error detected by this library with `message` and returns `code`.
+pub(crate) fn record_ffi_error(code: c_int, message: impl Into<String>) -> c_int {
+    set_last_error(code, FfiError(message.into()));
+    code
+}
+
+/// A URL extraction failure with a recognised network cause.
+#[derive(Debug)]
+struct UrlError {
+    reason: &'static str,
+    source: Error,
+}
+
+impl fmt::Display for UrlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.reason, self.source)
+    }
+}
+
+impl StdError for UrlError {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        Some(&self.source)
+    }
+}
+
+/// Inspects a URL extraction error for the underlying network cause.
+fn url_failure_reason(err: &Error) -> Option<&'static str> {
+    let msg = format!("{} {:?}", err, err).to_lowercase();
+    if msg.contains("unknownhost")
+        || msg.contains("unknown host")
+        || msg.contains("name or service not known")
+        || msg.contains("nodename nor servname")
+    {
+        Some("dns_failure")
+    } else if msg.contains("connection refused") || msg.contains("connectexception") {
+        Some("connection_refused")
+    } else if msg.contains("server returned http response code: 404")
+        || msg.contains("filenotfoundexception")
+    {
+        // Java reports an HTTP 404 as a FileNotFoundException on the URL, and
+        // names the status in the message when it reads the error stream.
+        // The URL itself is in the message too, so a bare "404" proves nothing.
+        Some("http_404")
+    } else {
+        None
+    }
+}
+
+/// Records a failed URL extraction. When the network cause is recognised it is
+/// reported as `ERR_IO_ERROR` and the debug report starts with the reason
+/// (`dns_failure`, `connection_refused` or `http_404`).
+pub(crate) fn record_url_error(err: Error) -> c_int {
+    match url_failure_reason(&err) {
+        Some(reason) => {
+            set_last_error(
+                ERR_IO_ERROR,
+                UrlError {
+                    reason,
+                    source: err,
+                },
+            );
+            ERR_IO_ERROR
+        }
+        None => record_error(err),
+    }
+}
+
 #[unsafe(no_mangle)]
 pub extern "C" fn extractous_error_message(code: c_int) -> *mut c_char {
-    let msg = match code {
-        ERR_OK => "Operation completed successfully",
-        ERR_NULL_POINTER => "Null pointer provided as argument",
-        ERR_INVALID_UTF8 => "Invalid UTF-8 string encoding",
-        ERR_INVALID_STRING => "String conversion or allocation failed",
-        ERR_EXTRACTION_FAILED => "Document extraction failed",
-        ERR_IO_ERROR => "File system or network I/O error",
-        ERR_INVALID_CONFIG => "Invalid configuration value",
-        ERR_INVALID_ENUM => "Invalid enumeration value",
-        ERR_UNSUPPORTED_FORMAT => "Unsupported file format",
-        ERR_OUT_OF_MEMORY => "Memory allocation failed",
-        ERR_OCR_FAILED => "OCR operation failed",
-        _ => "Unknown error code",
-    };
-    match CString::new(msg) {
-        Ok(s) => s.into_raw(),
-        Err(_) => ptr::null_mut(),
+    message(code).to_owned().into_raw()
+}
+
+/// Returns the same message as `extractous_error_message` without allocating.
+/// The returned pointer is to a static string and must not be freed.
+#[unsafe(no_mangle)]
+pub extern "C" fn extractous_error_message_static(code: c_int) -> *const c_char {
+    message(code).as_ptr()
+}
+
+fn message(code: c_int) -> &'static CStr {
+    match code {
+        ERR_OK => c"Operation completed successfully",
+        ERR_NULL_POINTER => c"Null pointer provided as argument",
+        ERR_INVALID_UTF8 => c"Invalid UTF-8 string encoding",
+        ERR_INVALID_STRING => c"String conversion or allocation failed",
+        ERR_EXTRACTION_FAILED => c"Document extraction failed",
+        ERR_IO_ERROR => c"File system or network I/O error",
+        ERR_INVALID_CONFIG => c"Invalid configuration value",
+        ERR_INVALID_ENUM => c"Invalid enumeration value",
+        ERR_UNSUPPORTED_FORMAT => c"Unsupported file format",
+        ERR_OUT_OF_MEMORY => c"Memory allocation failed",
+        ERR_OCR_FAILED => c"OCR operation failed",
+        ERR_BUFFER_TOO_SMALL => c"Output buffer is too small",
+        ERR_MIME_NOT_ALLOWED => c"Document type is not allowed",
+        ERR_TIMEOUT => c"Operation timed out",
+        ERR_ABORTED => c"Operation was cancelled",
+        ERR_METADATA_ABSENT => c"Metadata value is absent",
+        ERR_NO_IMAGE => c"Document has no embedded image",
+        ERR_INDEX_OUT_OF_RANGE => c"Index is out of range",
+        ERR_UNSUPPORTED_FEATURE => c"Feature is not supported",
+        ERR_NOT_SEEKABLE => c"Stream cannot seek to that position",
+        ERR_BUSY => c"Too many extractions are running",
+        _ => c"Unknown error code",
+    }
+}
+
+/// Every error code with its category, in code order.
+const CATEGORIES: [(c_int, &CStr); 21] = [
+    (ERR_OK, c"ok"),
+    (ERR_NULL_POINTER, c"null_pointer"),
+    (ERR_INVALID_UTF8, c"invalid_utf8"),
+    (ERR_INVALID_STRING, c"invalid_string"),
+    (ERR_EXTRACTION_FAILED, c"extraction_failed"),
+    (ERR_IO_ERROR, c"io_error"),
+    (ERR_INVALID_CONFIG, c"invalid_config"),
+    (ERR_INVALID_ENUM, c"invalid_enum"),
+    (ERR_UNSUPPORTED_FORMAT, c"unsupported_format"),
+    (ERR_OUT_OF_MEMORY, c"out_of_memory"),
+    (ERR_OCR_FAILED, c"ocr_failed"),
+    (ERR_BUFFER_TOO_SMALL, c"buffer_too_small"),
+    (ERR_MIME_NOT_ALLOWED, c"mime_not_allowed"),
+    (ERR_TIMEOUT, c"timeout"),
+    (ERR_ABORTED, c"aborted"),
+    (ERR_METADATA_ABSENT, c"metadata_absent"),
+    (ERR_NO_IMAGE, c"no_image"),
+    (ERR_INDEX_OUT_OF_RANGE, c"index_out_of_range"),
+    (ERR_UNSUPPORTED_FEATURE, c"unsupported_feature"),
+    (ERR_NOT_SEEKABLE, c"not_seekable"),
+    (ERR_BUSY, c"busy"),
+];
+
+static CATEGORY_CODES: [c_int; CATEGORIES.len()] = {
+    let mut codes = [0; CATEGORIES.len()];
+    let mut i = 0;
+    while i < codes.len() {
+        codes[i] = CATEGORIES[i].0;
+        i += 1;
+    }
+    codes
+};
+
+/// Category names as C string pointers, parallel to `CATEGORY_CODES`.
+struct CategoryNames([*const c_char; CATEGORIES.len()]);
+
+// The pointers are to static, immutable strings.
+unsafe impl Sync for CategoryNames {}
+
+static CATEGORY_NAMES: CategoryNames = {
+    let mut names = [ptr::null(); CATEGORIES.len()];
+    let mut i = 0;
+    while i < names.len() {
+        names[i] = CATEGORIES[i].1.as_ptr();
+        i += 1;
+    }
+    CategoryNames(names)
+};
+
+/// Returns a stable, machine-readable category for an error code, such as
+/// `"io_error"`. Unknown codes map to `"unknown"`.
+/// The returned pointer is to a static string and must not be freed.
+#[unsafe(no_mangle)]
+pub extern "C" fn extractous_error_category(code: c_int) -> *const c_char {
+    category_name(code).as_ptr()
+}
+
+/// Exposes every error code and its category as two static parallel arrays
+/// of `*out_count` entries, so bindings can build their mapping up front.
+/// These are the categories `extractous_error_category` returns; codes not
+/// listed map to `"unknown"`. Nothing is allocated and nothing must be freed.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_error_category_table(
+    out_codes: *mut *const c_int,
+    out_categories: *mut *const *const c_char,
+    out_count: *mut libc::size_t,
+) -> c_int {
+    if out_codes.is_null() || out_categories.is_null() || out_count.is_null() {
+        return ERR_NULL_POINTER;
+    }
+    unsafe {
+        *out_codes = CATEGORY_CODES.as_ptr();
+        *out_categories = CATEGORY_NAMES.0.as_ptr();
+        *out_count = CATEGORIES.len();
     }
+    ERR_OK
+}
+
+fn category_name(code: c_int) -> &'static CStr {
+    CATEGORIES
+        .iter()
+        .find(|(c, _)| *c == code)
+        .map_or(c"unknown", |(_, name)| name)
+}
+
+/// The last error recorded on a thread, with the code it was reported as.
+struct LastError {
+    code: c_int,
+    error: Box<dyn StdError + Send>,
 }
 
 thread_local! {
     /// Stores the last detailed error that occurred on the current thread
-    static LAST_ERROR: RefCell<Option<Box<dyn StdError + Send>>> = RefCell::new(None);
+    static LAST_ERROR: RefCell<Option<LastError>> = const { RefCell::new(None) };
 }
 
-pub(crate) fn set_last_error(err: impl StdError + Send + 'static) {
+pub(crate) fn set_last_error(code: c_int, err: impl StdError + Send + 'static) {
     LAST_ERROR.with(|cell| {
-        *cell.borrow_mut() = Some(Box::new(err));
+        *cell.borrow_mut() = Some(LastError {
+            code,
+            error: Box::new(err),
+        });
     });
 }
 
@@ -95,7 +406,7 @@ pub(crate) fn set_last_error(err: impl StdError + Send + 'static) {
 #[unsafe(no_mangle)]
 pub extern "C" fn extractous_error_get_last_debug() -> *mut c_char {
     LAST_ERROR.with(|cell| {
-        if let Some(err) = cell.borrow_mut().take() {
+        if let Some(LastError { error: err, .. }) = cell.borrow_mut().take() {
             let mut debug_output = format!("Error: {}", err);
             let mut source = err.source();
             if source.is_some() {
@@ -118,6 +429,18 @@ pub extern "C" fn extractous_error_get_last_debug() -> *mut c_char {
     })
 }
 
+/// Returns the category of the last error stored on this thread, as
+/// `extractous_error_category` would for its code, or `"none"` if there is
+/// none. Retrieving the debug report clears the stored error.
+/// The returned pointer is to a static string and must not be freed.
+#[unsafe(no_mangle)]
+pub extern "C" fn extractous_error_get_last_category() -> *const c_char {
+    LAST_ERROR.with(|cell| match cell.borrow().as_ref() {
+        Some(last) => extractous_error_category(last.code),
+        None => c"none".as_ptr(),
+    })
+}
+
 /// Checks if debug information is available for the current thread
 #[unsafe(no_mangle)]
 pub extern "C" fn extractous_error_has_debug() -> c_int {
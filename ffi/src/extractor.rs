@@ -1,252 +1,3815 @@
-use crate::ecore::{CharSet, Extractor as CoreExtractor};
+use crate::archive;
+use crate::cancel::CancelToken;
+use crate::charset;
+use crate::detect;
+use crate::ecore::{
+    CharSet, Extractor as CoreExtractor, StreamReader, TesseractOcrConfig as CoreOcrConfig,
+};
 use crate::errors::*;
-use crate::metadata::metadata_to_c;
+use crate::json;
+use crate::lang;
+use crate::metadata::{
+    ensure_content_length, extractous_metadata_free, metadata_to_c_capped, push_metadata_json,
+    sorted_entries,
+};
+use crate::pdf;
+use crate::stream::StreamState;
+use crate::text;
+use crate::throttle;
 use crate::types::*;
+use crate::xhtml;
+use sha2::{Digest, Sha256};
+use std::borrow::Cow;
+use std::collections::HashMap;
 use std::ffi::{CStr, CString};
-use std::os::raw::c_char;
+use std::io::Read;
+use std::os::raw::{c_char, c_void};
+use std::path::{Path, PathBuf};
 use std::ptr;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Metadata as returned by the core extractor.
+type Metadata = HashMap<String, Vec<String>>;
+
+/// Default maximum length of string output, matching the core `Extractor`.
+pub(crate) const DEFAULT_EXTRACT_STRING_MAX_LENGTH: libc::c_int = 500_000;
+
+/// The state behind a `CExtractor` handle.
+///
+/// The core `Extractor` does not expose getters, so any setting that callers
+/// need to query back is mirrored here next to it.
+pub(crate) struct ExtractorState {
+    pub(crate) inner: CoreExtractor,
+    pub(crate) output_format: libc::c_int,
+    pub(crate) extract_string_max_length: libc::c_int,
+    /// One of the `CHARSET_*` encodings of the extracted text.
+    pub(crate) encoding: libc::c_int,
+    /// MIME types accepted before parsing; empty allows everything.
+    pub(crate) allowed_mime_types: Vec<String>,
+    /// Whether control characters are stripped from string output.
+    pub(crate) sanitize_output: bool,
+    /// The characters kept in or removed from string output, if filtered.
+    pub(crate) char_filter: Option<text::CharFilter>,
+    /// Whether runs of whitespace in string output are collapsed.
+    pub(crate) collapse_whitespace: bool,
+    /// One of the `NORM_*` forms applied to string output.
+    pub(crate) unicode_normalization: libc::c_int,
+    /// Maximum size in bytes of string output; 0 means no limit.
+    pub(crate) max_output_bytes: usize,
+    /// Whether file inputs may be symbolic links.
+    pub(crate) follow_symlinks: bool,
+    /// Directory that file inputs must resolve inside, if set.
+    pub(crate) root_dir: Option<PathBuf>,
+    /// Whether XML output is cut down to the inner markup of `<body>`.
+    pub(crate) xml_body_only: bool,
+    /// MIME type forced on a retry when detection finds no known type.
+    pub(crate) fallback_mime: Option<String>,
+    /// The OCR configuration last set, which per-call overrides start from.
+    pub(crate) ocr_config: CoreOcrConfig,
+    /// Largest uncompressed size of an archive entry; 0 means no limit.
+    pub(crate) max_embedded_bytes: u64,
+    /// MIME types of archive entries that are listed but not parsed.
+    pub(crate) skip_embedded_mime_types: Vec<String>,
+    /// Whether archive entries with the same bytes are parsed only once.
+    pub(crate) dedupe_embedded: bool,
+    /// How many times an extraction is retried after a transient JNI failure.
+    pub(crate) jni_retry: libc::c_int,
+    /// Most metadata entries returned from an extraction; 0 means no limit.
+    pub(crate) max_metadata_entries: usize,
+    /// Whether positioned extraction is requested.
+    pub(crate) include_coordinates: bool,
+    /// One of the `LINE_ENDING_*` modes applied to string output.
+    pub(crate) line_ending: libc::c_int,
+    /// One of the `BIDI_*` modes applied to string output.
+    pub(crate) bidi_mode: libc::c_int,
+    /// Whether extractions that would use the network are refused.
+    pub(crate) offline: bool,
+    /// Whether byte inputs decoded as UTF-8 must be valid UTF-8.
+    pub(crate) strict_utf8: bool,
+    /// Whether segmented extraction reports the language of each segment.
+    pub(crate) segment_language: bool,
+    /// The caller's transform of byte inputs before they are parsed, if set.
+    pub(crate) input_transform: Option<InputTransform>,
+}
+
+impl ExtractorState {
+    fn new() -> Self {
+        Self {
+            inner: CoreExtractor::new(),
+            output_format: OUTPUT_TEXT,
+            extract_string_max_length: DEFAULT_EXTRACT_STRING_MAX_LENGTH,
+            encoding: CHARSET_UTF_8,
+            allowed_mime_types: Vec::new(),
+            sanitize_output: false,
+            char_filter: None,
+            collapse_whitespace: false,
+            unicode_normalization: NORM_NONE,
+            max_output_bytes: 0,
+            follow_symlinks: true,
+            root_dir: None,
+            xml_body_only: false,
+            fallback_mime: None,
+            ocr_config: CoreOcrConfig::new(),
+            max_embedded_bytes: 0,
+            skip_embedded_mime_types: Vec::new(),
+            dedupe_embedded: false,
+            jni_retry: 0,
+            max_metadata_entries: 0,
+            include_coordinates: false,
+            line_ending: LINE_ENDING_PRESERVE,
+            bidi_mode: BIDI_PRESERVE,
+            offline: false,
+            strict_utf8: false,
+            segment_language: false,
+            input_transform: None,
+        }
+    }
+
+    /// Applies the configured post-processing to extracted string content.
+    pub(crate) fn finish_content(&self, content: String) -> String {
+        self.finish_content_checked(content).0
+    }
+
+    /// Like `finish_content`, also returning true if the content was cut to
+    /// the maximum output size.
+    pub(crate) fn finish_content_checked(&self, content: String) -> (String, bool) {
+        self.finish_content_as(content, self.output_format)
+    }
+
+    /// Like `finish_content_checked`, for content extracted in the given
+    /// `OUTPUT_*` format rather than the handle's own.
+    pub(crate) fn finish_content_as(&self, content: String, format: libc::c_int) -> (String, bool) {
+        let content = match xhtml::body_inner(&content) {
+            Some(body) if self.xml_body_only && format == OUTPUT_XML => body.to_string(),
+            _ => content,
+        };
+        let content = if self.sanitize_output {
+            text::strip_control_chars(content)
+        } else {
+            content
+        };
+        let content = match &self.char_filter {
+            Some(filter) => filter.apply(content),
+            None => content,
+        };
+        let content = if self.collapse_whitespace {
+            text::collapse_whitespace(content)
+        } else {
+            content
+        };
+        let content = text::reorder_bidi(content, self.bidi_mode);
+        let content = text::normalize(content, self.unicode_normalization);
+        let mut content = text::normalize_line_endings(content, self.line_ending);
+        let truncated = self.max_output_bytes > 0
+            && text::truncate_to_bytes(&mut content, self.max_output_bytes);
+        (content, truncated)
+    }
+
+    /// Reports the fallback MIME type, if one is set, as the `Content-Type` of
+    /// a document the parser could not identify.
+    pub(crate) fn with_fallback_content_type(&self, mut metadata: Metadata) -> Metadata {
+        let Some(fallback) = &self.fallback_mime else {
+            return metadata;
+        };
+        if let Some(mime) = metadata
+            .get_mut("Content-Type")
+            .and_then(|values| values.first_mut())
+            .filter(|mime| detect::mime_matches(detect::OCTET_STREAM, mime))
+        {
+            *mime = fallback.clone();
+        }
+        metadata
+    }
+
+    /// Rejects a file path with `ERR_IO_ERROR` if it is a symbolic link while
+    /// links are not followed, or if it resolves outside the root directory.
+    pub(crate) fn check_path(&self, path: &str) -> libc::c_int {
+        if !self.follow_symlinks
+            && std::fs::symlink_metadata(path).is_ok_and(|m| m.file_type().is_symlink())
+        {
+            return record_ffi_error(ERR_IO_ERROR, format!("{} is a symbolic link", path));
+        }
+        let Some(root) = &self.root_dir else {
+            return ERR_OK;
+        };
+        let root = match root.canonicalize() {
+            Ok(root) => root,
+            Err(e) => {
+                return record_ffi_error(
+                    ERR_IO_ERROR,
+                    format!("cannot resolve root {}: {}", root.display(), e),
+                );
+            }
+        };
+        match Path::new(path).canonicalize() {
+            Ok(resolved) if resolved.starts_with(&root) => ERR_OK,
+            Ok(_) => record_ffi_error(ERR_IO_ERROR, format!("path escapes root: {}", path)),
+            Err(e) => record_ffi_error(ERR_IO_ERROR, format!("cannot resolve {}: {}", path, e)),
+        }
+    }
+
+    /// Sniffs the MIME type of an input from its leading bytes and rejects it
+    /// with `ERR_MIME_NOT_ALLOWED` if it is not on the allow-list.
+    fn check_mime_allowed(&self, head: &[u8]) -> libc::c_int {
+        if self.allowed_mime_types.is_empty() {
+            return ERR_OK;
+        }
+        let mime = detect::sniff_mime(head);
+        if self
+            .allowed_mime_types
+            .iter()
+            .any(|allowed| detect::mime_matches(allowed, mime))
+        {
+            ERR_OK
+        } else {
+            record_ffi_error(
+                ERR_MIME_NOT_ALLOWED,
+                format!("detected MIME type {} is not allowed", mime),
+            )
+        }
+    }
+
+    /// Refuses a document with `ERR_INVALID_CONFIG` if the handle is offline
+    /// and the document refers to a resource on the network, naming it
+    /// `name` in the error.
+    fn check_offline_document(&self, doc: &[u8], name: &str) -> libc::c_int {
+        if !self.offline {
+            return ERR_OK;
+        }
+        match detect::network_reference(doc) {
+            Some(url) => record_ffi_error(
+                ERR_INVALID_CONFIG,
+                format!("cannot parse {} offline: it references {}", name, url),
+            ),
+            None => ERR_OK,
+        }
+    }
+
+    /// Applies `check_offline_document` to the file at `path`, naming it
+    /// `name`, reading the whole file if it is text, or each text entry of a
+    /// plain ZIP archive in full. Fails with `ERR_IO_ERROR` if the file cannot
+    /// be read for the check, rather than going ahead unchecked.
+    fn check_offline_file(&self, path: &str, name: &str) -> libc::c_int {
+        if !self.offline {
+            return ERR_OK;
+        }
+        let doc = match detect::read_head(path) {
+            Ok(head) if !detect::looks_like_text(&head) => {
+                return match archive::network_reference(path) {
+                    Ok(Some((entry, url))) => record_ffi_error(
+                        ERR_INVALID_CONFIG,
+                        format!(
+                            "cannot parse {} offline: {} references {}",
+                            name, entry, url
+                        ),
+                    ),
+                    Ok(None) => ERR_OK,
+                    Err(e) => record_ffi_error(
+                        ERR_IO_ERROR,
+                        format!("cannot read {} for the offline check: {}", name, e),
+                    ),
+                };
+            }
+            Ok(head) if head.len() < detect::SNIFF_LEN => Ok(head),
+            Ok(_) => std::fs::read(path),
+            Err(e) => Err(e),
+        };
+        match doc {
+            Ok(doc) => self.check_offline_document(&doc, name),
+            Err(e) => record_ffi_error(
+                ERR_IO_ERROR,
+                format!("cannot read {} for the offline check: {}", name, e),
+            ),
+        }
+    }
+
+    /// Applies the embedded-file filters to the archive at `path`, returning
+    /// the filtered copy to parse in its place if anything was filtered out.
+    /// Fails with `ERR_IO_ERROR` if the archive cannot be read for the check,
+    /// rather than going ahead unchecked.
+    fn filter_embedded(&self, path: &str) -> Result<Option<archive::TempCopy>, libc::c_int> {
+        let filter = archive::EntryFilter {
+            max_bytes: self.max_embedded_bytes,
+            skip_mime_types: &self.skip_embedded_mime_types,
+            dedupe: self.dedupe_embedded,
+        };
+        if !filter.is_active() {
+            return Ok(None);
+        }
+        archive::filter_entries(path, &filter).map_err(|e| {
+            record_ffi_error(
+                ERR_IO_ERROR,
+                format!("cannot check embedded files in {}: {}", path, e),
+            )
+        })
+    }
+
+    /// Renders the settings for `extractous_extractor_config_to_json`.
+    fn config_json(&self) -> String {
+        let strings = |values: &[String]| {
+            let mut out = String::from("[");
+            for (i, value) in values.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                json::push_string(&mut out, value);
+            }
+            out.push(']');
+            out
+        };
+        let optional = |value: Option<&str>| {
+            let mut out = String::new();
+            match value {
+                Some(value) => json::push_string(&mut out, value),
+                None => out.push_str("null"),
+            }
+            out
+        };
+        let char_filter = match &self.char_filter {
+            Some(filter) => {
+                let ranges: Vec<String> = filter
+                    .ranges
+                    .iter()
+                    .map(|(start, end)| format!("[{},{}]", start, end))
+                    .collect();
+                format!(
+                    "{{\"mode\":{},\"ranges\":[{}]}}",
+                    if filter.allow {
+                        CHAR_FILTER_ALLOW
+                    } else {
+                        CHAR_FILTER_DENY
+                    },
+                    ranges.join(",")
+                )
+            }
+            None => "null".to_string(),
+        };
+        let root_dir = self.root_dir.as_ref().map(|dir| dir.to_string_lossy());
+
+        let fields = [
+            ("output_format", self.output_format.to_string()),
+            (
+                "extract_string_max_length",
+                self.extract_string_max_length.to_string(),
+            ),
+            ("encoding", self.encoding.to_string()),
+            ("allowed_mime_types", strings(&self.allowed_mime_types)),
+            ("sanitize_output", self.sanitize_output.to_string()),
+            ("char_filter", char_filter),
+            ("collapse_whitespace", self.collapse_whitespace.to_string()),
+            (
+                "unicode_normalization",
+                self.unicode_normalization.to_string(),
+            ),
+            ("max_output_bytes", self.max_output_bytes.to_string()),
+            ("line_ending", self.line_ending.to_string()),
+            ("bidi_mode", self.bidi_mode.to_string()),
+            ("follow_symlinks", self.follow_symlinks.to_string()),
+            ("root_dir", optional(root_dir.as_deref())),
+            ("xml_body_only", self.xml_body_only.to_string()),
+            ("fallback_mime", optional(self.fallback_mime.as_deref())),
+            ("max_embedded_bytes", self.max_embedded_bytes.to_string()),
+            (
+                "skip_embedded_mime_types",
+                strings(&self.skip_embedded_mime_types),
+            ),
+            ("dedupe_embedded", self.dedupe_embedded.to_string()),
+            ("jni_retry", self.jni_retry.to_string()),
+            (
+                "max_metadata_entries",
+                self.max_metadata_entries.to_string(),
+            ),
+            ("include_coordinates", self.include_coordinates.to_string()),
+            ("offline", self.offline.to_string()),
+            ("strict_utf8", self.strict_utf8.to_string()),
+            ("segment_language", self.segment_language.to_string()),
+        ];
+        let mut out = String::from("{");
+        for (i, (key, value)) in fields.iter().enumerate() {
+            if i > 0 {
+                out.push(',');
+            }
+            json::push_string(&mut out, key);
+            out.push(':');
+            out.push_str(value);
+        }
+        out.push('}');
+        out
+    }
+}
+
+/// Applies the handle's post-processing to extracted string content.
+/// The handle must not be null.
+pub(crate) unsafe fn finish_content(handle: *const CExtractor, content: String) -> String {
+    unsafe { &*(handle as *const ExtractorState) }.finish_content(content)
+}
+
+/// Converts extracted metadata for output, keeping at most the handle's
+/// maximum number of entries. The handle must not be null.
+pub(crate) unsafe fn finish_metadata(
+    handle: *const CExtractor,
+    metadata: Metadata,
+) -> *mut CMetadata {
+    let state = unsafe { &*(handle as *const ExtractorState) };
+    let metadata = state.with_fallback_content_type(metadata);
+    unsafe { metadata_to_c_capped(metadata, state.max_metadata_entries) }
+}
+
+/// Applies the handle's post-processing to extracted string content, also
+/// returning true if it was cut to the maximum output size.
+/// The handle must not be null.
+pub(crate) unsafe fn finish_content_checked(
+    handle: *const CExtractor,
+    content: String,
+) -> (String, bool) {
+    unsafe { &*(handle as *const ExtractorState) }.finish_content_checked(content)
+}
+
+/// Refuses a URL extraction with `ERR_INVALID_CONFIG` if the handle is
+/// offline. A null handle passes, leaving the extraction to report it.
+unsafe fn check_online(handle: *const CExtractor, url: &str) -> libc::c_int {
+    if !handle.is_null() && unsafe { (*(handle as *const ExtractorState)).offline } {
+        return record_ffi_error(
+            ERR_INVALID_CONFIG,
+            format!("cannot fetch {}: the extractor is offline", url),
+        );
+    }
+    ERR_OK
+}
+
+/// Applies the handle's path restrictions and MIME allow-list to the file at
+/// `path`, and checks that it can be read. The handle must not be null.
+unsafe fn check_file_allowed(handle: *const CExtractor, path: &str) -> libc::c_int {
+    let state = unsafe { &*(handle as *const ExtractorState) };
+    let path_ok = state.check_path(path);
+    if path_ok != ERR_OK {
+        return path_ok;
+    }
+    let readable = check_readable(path);
+    if readable != ERR_OK {
+        return readable;
+    }
+    if !state.allowed_mime_types.is_empty() {
+        let mime_ok = match detect::read_head(path) {
+            Ok(head) => state.check_mime_allowed(&head),
+            Err(_) => ERR_OK,
+        };
+        if mime_ok != ERR_OK {
+            return mime_ok;
+        }
+    }
+    state.check_offline_file(path, path)
+}
+
+/// Rejects a file with `ERR_IO_ERROR` if it cannot be opened for reading or
+/// is a directory, so the common missing-file case is reported precisely and
+/// without a round trip through the JVM.
+fn check_readable(path: &str) -> libc::c_int {
+    let reason = match std::fs::File::open(path) {
+        Ok(file) if file.metadata().is_ok_and(|m| m.is_dir()) => "is a directory",
+        Ok(_) => return ERR_OK,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => "file not found",
+        Err(e) if e.kind() == std::io::ErrorKind::PermissionDenied => "permission denied",
+        Err(e) => return record_ffi_error(ERR_IO_ERROR, format!("cannot open {}: {}", path, e)),
+    };
+    record_ffi_error(ERR_IO_ERROR, format!("{}: {}", reason, path))
+}
+
+/// Applies the handle's MIME allow-list and offline mode to an in-memory
+/// document.
+pub(crate) unsafe fn check_bytes_allowed(handle: *const CExtractor, bytes: &[u8]) -> libc::c_int {
+    if handle.is_null() {
+        return ERR_OK;
+    }
+    let state = unsafe { &*(handle as *const ExtractorState) };
+    let mime_ok = state.check_mime_allowed(&bytes[..bytes.len().min(detect::SNIFF_LEN)]);
+    if mime_ok != ERR_OK {
+        return mime_ok;
+    }
+    state.check_offline_document(bytes, "the input")
+}
+
+/// A local file accepted for extraction, holding a slot of the concurrency
+/// limit until it is dropped or detached.
+///
+/// Every file extraction opens its input here, so the path restrictions, the
+/// MIME allow-list, the embedded-file filters, the concurrency limit and the
+/// handling of empty files apply to all of them alike.
+pub(crate) struct FileInput<'a> {
+    pub(crate) state: &'a ExtractorState,
+    /// The path as the caller gave it, reported in metadata and errors.
+    pub(crate) path: &'a str,
+    /// The filtered copy of an archive that is parsed in its place, if any.
+    filtered: Option<archive::TempCopy>,
+    /// Whether the file has no bytes, in which case it is not parsed.
+    empty: bool,
+    /// The slot taken for the extraction, until `detach` hands it over.
+    permit: Option<throttle::Permit>,
+}
+
+/// Content parsed from an input, with the value a 0-byte input gives.
+pub(crate) trait ParsedContent {
+    /// Returns the content of an input with no bytes.
+    fn empty() -> Self;
+}
+
+impl ParsedContent for String {
+    fn empty() -> Self {
+        String::new()
+    }
+}
+
+/// A stream, which is `None` for an input with no bytes.
+impl ParsedContent for Option<StreamReader> {
+    fn empty() -> Self {
+        None
+    }
+}
+
+impl<'a> FileInput<'a> {
+    /// Checks the file at `path` against the handle's settings, filters the
+    /// entries of an archive and takes a slot for the extraction.
+    ///
+    /// Fails with the code to return if `handle` or `path` is NULL, the path
+    /// is not valid UTF-8, the file is refused or cannot be read, or no slot
+    /// is free.
+    pub(crate) unsafe fn open(
+        handle: *const CExtractor,
+        path: *const c_char,
+    ) -> Result<Self, libc::c_int> {
+        unsafe { Self::open_while(handle, path, || Ok(None)) }
+    }
+
+    /// Like `open`, waiting for a slot only while `keep_waiting` allows, as
+    /// `throttle::acquire_while` does.
+    pub(crate) unsafe fn open_while(
+        handle: *const CExtractor,
+        path: *const c_char,
+        keep_waiting: impl FnMut() -> Result<Option<Duration>, libc::c_int>,
+    ) -> Result<Self, libc::c_int> {
+        if handle.is_null() || path.is_null() {
+            return Err(ERR_NULL_POINTER);
+        }
+        let path = unsafe { CStr::from_ptr(path) }
+            .to_str()
+            .map_err(|_| ERR_INVALID_UTF8)?;
+        let allowed = unsafe { check_file_allowed(handle, path) };
+        if allowed != ERR_OK {
+            return Err(allowed);
+        }
+        let state = unsafe { &*(handle as *const ExtractorState) };
+        let filtered = state.filter_embedded(path)?;
+        if filtered
+            .as_ref()
+            .is_some_and(|copy| copy.path().to_str().is_none())
+        {
+            return Err(ERR_INVALID_UTF8);
+        }
+        Ok(Self {
+            state,
+            path,
+            filtered,
+            empty: is_empty_file(path),
+            permit: Some(throttle::acquire_while(keep_waiting)?),
+        })
+    }
+
+    /// Returns the path the parser reads: the filtered copy if there is one.
+    pub(crate) fn parse_path(&self) -> &str {
+        self.filtered
+            .as_ref()
+            .and_then(|copy| copy.path().to_str())
+            .unwrap_or(self.path)
+    }
+
+    /// Parses the input by calling `parse` with `extractor` and the path to
+    /// read, adding the file's size and name to the metadata. Transient JNI
+    /// failures are retried as the handle allows. An empty file is not
+    /// parsed: it gives empty content and empty metadata, whatever its
+    /// extension.
+    pub(crate) fn extract<T: ParsedContent>(
+        &self,
+        extractor: &CoreExtractor,
+        parse: impl Fn(&CoreExtractor, &str) -> Result<(T, Metadata), crate::ecore::Error>,
+    ) -> Result<(T, Metadata), crate::ecore::Error> {
+        if self.empty {
+            return Ok((T::empty(), Metadata::new()));
+        }
+        retry_transient_jni(self.state.jni_retry, || {
+            with_file_metadata(parse(extractor, self.parse_path()), self.path)
+        })
+    }
+
+    /// Parses the input into a string with `extractor`, retrying with the
+    /// handle's fallback MIME type if the parser cannot identify it.
+    pub(crate) fn extract_to_string(
+        &self,
+        extractor: &CoreExtractor,
+    ) -> Result<(String, Metadata), crate::ecore::Error> {
+        let fallback = self.state.fallback_mime.as_deref();
+        self.extract(extractor, |extractor, path| {
+            parse_string(extractor, path, fallback)
+        })
+    }
+
+    /// Parses the input into a stream with `extractor`, or `None` for an
+    /// empty file.
+    pub(crate) fn extract_stream(
+        &self,
+        extractor: &CoreExtractor,
+    ) -> Result<(Option<StreamReader>, Metadata), crate::ecore::Error> {
+        self.extract(extractor, parse_stream)
+    }
+
+    /// Wraps a stream parsed from the input in a new handle. The handle keeps
+    /// any filtered copy, which the parser reads as the stream is read.
+    fn into_stream_handle(self, reader: Option<StreamReader>) -> *mut CStreamReader {
+        StreamState::into_file_handle(reader, self.filtered)
+    }
+
+    /// Moves what a worker thread needs to parse the input into a
+    /// `DetachedInput`, so that a filtered copy and the extraction's slot
+    /// outlive the call if the caller stops waiting for the worker.
+    fn detach(&mut self) -> DetachedInput {
+        DetachedInput {
+            path: self.path.to_string(),
+            parse_path: self.parse_path().to_string(),
+            jni_retry: self.state.jni_retry,
+            fallback_mime: self.state.fallback_mime.clone(),
+            empty: self.empty,
+            _filtered: self.filtered.take(),
+            _permit: self.permit.take().map(throttle::Permit::detach),
+        }
+    }
+}
+
+/// Parses the file at `path` into a string, retrying with the `fallback` MIME
+/// type as `with_fallback_mime` does.
+fn parse_string(
+    extractor: &CoreExtractor,
+    path: &str,
+    fallback: Option<&str>,
+) -> Result<(String, Metadata), crate::ecore::Error> {
+    let result = extractor.extract_file_to_string(path);
+    with_fallback_mime(fallback, extractor, result, |tmp| {
+        std::fs::copy(path, tmp).map(|_| ())
+    })
+}
+
+/// Parses the file at `path` into a stream.
+fn parse_stream(
+    extractor: &CoreExtractor,
+    path: &str,
+) -> Result<(Option<StreamReader>, Metadata), crate::ecore::Error> {
+    extractor
+        .extract_file(path)
+        .map(|(reader, metadata)| (Some(reader), metadata))
+}
+
+/// A `FileInput` handed to a worker thread.
+struct DetachedInput {
+    path: String,
+    parse_path: String,
+    jni_retry: libc::c_int,
+    fallback_mime: Option<String>,
+    empty: bool,
+    _filtered: Option<archive::TempCopy>,
+    _permit: Option<throttle::Permit>,
+}
+
+impl DetachedInput {
+    /// Parses the input as `FileInput::extract` does.
+    fn extract<T: ParsedContent>(
+        &self,
+        extractor: &CoreExtractor,
+        parse: impl Fn(&CoreExtractor, &str) -> Result<(T, Metadata), crate::ecore::Error>,
+    ) -> Result<(T, Metadata), crate::ecore::Error> {
+        if self.empty {
+            return Ok((T::empty(), Metadata::new()));
+        }
+        retry_transient_jni(self.jni_retry, || {
+            with_file_metadata(parse(extractor, &self.parse_path), &self.path)
+        })
+    }
+
+    /// Parses the input as `FileInput::extract_to_string` does.
+    fn extract_to_string(
+        &self,
+        extractor: &CoreExtractor,
+    ) -> Result<(String, Metadata), crate::ecore::Error> {
+        let fallback = self.fallback_mime.as_deref();
+        self.extract(extractor, |extractor, path| {
+            parse_string(extractor, path, fallback)
+        })
+    }
+}
 
 /// Creates a new `Extractor` with a default configuration.
 /// The returned handle must be freed with `extractous_extractor_free`.
 // #[must_use]
 #[unsafe(no_mangle)]
 pub extern "C" fn extractous_extractor_new() -> *mut CExtractor {
-    let extractor = Box::new(CoreExtractor::new());
+    let extractor = Box::new(ExtractorState::new());
     Box::into_raw(extractor) as *mut CExtractor
 }
 
-/// Frees the memory associated with an `Extractor` handle.
-#[unsafe(no_mangle)]
-pub unsafe extern "C" fn extractous_extractor_free(handle: *mut CExtractor) {
-    if !handle.is_null() {
-        unsafe {
-            drop(Box::from_raw(handle as *mut CoreExtractor));
+/// Frees the memory associated with an `Extractor` handle.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_extractor_free(handle: *mut CExtractor) {
+    if !handle.is_null() {
+        unsafe {
+            drop(Box::from_raw(handle as *mut ExtractorState));
+        }
+    }
+}
+
+/// A macro to safely update an Extractor instance behind a raw pointer.
+macro_rules! update_extractor {
+    ($handle:expr, |$extractor_val:ident| $body:block) => {
+        if $handle.is_null() {
+            return;
+        }
+        unsafe {
+            let extractor_ptr = &raw mut (*($handle as *mut ExtractorState)).inner;
+            let old_extractor = ptr::read(extractor_ptr);
+            let new_extractor = {
+                let $extractor_val = old_extractor;
+                $body
+            };
+            ptr::write(extractor_ptr, new_extractor);
+        }
+    };
+}
+
+/// Sets the maximum length for extracted string content.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_extractor_set_extract_string_max_length_mut(
+    handle: *mut CExtractor,
+    max_length: libc::c_int,
+) {
+    update_extractor!(handle, |extractor| {
+        extractor.set_extract_string_max_length(max_length)
+    });
+    unsafe { (*(handle as *mut ExtractorState)).extract_string_max_length = max_length };
+}
+
+/// Sets the character encoding for the extracted text.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_extractor_set_encoding_mut(
+    handle: *mut CExtractor,
+    encoding: libc::c_int,
+) {
+    update_extractor!(handle, |extractor| {
+        let charset = match encoding {
+            CHARSET_UTF_8 => CharSet::UTF_8,
+            CHARSET_US_ASCII => CharSet::US_ASCII,
+            CHARSET_UTF_16BE => CharSet::UTF_16BE,
+            _ => return,
+        };
+        extractor.set_encoding(charset)
+    });
+    unsafe { (*(handle as *mut ExtractorState)).encoding = encoding };
+}
+
+/// Sets the configuration for the PDF parser.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_extractor_set_pdf_config_mut(
+    handle: *mut CExtractor,
+    config: *const CPdfParserConfig,
+) {
+    if config.is_null() {
+        return;
+    }
+    update_extractor!(handle, |extractor| {
+        let pdf_config = &*(config as *const crate::ecore::PdfParserConfig);
+        extractor.set_pdf_config(pdf_config.clone())
+    });
+}
+
+/// Sets the configuration for the Office document parser.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_extractor_set_office_config_mut(
+    handle: *mut CExtractor,
+    config: *const COfficeParserConfig,
+) {
+    if config.is_null() {
+        return;
+    }
+    update_extractor!(handle, |extractor| {
+        let office_config = &*(config as *const crate::ecore::OfficeParserConfig);
+        extractor.set_office_config(office_config.clone())
+    });
+}
+
+/// Sets the configuration for Tesseract OCR.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_extractor_set_ocr_config_mut(
+    handle: *mut CExtractor,
+    config: *const CTesseractOcrConfig,
+) {
+    if config.is_null() {
+        return;
+    }
+    let ocr_config = unsafe { &*(config as *const CoreOcrConfig) };
+    update_extractor!(handle, |extractor| {
+        extractor.set_ocr_config(ocr_config.clone())
+    });
+    unsafe { (*(handle as *mut ExtractorState)).ocr_config = ocr_config.clone() };
+}
+
+/// Sets whether to output structured XML instead of plain text.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_extractor_set_xml_output_mut(
+    handle: *mut CExtractor,
+    xml_output: bool,
+) {
+    update_extractor!(handle, |extractor| { extractor.set_xml_output(xml_output) });
+    unsafe {
+        (*(handle as *mut ExtractorState)).output_format =
+            if xml_output { OUTPUT_XML } else { OUTPUT_TEXT };
+    }
+}
+
+/// Restricts extraction to documents whose MIME type is in `types`.
+///
+/// The type is sniffed from the leading bytes of file and byte inputs before
+/// the parser runs, and other documents are rejected with
+/// `ERR_MIME_NOT_ALLOWED`. An entry with a `*` subtype allows a whole
+/// top-level type. Passing an empty list allows everything again. URL inputs
+/// are not checked. The strings are copied; entries that are NULL or not valid
+/// UTF-8 are skipped.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_extractor_set_allowed_mime_types_mut(
+    handle: *mut CExtractor,
+    types: *const *const c_char,
+    count: libc::size_t,
+) {
+    if handle.is_null() || (types.is_null() && count > 0) {
+        return;
+    }
+    let entries: &[*const c_char] = if count == 0 {
+        &[]
+    } else {
+        unsafe { std::slice::from_raw_parts(types, count) }
+    };
+    let allowed = entries
+        .iter()
+        .filter(|p| !p.is_null())
+        .filter_map(|&p| unsafe { CStr::from_ptr(p) }.to_str().ok())
+        .map(|t| t.trim().to_string())
+        .collect();
+    unsafe { (*(handle as *mut ExtractorState)).allowed_mime_types = allowed };
+}
+
+/// Sets whether file inputs may be symbolic links. When disabled, a path
+/// that is itself a symbolic link is rejected with `ERR_IO_ERROR`; links in
+/// its parent directories are not checked. Enabled by default.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_extractor_set_follow_symlinks_mut(
+    handle: *mut CExtractor,
+    follow: bool,
+) {
+    if handle.is_null() {
+        return;
+    }
+    unsafe { (*(handle as *mut ExtractorState)).follow_symlinks = follow };
+}
+
+/// Restricts file inputs to paths inside the directory `root`.
+///
+/// Each input path is resolved, following `..` components and symbolic links,
+/// and rejected with `ERR_IO_ERROR` if it ends up outside `root`; the debug
+/// report then reads "path escapes root". Paths that cannot be resolved, such
+/// as missing files, are rejected too. Passing NULL removes the restriction,
+/// which is the default. URL and byte inputs are not affected.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_extractor_set_root_dir_mut(
+    handle: *mut CExtractor,
+    root: *const c_char,
+) {
+    if handle.is_null() {
+        return;
+    }
+    let root_dir = if root.is_null() {
+        None
+    } else {
+        match unsafe { CStr::from_ptr(root) }.to_str() {
+            Ok(root) => Some(PathBuf::from(root)),
+            Err(_) => return,
+        }
+    };
+    unsafe { (*(handle as *mut ExtractorState)).root_dir = root_dir };
+}
+
+/// Sets whether control characters are stripped from string output.
+///
+/// When enabled, the C0 control characters U+0000–U+0008, U+000B (vertical
+/// tab), U+000C (form feed) and U+000E–U+001F are removed from content
+/// returned as a string. Tab, line feed and carriage return are kept, as are
+/// DEL and the C1 controls. Stream output is not affected. Disabled by default.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_extractor_set_sanitize_output_mut(
+    handle: *mut CExtractor,
+    enabled: bool,
+) {
+    if handle.is_null() {
+        return;
+    }
+    unsafe { (*(handle as *mut ExtractorState)).sanitize_output = enabled };
+}
+
+/// Sets whether whitespace in string output is collapsed, for search indexing
+/// where layout spacing only bloats the index.
+///
+/// When enabled, each run of whitespace becomes a single newline if it
+/// contains a line break, keeping paragraph boundaries, or a single space
+/// otherwise, and leading and trailing whitespace is removed. This happens
+/// after sanitization and before normalization. Stream output is not affected.
+/// Disabled by default.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_extractor_set_collapse_whitespace_mut(
+    handle: *mut CExtractor,
+    enabled: bool,
+) {
+    if handle.is_null() {
+        return;
+    }
+    unsafe { (*(handle as *mut ExtractorState)).collapse_whitespace = enabled };
+}
+
+/// Sets the largest uncompressed size in bytes of a file embedded in an
+/// archive, to guard against decompression bombs. 0, the default, means no
+/// limit.
+///
+/// Before any file extraction parses a plain ZIP archive, each entry is
+/// decompressed up to the limit, and entries over it are left out of a
+/// temporary copy that is parsed instead, so a header that understates the
+/// size does not let an entry through. A `LOG_WARN` message naming each
+/// skipped entry goes to the log callback. ZIP-based document formats such
+/// as DOCX and ODT are not checked, since every part is needed to read them,
+/// nor are entries of nested archives. If the archive cannot be read for the
+/// check, extraction fails with `ERR_IO_ERROR` rather than going ahead
+/// unchecked.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_extractor_set_max_embedded_bytes_mut(
+    handle: *mut CExtractor,
+    max_bytes: libc::size_t,
+) {
+    if handle.is_null() {
+        return;
+    }
+    unsafe { (*(handle as *mut ExtractorState)).max_embedded_bytes = max_bytes as u64 };
+}
+
+/// Sets MIME types of embedded files that are listed but not parsed for
+/// content, to speed up recursion over archives full of media such as images
+/// and videos. An entry with a `*` subtype skips a whole top-level type.
+///
+/// Before any file extraction parses a plain ZIP archive, the type of each
+/// entry is sniffed from its leading bytes as for
+/// `extractous_extractor_set_allowed_mime_types_mut`, and matching entries are
+/// emptied in a temporary copy that is parsed instead. They keep their names,
+/// so the parser still reports them. A `LOG_INFO` message naming each skipped
+/// entry goes to the log callback. As for
+/// `extractous_extractor_set_max_embedded_bytes_mut`, ZIP-based document
+/// formats such as DOCX are left alone, only the archive's own entries are
+/// checked, and an archive that cannot be read for the check fails with
+/// `ERR_IO_ERROR`. Passing an empty list parses everything again.
+/// The strings are copied; entries that are NULL or not valid UTF-8 are
+/// skipped.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_extractor_set_skip_embedded_mime_types_mut(
+    handle: *mut CExtractor,
+    types: *const *const c_char,
+    count: libc::size_t,
+) {
+    if handle.is_null() || (types.is_null() && count > 0) {
+        return;
+    }
+    let entries: &[*const c_char] = if count == 0 {
+        &[]
+    } else {
+        unsafe { std::slice::from_raw_parts(types, count) }
+    };
+    let skipped = entries
+        .iter()
+        .filter(|p| !p.is_null())
+        .filter_map(|&p| unsafe { CStr::from_ptr(p) }.to_str().ok())
+        .map(|t| t.trim().to_string())
+        .collect();
+    unsafe { (*(handle as *mut ExtractorState)).skip_embedded_mime_types = skipped };
+}
+
+/// Sets whether archive entries byte-for-byte identical to an earlier entry
+/// are parsed, so archives holding copies of the same file do not repeat its
+/// content. Disabled by default.
+///
+/// Before any file extraction parses a plain ZIP archive, each entry's
+/// decompressed bytes are hashed, and entries with the same bytes as an
+/// earlier one are emptied in a temporary copy that is parsed instead. They
+/// keep their names, so the parser still reports them. Entries are compared
+/// by their bytes, not by the text extracted from them, so files that differ
+/// in any byte are all parsed even when they give the same text. The number
+/// of duplicates goes to the log callback as a `LOG_INFO` message. As for
+/// `extractous_extractor_set_max_embedded_bytes_mut`, ZIP-based document
+/// formats such as DOCX are left alone and only the archive's own entries
+/// are checked.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_extractor_set_dedupe_embedded_mut(
+    handle: *mut CExtractor,
+    enabled: bool,
+) {
+    if handle.is_null() {
+        return;
+    }
+    unsafe { (*(handle as *mut ExtractorState)).dedupe_embedded = enabled };
+}
+
+/// Sets how many times an extraction is retried when calling into the parser
+/// runtime fails transiently, for example because the thread could not be
+/// attached to the JVM. Failures raised by the parser itself, such as an
+/// unreadable document, are never retried.
+///
+/// Retries wait 10 ms longer each time: 10 ms, 20 ms and so on. They apply to
+/// every call that parses a file, bytes or a URL, including those that run
+/// on a worker thread. The default is 0, no retries; negative values are
+/// treated as 0.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_extractor_set_jni_retry_mut(
+    handle: *mut CExtractor,
+    attempts: libc::c_int,
+) {
+    if handle.is_null() {
+        return;
+    }
+    unsafe { (*(handle as *mut ExtractorState)).jni_retry = attempts.max(0) };
+}
+
+/// Sets the most metadata entries an extraction returns, to keep documents
+/// with thousands of entries from bloating `CMetadata` and derived JSON.
+///
+/// Keys are sorted as in every `CMetadata` and the first `max` are kept, so
+/// the subset is the same each time. The number of entries dropped goes to
+/// the log callback as a `LOG_WARN` message. 0 or a negative value, the
+/// default, means no limit.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_extractor_set_max_metadata_entries_mut(
+    handle: *mut CExtractor,
+    max: libc::c_int,
+) {
+    if handle.is_null() {
+        return;
+    }
+    unsafe { (*(handle as *mut ExtractorState)).max_metadata_entries = max.max(0) as usize };
+}
+
+/// Sets whether `extractous_extractor_extract_file_positioned` may run, which
+/// returns text runs with their coordinates on the page. Disabled by default,
+/// in which case that call returns `ERR_INVALID_CONFIG`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_extractor_set_include_coordinates_mut(
+    handle: *mut CExtractor,
+    enabled: bool,
+) {
+    if handle.is_null() {
+        return;
+    }
+    unsafe { (*(handle as *mut ExtractorState)).include_coordinates = enabled };
+}
+
+/// Sets the Unicode normalization form applied to string output, using one of
+/// the `NORM_*` constants. The default is `NORM_NONE`, which leaves the text as
+/// the parser produced it. Unknown forms are ignored. Stream output is not
+/// affected.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_extractor_set_unicode_normalization_mut(
+    handle: *mut CExtractor,
+    form: libc::c_int,
+) {
+    if handle.is_null() || !(NORM_NONE..=NORM_NFKD).contains(&form) {
+        return;
+    }
+    unsafe { (*(handle as *mut ExtractorState)).unicode_normalization = form };
+}
+
+/// Sets whether the handle must work without network access, for sandboxed
+/// and air-gapped deployments.
+///
+/// When offline, the URL extraction functions return `ERR_INVALID_CONFIG`
+/// without connecting, and so does extracting a text document (XML, HTML,
+/// SVG or any other text file or buffer) that refers to a resource on the
+/// network: a doctype or entity declaration whose system identifier is an
+/// `http:`, `ftp:` or other non-`file:` URL, an XInclude `href`, or an XML
+/// Schema location. Such documents are refused before parsing rather than
+/// relying on the parser not to resolve them, so a common XHTML doctype
+/// naming its DTD by URL is refused too. Text inputs, and the text entries
+/// of a plain ZIP file, are read in full for the check; entries of nested
+/// archives and of ZIP buffers are not checked. Disabled by default for
+/// compatibility; enabling it is recommended wherever inputs are untrusted.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_extractor_set_offline_mut(
+    handle: *mut CExtractor,
+    offline: bool,
+) {
+    if handle.is_null() {
+        return;
+    }
+    unsafe { (*(handle as *mut ExtractorState)).offline = offline };
+}
+
+/// Sets code point ranges that filter string output, such as U+E000–U+F8FF
+/// to drop the private-use glyphs some PDFs' fonts leave in their text.
+///
+/// With `CHAR_FILTER_DENY`, characters in any of the `count` ranges are
+/// removed; with `CHAR_FILTER_ALLOW`, only those characters are kept, so the
+/// ranges must include any whitespace wanted. Ranges are inclusive, and those
+/// with `start` after `end` are skipped. Passing no ranges turns filtering
+/// off, as it is by default. Unknown modes are ignored. The ranges are
+/// copied. Filtering runs after control characters are stripped and before
+/// whitespace is collapsed. Stream output is not affected.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_extractor_set_char_filter_mut(
+    handle: *mut CExtractor,
+    ranges: *const CCharRange,
+    count: libc::size_t,
+    mode: libc::c_int,
+) {
+    if handle.is_null()
+        || (ranges.is_null() && count > 0)
+        || !(CHAR_FILTER_DENY..=CHAR_FILTER_ALLOW).contains(&mode)
+    {
+        return;
+    }
+    let ranges: &[CCharRange] = if count == 0 {
+        &[]
+    } else {
+        unsafe { std::slice::from_raw_parts(ranges, count) }
+    };
+    let ranges: Vec<(u32, u32)> = ranges
+        .iter()
+        .filter(|r| r.start <= r.end)
+        .map(|r| (r.start, r.end))
+        .collect();
+    let filter = (!ranges.is_empty()).then_some(text::CharFilter {
+        ranges,
+        allow: mode == CHAR_FILTER_ALLOW,
+    });
+    unsafe { (*(handle as *mut ExtractorState)).char_filter = filter };
+}
+
+/// Sets whether byte inputs that the parser decodes as UTF-8 text must be
+/// valid UTF-8. Disabled by default.
+///
+/// The parser replaces bytes it cannot decode with U+FFFD. When strict, the
+/// `extractous_extractor_extract_bytes*` functions instead fail with
+/// `ERR_INVALID_UTF8` if the input was decoded as UTF-8, according to its
+/// `Content-Encoding` or `Content-Type` charset, and is not valid UTF-8. The
+/// debug report gives the offset of the first invalid byte. Text the parser
+/// decodes in another charset, such as Windows-1252, is not affected.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_extractor_set_strict_utf8_mut(
+    handle: *mut CExtractor,
+    strict: bool,
+) {
+    if handle.is_null() {
+        return;
+    }
+    unsafe { (*(handle as *mut ExtractorState)).strict_utf8 = strict };
+}
+
+/// Sets whether `extractous_extractor_extract_file_segmented` guesses the
+/// language of each segment on its own. Disabled by default.
+///
+/// When enabled, the metadata of every segment whose text can be classified,
+/// the top-level document and each embedded resource alike, gets a
+/// `language` key with the code
+/// `extractous_extractor_extract_file_to_string_lang` would report for that
+/// text alone, so the parts of a multilingual archive keep their own
+/// languages.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_extractor_set_segment_language_mut(
+    handle: *mut CExtractor,
+    enabled: bool,
+) {
+    if handle.is_null() {
+        return;
+    }
+    unsafe { (*(handle as *mut ExtractorState)).segment_language = enabled };
+}
+
+/// Turns the raw bytes of an input into the bytes to parse, for instance by
+/// decrypting or decompressing a custom container. On success it sets
+/// `*out_ptr` to a buffer allocated with `malloc` and `*out_len` to its
+/// length, and returns `ERR_OK`. `in_ptr` is only valid for the duration of
+/// the call.
+pub type CInputTransformCallback = Option<
+    unsafe extern "C" fn(
+        ctx: *mut c_void,
+        in_ptr: *const u8,
+        in_len: libc::size_t,
+        out_ptr: *mut *mut u8,
+        out_len: *mut libc::size_t,
+    ) -> libc::c_int,
+>;
+
+/// A caller's transform of byte inputs, with the context passed to it.
+pub(crate) struct InputTransform {
+    callback: unsafe extern "C" fn(
+        *mut c_void,
+        *const u8,
+        libc::size_t,
+        *mut *mut u8,
+        *mut libc::size_t,
+    ) -> libc::c_int,
+    ctx: *mut c_void,
+}
+
+/// Sets a transform applied to the bytes of every byte input before it is
+/// checked and parsed, for formats the parser cannot read as they are, such
+/// as encrypted or custom-compressed containers. Pass NULL to remove it,
+/// which is the default.
+///
+/// The transform gets `ctx` and the input, and hands back the bytes to
+/// parse in a buffer allocated with `malloc`, which the library frees after
+/// copying it. A NULL buffer with a length of 0 is an empty document. If the
+/// transform returns anything but `ERR_OK`, the extraction fails with
+/// `ERR_ABORTED`, after freeing any buffer it set. The
+/// `extractous_extractor_extract_bytes*` and
+/// `extractous_extractor_extract_iovec_to_string` functions apply it; file
+/// and URL inputs are parsed as they are. The transform runs on the
+/// extracting thread, and `ctx` must stay valid while it is set.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_extractor_set_input_transform_mut(
+    handle: *mut CExtractor,
+    transform_cb: CInputTransformCallback,
+    ctx: *mut c_void,
+) {
+    if handle.is_null() {
+        return;
+    }
+    let transform = transform_cb.map(|callback| InputTransform { callback, ctx });
+    unsafe { (*(handle as *mut ExtractorState)).input_transform = transform };
+}
+
+/// Sets how line endings in string output are normalized, using one of the
+/// `LINE_ENDING_*` constants: `LINE_ENDING_LF` and `LINE_ENDING_CRLF` rewrite
+/// every `\r\n`, `\r` and `\n` to that ending. The default is
+/// `LINE_ENDING_PRESERVE`, which leaves them as the parser produced them.
+/// Unknown modes are ignored. Stream output is not affected.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_extractor_set_line_ending_mut(
+    handle: *mut CExtractor,
+    mode: libc::c_int,
+) {
+    if handle.is_null() || !(LINE_ENDING_PRESERVE..=LINE_ENDING_CRLF).contains(&mode) {
+        return;
+    }
+    unsafe { (*(handle as *mut ExtractorState)).line_ending = mode };
+}
+
+/// Sets how right-to-left text such as Arabic and Hebrew is ordered in
+/// string output, using one of the `BIDI_*` constants. The default is
+/// `BIDI_PRESERVE`, which leaves it in the order the parser produced.
+///
+/// Some PDFs yield right-to-left text in visual order, reversed from the
+/// order it is read in. `BIDI_LOGICAL` takes each line holding right-to-left
+/// text from visual order back to logical order, undoing the Unicode
+/// Bidirectional Algorithm, with the line's direction taken from its last
+/// strong character. Visual order does not record everything the algorithm
+/// used, so a few lines, such as a number next to a right-to-left word in a
+/// left-to-right line, may not come back exactly as written. `BIDI_VISUAL`
+/// takes logical text to visual order with the algorithm, for displays
+/// without bidirectional support, with the line's direction taken from its
+/// first strong character. The parser does not report which order it
+/// produced, so choosing the wrong mode scrambles text that was already in
+/// the wanted order. Lines without right-to-left characters are left as they
+/// are. Unknown modes are ignored. Stream output is not affected.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_extractor_set_bidi_mode_mut(
+    handle: *mut CExtractor,
+    mode: libc::c_int,
+) {
+    if handle.is_null() || !(BIDI_PRESERVE..=BIDI_VISUAL).contains(&mode) {
+        return;
+    }
+    unsafe { (*(handle as *mut ExtractorState)).bidi_mode = mode };
+}
+
+/// Sets the maximum size in bytes of string output, not counting the null
+/// terminator. Longer content is cut at the last UTF-8 character boundary
+/// within the limit, after sanitization and normalization. Unlike
+/// `extractous_extractor_set_extract_string_max_length_mut`, which counts
+/// characters, this bounds the size of the returned buffer. 0, the default,
+/// means no limit. Stream output is not affected.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_extractor_set_max_output_bytes_mut(
+    handle: *mut CExtractor,
+    max_bytes: libc::size_t,
+) {
+    if handle.is_null() {
+        return;
+    }
+    unsafe { (*(handle as *mut ExtractorState)).max_output_bytes = max_bytes };
+}
+
+/// Sets whether XML output is reduced to the inner markup of the `<body>`
+/// element, dropping the XHTML `<head>` with its metadata and the surrounding
+/// scaffolding. Disabled by default, which returns the full document. Text and
+/// stream output are not affected.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_extractor_set_xml_body_only_mut(
+    handle: *mut CExtractor,
+    body_only: bool,
+) {
+    if handle.is_null() {
+        return;
+    }
+    unsafe { (*(handle as *mut ExtractorState)).xml_body_only = body_only };
+}
+
+/// Sets a MIME type to fall back on when the parser cannot identify a
+/// document and reports it as `application/octet-stream`.
+///
+/// Every file extraction that parses into a string or JSON, and every string
+/// extraction from bytes or slices, then retries once with the document
+/// treated as `mime`, which suits pipelines that know what their inputs are
+/// even when detection fails.
+/// The type is forced through the file extension the parser sees, using a
+/// copy of the input in the temp directory, so only the types that
+/// `extractous_detect_mime_by_filename` gives an extension, such as
+/// `text/csv`, `text/plain` or `application/pdf`, are accepted; others are
+/// ignored, as is invalid UTF-8. Every extraction also reports `mime` as the
+/// `Content-Type` of such a document, including streamed ones, whose content
+/// is not parsed again. Passing NULL clears the fallback, which is the
+/// default.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_extractor_set_fallback_mime_mut(
+    handle: *mut CExtractor,
+    mime: *const c_char,
+) {
+    if handle.is_null() {
+        return;
+    }
+    let fallback = if mime.is_null() {
+        None
+    } else {
+        match unsafe { CStr::from_ptr(mime) }.to_str() {
+            Ok(mime) if detect::extension_for(mime).is_some() => Some(mime.trim().to_string()),
+            _ => return,
+        }
+    };
+    unsafe { (*(handle as *mut ExtractorState)).fallback_mime = fallback };
+}
+
+/// Sets the output format using one of the `OUTPUT_*` constants.
+/// Unknown formats are ignored and leave the current format unchanged.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_extractor_set_output_format_mut(
+    handle: *mut CExtractor,
+    format: libc::c_int,
+) {
+    let xml_output = match format {
+        OUTPUT_TEXT => false,
+        OUTPUT_XML => true,
+        _ => return,
+    };
+    unsafe { extractous_extractor_set_xml_output_mut(handle, xml_output) };
+}
+
+/// Returns the current output format as one of the `OUTPUT_*` constants,
+/// or `ERR_NULL_POINTER` if the handle is null.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_extractor_get_output_format(
+    handle: *const CExtractor,
+) -> libc::c_int {
+    if handle.is_null() {
+        return ERR_NULL_POINTER;
+    }
+    unsafe { (*(handle as *const ExtractorState)).output_format }
+}
+
+/// Returns true only if the output format is `OUTPUT_XML`.
+///
+/// Kept for compatibility; prefer `extractous_extractor_get_output_format`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_extractor_get_xml_output(handle: *const CExtractor) -> bool {
+    unsafe { extractous_extractor_get_output_format(handle) == OUTPUT_XML }
+}
+
+/// Describes the extractor's effective settings as a JSON object, so bug
+/// reports can include the exact configuration.
+///
+/// Each setting is included under the name of its setter, such as
+/// `"output_format"` or `"max_output_bytes"`, with enumerations given as the
+/// value of their C constant, a setting that is off or unlimited as `false`,
+/// `0` or `null`, and lists as arrays. The PDF, Office and OCR
+/// configurations are left out: the core library does not expose their
+/// fields once set. `*out_json` must be freed with `extractous_string_free`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_extractor_config_to_json(
+    handle: *const CExtractor,
+    out_json: *mut *mut c_char,
+) -> libc::c_int {
+    if handle.is_null() || out_json.is_null() {
+        return ERR_NULL_POINTER;
+    }
+    let state = unsafe { &*(handle as *const ExtractorState) };
+    let json = state.config_json();
+    // Control characters are escaped, so the document has no interior NUL.
+    unsafe { *out_json = CString::new(json).map_or(ptr::null_mut(), |s| s.into_raw()) };
+    ERR_OK
+}
+
+// Macro to handle the common extraction logic and error wrapping.
+macro_rules! perform_extraction {
+    (
+        $handle:expr,
+        $out_ptr1:expr,
+        $out_ptr2:expr,
+        $extractor_call:expr,
+        $success_handler:expr
+    ) => {
+        perform_extraction!(
+            $handle,
+            $out_ptr1,
+            $out_ptr2,
+            $extractor_call,
+            $success_handler,
+            record_error
+        )
+    };
+    (
+        $handle:expr,
+        $out_ptr1:expr,
+        $out_ptr2:expr,
+        $extractor_call:expr,
+        $success_handler:expr,
+        $error_handler:expr
+    ) => {{
+        if $handle.is_null() || $out_ptr1.is_null() || $out_ptr2.is_null() {
+            return ERR_NULL_POINTER;
+        }
+
+        let _permit = match throttle::acquire() {
+            Ok(permit) => permit,
+            Err(code) => return code,
+        };
+        // Safely get a shared reference to the extractor.
+        let state = unsafe { &*($handle as *const ExtractorState) };
+        let extractor = &state.inner;
+        let call = $extractor_call;
+
+        match retry_transient_jni(state.jni_retry, || call(extractor)) {
+            Ok((res1, res2)) => {
+                $success_handler($out_ptr1, $out_ptr2, res1, res2);
+                ERR_OK
+            }
+            Err(e) => $error_handler(e),
+        }
+    }};
+}
+
+// The same for a `FileInput`, which has already checked the handle, taken a
+// slot and retries the parse itself.
+macro_rules! perform_file_extraction {
+    (
+        $out_ptr1:expr,
+        $out_ptr2:expr,
+        $extraction:expr,
+        $success_handler:expr
+    ) => {
+        perform_file_extraction!(
+            $out_ptr1,
+            $out_ptr2,
+            $extraction,
+            $success_handler,
+            record_error
+        )
+    };
+    (
+        $out_ptr1:expr,
+        $out_ptr2:expr,
+        $extraction:expr,
+        $success_handler:expr,
+        $error_handler:expr
+    ) => {{
+        match $extraction {
+            Ok((res1, res2)) => {
+                $success_handler($out_ptr1, $out_ptr2, res1, res2);
+                ERR_OK
+            }
+            Err(e) => $error_handler(e),
+        }
+    }};
+}
+
+/// Runs `call`, running it again up to `attempts` more times while it fails
+/// with a transient JNI error, waiting a little longer before each retry.
+fn retry_transient_jni<T>(
+    attempts: libc::c_int,
+    mut call: impl FnMut() -> Result<T, crate::ecore::Error>,
+) -> Result<T, crate::ecore::Error> {
+    let mut retries = 0;
+    loop {
+        #[cfg(feature = "fault-injection")]
+        let result = match crate::fault::take_jni_failure() {
+            true => Err(crate::ecore::Error::JniEnvCall(
+                "injected transient failure",
+            )),
+            false => call(),
+        };
+        #[cfg(not(feature = "fault-injection"))]
+        let result = call();
+
+        match result {
+            Err(e) if retries < attempts && is_transient_jni_error(&e) => {
+                retries += 1;
+                thread::sleep(Duration::from_millis(10 * retries as u64));
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Extracts content and metadata from a local file path into a string.
+///
+/// An empty file is not parsed: it gives `ERR_OK` with empty content and
+/// empty metadata, whatever its extension.
+/// Output strings must be freed with `extractous_string_free`.
+/// Output metadata must be freed with `extractous_metadata_free`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_extractor_extract_file_to_string(
+    handle: *mut CExtractor,
+    path: *const c_char,
+    out_content: *mut *mut c_char,
+    out_metadata: *mut *mut CMetadata,
+) -> libc::c_int {
+    if out_content.is_null() || out_metadata.is_null() {
+        return ERR_NULL_POINTER;
+    }
+    let input = match unsafe { FileInput::open(handle, path) } {
+        Ok(input) => input,
+        Err(code) => return code,
+    };
+
+    perform_file_extraction!(
+        out_content,
+        out_metadata,
+        input.extract_to_string(&input.state.inner),
+        |out_c: *mut *mut c_char, out_m: *mut *mut CMetadata, content, metadata| {
+            unsafe {
+                let content = finish_content(handle, content);
+                *out_c = text::content_to_c(content);
+                *out_m = finish_metadata(handle, metadata);
+            }
+        }
+    )
+}
+
+/// Extracts content and metadata from a local file path as UTF-16, for callers
+/// such as .NET and Windows APIs that work in UTF-16 natively.
+///
+/// `*out_content` is a NUL-terminated array of UTF-16 code units in native
+/// byte order, and `*out_len_units` its length in code units without the
+/// terminator. The content is always well-formed UTF-16, without lone
+/// surrogates. Since the length is reported, NUL characters in the content
+/// are kept and the NUL policy does not apply. An empty file gives empty
+/// content and metadata, as for `extractous_extractor_extract_file_to_string`.
+/// Output content must be freed with `extractous_utf16_free`.
+/// Output metadata must be freed with `extractous_metadata_free`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_extractor_extract_file_to_utf16(
+    handle: *mut CExtractor,
+    path: *const c_char,
+    out_content: *mut *mut u16,
+    out_len_units: *mut libc::size_t,
+    out_metadata: *mut *mut CMetadata,
+) -> libc::c_int {
+    if out_content.is_null() || out_len_units.is_null() || out_metadata.is_null() {
+        return ERR_NULL_POINTER;
+    }
+    let input = match unsafe { FileInput::open(handle, path) } {
+        Ok(input) => input,
+        Err(code) => return code,
+    };
+
+    perform_file_extraction!(
+        out_content,
+        out_metadata,
+        input.extract_to_string(&input.state.inner),
+        |out_c: *mut *mut u16, out_m: *mut *mut CMetadata, content, metadata| {
+            unsafe {
+                let content = finish_content(handle, content);
+                let mut units: Vec<u16> = content.encode_utf16().collect();
+                *out_len_units = units.len();
+                units.push(0);
+                *out_c = Box::into_raw(units.into_boxed_slice()) as *mut u16;
+                *out_m = finish_metadata(handle, metadata);
+            }
+        }
+    )
+}
+
+/// Extracts content and metadata from a local file path into a string in the
+/// first charset of a preference list that can represent the content.
+///
+/// `accept` is a comma-separated list of charset labels, as in HTTP
+/// `Accept-Charset`, such as `"windows-1252, utf-8"`. The supported charsets
+/// are UTF-8 (`utf-8`, or `*`), US-ASCII (`us-ascii`), ISO-8859-1
+/// (`iso-8859-1`, `latin1`) and Windows-1252 (`windows-1252`, `cp1252`).
+/// Labels are matched without regard to case, parameters such as `;q=0.5`
+/// are ignored and other charsets are skipped. UTF-16 is not offered because
+/// it cannot be returned as a null-terminated string; use
+/// `extractous_extractor_extract_file_to_utf16` for it.
+///
+/// `*out_encoding` receives the IANA name of the charset chosen: `UTF-8`,
+/// `US-ASCII`, `ISO-8859-1` or `windows-1252`. Returns `ERR_INVALID_CONFIG`
+/// if `accept` names no supported charset, or if none of those it names can
+/// represent the content, in which case no output is set. Listing `utf-8`
+/// last always avoids the latter. The NUL policy applies as for
+/// `extractous_extractor_extract_file_to_string`.
+/// Output strings must be freed with `extractous_string_free`.
+/// Output metadata must be freed with `extractous_metadata_free`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_extractor_extract_file_negotiated(
+    handle: *mut CExtractor,
+    path: *const c_char,
+    accept: *const c_char,
+    out_content: *mut *mut c_char,
+    out_encoding: *mut *mut c_char,
+    out_metadata: *mut *mut CMetadata,
+) -> libc::c_int {
+    if accept.is_null() || out_content.is_null() || out_encoding.is_null() || out_metadata.is_null()
+    {
+        return ERR_NULL_POINTER;
+    }
+    let accept_str = match unsafe { CStr::from_ptr(accept).to_str() } {
+        Ok(s) => s,
+        Err(_) => return ERR_INVALID_UTF8,
+    };
+    let accepted = charset::parse_accept(accept_str);
+    if accepted.is_empty() {
+        return record_ffi_error(
+            ERR_INVALID_CONFIG,
+            format!("no supported charset in {:?}", accept_str),
+        );
+    }
+    let input = match unsafe { FileInput::open(handle, path) } {
+        Ok(input) => input,
+        Err(code) => return code,
+    };
+
+    let mut unrepresentable = false;
+    let result = perform_file_extraction!(
+        out_content,
+        out_metadata,
+        input.extract_to_string(&input.state.inner),
+        |out_c: *mut *mut c_char, out_m: *mut *mut CMetadata, content, metadata| {
+            let content = unsafe { finish_content(handle, content) };
+            let Some((content, _)) = text::apply_nul_policy(content) else {
+                unsafe {
+                    *out_c = ptr::null_mut();
+                    *out_encoding = ptr::null_mut();
+                    *out_m = finish_metadata(handle, metadata);
+                }
+                return;
+            };
+            let Some((chosen, bytes)) = charset::negotiate(&accepted, &content) else {
+                unrepresentable = true;
+                return;
+            };
+            unsafe {
+                *out_c = CString::new(bytes).map_or(ptr::null_mut(), |s| s.into_raw());
+                *out_encoding = CString::new(chosen.name()).unwrap().into_raw();
+                *out_m = finish_metadata(handle, metadata);
+            }
+        }
+    );
+    if unrepresentable {
+        return record_ffi_error(
+            ERR_INVALID_CONFIG,
+            format!(
+                "no charset in {:?} can represent the content of {}",
+                accept_str, input.path
+            ),
+        );
+    }
+    result
+}
+
+/// Extracts content and metadata from a local file path into a string in the
+/// given `OUTPUT_*` format, leaving the handle's own format unchanged.
+///
+/// Returns `ERR_INVALID_ENUM` for an unknown format.
+/// Output strings must be freed with `extractous_string_free`.
+/// Output metadata must be freed with `extractous_metadata_free`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_extractor_extract_file_as_format(
+    handle: *mut CExtractor,
+    path: *const c_char,
+    format: libc::c_int,
+    out_content: *mut *mut c_char,
+    out_metadata: *mut *mut CMetadata,
+) -> libc::c_int {
+    if handle.is_null() || path.is_null() || out_content.is_null() || out_metadata.is_null() {
+        return ERR_NULL_POINTER;
+    }
+    let xml_output = match format {
+        OUTPUT_TEXT => false,
+        OUTPUT_XML => true,
+        _ => return ERR_INVALID_ENUM,
+    };
+    let input = match unsafe { FileInput::open(handle, path) } {
+        Ok(input) => input,
+        Err(code) => return code,
+    };
+
+    let extractor = input.state.inner.clone().set_xml_output(xml_output);
+    match input.extract_to_string(&extractor) {
+        Ok((content, metadata)) => {
+            unsafe {
+                let content = input.state.finish_content_as(content, format).0;
+                *out_content = text::content_to_c(content);
+                *out_metadata = finish_metadata(handle, metadata);
+            }
+            ERR_OK
+        }
+        Err(e) => record_error(e),
+    }
+}
+
+/// Extracts content and metadata from a local file path into a string, running
+/// OCR with the Tesseract language `ocr_lang`, such as `"fra"` or `"eng+deu"`,
+/// for this call only.
+///
+/// The rest of the handle's OCR configuration applies unchanged, and the stored
+/// configuration is not modified. NULL keeps the configured language.
+/// Output strings must be freed with `extractous_string_free`.
+/// Output metadata must be freed with `extractous_metadata_free`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_extractor_extract_file_ocr_lang(
+    handle: *mut CExtractor,
+    path: *const c_char,
+    ocr_lang: *const c_char,
+    out_content: *mut *mut c_char,
+    out_metadata: *mut *mut CMetadata,
+) -> libc::c_int {
+    if out_content.is_null() || out_metadata.is_null() {
+        return ERR_NULL_POINTER;
+    }
+    let lang = if ocr_lang.is_null() {
+        None
+    } else {
+        match unsafe { CStr::from_ptr(ocr_lang).to_str() } {
+            Ok(s) => Some(s),
+            Err(_) => return ERR_INVALID_UTF8,
+        }
+    };
+    let input = match unsafe { FileInput::open(handle, path) } {
+        Ok(input) => input,
+        Err(code) => return code,
+    };
+
+    let state = input.state;
+    let extractor = match lang {
+        Some(lang) => state
+            .inner
+            .clone()
+            .set_ocr_config(state.ocr_config.clone().set_language(lang)),
+        None => state.inner.clone(),
+    };
+    match input.extract_to_string(&extractor) {
+        Ok((content, metadata)) => {
+            unsafe {
+                *out_content = text::content_to_c(state.finish_content(content));
+                *out_metadata = finish_metadata(handle, metadata);
+            }
+            ERR_OK
+        }
+        Err(e) => record_error(e),
+    }
+}
+
+/// Extracts content and metadata from a local file path into a string, running
+/// OCR at `dpi` dots per inch for this call only.
+///
+/// The rest of the handle's OCR configuration applies unchanged, and the stored
+/// configuration is not modified. `dpi` must be positive, otherwise
+/// `ERR_INVALID_CONFIG` is returned.
+/// Output strings must be freed with `extractous_string_free`.
+/// Output metadata must be freed with `extractous_metadata_free`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_extractor_extract_file_ocr_dpi(
+    handle: *mut CExtractor,
+    path: *const c_char,
+    dpi: libc::c_int,
+    out_content: *mut *mut c_char,
+    out_metadata: *mut *mut CMetadata,
+) -> libc::c_int {
+    if handle.is_null() || path.is_null() || out_content.is_null() || out_metadata.is_null() {
+        return ERR_NULL_POINTER;
+    }
+    if dpi <= 0 {
+        return record_ffi_error(ERR_INVALID_CONFIG, "OCR DPI must be positive");
+    }
+    let input = match unsafe { FileInput::open(handle, path) } {
+        Ok(input) => input,
+        Err(code) => return code,
+    };
+
+    let state = input.state;
+    let extractor = state
+        .inner
+        .clone()
+        .set_ocr_config(state.ocr_config.clone().set_density(dpi));
+    match input.extract_to_string(&extractor) {
+        Ok((content, metadata)) => {
+            unsafe {
+                *out_content = text::content_to_c(state.finish_content(content));
+                *out_metadata = finish_metadata(handle, metadata);
+            }
+            ERR_OK
+        }
+        Err(e) => record_error(e),
+    }
+}
+
+/// Extracts only the metadata of a local file, such as its properties,
+/// author and dates, without returning the body text.
+///
+/// The core has no metadata-only parse, so this runs the usual parse with the
+/// maximum string length capped at 0 for this call. The parser stops at the
+/// first body text it writes, which skips most of the work for large
+/// documents, and no content string is allocated. Metadata that a format only
+/// records after its body, which is rare, may be missing as a result.
+/// Output metadata must be freed with `extractous_metadata_free`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_extractor_extract_file_metadata_only(
+    handle: *mut CExtractor,
+    path: *const c_char,
+    out_metadata: *mut *mut CMetadata,
+) -> libc::c_int {
+    if handle.is_null() || path.is_null() || out_metadata.is_null() {
+        return ERR_NULL_POINTER;
+    }
+    let input = match unsafe { FileInput::open(handle, path) } {
+        Ok(input) => input,
+        Err(code) => return code,
+    };
+
+    let extractor = input.state.inner.clone().set_extract_string_max_length(0);
+    match input.extract_to_string(&extractor) {
+        Ok((_, metadata)) => {
+            unsafe { *out_metadata = finish_metadata(handle, metadata) };
+            ERR_OK
+        }
+        Err(e) => record_error(e),
+    }
+}
+
+/// Called once per metadata entry with its key and value, which are only
+/// valid for the duration of the call. Returning anything but `ERR_OK` stops
+/// the enumeration.
+pub type CMetadataCallback = Option<
+    unsafe extern "C" fn(ctx: *mut c_void, key: *const c_char, value: *const c_char) -> libc::c_int,
+>;
+
+/// Extracts content from a local file path into a string, passing each
+/// metadata entry to `meta_cb` instead of returning a `CMetadata`, so the
+/// parallel key and value arrays are never allocated.
+///
+/// Entries arrive in the order, and with the values joined and capped, as
+/// `CMetadata` would hold them. They are passed before the content is set.
+/// If the callback returns anything but `ERR_OK`, no further entries are
+/// passed and `ERR_ABORTED` is returned with `*out_content` left unset.
+/// Output strings must be freed with `extractous_string_free`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_extractor_extract_file_meta_cb(
+    handle: *mut CExtractor,
+    path: *const c_char,
+    meta_cb: CMetadataCallback,
+    ctx: *mut c_void,
+    out_content: *mut *mut c_char,
+) -> libc::c_int {
+    let Some(meta_cb) = meta_cb else {
+        return ERR_NULL_POINTER;
+    };
+    if handle.is_null() || path.is_null() || out_content.is_null() {
+        return ERR_NULL_POINTER;
+    }
+    let input = match unsafe { FileInput::open(handle, path) } {
+        Ok(input) => input,
+        Err(code) => return code,
+    };
+
+    let state = input.state;
+    let result = input.extract_to_string(&state.inner);
+    let (content, metadata) = match result {
+        Ok(result) => result,
+        Err(e) => return record_error(e),
+    };
+    let metadata = state.with_fallback_content_type(metadata);
+    for (key, value) in sorted_entries(metadata, state.max_metadata_entries) {
+        if unsafe { meta_cb(ctx, key.as_ptr(), value.as_ptr()) } != ERR_OK {
+            return record_ffi_error(ERR_ABORTED, "metadata callback stopped the extraction");
+        }
+    }
+    unsafe { *out_content = text::content_to_c(state.finish_content(content)) };
+    ERR_OK
+}
+
+/// Extracts content and metadata from a local file path into a string,
+/// reporting whether the content was cut off.
+///
+/// `*out_truncated` is set as for
+/// `extractous_extractor_extract_bytes_to_string_ex`.
+/// Output strings must be freed with `extractous_string_free`.
+/// Output metadata must be freed with `extractous_metadata_free`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_extractor_extract_file_to_string_ex(
+    handle: *mut CExtractor,
+    path: *const c_char,
+    out_content: *mut *mut c_char,
+    out_metadata: *mut *mut CMetadata,
+    out_truncated: *mut libc::c_int,
+) -> libc::c_int {
+    if handle.is_null()
+        || out_content.is_null()
+        || out_metadata.is_null()
+        || out_truncated.is_null()
+    {
+        return ERR_NULL_POINTER;
+    }
+    let max_length = unsafe { (*(handle as *const ExtractorState)).extract_string_max_length };
+    let input = match unsafe { FileInput::open(handle, path) } {
+        Ok(input) => input,
+        Err(code) => return code,
+    };
+
+    perform_file_extraction!(
+        out_content,
+        out_metadata,
+        input.extract_to_string(&one_past_max_length(input.state)),
+        |out_c: *mut *mut c_char, out_m: *mut *mut CMetadata, content: String, metadata| {
+            unsafe {
+                let (content, reached) = cut_to_max_length(content, max_length);
+                let (content, capped) = finish_content_checked(handle, content);
+                *out_truncated = (reached || capped) as libc::c_int;
+                *out_c = text::content_to_c(content);
+                *out_m = finish_metadata(handle, metadata);
+            }
+        }
+    )
+}
+
+/// The metadata entry Tika adds when the content reached the write limit,
+/// which is truncation rather than damage.
+const WRITE_LIMIT_REACHED: &str = "X-TIKA:EXCEPTION:write_limit_reached";
+
+/// Extracts content and metadata from a local file path into a string,
+/// reporting whether the parser had to work around damage in the document,
+/// so questionable extractions can be flagged for another look.
+///
+/// `*out_repaired` is set to 1 when Tika recorded a problem it recovered
+/// from under an `X-TIKA:EXCEPTION:` metadata key, such as a warning or an
+/// embedded document that could not be parsed, or when a PDF's `startxref`
+/// does not lead to its cross-reference table, which PDFBox then rebuilds
+/// without reporting it. Content cut short by the length limit does not
+/// count. It is 0 otherwise. Content and metadata are returned either way.
+/// Output strings must be freed with `extractous_string_free`.
+/// Output metadata must be freed with `extractous_metadata_free`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_extractor_extract_file_to_string_repaired(
+    handle: *mut CExtractor,
+    path: *const c_char,
+    out_content: *mut *mut c_char,
+    out_metadata: *mut *mut CMetadata,
+    out_repaired: *mut libc::c_int,
+) -> libc::c_int {
+    if out_content.is_null() || out_metadata.is_null() || out_repaired.is_null() {
+        return ERR_NULL_POINTER;
+    }
+    let input = match unsafe { FileInput::open(handle, path) } {
+        Ok(input) => input,
+        Err(code) => return code,
+    };
+    let broken_xref = detect::read_head(input.path).is_ok_and(|head| head.starts_with(b"%PDF-"))
+        && pdf::has_broken_xref(input.path).unwrap_or(false);
+
+    perform_file_extraction!(
+        out_content,
+        out_metadata,
+        input.extract_to_string(&input.state.inner),
+        |out_c: *mut *mut c_char, out_m: *mut *mut CMetadata, content, metadata: Metadata| {
+            let recovered = metadata
+                .keys()
+                .any(|key| key.starts_with("X-TIKA:EXCEPTION:") && key != WRITE_LIMIT_REACHED);
+            unsafe {
+                *out_repaired = (recovered || broken_xref) as libc::c_int;
+                *out_c = text::content_to_c(finish_content(handle, content));
+                *out_m = finish_metadata(handle, metadata);
+            }
+        }
+    )
+}
+
+/// Extracts content and metadata from a local file path into a string,
+/// reporting which limit, if any, cut the content short.
+///
+/// `*out_truncation_reason` is set to one of the `TRUNC_*` constants:
+/// `TRUNC_MAX_CHARS` when the content exceeded the core's maximum length set
+/// with `extractous_extractor_set_extract_string_max_length_mut`,
+/// `TRUNC_MAX_BYTES` when it was cut to the size set with
+/// `extractous_extractor_set_max_output_bytes_mut`, `TRUNC_NUL` when
+/// `NUL_POLICY_TRUNCATE` cut it at a NUL character, and `TRUNC_NONE`
+/// otherwise. These limits apply in that order, and when several cut the
+/// content the last one, which set its final length, is reported. As for
+/// `extractous_extractor_extract_file_to_string_ex`, content exactly as long
+/// as the maximum length is not truncated.
+/// Output strings must be freed with `extractous_string_free`.
+/// Output metadata must be freed with `extractous_metadata_free`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_extractor_extract_file_to_string_with_truncation_reason(
+    handle: *mut CExtractor,
+    path: *const c_char,
+    out_content: *mut *mut c_char,
+    out_metadata: *mut *mut CMetadata,
+    out_truncation_reason: *mut libc::c_int,
+) -> libc::c_int {
+    if handle.is_null()
+        || out_content.is_null()
+        || out_metadata.is_null()
+        || out_truncation_reason.is_null()
+    {
+        return ERR_NULL_POINTER;
+    }
+    let max_length = unsafe { (*(handle as *const ExtractorState)).extract_string_max_length };
+    let input = match unsafe { FileInput::open(handle, path) } {
+        Ok(input) => input,
+        Err(code) => return code,
+    };
+
+    perform_file_extraction!(
+        out_content,
+        out_metadata,
+        input.extract_to_string(&one_past_max_length(input.state)),
+        |out_c: *mut *mut c_char, out_m: *mut *mut CMetadata, content: String, metadata| {
+            unsafe {
+                let (content, reached) = cut_to_max_length(content, max_length);
+                let (content, capped) = finish_content_checked(handle, content);
+                let (content, cut_at_nul) = text::content_to_c_checked(content);
+                *out_truncation_reason = if cut_at_nul {
+                    TRUNC_NUL
+                } else if capped {
+                    TRUNC_MAX_BYTES
+                } else if reached {
+                    TRUNC_MAX_CHARS
+                } else {
+                    TRUNC_NONE
+                };
+                *out_c = content;
+                *out_m = finish_metadata(handle, metadata);
+            }
+        }
+    )
+}
+
+/// Extracts content and metadata from a local file path into a string, along
+/// with a hash of the content's UTF-8 bytes.
+///
+/// `algo` is one of the `HASH_*` constants; unknown values return
+/// `ERR_INVALID_CONFIG`. The hash is written as a lowercase hex string.
+/// Output strings must be freed with `extractous_string_free`.
+/// Output metadata must be freed with `extractous_metadata_free`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_extractor_extract_file_to_string_hashed(
+    handle: *mut CExtractor,
+    path: *const c_char,
+    algo: libc::c_int,
+    out_content: *mut *mut c_char,
+    out_hash_hex: *mut *mut c_char,
+    out_metadata: *mut *mut CMetadata,
+) -> libc::c_int {
+    if out_content.is_null() || out_metadata.is_null() || out_hash_hex.is_null() {
+        return ERR_NULL_POINTER;
+    }
+    if !matches!(algo, HASH_SHA256 | HASH_BLAKE3) {
+        return record_ffi_error(
+            ERR_INVALID_CONFIG,
+            format!("unknown hash algorithm {}", algo),
+        );
+    }
+    let input = match unsafe { FileInput::open(handle, path) } {
+        Ok(input) => input,
+        Err(code) => return code,
+    };
+
+    perform_file_extraction!(
+        out_content,
+        out_metadata,
+        input.extract_to_string(&input.state.inner),
+        |out_c: *mut *mut c_char, out_m: *mut *mut CMetadata, content, metadata| {
+            unsafe {
+                let content = finish_content(handle, content);
+                let hash = content_hash(algo, content.as_bytes());
+                *out_hash_hex = CString::new(hash).map_or(ptr::null_mut(), |s| s.into_raw());
+                *out_c = text::content_to_c(content);
+                *out_m = finish_metadata(handle, metadata);
+            }
+        }
+    )
+}
+
+/// Hashes `bytes` with one of the `HASH_*` algorithms into lowercase hex.
+fn content_hash(algo: libc::c_int, bytes: &[u8]) -> String {
+    let digest: Vec<u8> = match algo {
+        HASH_BLAKE3 => blake3::hash(bytes).as_bytes().to_vec(),
+        _ => Sha256::digest(bytes).to_vec(),
+    };
+    digest.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Returns an identifier for the file at `path`: the SHA-256 of its raw bytes
+/// as a lowercase hex string, for use as a cache key before deciding whether
+/// to extract at all.
+///
+/// The file is hashed as-is without being parsed, so the id is cheap to
+/// compute and changes only when the bytes do. The handle's path
+/// restrictions apply, and `handle` may be NULL to skip them; its other
+/// settings do not affect the id. Returns NULL
+/// if the file cannot be read, with the reason available from the last error;
+/// use `extractous_extractor_compute_doc_id_checked` to get the error code.
+/// The returned string must be freed with `extractous_string_free`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_extractor_compute_doc_id(
+    handle: *mut CExtractor,
+    path: *const c_char,
+) -> *mut c_char {
+    let mut id = ptr::null_mut();
+    unsafe { extractous_extractor_compute_doc_id_checked(handle, path, &mut id) };
+    id
+}
+
+/// Like `extractous_extractor_compute_doc_id`, returning an error code and
+/// writing the id to `*out_id`.
+///
+/// Returns `ERR_IO_ERROR` if the file cannot be read.
+/// The output string must be freed with `extractous_string_free`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_extractor_compute_doc_id_checked(
+    handle: *mut CExtractor,
+    path: *const c_char,
+    out_id: *mut *mut c_char,
+) -> libc::c_int {
+    if path.is_null() || out_id.is_null() {
+        return ERR_NULL_POINTER;
+    }
+    unsafe { *out_id = ptr::null_mut() };
+    let path_str = match unsafe { CStr::from_ptr(path).to_str() } {
+        Ok(s) => s,
+        Err(_) => return ERR_INVALID_UTF8,
+    };
+    if !handle.is_null() {
+        let state = unsafe { &*(handle as *const ExtractorState) };
+        let path_ok = state.check_path(path_str);
+        if path_ok != ERR_OK {
+            return path_ok;
+        }
+    }
+
+    let id = match file_sha256(path_str) {
+        Ok(digest) => digest
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect::<String>(),
+        Err(e) => {
+            return record_ffi_error(ERR_IO_ERROR, format!("cannot read {}: {}", path_str, e));
+        }
+    };
+    match CString::new(id) {
+        Ok(id) => {
+            unsafe { *out_id = id.into_raw() };
+            ERR_OK
+        }
+        Err(_) => ERR_INVALID_STRING,
+    }
+}
+
+/// Hashes a file's bytes with SHA-256, reading it in chunks.
+fn file_sha256(path: &str) -> std::io::Result<Vec<u8>> {
+    let mut file = std::fs::File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0u8; 64 * 1024];
+    loop {
+        let n = file.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    Ok(hasher.finalize().to_vec())
+}
+
+/// Extracts content and metadata from a local file path into a string and
+/// reports how long the extraction took.
+///
+/// `*out_elapsed_ms` is set to the wall-clock milliseconds spent in the
+/// parser, on failure as well as on success, counting any retries of
+/// transient runtime errors (see `extractous_extractor_set_jni_retry_mut`)
+/// and the waits between them. It is 0 if the file is refused before
+/// parsing.
+/// Output strings must be freed with `extractous_string_free`.
+/// Output metadata must be freed with `extractous_metadata_free`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_extractor_extract_file_to_string_timed(
+    handle: *mut CExtractor,
+    path: *const c_char,
+    out_content: *mut *mut c_char,
+    out_metadata: *mut *mut CMetadata,
+    out_elapsed_ms: *mut u64,
+) -> libc::c_int {
+    if out_content.is_null() || out_metadata.is_null() || out_elapsed_ms.is_null() {
+        return ERR_NULL_POINTER;
+    }
+    unsafe { *out_elapsed_ms = 0 };
+    let input = match unsafe { FileInput::open(handle, path) } {
+        Ok(input) => input,
+        Err(code) => return code,
+    };
+
+    // The retries run inside `extract_to_string`, so the one measurement
+    // covers them all.
+    let start = Instant::now();
+    let result = input.extract_to_string(&input.state.inner);
+    unsafe { *out_elapsed_ms = start.elapsed().as_millis() as u64 };
+
+    perform_file_extraction!(
+        out_content,
+        out_metadata,
+        result,
+        |out_c: *mut *mut c_char, out_m: *mut *mut CMetadata, content, metadata| {
+            unsafe {
+                let content = finish_content(handle, content);
+                *out_c = text::content_to_c(content);
+                *out_m = finish_metadata(handle, metadata);
+            }
+        }
+    )
+}
+
+/// Extracts content and metadata from a local file path into a string and
+/// explains why the content is empty, if it is.
+///
+/// `*out_content_empty_reason` is set to one of the `EMPTY_*` constants:
+/// `EMPTY_NONE` when the content has non-whitespace text, `EMPTY_OCR_DISABLED`
+/// for a PDF or image that OCR did not run on, `EMPTY_NO_TEXT_LAYER` for a PDF
+/// or image that OCR ran on without recognising any text, and
+/// `EMPTY_UNSUPPORTED_BODY` for other formats, whose parser produced no body
+/// text. The format is taken from the parser's `Content-Type`, and OCR counts
+/// as having run if the Tesseract parser appears in `X-TIKA:Parsed-By`.
+///
+/// Output strings must be freed with `extractous_string_free`.
+/// Output metadata must be freed with `extractous_metadata_free`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_extractor_extract_file_to_string_with_empty_reason(
+    handle: *mut CExtractor,
+    path: *const c_char,
+    out_content: *mut *mut c_char,
+    out_metadata: *mut *mut CMetadata,
+    out_content_empty_reason: *mut libc::c_int,
+) -> libc::c_int {
+    if out_content.is_null() || out_metadata.is_null() || out_content_empty_reason.is_null() {
+        return ERR_NULL_POINTER;
+    }
+    let input = match unsafe { FileInput::open(handle, path) } {
+        Ok(input) => input,
+        Err(code) => return code,
+    };
+
+    perform_file_extraction!(
+        out_content,
+        out_metadata,
+        input.extract_to_string(&input.state.inner),
+        |out_c: *mut *mut c_char, out_m: *mut *mut CMetadata, content, metadata| {
+            unsafe {
+                let content = finish_content(handle, content);
+                *out_content_empty_reason = empty_reason(&content, &metadata);
+                *out_c = text::content_to_c(content);
+                *out_m = finish_metadata(handle, metadata);
+            }
+        }
+    )
+}
+
+/// Classifies why `content` is empty as one of the `EMPTY_*` constants.
+fn empty_reason(content: &str, metadata: &Metadata) -> libc::c_int {
+    if !content.trim().is_empty() {
+        return EMPTY_NONE;
+    }
+    let mime = metadata
+        .get("Content-Type")
+        .and_then(|values| values.first())
+        .map_or(detect::OCTET_STREAM, String::as_str);
+    if !detect::mime_matches("application/pdf", mime) && !detect::mime_matches("image/*", mime) {
+        return EMPTY_UNSUPPORTED_BODY;
+    }
+    if ocr_used(metadata) {
+        EMPTY_NO_TEXT_LAYER
+    } else {
+        EMPTY_OCR_DISABLED
+    }
+}
+
+/// Returns true if the Tesseract OCR parser took part in the extraction.
+fn ocr_used(metadata: &Metadata) -> bool {
+    metadata
+        .get("X-TIKA:Parsed-By")
+        .is_some_and(|parsers| parsers.iter().any(|p| p.contains("TesseractOCRParser")))
+}
+
+/// Extracts content and metadata from a local file path into a string and
+/// reports whether OCR was run.
+///
+/// `*out_ocr_used` is set to 1 if the Tesseract parser appears in
+/// `X-TIKA:Parsed-By`, which happens when the PDF OCR strategy or an image
+/// input sent any page to OCR and Tesseract is installed, and to 0 otherwise.
+///
+/// Output strings must be freed with `extractous_string_free`.
+/// Output metadata must be freed with `extractous_metadata_free`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_extractor_extract_file_to_string_ocrinfo(
+    handle: *mut CExtractor,
+    path: *const c_char,
+    out_content: *mut *mut c_char,
+    out_metadata: *mut *mut CMetadata,
+    out_ocr_used: *mut libc::c_int,
+) -> libc::c_int {
+    if out_content.is_null() || out_metadata.is_null() || out_ocr_used.is_null() {
+        return ERR_NULL_POINTER;
+    }
+    let input = match unsafe { FileInput::open(handle, path) } {
+        Ok(input) => input,
+        Err(code) => return code,
+    };
+
+    perform_file_extraction!(
+        out_content,
+        out_metadata,
+        input.extract_to_string(&input.state.inner),
+        |out_c: *mut *mut c_char, out_m: *mut *mut CMetadata, content, metadata: Metadata| {
+            unsafe {
+                *out_ocr_used = ocr_used(&metadata) as libc::c_int;
+                let content = finish_content(handle, content);
+                *out_c = text::content_to_c(content);
+                *out_m = finish_metadata(handle, metadata);
+            }
+        }
+    )
+}
+
+/// Extracts content and metadata from a local file path into a string, along
+/// with character and word counts of the returned content.
+///
+/// `*out_char_count` is the number of Unicode scalar values, so a multi-byte
+/// character counts once. `*out_word_count` is the number of runs of
+/// non-whitespace characters, where whitespace is any character with the
+/// Unicode `White_Space` property.
+/// Output strings must be freed with `extractous_string_free`.
+/// Output metadata must be freed with `extractous_metadata_free`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_extractor_extract_file_to_string_stats(
+    handle: *mut CExtractor,
+    path: *const c_char,
+    out_content: *mut *mut c_char,
+    out_metadata: *mut *mut CMetadata,
+    out_char_count: *mut libc::size_t,
+    out_word_count: *mut libc::size_t,
+) -> libc::c_int {
+    if out_content.is_null()
+        || out_metadata.is_null()
+        || out_char_count.is_null()
+        || out_word_count.is_null()
+    {
+        return ERR_NULL_POINTER;
+    }
+    let input = match unsafe { FileInput::open(handle, path) } {
+        Ok(input) => input,
+        Err(code) => return code,
+    };
+
+    perform_file_extraction!(
+        out_content,
+        out_metadata,
+        input.extract_to_string(&input.state.inner),
+        |out_c: *mut *mut c_char, out_m: *mut *mut CMetadata, content, metadata| {
+            unsafe {
+                let content = finish_content(handle, content);
+                *out_char_count = content.chars().count();
+                *out_word_count = content.split_whitespace().count();
+                *out_c = text::content_to_c(content);
+                *out_m = finish_metadata(handle, metadata);
+            }
+        }
+    )
+}
+
+/// Extracts content and metadata from a local file path into a string, along
+/// with statistics of the returned content for display in content-management
+/// interfaces.
+///
+/// Words and characters are counted as for
+/// `extractous_extractor_extract_file_to_string_stats`. A sentence ends at
+/// `.`, `!`, `?` or an ellipsis followed by whitespace, so abbreviations such
+/// as "e.g." are counted as sentence ends. Reading time assumes
+/// `READING_WORDS_PER_MINUTE`.
+/// Output strings must be freed with `extractous_string_free`.
+/// Output metadata must be freed with `extractous_metadata_free`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_extractor_extract_file_to_string_analysis(
+    handle: *mut CExtractor,
+    path: *const c_char,
+    out_content: *mut *mut c_char,
+    out_analysis: *mut CContentAnalysis,
+    out_metadata: *mut *mut CMetadata,
+) -> libc::c_int {
+    if out_content.is_null() || out_metadata.is_null() || out_analysis.is_null() {
+        return ERR_NULL_POINTER;
+    }
+    let input = match unsafe { FileInput::open(handle, path) } {
+        Ok(input) => input,
+        Err(code) => return code,
+    };
+
+    perform_file_extraction!(
+        out_content,
+        out_metadata,
+        input.extract_to_string(&input.state.inner),
+        |out_c: *mut *mut c_char, out_m: *mut *mut CMetadata, content, metadata| {
+            unsafe {
+                let content = finish_content(handle, content);
+                let word_count = content.split_whitespace().count();
+                *out_analysis = CContentAnalysis {
+                    word_count,
+                    char_count: content.chars().count(),
+                    sentence_count: text::sentence_count(&content),
+                    reading_minutes: word_count as f64 / READING_WORDS_PER_MINUTE as f64,
+                };
+                *out_c = text::content_to_c(content);
+                *out_m = finish_metadata(handle, metadata);
+            }
+        }
+    )
+}
+
+/// Extracts content and metadata from a local file path into a string, along
+/// with a guess at the language of the returned content.
+///
+/// `*out_language` is set to an ISO 639-1 code such as `"en"`, or an ISO 639-3
+/// code for languages without one, and `*out_language_confidence` to how far
+/// the guess can be trusted, from 0 to 100; callers may want to ignore guesses
+/// below about 50. Content with fewer than ten letters is too short to
+/// classify, and then, as when no language matches, the language is NULL and
+/// the confidence 0.
+/// Output strings must be freed with `extractous_string_free`.
+/// Output metadata must be freed with `extractous_metadata_free`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_extractor_extract_file_to_string_lang(
+    handle: *mut CExtractor,
+    path: *const c_char,
+    out_content: *mut *mut c_char,
+    out_metadata: *mut *mut CMetadata,
+    out_language: *mut *mut c_char,
+    out_language_confidence: *mut libc::c_int,
+) -> libc::c_int {
+    if out_content.is_null()
+        || out_metadata.is_null()
+        || out_language.is_null()
+        || out_language_confidence.is_null()
+    {
+        return ERR_NULL_POINTER;
+    }
+    let input = match unsafe { FileInput::open(handle, path) } {
+        Ok(input) => input,
+        Err(code) => return code,
+    };
+
+    perform_file_extraction!(
+        out_content,
+        out_metadata,
+        input.extract_to_string(&input.state.inner),
+        |out_c: *mut *mut c_char, out_m: *mut *mut CMetadata, content, metadata| {
+            unsafe {
+                let content = finish_content(handle, content);
+                let (language, confidence) = match lang::detect_language(&content) {
+                    Some((code, confidence)) => (
+                        CString::new(code).map_or(ptr::null_mut(), |s| s.into_raw()),
+                        confidence,
+                    ),
+                    None => (ptr::null_mut(), 0),
+                };
+                *out_language = language;
+                *out_language_confidence = confidence;
+                *out_c = text::content_to_c(content);
+                *out_m = finish_metadata(handle, metadata);
+            }
+        }
+    )
+}
+
+/// Extracts content and metadata from a local file path and returns the
+/// window of at most `char_limit` characters starting at `char_offset`, for
+/// paging through a large document.
+///
+/// Characters are Unicode scalar values, counted after the handle's
+/// post-processing. `*out_total_len` is set to the length of the whole content
+/// in characters, so callers know how many windows there are. A window
+/// reaching past the end is cut short, and one starting past it is empty.
+/// Each call extracts the whole document again, so paging through it costs
+/// one extraction per page; callers paging repeatedly should cache the result
+/// of `extractous_extractor_extract_file_to_string` instead. The handle's
+/// maximum string length still bounds the content being windowed.
+/// Output strings must be freed with `extractous_string_free`.
+/// Output metadata must be freed with `extractous_metadata_free`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_extractor_extract_file_window(
+    handle: *mut CExtractor,
+    path: *const c_char,
+    char_offset: libc::size_t,
+    char_limit: libc::size_t,
+    out_content: *mut *mut c_char,
+    out_total_len: *mut libc::size_t,
+    out_metadata: *mut *mut CMetadata,
+) -> libc::c_int {
+    if out_content.is_null() || out_metadata.is_null() || out_total_len.is_null() {
+        return ERR_NULL_POINTER;
+    }
+    let input = match unsafe { FileInput::open(handle, path) } {
+        Ok(input) => input,
+        Err(code) => return code,
+    };
+
+    perform_file_extraction!(
+        out_content,
+        out_metadata,
+        input.extract_to_string(&input.state.inner),
+        |out_c: *mut *mut c_char, out_m: *mut *mut CMetadata, content, metadata| {
+            unsafe {
+                let content = finish_content(handle, content);
+                *out_total_len = content.chars().count();
+                *out_c = text::content_to_c(text::char_window(&content, char_offset, char_limit));
+                *out_m = finish_metadata(handle, metadata);
+            }
+        }
+    )
+}
+
+/// Extracts content and metadata from a local file path into a string, giving
+/// up once the absolute deadline `deadline_unix_ms` (milliseconds since the
+/// Unix epoch) has passed.
+///
+/// The extraction runs on a worker thread. If the deadline passes first,
+/// `ERR_TIMEOUT` is returned; a deadline already in the past returns it without
+/// starting. The deadline also bounds the wait for a slot of the limit set
+/// with `extractous_set_max_concurrent_extractions`, which returns
+/// `ERR_TIMEOUT` too when it runs out, or `ERR_BUSY` at once if waiting is
+/// turned off. The core parser cannot be interrupted, so a timed-out worker is
+/// abandoned and keeps running in the background until the parse finishes,
+/// still holding its JVM thread, its memory and its slot of the limit set
+/// with `extractous_set_max_concurrent_extractions`; its result is discarded.
+///
+/// Output strings must be freed with `extractous_string_free`.
+/// Output metadata must be freed with `extractous_metadata_free`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_extractor_extract_file_with_deadline(
+    handle: *mut CExtractor,
+    path: *const c_char,
+    deadline_unix_ms: u64,
+    out_content: *mut *mut c_char,
+    out_metadata: *mut *mut CMetadata,
+) -> libc::c_int {
+    if handle.is_null() || path.is_null() || out_content.is_null() || out_metadata.is_null() {
+        return ERR_NULL_POINTER;
+    }
+    if time_until(deadline_unix_ms).is_none() {
+        return record_ffi_error(ERR_TIMEOUT, "deadline passed before extraction started");
+    }
+    let keep_waiting = || match time_until(deadline_unix_ms) {
+        Some(remaining) => Ok(Some(remaining)),
+        None => Err(record_ffi_error(
+            ERR_TIMEOUT,
+            "deadline passed while waiting for an extraction slot",
+        )),
+    };
+    let mut input = match unsafe { FileInput::open_while(handle, path, keep_waiting) } {
+        Ok(input) => input,
+        Err(code) => return code,
+    };
+
+    let remaining = match time_until(deadline_unix_ms) {
+        Some(remaining) => remaining,
+        None => return record_ffi_error(ERR_TIMEOUT, "deadline passed before extraction started"),
+    };
+    let extractor = input.state.inner.clone();
+    let detached = input.detach();
+    let receiver = run_on_worker(move || detached.extract_to_string(&extractor));
+
+    match receiver.recv_timeout(remaining) {
+        Ok(Ok((content, metadata))) => {
+            unsafe {
+                let content = finish_content(handle, content);
+                *out_content = text::content_to_c(content);
+                *out_metadata = finish_metadata(handle, metadata);
+            }
+            ERR_OK
+        }
+        Ok(Err(e)) => record_error(e),
+        Err(RecvTimeoutError::Timeout) => {
+            record_ffi_error(ERR_TIMEOUT, "deadline passed during extraction")
+        }
+        Err(RecvTimeoutError::Disconnected) => record_ffi_error(
+            ERR_EXTRACTION_FAILED,
+            "extraction worker exited without a result",
+        ),
+    }
+}
+
+/// Returns the time left until `deadline_unix_ms`, or None if it has passed.
+fn time_until(deadline_unix_ms: u64) -> Option<Duration> {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).ok()?;
+    Duration::from_millis(deadline_unix_ms)
+        .checked_sub(now)
+        .filter(|remaining| !remaining.is_zero())
+}
+
+/// Runs `job` on a new thread and returns a channel that receives its result.
+fn run_on_worker<T: Send + 'static>(job: impl FnOnce() -> T + Send + 'static) -> mpsc::Receiver<T> {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        // The receiver is gone if the caller stopped waiting.
+        let _ = sender.send(job());
+    });
+    receiver
+}
+
+/// Extracts content and metadata from a local file path into a string,
+/// retrying once with a smaller output limit if the first attempt runs out of
+/// memory.
+///
+/// When the first attempt fails with `ERR_OUT_OF_MEMORY`, the extraction is
+/// repeated with the maximum string length capped at `hard_cap` characters and
+/// `*out_capped` is set to 1; otherwise it is set to 0. The handle's own
+/// configuration is not changed. Returns `ERR_INVALID_CONFIG` if `hard_cap`
+/// is not positive.
+///
+/// Output strings must be freed with `extractous_string_free`.
+/// Output metadata must be freed with `extractous_metadata_free`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_extractor_extract_file_to_string_bounded(
+    handle: *mut CExtractor,
+    path: *const c_char,
+    hard_cap: libc::c_int,
+    out_content: *mut *mut c_char,
+    out_metadata: *mut *mut CMetadata,
+    out_capped: *mut libc::c_int,
+) -> libc::c_int {
+    if out_content.is_null() || out_metadata.is_null() || out_capped.is_null() {
+        return ERR_NULL_POINTER;
+    }
+    if hard_cap <= 0 {
+        return record_ffi_error(ERR_INVALID_CONFIG, "hard cap must be positive");
+    }
+    let input = match unsafe { FileInput::open(handle, path) } {
+        Ok(input) => input,
+        Err(code) => return code,
+    };
+
+    unsafe { *out_capped = 0 };
+    let extractor = &input.state.inner;
+    let result = match input.extract_to_string(extractor) {
+        Err(e) if extractous_error_to_code(&e) == ERR_OUT_OF_MEMORY => {
+            unsafe { *out_capped = 1 };
+            input.extract_to_string(&extractor.clone().set_extract_string_max_length(hard_cap))
+        }
+        result => result,
+    };
+
+    match result {
+        Ok((content, metadata)) => {
+            unsafe {
+                let content = finish_content(handle, content);
+                *out_content = text::content_to_c(content);
+                *out_metadata = finish_metadata(handle, metadata);
+            }
+            ERR_OK
+        }
+        Err(e) => record_error(e),
+    }
+}
+
+/// How often a waiting caller checks its cancellation token.
+const CANCEL_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// The number of bytes a cancellable extraction reads between token checks.
+const CANCEL_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Extracts content and metadata from a local file path into a string,
+/// stopping with `ERR_ABORTED` once `token` is cancelled.
+///
+/// The extraction streams on a worker thread that checks the token between
+/// chunks, so a cancelled call returns promptly and the worker stops at the
+/// next chunk boundary. Until it does, the worker keeps its slot of the limit
+/// set with `extractous_set_max_concurrent_extractions`, and a call still
+/// waiting for a slot stops waiting when cancelled. Because the content is
+/// streamed, it is not limited by
+/// `extractous_extractor_set_extract_string_max_length_mut`.
+///
+/// Output strings must be freed with `extractous_string_free`.
+/// Output metadata must be freed with `extractous_metadata_free`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_extractor_extract_file_cancellable(
+    handle: *mut CExtractor,
+    path: *const c_char,
+    token: *const CCancelToken,
+    out_content: *mut *mut c_char,
+    out_metadata: *mut *mut CMetadata,
+) -> libc::c_int {
+    if handle.is_null()
+        || path.is_null()
+        || token.is_null()
+        || out_content.is_null()
+        || out_metadata.is_null()
+    {
+        return ERR_NULL_POINTER;
+    }
+    let cancelled = unsafe { &*(token as *const CancelToken) }.flag.clone();
+    if cancelled.load(Ordering::Acquire) {
+        return record_ffi_error(ERR_ABORTED, "cancelled before extraction started");
+    }
+    let keep_waiting = || match cancelled.load(Ordering::Acquire) {
+        false => Ok(Some(CANCEL_POLL_INTERVAL)),
+        true => Err(record_ffi_error(
+            ERR_ABORTED,
+            "cancelled while waiting for an extraction slot",
+        )),
+    };
+    let mut input = match unsafe { FileInput::open_while(handle, path, keep_waiting) } {
+        Ok(input) => input,
+        Err(code) => return code,
+    };
+    if cancelled.load(Ordering::Acquire) {
+        return record_ffi_error(ERR_ABORTED, "cancelled before extraction started");
+    }
+    let extractor = input.state.inner.clone();
+    let detached = input.detach();
+    let worker_cancelled = Arc::clone(&cancelled);
+    let receiver = run_on_worker(move || {
+        let result = detached.extract(&extractor, parse_stream);
+        read_until_cancelled(result, &worker_cancelled)
+    });
+
+    loop {
+        if cancelled.load(Ordering::Acquire) {
+            return record_ffi_error(ERR_ABORTED, "extraction cancelled");
+        }
+        match receiver.recv_timeout(CANCEL_POLL_INTERVAL) {
+            Ok(Ok(Some((content, metadata)))) => {
+                unsafe {
+                    let content = finish_content(handle, content);
+                    *out_content = text::content_to_c(content);
+                    *out_metadata = finish_metadata(handle, metadata);
+                }
+                return ERR_OK;
+            }
+            Ok(Ok(None)) => return record_ffi_error(ERR_ABORTED, "extraction cancelled"),
+            Ok(Err(e)) => return record_error(e),
+            Err(RecvTimeoutError::Timeout) => continue,
+            Err(RecvTimeoutError::Disconnected) => {
+                return record_ffi_error(
+                    ERR_EXTRACTION_FAILED,
+                    "extraction worker exited without a result",
+                );
+            }
+        }
+    }
+}
+
+/// Reads a streamed extraction to the end in chunks, returning `None` if
+/// `cancelled` is set before it finishes.
+fn read_until_cancelled(
+    result: Result<(Option<StreamReader>, Metadata), crate::ecore::Error>,
+    cancelled: &AtomicBool,
+) -> Result<Option<(String, Metadata)>, crate::ecore::Error> {
+    let (mut reader, metadata) = match result? {
+        (Some(reader), metadata) => (reader, metadata),
+        (None, metadata) => return Ok(Some((String::new(), metadata))),
+    };
+    let mut content = Vec::new();
+    let mut chunk = vec![0u8; CANCEL_CHUNK_SIZE];
+    loop {
+        if cancelled.load(Ordering::Acquire) {
+            return Ok(None);
+        }
+        match reader.read(&mut chunk) {
+            Ok(0) => break,
+            Ok(n) => content.extend_from_slice(&chunk[..n]),
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(crate::ecore::Error::IoError(e.to_string())),
+        }
+    }
+    let content = match String::from_utf8(content) {
+        Ok(s) => s,
+        Err(e) => String::from_utf8_lossy(e.as_bytes()).into_owned(),
+    };
+    Ok(Some((content, metadata)))
+}
+
+/// Extracts content from a local file path into a caller-provided buffer.
+///
+/// The content is written as UTF-8 without a null terminator and `*out_len` is
+/// set to its size in bytes. If `buf_cap` is too small nothing is written,
+/// `*out_len` is set to the required size and `ERR_BUFFER_TOO_SMALL` is
+/// returned so the caller can grow the buffer and retry. `buf` may be NULL when
+/// `buf_cap` is 0 to query the size.
+///
+/// `*out_metadata` is set to NULL whenever the result is not `ERR_OK`.
+/// Output metadata must be freed with `extractous_metadata_free`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_extractor_extract_file_into(
+    handle: *mut CExtractor,
+    path: *const c_char,
+    buf: *mut u8,
+    buf_cap: libc::size_t,
+    out_len: *mut libc::size_t,
+    out_metadata: *mut *mut CMetadata,
+) -> libc::c_int {
+    if out_len.is_null() || out_metadata.is_null() {
+        return ERR_NULL_POINTER;
+    }
+    unsafe { *out_metadata = ptr::null_mut() };
+    if buf.is_null() && buf_cap > 0 {
+        return ERR_NULL_POINTER;
+    }
+    let input = match unsafe { FileInput::open(handle, path) } {
+        Ok(input) => input,
+        Err(code) => return code,
+    };
+
+    match input.extract_to_string(&input.state.inner) {
+        Ok((content, metadata)) => {
+            let content = input.state.finish_content(content);
+            let bytes = content.as_bytes();
+            unsafe { *out_len = bytes.len() };
+            if bytes.len() > buf_cap {
+                return record_ffi_error(
+                    ERR_BUFFER_TOO_SMALL,
+                    format!(
+                        "content needs {} bytes but the buffer holds {}",
+                        bytes.len(),
+                        buf_cap
+                    ),
+                );
+            }
+            unsafe {
+                ptr::copy_nonoverlapping(bytes.as_ptr(), buf, bytes.len());
+                *out_metadata = finish_metadata(handle, metadata);
+            }
+            ERR_OK
+        }
+        Err(e) => record_error(e),
+    }
+}
+
+/// Resizes a block of the caller's memory with the contract of C `realloc`:
+/// called with NULL to allocate, and returning NULL if the block cannot be
+/// grown, in which case the old block is left as it was.
+pub type CReallocCallback =
+    Option<unsafe extern "C" fn(ptr: *mut c_void, new_size: libc::size_t) -> *mut c_void>;
+
+/// Size of the first block requested from a `CReallocCallback`; each later
+/// request doubles it.
+const REALLOC_INITIAL_LEN: usize = 64 * 1024;
+
+/// Extracts content from a local file path into memory from the caller's own
+/// allocator, growing it through `realloc_cb` as the content streams in, so
+/// no second copy of the content is made.
+///
+/// The content is read as from `extractous_extractor_extract_file`: in the
+/// handle's encoding and output format, without the string post-processing
+/// options. It is followed by a null terminator that `*out_len` does not
+/// count.
+///
+/// Ownership of the block passes to the caller, who frees it with the
+/// allocator behind `realloc_cb`, never with a function of this library.
+/// `*out_ptr` is set on failure too, to the block as far as it was filled,
+/// or NULL if nothing was allocated, as for an empty file. `ERR_OUT_OF_MEMORY`
+/// means the callback returned NULL.
+///
+/// Output metadata must be freed with `extractous_metadata_free`; it is NULL
+/// on failure.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_extractor_extract_file_realloc(
+    handle: *mut CExtractor,
+    path: *const c_char,
+    realloc_cb: CReallocCallback,
+    out_ptr: *mut *mut u8,
+    out_len: *mut libc::size_t,
+    out_metadata: *mut *mut CMetadata,
+) -> libc::c_int {
+    let Some(realloc_cb) = realloc_cb else {
+        return ERR_NULL_POINTER;
+    };
+    if out_ptr.is_null() || out_len.is_null() || out_metadata.is_null() {
+        return ERR_NULL_POINTER;
+    }
+    unsafe {
+        *out_ptr = ptr::null_mut();
+        *out_len = 0;
+        *out_metadata = ptr::null_mut();
+    }
+    let input = match unsafe { FileInput::open(handle, path) } {
+        Ok(input) => input,
+        Err(code) => return code,
+    };
+
+    let state = input.state;
+    let result = input.extract_stream(&state.inner);
+    let (mut reader, metadata) = match result {
+        Ok((Some(reader), metadata)) => (reader, metadata),
+        Ok((None, metadata)) => {
+            unsafe { *out_metadata = finish_metadata(handle, metadata) };
+            return ERR_OK;
+        }
+        Err(e) => return record_error(e),
+    };
+
+    let mut buf: *mut u8 = ptr::null_mut();
+    let mut cap = 0;
+    let mut len = 0;
+    let status = loop {
+        // One byte is kept for the null terminator.
+        if cap - len <= 1 {
+            let new_cap = if cap == 0 {
+                REALLOC_INITIAL_LEN
+            } else {
+                cap * 2
+            };
+            let grown = unsafe { realloc_cb(buf as *mut c_void, new_cap) } as *mut u8;
+            if grown.is_null() {
+                break record_ffi_error(
+                    ERR_OUT_OF_MEMORY,
+                    format!(
+                        "realloc callback could not grow the buffer to {} bytes",
+                        new_cap
+                    ),
+                );
+            }
+            // Zeroed, so the spare capacity can be handed to the reader as a slice.
+            unsafe { ptr::write_bytes(grown.add(cap), 0, new_cap - cap) };
+            buf = grown;
+            cap = new_cap;
+        }
+        let spare = unsafe { std::slice::from_raw_parts_mut(buf.add(len), cap - len - 1) };
+        match reader.read(spare) {
+            Ok(0) => break ERR_OK,
+            Ok(n) => len += n,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(e) => {
+                break record_ffi_error(
+                    ERR_IO_ERROR,
+                    format!("cannot read the content of {}: {}", input.path, e),
+                );
+            }
+        }
+    };
+
+    unsafe {
+        if !buf.is_null() {
+            *buf.add(len) = 0;
+        }
+        *out_ptr = buf;
+        *out_len = len;
+        if status == ERR_OK {
+            *out_metadata = finish_metadata(handle, metadata);
+        }
+    }
+    status
+}
+
+/// Extracts content and metadata from a local file path into a single JSON
+/// document of the form `{"content": "...", "metadata": {"key": ["value"]}}`.
+///
+/// `*out_len` is set to the length of the JSON in bytes, excluding the null
+/// terminator. The output string must be freed with `extractous_string_free`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_extractor_extract_file_to_json(
+    handle: *mut CExtractor,
+    path: *const c_char,
+    out_json: *mut *mut c_char,
+    out_len: *mut libc::size_t,
+) -> libc::c_int {
+    if out_json.is_null() || out_len.is_null() {
+        return ERR_NULL_POINTER;
+    }
+    let input = match unsafe { FileInput::open(handle, path) } {
+        Ok(input) => input,
+        Err(code) => return code,
+    };
+
+    perform_file_extraction!(
+        out_json,
+        out_len,
+        input.extract_to_string(&input.state.inner),
+        |out_j: *mut *mut c_char, out_l: *mut libc::size_t, content: String, metadata| {
+            let state = unsafe { &*(handle as *const ExtractorState) };
+            let content = state.finish_content(content);
+            let metadata = state.with_fallback_content_type(metadata);
+            let mut doc = String::with_capacity(content.len() + 64);
+            doc.push_str("{\"content\":");
+            json::push_string(&mut doc, &content);
+            doc.push_str(",\"metadata\":");
+            push_metadata_json(&mut doc, metadata, state.max_metadata_entries);
+            doc.push('}');
+            unsafe {
+                // Control characters are escaped, so the document has no interior NUL.
+                *out_l = doc.len();
+                *out_j = CString::new(doc).map_or(ptr::null_mut(), |s| s.into_raw());
+            }
+        }
+    )
+}
+
+/// Extracts content and metadata from a local file path into a stream.
+///
+/// An empty file is not parsed: it gives `ERR_OK` with a stream that is
+/// already at its end and empty metadata.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_extractor_extract_file(
+    handle: *mut CExtractor,
+    path: *const c_char,
+    out_reader: *mut *mut CStreamReader,
+    out_metadata: *mut *mut CMetadata,
+) -> libc::c_int {
+    if out_reader.is_null() || out_metadata.is_null() {
+        return ERR_NULL_POINTER;
+    }
+    let input = match unsafe { FileInput::open(handle, path) } {
+        Ok(input) => input,
+        Err(code) => return code,
+    };
+
+    perform_file_extraction!(
+        out_reader,
+        out_metadata,
+        input.extract_stream(&input.state.inner),
+        |out_r: *mut *mut CStreamReader, out_m: *mut *mut CMetadata, reader, metadata| {
+            unsafe {
+                *out_r = input.into_stream_handle(reader);
+                *out_m = finish_metadata(handle, metadata);
+            }
+        }
+    )
+}
+
+/// Extracts content and metadata from a byte slice into a string.
+///
+/// An empty slice is not parsed: it gives `ERR_OK` with empty content and
+/// empty metadata.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_extractor_extract_bytes_to_string(
+    handle: *mut CExtractor,
+    data: *const u8,
+    data_len: libc::size_t,
+    out_content: *mut *mut c_char,
+    out_metadata: *mut *mut CMetadata,
+) -> libc::c_int {
+    unsafe { bytes_to_string(handle, data, data_len, None, out_content, out_metadata) }
+}
+
+/// Extracts content and metadata from a byte slice into a string, reporting
+/// `name` as the document's `resourceName` metadata.
+///
+/// The name replaces any `resourceName` the parser reports, so callers can
+/// correlate results with their source. It does not affect format detection.
+/// Returns `ERR_INVALID_UTF8` if `name` is not valid UTF-8.
+/// Output strings must be freed with `extractous_string_free`.
+/// Output metadata must be freed with `extractous_metadata_free`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_extractor_extract_bytes_to_string_named(
+    handle: *mut CExtractor,
+    data: *const u8,
+    data_len: libc::size_t,
+    name: *const c_char,
+    out_content: *mut *mut c_char,
+    out_metadata: *mut *mut CMetadata,
+) -> libc::c_int {
+    if name.is_null() {
+        return ERR_NULL_POINTER;
+    }
+    let name = match unsafe { CStr::from_ptr(name).to_str() } {
+        Ok(s) => s,
+        Err(_) => return ERR_INVALID_UTF8,
+    };
+    unsafe {
+        bytes_to_string(
+            handle,
+            data,
+            data_len,
+            Some(name),
+            out_content,
+            out_metadata,
+        )
+    }
+}
+
+/// Runs the handle's input transform, if one is set, over `bytes`, returning
+/// the bytes to parse.
+unsafe fn transform_input(
+    handle: *const CExtractor,
+    bytes: &[u8],
+) -> Result<Cow<'_, [u8]>, libc::c_int> {
+    if handle.is_null() {
+        return Ok(Cow::Borrowed(bytes));
+    }
+    let Some(transform) = &unsafe { &*(handle as *const ExtractorState) }.input_transform else {
+        return Ok(Cow::Borrowed(bytes));
+    };
+    let mut out_ptr: *mut u8 = ptr::null_mut();
+    let mut out_len: libc::size_t = 0;
+    let rc = unsafe {
+        (transform.callback)(
+            transform.ctx,
+            bytes.as_ptr(),
+            bytes.len(),
+            &mut out_ptr,
+            &mut out_len,
+        )
+    };
+    if out_ptr.is_null() {
+        return match (rc, out_len) {
+            (ERR_OK, 0) => Ok(Cow::Owned(Vec::new())),
+            (ERR_OK, _) => Err(record_ffi_error(
+                ERR_NULL_POINTER,
+                "input transform returned a NULL buffer",
+            )),
+            _ => Err(record_ffi_error(
+                ERR_ABORTED,
+                format!("input transform returned {}", rc),
+            )),
+        };
+    }
+    let transformed = match rc {
+        ERR_OK => Ok(Cow::Owned(
+            unsafe { std::slice::from_raw_parts(out_ptr, out_len) }.to_vec(),
+        )),
+        _ => Err(record_ffi_error(
+            ERR_ABORTED,
+            format!("input transform returned {}", rc),
+        )),
+    };
+    unsafe { libc::free(out_ptr as *mut c_void) };
+    transformed
+}
+
+/// Extracts a byte slice into a string, setting `resourceName` to `name`.
+unsafe fn bytes_to_string(
+    handle: *mut CExtractor,
+    data: *const u8,
+    data_len: libc::size_t,
+    name: Option<&str>,
+    out_content: *mut *mut c_char,
+    out_metadata: *mut *mut CMetadata,
+) -> libc::c_int {
+    if data.is_null() {
+        return ERR_NULL_POINTER;
+    }
+    let raw = unsafe { std::slice::from_raw_parts(data, data_len) };
+    let input = match unsafe { transform_input(handle, raw) } {
+        Ok(input) => input,
+        Err(code) => return code,
+    };
+    let bytes: &[u8] = &input;
+    let allowed = unsafe { check_bytes_allowed(handle, bytes) };
+    if allowed != ERR_OK {
+        return allowed;
+    }
+    if bytes.is_empty() {
+        return unsafe { empty_string_result(handle, out_content, out_metadata) };
+    }
+
+    perform_extraction!(
+        handle,
+        out_content,
+        out_metadata,
+        |extractor: &CoreExtractor| {
+            let state = unsafe { &*(handle as *const ExtractorState) };
+            let fallback = state.fallback_mime.as_deref();
+            let result = extractor.extract_bytes_to_string(bytes);
+            let result = with_fallback_mime(fallback, extractor, result, |tmp| {
+                std::fs::write(tmp, bytes)
+            });
+            let result = with_strict_utf8(state, bytes, result);
+            with_resource_name(with_content_length(result, Some(bytes.len() as u64)), name)
+        },
+        |out_c: *mut *mut c_char, out_m: *mut *mut CMetadata, content, metadata| {
+            unsafe {
+                let content = finish_content(handle, content);
+                *out_c = text::content_to_c(content);
+                *out_m = finish_metadata(handle, metadata);
+            }
+        }
+    )
+}
+
+/// Extracts content and metadata from a byte slice into a string without
+/// copying the input on the Rust side, for callers passing large or
+/// memory-mapped buffers.
+///
+/// The slice is borrowed for the duration of the call and handed straight to
+/// the core, as `extractous_extractor_extract_bytes_to_string` also does; this
+/// entry point states that guarantee explicitly. The core still copies the
+/// bytes once into the parser runtime, which cannot read native memory.
+/// Output strings must be freed with `extractous_string_free`.
+/// Output metadata must be freed with `extractous_metadata_free`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_extractor_extract_bytes_zero_copy(
+    handle: *mut CExtractor,
+    data: *const u8,
+    data_len: libc::size_t,
+    out_content: *mut *mut c_char,
+    out_metadata: *mut *mut CMetadata,
+) -> libc::c_int {
+    unsafe {
+        extractous_extractor_extract_bytes_to_string(
+            handle,
+            data,
+            data_len,
+            out_content,
+            out_metadata,
+        )
+    }
+}
+
+/// Extracts content and metadata from a byte slice into a string, reporting
+/// whether the content was cut off by the configured maximum length.
+///
+/// `*out_truncated` is set to 1 when the content was longer than the limit
+/// set with `extractous_extractor_set_extract_string_max_length_mut` or was
+/// cut to the one set with `extractous_extractor_set_max_output_bytes_mut`,
+/// 0 otherwise. The length limit is counted in UTF-16 code units like the
+/// core does. The parser reads one character past it, so content exactly as
+/// long as the limit is not reported as truncated.
+///
+/// An empty slice is not parsed: it gives `ERR_OK` with empty content, empty
+/// metadata and `*out_truncated` set to 0.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_extractor_extract_bytes_to_string_ex(
+    handle: *mut CExtractor,
+    data: *const u8,
+    data_len: libc::size_t,
+    out_content: *mut *mut c_char,
+    out_metadata: *mut *mut CMetadata,
+    out_truncated: *mut libc::c_int,
+) -> libc::c_int {
+    if handle.is_null() || data.is_null() || out_truncated.is_null() {
+        return ERR_NULL_POINTER;
+    }
+    let max_length = unsafe { (*(handle as *const ExtractorState)).extract_string_max_length };
+    let raw = unsafe { std::slice::from_raw_parts(data, data_len) };
+    let input = match unsafe { transform_input(handle, raw) } {
+        Ok(input) => input,
+        Err(code) => return code,
+    };
+    let bytes: &[u8] = &input;
+    let allowed = unsafe { check_bytes_allowed(handle, bytes) };
+    if allowed != ERR_OK {
+        return allowed;
+    }
+    if bytes.is_empty() {
+        let status = unsafe { empty_string_result(handle, out_content, out_metadata) };
+        if status == ERR_OK {
+            unsafe { *out_truncated = 0 };
+        }
+        return status;
+    }
+
+    perform_extraction!(
+        handle,
+        out_content,
+        out_metadata,
+        |_: &CoreExtractor| {
+            let state = unsafe { &*(handle as *const ExtractorState) };
+            let fallback = state.fallback_mime.as_deref();
+            let extractor = one_past_max_length(state);
+            let result = extractor.extract_bytes_to_string(bytes);
+            let result = with_fallback_mime(fallback, &extractor, result, |tmp| {
+                std::fs::write(tmp, bytes)
+            });
+            with_content_length(
+                with_strict_utf8(state, bytes, result),
+                Some(bytes.len() as u64),
+            )
+        },
+        |out_c: *mut *mut c_char, out_m: *mut *mut CMetadata, content: String, metadata| {
+            unsafe {
+                let (content, reached) = cut_to_max_length(content, max_length);
+                let (content, capped) = finish_content_checked(handle, content);
+                *out_truncated = (reached || capped) as libc::c_int;
+                *out_c = text::content_to_c(content);
+                *out_m = finish_metadata(handle, metadata);
+            }
+        }
+    )
+}
+
+/// Extracts content and metadata from a byte slice into a string, also
+/// returning the MIME type the parser detected.
+///
+/// `*out_mime` is set to the first `Content-Type` value in the metadata, or
+/// NULL if the parser did not report one. Pass NULL for `out_mime` to skip it.
+/// An empty slice is not parsed: it gives `ERR_OK` with empty content, empty
+/// metadata and `*out_mime` set to NULL.
+/// Output strings must be freed with `extractous_string_free`.
+/// Output metadata must be freed with `extractous_metadata_free`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_extractor_extract_bytes_to_string_detect(
+    handle: *mut CExtractor,
+    data: *const u8,
+    data_len: libc::size_t,
+    out_content: *mut *mut c_char,
+    out_metadata: *mut *mut CMetadata,
+    out_mime: *mut *mut c_char,
+) -> libc::c_int {
+    if data.is_null() {
+        return ERR_NULL_POINTER;
+    }
+    let raw = unsafe { std::slice::from_raw_parts(data, data_len) };
+    let input = match unsafe { transform_input(handle, raw) } {
+        Ok(input) => input,
+        Err(code) => return code,
+    };
+    let bytes: &[u8] = &input;
+    let allowed = unsafe { check_bytes_allowed(handle, bytes) };
+    if allowed != ERR_OK {
+        return allowed;
+    }
+    if bytes.is_empty() {
+        let status = unsafe { empty_string_result(handle, out_content, out_metadata) };
+        if status == ERR_OK && !out_mime.is_null() {
+            unsafe { *out_mime = ptr::null_mut() };
+        }
+        return status;
+    }
+
+    perform_extraction!(
+        handle,
+        out_content,
+        out_metadata,
+        |extractor: &CoreExtractor| {
+            let state = unsafe { &*(handle as *const ExtractorState) };
+            let fallback = state.fallback_mime.as_deref();
+            let result = extractor.extract_bytes_to_string(bytes);
+            let result = with_fallback_mime(fallback, extractor, result, |tmp| {
+                std::fs::write(tmp, bytes)
+            });
+            with_content_length(
+                with_strict_utf8(state, bytes, result),
+                Some(bytes.len() as u64),
+            )
+        },
+        |out_c: *mut *mut c_char, out_m: *mut *mut CMetadata, content, metadata: Metadata| {
+            unsafe {
+                let state = &*(handle as *const ExtractorState);
+                let metadata = state.with_fallback_content_type(metadata);
+                if !out_mime.is_null() {
+                    *out_mime = metadata
+                        .get("Content-Type")
+                        .and_then(|values| values.first())
+                        .and_then(|mime| CString::new(mime.as_str()).ok())
+                        .map_or(ptr::null_mut(), CString::into_raw);
+                }
+                let content = finish_content(handle, content);
+                *out_c = text::content_to_c(content);
+                *out_m = finish_metadata(handle, metadata);
+            }
+        }
+    )
+}
+
+/// Returns a copy of the handle's core extractor that reads one character
+/// past the maximum length, so that content cut off at the limit can be told
+/// from content exactly that long by `cut_to_max_length`.
+fn one_past_max_length(state: &ExtractorState) -> CoreExtractor {
+    match state.extract_string_max_length {
+        max if (0..libc::c_int::MAX).contains(&max) => {
+            state.inner.clone().set_extract_string_max_length(max + 1)
         }
+        _ => state.inner.clone(),
     }
 }
 
-/// A macro to safely update an Extractor instance behind a raw pointer.
-macro_rules! update_extractor {
-    ($handle:expr, |$extractor_val:ident| $body:block) => {
-        if $handle.is_null() {
-            return;
-        }
-        unsafe {
-            let extractor_ptr = $handle as *mut CoreExtractor;
-            let old_extractor = ptr::read(extractor_ptr);
-            let new_extractor = {
-                let $extractor_val = old_extractor;
-                $body
-            };
-            ptr::write(extractor_ptr, new_extractor);
+/// Cuts content read by `one_past_max_length` back to `max_length` UTF-16
+/// code units at a character boundary, also returning true if anything was
+/// cut. A negative `max_length` means no limit.
+fn cut_to_max_length(mut content: String, max_length: libc::c_int) -> (String, bool) {
+    if max_length < 0 {
+        return (content, false);
+    }
+    let mut units = 0;
+    let end = content.char_indices().find_map(|(i, c)| {
+        units += c.len_utf16();
+        (units > max_length as usize).then_some(i)
+    });
+    match end {
+        Some(end) => {
+            content.truncate(end);
+            (content, true)
         }
+        None => (content, false),
+    }
+}
+
+/// Fails a successful byte extraction with the input's UTF-8 error when strict
+/// UTF-8 is on and the parser decoded the input as UTF-8 despite invalid
+/// bytes, which it then replaced.
+fn with_strict_utf8<T>(
+    state: &ExtractorState,
+    bytes: &[u8],
+    result: Result<(T, Metadata), crate::ecore::Error>,
+) -> Result<(T, Metadata), crate::ecore::Error> {
+    let Ok((_, metadata)) = &result else {
+        return result;
     };
+    if !state.strict_utf8 || !decoded_as_utf8(metadata) {
+        return result;
+    }
+    match std::str::from_utf8(bytes) {
+        Ok(_) => result,
+        Err(e) => Err(crate::ecore::Error::Utf8Error(e)),
+    }
 }
 
-/// Sets the maximum length for extracted string content.
-#[unsafe(no_mangle)]
-pub unsafe extern "C" fn extractous_extractor_set_extract_string_max_length_mut(
-    handle: *mut CExtractor,
-    max_length: libc::c_int,
-) {
-    update_extractor!(handle, |extractor| {
-        extractor.set_extract_string_max_length(max_length as i32)
+/// Returns true if the metadata names UTF-8 as the charset the input was
+/// decoded in.
+fn decoded_as_utf8(metadata: &Metadata) -> bool {
+    let is_utf8 = |charset: &str| {
+        let charset = charset.trim().trim_matches('"');
+        charset.eq_ignore_ascii_case("utf-8") || charset.eq_ignore_ascii_case("utf8")
+    };
+    let encoding = metadata
+        .get("Content-Encoding")
+        .is_some_and(|values| values.iter().any(|v| is_utf8(v)));
+    let content_type = metadata.get("Content-Type").is_some_and(|values| {
+        values.iter().any(|v| {
+            v.split(';').skip(1).any(|param| {
+                param
+                    .split_once('=')
+                    .is_some_and(|(k, v)| k.trim().eq_ignore_ascii_case("charset") && is_utf8(v))
+            })
+        })
     });
+    encoding || content_type
 }
 
-/// Sets the character encoding for the extracted text.
-#[unsafe(no_mangle)]
-pub unsafe extern "C" fn extractous_extractor_set_encoding_mut(
-    handle: *mut CExtractor,
-    encoding: libc::c_int,
-) {
-    update_extractor!(handle, |extractor| {
-        let charset = match encoding {
-            CHARSET_UTF_8 => CharSet::UTF_8,
-            CHARSET_US_ASCII => CharSet::US_ASCII,
-            CHARSET_UTF_16BE => CharSet::UTF_16BE,
-            _ => return,
-        };
-        extractor.set_encoding(charset)
-    });
+/// Adds a `Content-Length` entry to a successful result when the input size is
+/// known, so the key is present regardless of what the parser reported.
+fn with_content_length<T>(
+    result: Result<(T, Metadata), crate::ecore::Error>,
+    len: Option<u64>,
+) -> Result<(T, Metadata), crate::ecore::Error> {
+    result.map(|(content, mut metadata)| {
+        if let Some(len) = len {
+            ensure_content_length(&mut metadata, len);
+        }
+        (content, metadata)
+    })
 }
 
-/// Sets the configuration for the PDF parser.
-#[unsafe(no_mangle)]
-pub unsafe extern "C" fn extractous_extractor_set_pdf_config_mut(
-    handle: *mut CExtractor,
-    config: *const CPdfParserConfig,
-) {
-    if config.is_null() {
-        return;
-    }
-    update_extractor!(handle, |extractor| {
-        let pdf_config = &*(config as *const crate::ecore::PdfParserConfig);
-        extractor.set_pdf_config(pdf_config.clone())
-    });
+/// Like `with_content_length` for a local file, also setting `resourceName`
+/// to the file's name. This replaces the name the parser took from the path
+/// it read, which is a temporary copy when an archive was filtered or the
+/// fallback MIME type was applied.
+pub(crate) fn with_file_metadata<T>(
+    result: Result<(T, Metadata), crate::ecore::Error>,
+    path: &str,
+) -> Result<(T, Metadata), crate::ecore::Error> {
+    with_content_length(result, file_len(path)).map(|(content, mut metadata)| {
+        if let Some(name) = Path::new(path).file_name() {
+            let name = name.to_string_lossy().into_owned();
+            metadata.insert("resourceName".to_string(), vec![name]);
+        }
+        (content, metadata)
+    })
 }
 
-/// Sets the configuration for the Office document parser.
-#[unsafe(no_mangle)]
-pub unsafe extern "C" fn extractous_extractor_set_office_config_mut(
-    handle: *mut CExtractor,
-    config: *const COfficeParserConfig,
-) {
-    if config.is_null() {
-        return;
+/// Sets `resourceName` to the caller's name for the input, if given.
+fn with_resource_name<T>(
+    result: Result<(T, Metadata), crate::ecore::Error>,
+    name: Option<&str>,
+) -> Result<(T, Metadata), crate::ecore::Error> {
+    result.map(|(content, mut metadata)| {
+        if let Some(name) = name {
+            metadata.insert("resourceName".to_string(), vec![name.to_string()]);
+        }
+        (content, metadata)
+    })
+}
+
+/// Retries a string extraction that the parser could not identify, reported
+/// as `application/octet-stream`, with the `fallback` MIME type and the same
+/// `extractor`.
+///
+/// `write_input` writes the document to the temp path given to it, whose
+/// extension names the fallback type; the copy is removed afterwards, and so
+/// is the `resourceName` the parser took from it. The first result is kept if
+/// no fallback is set or the copy cannot be made.
+fn with_fallback_mime(
+    fallback: Option<&str>,
+    extractor: &CoreExtractor,
+    result: Result<(String, Metadata), crate::ecore::Error>,
+    write_input: impl FnOnce(&Path) -> std::io::Result<()>,
+) -> Result<(String, Metadata), crate::ecore::Error> {
+    static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+    let Some(ext) = fallback.and_then(detect::extension_for) else {
+        return result;
+    };
+    let unidentified = match &result {
+        Ok((_, metadata)) => metadata
+            .get("Content-Type")
+            .and_then(|values| values.first())
+            .is_some_and(|mime| detect::mime_matches(detect::OCTET_STREAM, mime)),
+        Err(_) => false,
+    };
+    if !unidentified {
+        return result;
     }
-    update_extractor!(handle, |extractor| {
-        let office_config = &*(config as *const crate::ecore::OfficeParserConfig);
-        extractor.set_office_config(office_config.clone())
-    });
+
+    let tmp = crate::temp_dir().join(format!(
+        "extractous-fallback-{}-{}.{}",
+        std::process::id(),
+        NEXT_ID.fetch_add(1, Ordering::Relaxed),
+        ext
+    ));
+    if write_input(&tmp).is_err() {
+        let _ = std::fs::remove_file(&tmp);
+        return result;
+    }
+    let retried = match tmp.to_str() {
+        Some(tmp) => extractor
+            .extract_file_to_string(tmp)
+            .map(|(content, mut metadata)| {
+                metadata.remove("resourceName");
+                (content, metadata)
+            }),
+        None => result,
+    };
+    let _ = std::fs::remove_file(&tmp);
+    retried
 }
 
-/// Sets the configuration for Tesseract OCR.
-#[unsafe(no_mangle)]
-pub unsafe extern "C" fn extractous_extractor_set_ocr_config_mut(
+/// Returns true if `path` is a regular file with no bytes. Pipes and special
+/// files report a length of 0 too, so they are left to the parser.
+fn is_empty_file(path: &str) -> bool {
+    std::fs::metadata(path).is_ok_and(|m| m.is_file() && m.len() == 0)
+}
+
+/// Writes the result of extracting a 0-byte input without parsing it: empty
+/// content and empty metadata. Returns `ERR_OK`, or `ERR_NULL_POINTER` for a
+/// NULL argument as a parse would.
+unsafe fn empty_string_result(
     handle: *mut CExtractor,
-    config: *const CTesseractOcrConfig,
-) {
-    if config.is_null() {
-        return;
+    out_content: *mut *mut c_char,
+    out_metadata: *mut *mut CMetadata,
+) -> libc::c_int {
+    if handle.is_null() || out_content.is_null() || out_metadata.is_null() {
+        return ERR_NULL_POINTER;
     }
-    update_extractor!(handle, |extractor| {
-        let ocr_config = &*(config as *const crate::ecore::TesseractOcrConfig);
-        extractor.set_ocr_config(ocr_config.clone())
-    });
+    unsafe {
+        *out_content = text::content_to_c(String::new());
+        *out_metadata = finish_metadata(handle, Metadata::new());
+    }
+    ERR_OK
 }
 
-/// Sets whether to output structured XML instead of plain text.
-#[unsafe(no_mangle)]
-pub unsafe extern "C" fn extractous_extractor_set_xml_output_mut(
+/// Like `empty_string_result`, writing a stream that is already at its end.
+unsafe fn empty_stream_result(
     handle: *mut CExtractor,
-    xml_output: bool,
-) {
-    update_extractor!(handle, |extractor| { extractor.set_xml_output(xml_output) });
+    out_reader: *mut *mut CStreamReader,
+    out_metadata: *mut *mut CMetadata,
+) -> libc::c_int {
+    if handle.is_null() || out_reader.is_null() || out_metadata.is_null() {
+        return ERR_NULL_POINTER;
+    }
+    unsafe {
+        *out_reader = StreamState::empty_handle();
+        *out_metadata = finish_metadata(handle, Metadata::new());
+    }
+    ERR_OK
 }
 
-// Macro to handle the common extraction logic and error wrapping.
-macro_rules! perform_extraction {
-    (
-        $handle:expr,
-        $out_ptr1:expr,
-        $out_ptr2:expr,
-        $extractor_call:expr,
-        $success_handler:expr
-    ) => {{
-        if $handle.is_null() || $out_ptr1.is_null() || $out_ptr2.is_null() {
-            return ERR_NULL_POINTER;
-        }
-
-        // Safely get a shared reference to the extractor.
-        let extractor = unsafe { &*($handle as *const CoreExtractor) };
-
-        match $extractor_call(extractor) {
-            Ok((res1, res2)) => {
-                $success_handler($out_ptr1, $out_ptr2, res1, res2);
-                ERR_OK
-            }
-            Err(e) => {
-                let code = extractous_error_to_code(&e);
-                set_last_error(e);
-                code
-            }
-        }
-    }};
+/// Returns the size in bytes of the file at `path`, if it can be read.
+fn file_len(path: &str) -> Option<u64> {
+    std::fs::metadata(path).ok().map(|m| m.len())
 }
 
-/// Extracts content and metadata from a local file path into a string.
+/// Extracts content and metadata from a document split across several
+/// non-contiguous buffers, read in order.
 ///
-/// Output strings must be freed with `extractous_string_free`.
-/// Output metadata must be freed with `extractous_metadata_free`.
+/// A single non-empty slice is parsed in place like
+/// `extractous_extractor_extract_bytes_to_string` does. Several slices are
+/// streamed in order into a file in the temp directory that the parser reads,
+/// as the core only parses contiguous buffers and files; the file is removed
+/// afterwards. An input transform takes one buffer, so with one set the
+/// slices are read into a buffer for it instead.
+///
+/// Returns `ERR_NULL_POINTER` if any slice has a NULL pointer and a nonzero
+/// length. Slices that hold no bytes between them are not parsed: they give
+/// `ERR_OK` with empty content and empty metadata.
 #[unsafe(no_mangle)]
-pub unsafe extern "C" fn extractous_extractor_extract_file_to_string(
+pub unsafe extern "C" fn extractous_extractor_extract_iovec_to_string(
     handle: *mut CExtractor,
-    path: *const c_char,
+    iov: *const CIoSlice,
+    iov_count: libc::size_t,
     out_content: *mut *mut c_char,
     out_metadata: *mut *mut CMetadata,
 ) -> libc::c_int {
-    if path.is_null() {
+    if iov.is_null() && iov_count > 0 {
         return ERR_NULL_POINTER;
     }
-    let path_str = match unsafe { CStr::from_ptr(path).to_str() } {
-        Ok(s) => s,
-        Err(_) => return ERR_INVALID_UTF8,
+    let slices: &[CIoSlice] = if iov_count == 0 {
+        &[]
+    } else {
+        unsafe { std::slice::from_raw_parts(iov, iov_count) }
+    };
+    if slices.iter().any(|s| s.ptr.is_null() && s.len > 0) {
+        return ERR_NULL_POINTER;
+    }
+    let segments: Vec<&[u8]> = slices
+        .iter()
+        .filter(|s| s.len > 0)
+        .map(|s| unsafe { std::slice::from_raw_parts(s.ptr, s.len) })
+        .collect();
+    let transforms = !handle.is_null()
+        && unsafe { &*(handle as *const ExtractorState) }
+            .input_transform
+            .is_some();
+    let mut gathered = Vec::new();
+    let bytes: &[u8] = match segments.as_slice() {
+        [] => &[],
+        [single] => single,
+        many if transforms => {
+            // The slices are in memory, so reading them cannot fail.
+            let _ = IoSlices::new(many).read_to_end(&mut gathered);
+            &gathered
+        }
+        many => return unsafe { iovec_via_file(handle, many, out_content, out_metadata) },
+    };
+    let input = match unsafe { transform_input(handle, bytes) } {
+        Ok(input) => input,
+        Err(code) => return code,
     };
+    let bytes: &[u8] = &input;
+    let allowed = unsafe { check_bytes_allowed(handle, bytes) };
+    if allowed != ERR_OK {
+        return allowed;
+    }
+    if bytes.is_empty() {
+        return unsafe { empty_string_result(handle, out_content, out_metadata) };
+    }
 
     perform_extraction!(
         handle,
         out_content,
         out_metadata,
-        |extractor: &CoreExtractor| extractor.extract_file_to_string(path_str),
+        |extractor: &CoreExtractor| {
+            let state = unsafe { &*(handle as *const ExtractorState) };
+            let fallback = state.fallback_mime.as_deref();
+            let result = extractor.extract_bytes_to_string(bytes);
+            let result = with_fallback_mime(fallback, extractor, result, |tmp| {
+                std::fs::write(tmp, bytes)
+            });
+            with_content_length(result, Some(bytes.len() as u64))
+        },
         |out_c: *mut *mut c_char, out_m: *mut *mut CMetadata, content, metadata| {
             unsafe {
-                *out_c = CString::new(content).map_or(ptr::null_mut(), |s| s.into_raw());
-                *out_m = metadata_to_c(metadata);
+                let content = finish_content(handle, content);
+                *out_c = text::content_to_c(content);
+                *out_m = finish_metadata(handle, metadata);
             }
         }
     )
 }
 
-/// Extracts content and metadata from a local file path into a stream.
-#[unsafe(no_mangle)]
-pub unsafe extern "C" fn extractous_extractor_extract_file(
-    handle: *mut CExtractor,
-    path: *const c_char,
-    out_reader: *mut *mut CStreamReader,
-    out_metadata: *mut *mut CMetadata,
-) -> libc::c_int {
-    if path.is_null() {
-        return ERR_NULL_POINTER;
+/// Reads several borrowed slices in order as one document.
+struct IoSlices<'a> {
+    rest: std::slice::Iter<'a, &'a [u8]>,
+    current: &'a [u8],
+}
+
+impl<'a> IoSlices<'a> {
+    fn new(slices: &'a [&'a [u8]]) -> Self {
+        IoSlices {
+            rest: slices.iter(),
+            current: &[],
+        }
     }
-    let path_str = match unsafe { CStr::from_ptr(path).to_str() } {
-        Ok(s) => s,
-        Err(_) => return ERR_INVALID_UTF8,
-    };
+}
 
-    perform_extraction!(
-        handle,
-        out_reader,
-        out_metadata,
-        |extractor: &CoreExtractor| extractor.extract_file(path_str),
-        |out_r: *mut *mut CStreamReader, out_m: *mut *mut CMetadata, reader, metadata| {
-            unsafe {
-                *out_r = Box::into_raw(Box::new(reader)) as *mut CStreamReader;
-                *out_m = metadata_to_c(metadata);
+impl Read for IoSlices<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        while self.current.is_empty() {
+            match self.rest.next() {
+                Some(next) => self.current = next,
+                None => return Ok(0),
             }
         }
-    )
+        self.current.read(buf)
+    }
 }
 
-/// Extracts content and metadata from a byte slice into a string.
-#[unsafe(no_mangle)]
-pub unsafe extern "C" fn extractous_extractor_extract_bytes_to_string(
+/// Extracts a document split across several non-empty `segments` into a
+/// string by copying them through `IoSlices` into a temp file and parsing
+/// that, applying the checks `check_bytes_allowed` makes to the file instead.
+/// The file's name is not reported as `resourceName`.
+unsafe fn iovec_via_file(
     handle: *mut CExtractor,
-    data: *const u8,
-    data_len: libc::size_t,
+    segments: &[&[u8]],
     out_content: *mut *mut c_char,
     out_metadata: *mut *mut CMetadata,
 ) -> libc::c_int {
-    if data.is_null() {
+    static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+    if handle.is_null() || out_content.is_null() || out_metadata.is_null() {
         return ERR_NULL_POINTER;
     }
-    let bytes = unsafe { std::slice::from_raw_parts(data, data_len) };
+    let state = unsafe { &*(handle as *const ExtractorState) };
+    let tmp = archive::TempCopy::new(crate::temp_dir().join(format!(
+        "extractous-iovec-{}-{}",
+        std::process::id(),
+        NEXT_ID.fetch_add(1, Ordering::Relaxed)
+    )));
+    let written = std::fs::File::create(tmp.path())
+        .and_then(|mut file| std::io::copy(&mut IoSlices::new(segments), &mut file));
+    let len = match written {
+        Ok(len) => len,
+        Err(e) => {
+            return record_ffi_error(
+                ERR_IO_ERROR,
+                format!("cannot write the input to {}: {}", tmp.path().display(), e),
+            );
+        }
+    };
+    let Some(path) = tmp.path().to_str() else {
+        return record_ffi_error(
+            ERR_IO_ERROR,
+            format!("temp path is not UTF-8: {}", tmp.path().display()),
+        );
+    };
+    if !state.allowed_mime_types.is_empty() {
+        let mime_ok = match detect::read_head(path) {
+            Ok(head) => state.check_mime_allowed(&head),
+            Err(_) => ERR_OK,
+        };
+        if mime_ok != ERR_OK {
+            return mime_ok;
+        }
+    }
+    let offline_ok = state.check_offline_file(path, "the input");
+    if offline_ok != ERR_OK {
+        return offline_ok;
+    }
 
     perform_extraction!(
         handle,
         out_content,
         out_metadata,
-        |extractor: &CoreExtractor| extractor.extract_bytes_to_string(bytes),
+        |extractor: &CoreExtractor| {
+            let result = parse_string(extractor, path, state.fallback_mime.as_deref()).map(
+                |(content, mut metadata)| {
+                    metadata.remove("resourceName");
+                    (content, metadata)
+                },
+            );
+            with_content_length(result, Some(len))
+        },
         |out_c: *mut *mut c_char, out_m: *mut *mut CMetadata, content, metadata| {
             unsafe {
-                *out_c = CString::new(content).map_or(ptr::null_mut(), |s| s.into_raw());
-                *out_m = metadata_to_c(metadata);
+                let content = finish_content(handle, content);
+                *out_c = text::content_to_c(content);
+                *out_m = finish_metadata(handle, metadata);
             }
         }
     )
 }
 
 /// Extracts content and metadata from a byte slice into a stream.
+///
+/// An empty slice is not parsed: it gives `ERR_OK` with a stream that is
+/// already at its end and empty metadata.
 #[unsafe(no_mangle)]
 pub unsafe extern "C" fn extractous_extractor_extract_bytes(
     handle: *mut CExtractor,
@@ -254,27 +3817,86 @@ pub unsafe extern "C" fn extractous_extractor_extract_bytes(
     data_len: libc::size_t,
     out_reader: *mut *mut CStreamReader,
     out_metadata: *mut *mut CMetadata,
+) -> libc::c_int {
+    unsafe { bytes_to_stream(handle, data, data_len, None, out_reader, out_metadata) }
+}
+
+/// Extracts content and metadata from a byte slice into a stream, reporting
+/// `name` as the document's `resourceName` metadata, as
+/// `extractous_extractor_extract_bytes_to_string_named` does.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_extractor_extract_bytes_named(
+    handle: *mut CExtractor,
+    data: *const u8,
+    data_len: libc::size_t,
+    name: *const c_char,
+    out_reader: *mut *mut CStreamReader,
+    out_metadata: *mut *mut CMetadata,
+) -> libc::c_int {
+    if name.is_null() {
+        return ERR_NULL_POINTER;
+    }
+    let name = match unsafe { CStr::from_ptr(name).to_str() } {
+        Ok(s) => s,
+        Err(_) => return ERR_INVALID_UTF8,
+    };
+    unsafe { bytes_to_stream(handle, data, data_len, Some(name), out_reader, out_metadata) }
+}
+
+/// Extracts a byte slice into a stream, setting `resourceName` to `name`.
+unsafe fn bytes_to_stream(
+    handle: *mut CExtractor,
+    data: *const u8,
+    data_len: libc::size_t,
+    name: Option<&str>,
+    out_reader: *mut *mut CStreamReader,
+    out_metadata: *mut *mut CMetadata,
 ) -> libc::c_int {
     if data.is_null() {
         return ERR_NULL_POINTER;
     }
-    let bytes = unsafe { std::slice::from_raw_parts(data, data_len) };
+    let raw = unsafe { std::slice::from_raw_parts(data, data_len) };
+    let input = match unsafe { transform_input(handle, raw) } {
+        Ok(input) => input,
+        Err(code) => return code,
+    };
+    let bytes: &[u8] = &input;
+    let allowed = unsafe { check_bytes_allowed(handle, bytes) };
+    if allowed != ERR_OK {
+        return allowed;
+    }
+    if bytes.is_empty() {
+        return unsafe { empty_stream_result(handle, out_reader, out_metadata) };
+    }
 
     perform_extraction!(
         handle,
         out_reader,
         out_metadata,
-        |extractor: &CoreExtractor| extractor.extract_bytes(bytes),
+        |extractor: &CoreExtractor| {
+            let state = unsafe { &*(handle as *const ExtractorState) };
+            let result = with_strict_utf8(state, bytes, extractor.extract_bytes(bytes));
+            with_resource_name(with_content_length(result, Some(bytes.len() as u64)), name)
+        },
         |out_r: *mut *mut CStreamReader, out_m: *mut *mut CMetadata, reader, metadata| {
             unsafe {
-                *out_r = Box::into_raw(Box::new(reader)) as *mut CStreamReader;
-                *out_m = metadata_to_c(metadata);
+                *out_r = StreamState::into_handle(reader);
+                *out_m = finish_metadata(handle, metadata);
             }
         }
     )
 }
 
 /// Extracts content and metadata from a URL into a string.
+///
+/// Network failures (unresolvable host, refused connection, HTTP 404) are
+/// returned as `ERR_IO_ERROR`; the debug report names the specific reason.
+/// An offline handle returns `ERR_INVALID_CONFIG`.
+///
+/// `Content-Length` is the length the server declared for the response. A
+/// response that declares none, such as a chunked one, has no
+/// `Content-Length` entry: the parser fetches and reads the body itself, so,
+/// unlike for files and bytes, the size of the input is never known here.
 #[unsafe(no_mangle)]
 pub unsafe extern "C" fn extractous_extractor_extract_url_to_string(
     handle: *mut CExtractor,
@@ -289,6 +3911,10 @@ pub unsafe extern "C" fn extractous_extractor_extract_url_to_string(
         Ok(s) => s,
         Err(_) => return ERR_INVALID_UTF8,
     };
+    let online = unsafe { check_online(handle, url_str) };
+    if online != ERR_OK {
+        return online;
+    }
 
     perform_extraction!(
         handle,
@@ -297,14 +3923,20 @@ pub unsafe extern "C" fn extractous_extractor_extract_url_to_string(
         |extractor: &CoreExtractor| extractor.extract_url_to_string(url_str),
         |out_c: *mut *mut c_char, out_m: *mut *mut CMetadata, content, metadata| {
             unsafe {
-                *out_c = CString::new(content).map_or(ptr::null_mut(), |s| s.into_raw());
-                *out_m = metadata_to_c(metadata);
+                let content = finish_content(handle, content);
+                *out_c = text::content_to_c(content);
+                *out_m = finish_metadata(handle, metadata);
             }
-        }
+        },
+        record_url_error
     )
 }
 
 /// Extracts content and metadata from a URL into a stream.
+///
+/// Network failures and `Content-Length` are reported as for
+/// `extractous_extractor_extract_url_to_string`. An offline handle returns
+/// `ERR_INVALID_CONFIG`.
 #[unsafe(no_mangle)]
 pub unsafe extern "C" fn extractous_extractor_extract_url(
     handle: *mut CExtractor,
@@ -319,6 +3951,10 @@ pub unsafe extern "C" fn extractous_extractor_extract_url(
         Ok(s) => s,
         Err(_) => return ERR_INVALID_UTF8,
     };
+    let online = unsafe { check_online(handle, url_str) };
+    if online != ERR_OK {
+        return online;
+    }
 
     perform_extraction!(
         handle,
@@ -327,10 +3963,11 @@ pub unsafe extern "C" fn extractous_extractor_extract_url(
         |extractor: &CoreExtractor| extractor.extract_url(url_str),
         |out_r: *mut *mut CStreamReader, out_m: *mut *mut CMetadata, reader, metadata| {
             unsafe {
-                *out_r = Box::into_raw(Box::new(reader)) as *mut CStreamReader;
-                *out_m = metadata_to_c(metadata);
+                *out_r = StreamState::into_handle(reader);
+                *out_m = finish_metadata(handle, metadata);
             }
-        }
+        },
+        record_url_error
     )
 }
 
@@ -341,3 +3978,69 @@ pub unsafe extern "C" fn extractous_string_free(s: *mut c_char) {
         drop(unsafe { CString::from_raw(s) });
     }
 }
+
+/// Frees UTF-16 content returned by `extractous_extractor_extract_file_to_utf16`.
+/// `len_units` is the length it reported, without the terminator.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_utf16_free(content: *mut u16, len_units: libc::size_t) {
+    if !content.is_null() {
+        drop(unsafe { Box::from_raw(ptr::slice_from_raw_parts_mut(content, len_units + 1)) });
+    }
+}
+
+/// Frees the content string and metadata returned by one extraction.
+///
+/// This is the preferred cleanup for the `_to_string` functions, replacing
+/// separate calls to `extractous_string_free` and `extractous_metadata_free`.
+/// Either pointer may be NULL.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_extraction_result_free(
+    content: *mut c_char,
+    metadata: *mut CMetadata,
+) {
+    unsafe {
+        extractous_string_free(content);
+        extractous_metadata_free(metadata);
+    }
+}
+
+/// Hands `strings` to C as an array of owned C strings, setting `*out_values`
+/// and `*out_count`. An empty list yields a NULL array. Strings with interior
+/// NUL bytes are cut at the first one.
+pub(crate) unsafe fn strings_to_c(
+    strings: Vec<String>,
+    out_values: *mut *mut *mut c_char,
+    out_count: *mut libc::size_t,
+) {
+    let ptrs: Box<[*mut c_char]> = strings
+        .into_iter()
+        .map(|s| {
+            let end = s.find('\0').unwrap_or(s.len());
+            CString::new(&s[..end]).map_or(ptr::null_mut(), |c| c.into_raw())
+        })
+        .collect();
+    unsafe {
+        *out_count = ptrs.len();
+        *out_values = if ptrs.is_empty() {
+            ptr::null_mut()
+        } else {
+            Box::into_raw(ptrs) as *mut *mut c_char
+        };
+    }
+}
+
+/// Frees an array of `count` strings returned by this library, along with
+/// each string in it.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_string_array_free(
+    values: *mut *mut c_char,
+    count: libc::size_t,
+) {
+    if values.is_null() {
+        return;
+    }
+    let ptrs = unsafe { Box::from_raw(ptr::slice_from_raw_parts_mut(values, count)) };
+    for &s in ptrs.iter() {
+        unsafe { extractous_string_free(s) };
+    }
+}
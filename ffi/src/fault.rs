@@ -0,0 +1,23 @@
+//! Fault injection for exercising error paths from the C tests. Only built
+//! with the `fault-injection` feature.
+
+use std::os::raw::c_int;
+use std::sync::atomic::{AtomicI32, Ordering};
+
+static PENDING_JNI_FAILURES: AtomicI32 = AtomicI32::new(0);
+
+/// Makes the next `count` extraction attempts fail with a transient JNI error
+/// before reaching the parser.
+#[unsafe(no_mangle)]
+pub extern "C" fn extractous_debug_fail_next_jni_calls(count: c_int) {
+    PENDING_JNI_FAILURES.store(count.max(0), Ordering::SeqCst);
+}
+
+/// Consumes one pending injected failure, returning true if there was one.
+pub(crate) fn take_jni_failure() -> bool {
+    PENDING_JNI_FAILURES
+        .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| {
+            (n > 0).then(|| n - 1)
+        })
+        .is_ok()
+}
@@ -0,0 +1,370 @@
+//! Retrieval of the first image embedded in a document, for previews.
+//!
+//! The core extractor reports embedded images only as references in its XHTML
+//! output, never their bytes, so images are read straight from the document's
+//! container instead. Office Open XML, OpenDocument and EPUB files are ZIP
+//! archives that store images as separate entries.
+
+use crate::detect;
+use crate::errors::*;
+use crate::extractor::{FileInput, extractous_string_free, finish_metadata};
+use crate::structure::xml_extractor;
+use crate::text;
+use crate::types::*;
+use crate::xhtml;
+use std::collections::HashMap;
+use std::ffi::CString;
+use std::fs::File;
+use std::io::Read;
+use std::ops::Range;
+use std::os::raw::{c_char, c_int};
+use std::ptr;
+
+/// Images larger than this are skipped rather than read into memory.
+const MAX_IMAGE_LEN: u64 = 64 * 1024 * 1024;
+
+/// Returns the MIME type of an image file name by its extension.
+fn image_mime_for_name(name: &str) -> Option<&'static str> {
+    let (_, ext) = name.rsplit_once('.')?;
+    let mime = match ext.to_ascii_lowercase().as_str() {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "tif" | "tiff" => "image/tiff",
+        "bmp" => "image/bmp",
+        "webp" => "image/webp",
+        "svg" => "image/svg+xml",
+        "emf" => "image/emf",
+        "wmf" => "image/wmf",
+        _ => return None,
+    };
+    Some(mime)
+}
+
+/// Finds the first image entry of a ZIP-based document, in archive order,
+/// and returns its bytes with its MIME type.
+fn first_zip_image(file: File) -> Result<Option<(Vec<u8>, &'static str)>, String> {
+    let mut archive = zip::ZipArchive::new(file).map_err(|e| e.to_string())?;
+    for i in 0..archive.len() {
+        let entry = archive.by_index(i).map_err(|e| e.to_string())?;
+        if let Some(image) = read_image_entry(entry)? {
+            return Ok(Some(image));
+        }
+    }
+    Ok(None)
+}
+
+/// Reads the images of a ZIP-based document whose file names, without their
+/// directories, are in `names`. Returns them by file name.
+fn zip_images_named(
+    file: File,
+    names: &[String],
+) -> Result<HashMap<String, (Vec<u8>, &'static str)>, String> {
+    let mut archive = zip::ZipArchive::new(file).map_err(|e| e.to_string())?;
+    let mut images = HashMap::new();
+    for i in 0..archive.len() {
+        let entry = archive.by_index(i).map_err(|e| e.to_string())?;
+        let file_name = entry
+            .name()
+            .rsplit('/')
+            .next()
+            .unwrap_or_default()
+            .to_string();
+        if images.contains_key(&file_name) || !names.contains(&file_name) {
+            continue;
+        }
+        if let Some(image) = read_image_entry(entry)? {
+            images.insert(file_name, image);
+        }
+    }
+    Ok(images)
+}
+
+/// Reads an archive entry that holds an image, with its MIME type. Returns
+/// None for entries that are not images, are empty or are too large.
+fn read_image_entry(
+    mut entry: zip::read::ZipFile<'_>,
+) -> Result<Option<(Vec<u8>, &'static str)>, String> {
+    let Some(mime) = image_mime_for_name(entry.name()) else {
+        return Ok(None);
+    };
+    if !entry.is_file() || entry.size() == 0 || entry.size() > MAX_IMAGE_LEN {
+        return Ok(None);
+    }
+    let mut image = Vec::with_capacity(entry.size() as usize);
+    entry
+        .by_ref()
+        .take(MAX_IMAGE_LEN)
+        .read_to_end(&mut image)
+        .map_err(|e| e.to_string())?;
+    let mime = sniffed_image_mime(&image).unwrap_or(mime);
+    Ok(Some((image, mime)))
+}
+
+/// Returns the image type sniffed from the leading bytes, if they identify one.
+fn sniffed_image_mime(image: &[u8]) -> Option<&'static str> {
+    let mime = detect::sniff_mime(&image[..image.len().min(detect::SNIFF_LEN)]);
+    mime.starts_with("image/").then_some(mime)
+}
+
+/// Returns the first image embedded in a local file, for document previews.
+///
+/// Images are read from the document's container, so Office Open XML,
+/// OpenDocument and EPUB files are supported, whatever the parser
+/// configuration; the first image entry in the archive is returned, which may
+/// be a saved thumbnail. An image file is returned as-is. Returns
+/// `ERR_NO_IMAGE` if the document holds no image, or one larger than 64 MiB,
+/// and `ERR_UNSUPPORTED_FORMAT` for other formats, such as PDF, whose images
+/// cannot be read this way.
+///
+/// `*out_mime` is set to the image's MIME type, taken from its leading bytes
+/// or else its file extension. The handle's path restrictions and MIME
+/// allow-list apply to `path`, and its embedded-file filters to the entries
+/// searched.
+/// The image must be freed with `extractous_buffer_free`.
+/// The output string must be freed with `extractous_string_free`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_extractor_extract_first_image(
+    handle: *mut CExtractor,
+    path: *const c_char,
+    out_image: *mut *mut u8,
+    out_len: *mut libc::size_t,
+    out_mime: *mut *mut c_char,
+) -> c_int {
+    if handle.is_null()
+        || path.is_null()
+        || out_image.is_null()
+        || out_len.is_null()
+        || out_mime.is_null()
+    {
+        return ERR_NULL_POINTER;
+    }
+    let input = match unsafe { FileInput::open(handle, path) } {
+        Ok(input) => input,
+        Err(code) => return code,
+    };
+    let path_str = input.path;
+
+    let head = match detect::read_head(input.parse_path()) {
+        Ok(head) => head,
+        Err(e) => {
+            return record_ffi_error(ERR_IO_ERROR, format!("cannot read {}: {}", path_str, e));
+        }
+    };
+    let found = if let Some(mime) = sniffed_image_mime(&head) {
+        match std::fs::read(input.parse_path()) {
+            Ok(image) => Some((image, mime)),
+            Err(e) => {
+                return record_ffi_error(ERR_IO_ERROR, format!("cannot read {}: {}", path_str, e));
+            }
+        }
+    } else if head.starts_with(b"PK\x03\x04") {
+        let file = match File::open(input.parse_path()) {
+            Ok(file) => file,
+            Err(e) => {
+                return record_ffi_error(ERR_IO_ERROR, format!("cannot open {}: {}", path_str, e));
+            }
+        };
+        match first_zip_image(file) {
+            Ok(found) => found,
+            Err(e) => {
+                return record_ffi_error(
+                    ERR_EXTRACTION_FAILED,
+                    format!("cannot read archive {}: {}", path_str, e),
+                );
+            }
+        }
+    } else {
+        return record_ffi_error(
+            ERR_UNSUPPORTED_FORMAT,
+            format!(
+                "images cannot be read from {} documents",
+                detect::sniff_mime(&head)
+            ),
+        );
+    };
+
+    let Some((image, mime)) = found else {
+        return record_ffi_error(ERR_NO_IMAGE, format!("{} has no embedded image", path_str));
+    };
+    let image = image.into_boxed_slice();
+    unsafe {
+        *out_len = image.len();
+        *out_image = Box::into_raw(image) as *mut u8;
+        *out_mime = CString::new(mime).map_or(ptr::null_mut(), |s| s.into_raw());
+    }
+    ERR_OK
+}
+
+/// Extracts a local file as XHTML in which the images it shows are linked to
+/// image data returned alongside, for rendering the document with its
+/// pictures.
+///
+/// Tika refers to an embedded image as `src="embedded:<name>"`. Each such
+/// reference whose image can be read from the document's container, as for
+/// `extractous_extractor_extract_first_image`, is rewritten to
+/// `src="cid:image<i>"`, where `i` is the image's index in `*out_images`.
+/// Images are numbered in order of first reference, and an image referenced
+/// several times is returned once. References that cannot be resolved, such
+/// as those to images inside a PDF, are left as they are.
+///
+/// The extraction runs in XML mode regardless of the handle's output format,
+/// and the handle's post-processing applies to the XHTML.
+/// The output string must be freed with `extractous_string_free`.
+/// The images must be freed with `extractous_images_free`.
+/// Output metadata must be freed with `extractous_metadata_free`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_extractor_extract_file_html_with_images(
+    handle: *mut CExtractor,
+    path: *const c_char,
+    out_html: *mut *mut c_char,
+    out_images: *mut *mut CImage,
+    out_image_count: *mut libc::size_t,
+    out_metadata: *mut *mut CMetadata,
+) -> c_int {
+    if handle.is_null()
+        || path.is_null()
+        || out_html.is_null()
+        || out_images.is_null()
+        || out_image_count.is_null()
+        || out_metadata.is_null()
+    {
+        return ERR_NULL_POINTER;
+    }
+    let input = match unsafe { FileInput::open(handle, path) } {
+        Ok(input) => input,
+        Err(code) => return code,
+    };
+    let path_str = input.path;
+
+    let extractor = unsafe { xml_extractor(handle) };
+    let (xml, metadata) = match input.extract_to_string(&extractor) {
+        Ok(result) => result,
+        Err(e) => return record_error(e),
+    };
+
+    let refs = embedded_refs(&xml);
+    let mut names: Vec<String> = refs.iter().map(|(_, name)| name.clone()).collect();
+    names.dedup();
+    let is_zip =
+        detect::read_head(input.parse_path()).is_ok_and(|head| head.starts_with(b"PK\x03\x04"));
+    let mut available = if names.is_empty() || !is_zip {
+        HashMap::new()
+    } else {
+        let read = File::open(input.parse_path())
+            .map_err(|e| e.to_string())
+            .and_then(|file| zip_images_named(file, &names));
+        match read {
+            Ok(images) => images,
+            Err(e) => {
+                return record_ffi_error(
+                    ERR_EXTRACTION_FAILED,
+                    format!("cannot read images from {}: {}", path_str, e),
+                );
+            }
+        }
+    };
+
+    // Rewrite resolvable references, numbering images by first reference.
+    let mut html = String::with_capacity(xml.len());
+    let mut images: Vec<(String, Vec<u8>, &'static str)> = Vec::new();
+    let mut copied = 0;
+    for (range, name) in refs {
+        let index = match images.iter().position(|(n, _, _)| *n == name) {
+            Some(index) => index,
+            None => match available.remove(&name) {
+                Some((data, mime)) => {
+                    images.push((name, data, mime));
+                    images.len() - 1
+                }
+                None => continue,
+            },
+        };
+        html.push_str(&xml[copied..range.start]);
+        html.push_str(&format!("cid:image{}", index));
+        copied = range.end;
+    }
+    html.push_str(&xml[copied..]);
+
+    let html = input.state.finish_content_as(html, OUTPUT_XML).0;
+    let c_images: Box<[CImage]> = images
+        .into_iter()
+        .enumerate()
+        .map(|(index, (name, data, mime))| {
+            let data = data.into_boxed_slice();
+            CImage {
+                cid: c_string(&format!("cid:image{}", index)),
+                name: c_string(&name),
+                mime: c_string(mime),
+                len: data.len(),
+                data: Box::into_raw(data) as *mut u8,
+            }
+        })
+        .collect();
+
+    unsafe {
+        *out_html = text::content_to_c(html);
+        *out_image_count = c_images.len();
+        *out_images = Box::into_raw(c_images) as *mut CImage;
+        *out_metadata = finish_metadata(handle, metadata);
+    }
+    ERR_OK
+}
+
+/// Frees an array of `count` images returned by
+/// `extractous_extractor_extract_file_html_with_images`, along with their
+/// strings and data.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_images_free(images: *mut CImage, count: libc::size_t) {
+    if images.is_null() {
+        return;
+    }
+    let images = unsafe { Box::from_raw(ptr::slice_from_raw_parts_mut(images, count)) };
+    for image in images.iter() {
+        unsafe {
+            extractous_string_free(image.cid);
+            extractous_string_free(image.name);
+            extractous_string_free(image.mime);
+            if !image.data.is_null() {
+                drop(Box::from_raw(ptr::slice_from_raw_parts_mut(
+                    image.data, image.len,
+                )));
+            }
+        }
+    }
+}
+
+/// Finds the `src="embedded:<name>"` attribute values in Tika's XHTML,
+/// returning the byte range of each value with the decoded image name.
+fn embedded_refs(xml: &str) -> Vec<(Range<usize>, String)> {
+    let mut refs = Vec::new();
+    let mut from = 0;
+    while let Some(i) = xml[from..].find("src=") {
+        let attr = from + i + "src=".len();
+        from = attr;
+        let Some(quote) = xml[attr..]
+            .chars()
+            .next()
+            .filter(|&q| q == '"' || q == '\'')
+        else {
+            continue;
+        };
+        let start = attr + 1;
+        let Some(len) = xml[start..].find(quote) else {
+            break;
+        };
+        let value = &xml[start..start + len];
+        if let Some(name) = value.strip_prefix("embedded:") {
+            refs.push((start..start + len, xhtml::decode_entities(name)));
+        }
+        from = start + len;
+    }
+    refs
+}
+
+/// Converts a string for C, dropping any interior NUL bytes.
+fn c_string(s: &str) -> *mut c_char {
+    CString::new(s.replace('\0', ""))
+        .unwrap_or_default()
+        .into_raw()
+}
@@ -0,0 +1,19 @@
+//! Minimal JSON writing helpers, enough to serialize strings and string maps
+//! without pulling in a serialization framework.
+
+/// Appends `value` to `out` as a quoted, escaped JSON string.
+pub(crate) fn push_string(out: &mut String, value: &str) {
+    out.push('"');
+    for ch in value.chars() {
+        match ch {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
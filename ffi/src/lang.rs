@@ -0,0 +1,105 @@
+//! Language identification of extracted text.
+//!
+//! The core extractor does not report the language of a document, so the
+//! text is classified here with `whatlang`, which rates how reliable each
+//! guess is.
+
+use std::os::raw::c_int;
+
+/// Text with fewer letters than this is too short to classify.
+const MIN_LETTERS: usize = 10;
+
+/// Guesses the language of `text`, returning its ISO 639-1 code, or the ISO
+/// 639-3 code for languages without one, and a confidence from 0 to 100.
+/// Returns None if the text is too short or no language could be guessed.
+pub(crate) fn detect_language(text: &str) -> Option<(&'static str, c_int)> {
+    if text
+        .chars()
+        .filter(|c| c.is_alphabetic())
+        .take(MIN_LETTERS)
+        .count()
+        < MIN_LETTERS
+    {
+        return None;
+    }
+    let info = whatlang::detect(text)?;
+    let confidence = (info.confidence() * 100.0).round().clamp(0.0, 100.0) as c_int;
+    Some((iso_639_1(info.lang().code()), confidence))
+}
+
+/// Maps an ISO 639-3 code to the ISO 639-1 code of the same language, if it
+/// has one.
+fn iso_639_1(code: &'static str) -> &'static str {
+    match code {
+        "afr" => "af",
+        "aka" => "ak",
+        "amh" => "am",
+        "ara" => "ar",
+        "aze" => "az",
+        "bel" => "be",
+        "ben" => "bn",
+        "bul" => "bg",
+        "cat" => "ca",
+        "ces" => "cs",
+        "cmn" => "zh",
+        "dan" => "da",
+        "deu" => "de",
+        "ell" => "el",
+        "eng" => "en",
+        "epo" => "eo",
+        "est" => "et",
+        "fin" => "fi",
+        "fra" => "fr",
+        "guj" => "gu",
+        "heb" => "he",
+        "hin" => "hi",
+        "hrv" => "hr",
+        "hun" => "hu",
+        "hye" => "hy",
+        "ind" => "id",
+        "ita" => "it",
+        "jav" => "jv",
+        "jpn" => "ja",
+        "kan" => "kn",
+        "kat" => "ka",
+        "khm" => "km",
+        "kor" => "ko",
+        "lat" => "la",
+        "lav" => "lv",
+        "lit" => "lt",
+        "mal" => "ml",
+        "mar" => "mr",
+        "mkd" => "mk",
+        "mya" => "my",
+        "nep" => "ne",
+        "nld" => "nl",
+        "nob" => "nb",
+        "ori" => "or",
+        "pan" => "pa",
+        "pes" => "fa",
+        "pol" => "pl",
+        "por" => "pt",
+        "ron" => "ro",
+        "rus" => "ru",
+        "sin" => "si",
+        "slk" => "sk",
+        "slv" => "sl",
+        "sna" => "sn",
+        "spa" => "es",
+        "srp" => "sr",
+        "swe" => "sv",
+        "tam" => "ta",
+        "tel" => "te",
+        "tgl" => "tl",
+        "tha" => "th",
+        "tuk" => "tk",
+        "tur" => "tr",
+        "ukr" => "uk",
+        "urd" => "ur",
+        "uzb" => "uz",
+        "vie" => "vi",
+        "yid" => "yi",
+        "zul" => "zu",
+        other => other,
+    }
+}
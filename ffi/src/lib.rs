@@ -73,6 +73,8 @@
 //! **3. Get a Full Debug Report:**
 //! If `extractous_error_has_debug()` returns `1`, you can call `extractous_error_get_last_debug()`
 //! to get a detailed report, including the full error chain and a backtrace (if enabled with `RUST_BACKTRACE=1`).
+//! When a parser failed with a Java exception, the report starts with
+//! `java_exception: <class>: <message>`, e.g. `java_exception: org.apache.tika.exception.TikaException: ...`.
 //! The returned string **must be freed**.
 //!
 //! ### Go Usage Pattern
@@ -112,19 +114,48 @@
 pub use extractous as ecore;
 
 // Module declarations.
+mod archive;
+mod cancel;
+mod charset;
 mod config;
+mod date;
+mod detect;
+mod embedded;
 mod errors;
 mod extractor;
+#[cfg(feature = "fault-injection")]
+mod fault;
+mod image;
+mod json;
+mod lang;
+mod log;
 mod metadata;
+mod mime;
+mod pdf;
 mod stream;
+mod structure;
+mod text;
+mod throttle;
 mod types;
+mod xhtml;
 
 // Publicly re-export all FFI-safe functions and types for C header generation.
+pub use cancel::*;
 pub use config::*;
+pub use detect::*;
+pub use embedded::*;
 pub use errors::*;
 pub use extractor::*;
+#[cfg(feature = "fault-injection")]
+pub use fault::*;
+pub use image::*;
+pub use log::*;
 pub use metadata::*;
+pub use pdf::*;
 pub use stream::*;
+pub use structure::*;
+pub use text::*;
+pub use throttle::*;
 pub use types::*;
 
 /// Returns the FFI wrapper version as a null-terminated UTF-8 string.
@@ -143,3 +174,85 @@ pub extern "C" fn extractous_core_version() -> *const libc::c_char {
     static VERSION: &[u8] = b"0.3.0\0";
     VERSION.as_ptr() as *const libc::c_char
 }
+
+/// Returns the version of Apache Tika the bundled `libtika_native` was built
+/// from, or `"unknown"` if the build could not determine it.
+///
+/// The version is captured by `build.rs` from the native build output, or
+/// from the `EXTRACTOUS_TIKA_VERSION` environment variable when set at build
+/// time. The returned pointer is to a static string and must not be freed.
+#[unsafe(no_mangle)]
+pub extern "C" fn extractous_tika_version() -> *const libc::c_char {
+    static VERSION: std::sync::OnceLock<std::ffi::CString> = std::sync::OnceLock::new();
+    VERSION
+        .get_or_init(|| {
+            let version = option_env!("EXTRACTOUS_TIKA_VERSION").unwrap_or_default();
+            std::ffi::CString::new(version.trim())
+                .ok()
+                .filter(|version| !version.is_empty())
+                .unwrap_or_else(|| c"unknown".into())
+        })
+        .as_ptr()
+}
+
+/// The directory set by `extractous_set_temp_dir`, if any.
+static TEMP_DIR: std::sync::RwLock<Option<std::path::PathBuf>> = std::sync::RwLock::new(None);
+
+/// Returns the directory for this library's own intermediate files.
+pub(crate) fn temp_dir() -> std::path::PathBuf {
+    TEMP_DIR
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone()
+        .unwrap_or_else(std::env::temp_dir)
+}
+
+/// Sets the directory this library uses for its own intermediate files: the
+/// filtered copies of archives parsed under the embedded-file limits, and the
+/// copies of unidentified inputs retried under a fallback MIME type.
+///
+/// The directory must exist and be writable; otherwise `ERR_IO_ERROR` is
+/// returned and nothing changes. Call this during startup: once the first
+/// extraction has started, `ERR_INVALID_CONFIG` is returned. The parser
+/// runtime's own temporary files, such as the images it passes to Tesseract
+/// for OCR, go to its `java.io.tmpdir`, which the core fixes when it starts
+/// the runtime and which cannot be changed from here. The process
+/// environment is left alone.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_set_temp_dir(path: *const libc::c_char) -> libc::c_int {
+    if path.is_null() {
+        return ERR_NULL_POINTER;
+    }
+    let path_str = match unsafe { std::ffi::CStr::from_ptr(path).to_str() } {
+        Ok(s) => s,
+        Err(_) => return ERR_INVALID_UTF8,
+    };
+    let mut temp_dir = TEMP_DIR.write().unwrap_or_else(|e| e.into_inner());
+    if throttle::has_started() {
+        return record_ffi_error(
+            ERR_INVALID_CONFIG,
+            "the temp dir cannot change once an extraction has started",
+        );
+    }
+    if let Err(e) = check_writable_dir(std::path::Path::new(path_str)) {
+        return record_ffi_error(
+            ERR_IO_ERROR,
+            format!("temp dir {} is not usable: {}", path_str, e),
+        );
+    }
+    *temp_dir = Some(path_str.into());
+    ERR_OK
+}
+
+/// Checks that `dir` is a directory in which files can be created.
+fn check_writable_dir(dir: &std::path::Path) -> std::io::Result<()> {
+    if !std::fs::metadata(dir)?.is_dir() {
+        return Err(std::io::Error::other("not a directory"));
+    }
+    let probe = dir.join(format!(".extractous-probe-{}", std::process::id()));
+    std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&probe)?;
+    std::fs::remove_file(probe)
+}
@@ -0,0 +1,51 @@
+//! A process-wide callback for warnings the library raises while extracting.
+
+use crate::types::LOG_WARN;
+use std::ffi::CString;
+use std::os::raw::{c_char, c_int, c_void};
+use std::sync::Mutex;
+
+/// Called with a `LOG_*` level and a null-terminated message, which is only
+/// valid for the duration of the call.
+pub type CLogCallback =
+    Option<unsafe extern "C" fn(ctx: *mut c_void, level: c_int, message: *const c_char)>;
+
+struct Logger {
+    callback: unsafe extern "C" fn(*mut c_void, c_int, *const c_char),
+    ctx: *mut c_void,
+}
+
+// The context pointer is the caller's; they promise it may be used from any
+// thread that extracts.
+unsafe impl Send for Logger {}
+
+static LOGGER: Mutex<Option<Logger>> = Mutex::new(None);
+
+/// Sets the callback that receives the library's log messages, replacing any
+/// previous one. Pass NULL to stop logging.
+///
+/// The callback runs on the thread that raised the message, possibly several
+/// threads at once, so it and `ctx` must be thread-safe. It must not call
+/// `extractous_set_log_callback` itself.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_set_log_callback(callback: CLogCallback, ctx: *mut c_void) {
+    let logger = callback.map(|callback| Logger { callback, ctx });
+    *LOGGER.lock().unwrap_or_else(|e| e.into_inner()) = logger;
+}
+
+/// Sends a message to the log callback, if one is set.
+pub(crate) fn log(level: c_int, message: &str) {
+    let logger = LOGGER.lock().unwrap_or_else(|e| e.into_inner());
+    let Some(logger) = logger.as_ref() else {
+        return;
+    };
+    let Ok(message) = CString::new(message.replace('\0', "")) else {
+        return;
+    };
+    unsafe { (logger.callback)(logger.ctx, level, message.as_ptr()) };
+}
+
+/// Sends a `LOG_WARN` message to the log callback, if one is set.
+pub(crate) fn warn(message: &str) {
+    log(LOG_WARN, message);
+}
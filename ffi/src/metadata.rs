@@ -1,43 +1,70 @@
+use crate::date;
+use crate::errors::*;
+use crate::extractor::strings_to_c;
+use crate::json;
+use crate::log;
 use crate::types::CMetadata;
+use std::borrow::Cow;
 use std::collections::HashMap;
-use std::ffi::CString;
-use std::os::raw::c_char;
+use std::ffi::{CStr, CString};
+use std::os::raw::{c_char, c_int};
 use std::ptr;
+use std::sync::RwLock;
 
-/// Convert a Rust HashMap to a C-compatible metadata structure.
-pub(crate) unsafe fn metadata_to_c(metadata: HashMap<String, Vec<String>>) -> *mut CMetadata {
-    if metadata.is_empty() {
-        return Box::into_raw(Box::new(CMetadata {
-            keys: ptr::null_mut(),
-            values: ptr::null_mut(),
-            len: 0,
-        }));
+/// Separator placed between the values of a multi-valued metadata key.
+static VALUE_SEPARATOR: RwLock<Cow<'static, str>> = RwLock::new(Cow::Borrowed("\x1f"));
+
+/// Sets the separator used to join the values of multi-valued metadata keys
+/// in `CMetadata`. The setting is global and applies to later extractions.
+///
+/// The default is the ASCII unit separator `"\x1f"`, which does not occur in
+/// real metadata values the way a comma does.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_set_metadata_value_separator(sep: *const c_char) -> c_int {
+    if sep.is_null() {
+        return ERR_NULL_POINTER;
     }
+    let sep_str = match unsafe { CStr::from_ptr(sep).to_str() } {
+        Ok(s) => s,
+        Err(_) => return ERR_INVALID_UTF8,
+    };
+    let mut separator = VALUE_SEPARATOR.write().unwrap_or_else(|e| e.into_inner());
+    *separator = Cow::Owned(sep_str.to_string());
+    ERR_OK
+}
 
-    let capacity = metadata.len();
-    let mut keys: Vec<*mut c_char> = Vec::with_capacity(capacity);
-    let mut values: Vec<*mut c_char> = Vec::with_capacity(capacity);
-
-    for (key, value_vec) in metadata {
-        // CString::new will return an error if the string contains `\0`.
-        let c_key = match CString::new(key) {
-            Ok(s) => s.into_raw(),
-            Err(_) => continue, // Skip metadata with invalid keys.
-        };
-
-        let joined_values = value_vec.join(", ");
-        let c_value = match CString::new(joined_values) {
-            Ok(s) => s.into_raw(),
-            Err(_) => {
-                // Clean up the already-allocated key if the value is invalid.
-                let _ = unsafe { CString::from_raw(c_key) };
-                continue;
-            }
-        };
+/// Returns the separator currently used to join the values of multi-valued
+/// metadata keys, so bindings can split them without assuming the default.
+/// Metadata returned before a change was joined with the old separator, so
+/// set it once, before extracting.
+///
+/// The returned string must be freed with `extractous_string_free`.
+#[unsafe(no_mangle)]
+pub extern "C" fn extractous_get_metadata_value_separator() -> *mut c_char {
+    let separator = VALUE_SEPARATOR.read().unwrap_or_else(|e| e.into_inner());
+    CString::new(separator.as_ref()).map_or(ptr::null_mut(), CString::into_raw)
+}
 
-        keys.push(c_key);
-        values.push(c_value);
-    }
+/// Convert a Rust HashMap to a C-compatible metadata structure.
+///
+/// Keys are emitted in lexicographic byte order so the output is reproducible
+/// across runs.
+pub(crate) unsafe fn metadata_to_c(metadata: HashMap<String, Vec<String>>) -> *mut CMetadata {
+    unsafe { metadata_to_c_capped(metadata, 0) }
+}
+
+/// Like `metadata_to_c`, keeping only the first `max_entries` keys in
+/// lexicographic order; 0 keeps them all. The number of entries dropped is
+/// logged as a warning.
+pub(crate) unsafe fn metadata_to_c_capped(
+    metadata: HashMap<String, Vec<String>>,
+    max_entries: usize,
+) -> *mut CMetadata {
+    let (mut keys, mut values): (Vec<*mut c_char>, Vec<*mut c_char>) =
+        sorted_entries(metadata, max_entries)
+            .into_iter()
+            .map(|(key, value)| (key.into_raw(), value.into_raw()))
+            .unzip();
 
     // Final length is derived from the vectors after they are populated.
     // Guarantees that the length matches the number of allocated pointers.
@@ -67,6 +94,363 @@ pub(crate) unsafe fn metadata_to_c(metadata: HashMap<String, Vec<String>>) -> *m
     }))
 }
 
+/// Returns the entries of `metadata` as they appear in `CMetadata`: sorted by
+/// key, capped at `max_entries` as for `metadata_to_c_capped`, and with the
+/// values of each key joined by the value separator. Entries whose key or
+/// value contains a NUL are skipped.
+pub(crate) fn sorted_entries(
+    metadata: HashMap<String, Vec<String>>,
+    max_entries: usize,
+) -> Vec<(CString, CString)> {
+    let separator = VALUE_SEPARATOR
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone();
+    capped_entries(metadata, max_entries)
+        .into_iter()
+        .filter_map(|(key, values)| {
+            // CString::new fails if the string contains `\0`.
+            Some((
+                CString::new(key).ok()?,
+                CString::new(values.join(&separator)).ok()?,
+            ))
+        })
+        .collect()
+}
+
+/// Sorts the entries of `metadata` by key and keeps the first `max_entries`,
+/// logging how many were dropped. A `max_entries` of 0 keeps them all.
+fn capped_entries(
+    metadata: HashMap<String, Vec<String>>,
+    max_entries: usize,
+) -> Vec<(String, Vec<String>)> {
+    let mut entries: Vec<(String, Vec<String>)> = metadata.into_iter().collect();
+    entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+    if max_entries > 0 && entries.len() > max_entries {
+        log::warn(&format!(
+            "dropped {} of {} metadata entries over the limit of {}",
+            entries.len() - max_entries,
+            entries.len(),
+            max_entries
+        ));
+        entries.truncate(max_entries);
+    }
+    entries
+}
+
+/// Adds a `Content-Length` entry of `len` bytes unless the parser already set one.
+pub(crate) fn ensure_content_length(metadata: &mut HashMap<String, Vec<String>>, len: u64) {
+    metadata
+        .entry("Content-Length".to_string())
+        .or_insert_with(|| vec![len.to_string()]);
+}
+
+/// Appends `metadata` to `out` as a JSON object mapping each key to an array
+/// of its values, with keys in the same order and capped at `max_entries` as
+/// for `metadata_to_c_capped`.
+pub(crate) fn push_metadata_json(
+    out: &mut String,
+    metadata: HashMap<String, Vec<String>>,
+    max_entries: usize,
+) {
+    out.push('{');
+    for (i, (key, values)) in capped_entries(metadata, max_entries)
+        .into_iter()
+        .enumerate()
+    {
+        if i > 0 {
+            out.push(',');
+        }
+        json::push_string(out, &key);
+        out.push_str(":[");
+        for (j, value) in values.iter().enumerate() {
+            if j > 0 {
+                out.push(',');
+            }
+            json::push_string(out, value);
+        }
+        out.push(']');
+    }
+    out.push('}');
+}
+
+/// Returns the values of `key` as a separate string for each value.
+///
+/// Values are split on the current separator (see
+/// `extractous_set_metadata_value_separator`), so the separator should not be
+/// changed between extraction and this call. An absent key returns `ERR_OK`
+/// with `*out_count` set to 0 and `*out_values` set to NULL.
+/// The array must be freed with `extractous_string_array_free`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_metadata_get_all_values(
+    metadata: *const CMetadata,
+    key: *const c_char,
+    out_values: *mut *mut *mut c_char,
+    out_count: *mut libc::size_t,
+) -> c_int {
+    if metadata.is_null() || key.is_null() || out_values.is_null() || out_count.is_null() {
+        return ERR_NULL_POINTER;
+    }
+    let key = unsafe { CStr::from_ptr(key) };
+    let values = unsafe { all_values(&*metadata, key) }.unwrap_or_default();
+    unsafe { strings_to_c(values, out_values, out_count) };
+    ERR_OK
+}
+
+/// Returns the chain of parser classes that handled the document, outermost
+/// first, from the `X-TIKA:Parsed-By` metadata key.
+///
+/// Returns `ERR_METADATA_ABSENT` if the key is missing.
+/// The array must be freed with `extractous_string_array_free`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_metadata_get_parsed_by(
+    metadata: *const CMetadata,
+    out_parsers: *mut *mut *mut c_char,
+    out_count: *mut libc::size_t,
+) -> c_int {
+    if metadata.is_null() || out_parsers.is_null() || out_count.is_null() {
+        return ERR_NULL_POINTER;
+    }
+    match unsafe { all_values(&*metadata, c"X-TIKA:Parsed-By") } {
+        Some(parsers) => {
+            unsafe { strings_to_c(parsers, out_parsers, out_count) };
+            ERR_OK
+        }
+        None => ERR_METADATA_ABSENT,
+    }
+}
+
+/// Returns the document's `resourceName`: the file's name for file inputs,
+/// or the name given with a `_named` byte extraction.
+///
+/// The string belongs to `metadata` and stays valid until it is freed; do not
+/// free it separately. Returns NULL if the key is missing.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_metadata_get_resource_name(
+    metadata: *const CMetadata,
+) -> *const c_char {
+    if metadata.is_null() {
+        return ptr::null();
+    }
+    unsafe { metadata_value(&*metadata, c"resourceName") }.map_or(ptr::null(), CStr::as_ptr)
+}
+
+/// Metadata keys that carry a page count, in order of preference. PDF parsers
+/// report `xmpTPg:NPages`, Office parsers `meta:page-count` or, for slides,
+/// `meta:slide-count`.
+const PAGE_COUNT_KEYS: [&CStr; 4] = [
+    c"xmpTPg:NPages",
+    c"Page-Count",
+    c"meta:page-count",
+    c"meta:slide-count",
+];
+
+/// Reads the document's page count from whichever metadata key the parser
+/// used for it.
+///
+/// Returns `ERR_METADATA_ABSENT` if no page count was reported, as is the case
+/// for single images and plain text, or if it is not a valid number.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_metadata_get_page_count(
+    metadata: *const CMetadata,
+    out_count: *mut c_int,
+) -> c_int {
+    if metadata.is_null() || out_count.is_null() {
+        return ERR_NULL_POINTER;
+    }
+    let metadata = unsafe { &*metadata };
+    let count = PAGE_COUNT_KEYS
+        .iter()
+        .find_map(|key| unsafe { first_value(metadata, key) })
+        .and_then(|value| value.trim().parse::<c_int>().ok())
+        .filter(|&count| count >= 0);
+    match count {
+        Some(count) => {
+            unsafe { *out_count = count };
+            ERR_OK
+        }
+        None => ERR_METADATA_ABSENT,
+    }
+}
+
+/// Metadata keys that carry the creation date, in order of preference.
+const CREATED_KEYS: [&CStr; 4] = [
+    c"dcterms:created",
+    c"meta:creation-date",
+    c"Creation-Date",
+    c"pdf:docinfo:created",
+];
+
+/// Metadata keys that carry the last modification date, in order of preference.
+const MODIFIED_KEYS: [&CStr; 4] = [
+    c"dcterms:modified",
+    c"Last-Modified",
+    c"meta:save-date",
+    c"pdf:docinfo:modified",
+];
+
+/// Reads the document's creation date as seconds since the Unix epoch.
+///
+/// The date is taken from the first of `dcterms:created`,
+/// `meta:creation-date`, `Creation-Date` and `pdf:docinfo:created` that is
+/// present. ISO 8601 dates with or without fractional seconds, a UTC offset or
+/// a time of day are accepted; dates without an offset are taken as UTC.
+/// Returns `ERR_METADATA_ABSENT` if no date was reported or it cannot be parsed.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_metadata_get_created_epoch(
+    metadata: *const CMetadata,
+    out: *mut i64,
+) -> c_int {
+    unsafe { date_epoch(metadata, &CREATED_KEYS, out) }
+}
+
+/// Reads the document's last modification date as seconds since the Unix
+/// epoch, from the first of `dcterms:modified`, `Last-Modified`,
+/// `meta:save-date` and `pdf:docinfo:modified` that is present.
+///
+/// Dates are parsed as for `extractous_metadata_get_created_epoch`.
+/// Returns `ERR_METADATA_ABSENT` if no date was reported or it cannot be parsed.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_metadata_get_modified_epoch(
+    metadata: *const CMetadata,
+    out: *mut i64,
+) -> c_int {
+    unsafe { date_epoch(metadata, &MODIFIED_KEYS, out) }
+}
+
+/// Parses the date stored under the first of `keys` that is present.
+unsafe fn date_epoch(metadata: *const CMetadata, keys: &[&CStr], out: *mut i64) -> c_int {
+    if metadata.is_null() || out.is_null() {
+        return ERR_NULL_POINTER;
+    }
+    let metadata = unsafe { &*metadata };
+    let epoch = keys
+        .iter()
+        .find_map(|key| unsafe { first_value(metadata, key) })
+        .and_then(|value| date::parse_epoch_seconds(&value));
+    match epoch {
+        Some(epoch) => {
+            unsafe { *out = epoch };
+            ERR_OK
+        }
+        None => ERR_METADATA_ABSENT,
+    }
+}
+
+/// Returns the first of the values stored for `key`.
+pub(crate) unsafe fn first_value(metadata: &CMetadata, key: &CStr) -> Option<String> {
+    unsafe { all_values(metadata, key) }?.into_iter().next()
+}
+
+/// Splits the joined value stored for `key` on the current separator.
+pub(crate) unsafe fn all_values(metadata: &CMetadata, key: &CStr) -> Option<Vec<String>> {
+    let joined = unsafe { metadata_value(metadata, key) }?.to_string_lossy();
+    let separator = VALUE_SEPARATOR.read().unwrap_or_else(|e| e.into_inner());
+    if separator.is_empty() {
+        return Some(vec![joined.into_owned()]);
+    }
+    Some(
+        joined
+            .split(separator.as_ref())
+            .map(str::to_string)
+            .collect(),
+    )
+}
+
+/// Looks up the joined value stored for `key`.
+pub(crate) unsafe fn metadata_value<'a>(metadata: &'a CMetadata, key: &CStr) -> Option<&'a CStr> {
+    (0..metadata.len).find_map(|i| unsafe {
+        let k = CStr::from_ptr(*metadata.keys.add(i));
+        (k == key).then(|| CStr::from_ptr(*metadata.values.add(i)))
+    })
+}
+
+/// Returns a deep copy of `metadata` whose strings are allocated separately,
+/// so the original can be freed while the copy is kept. Returns NULL if
+/// `metadata` is NULL.
+/// The copy must be freed with `extractous_metadata_free`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_metadata_clone(metadata: *const CMetadata) -> *mut CMetadata {
+    if metadata.is_null() {
+        return ptr::null_mut();
+    }
+    let metadata = unsafe { &*metadata };
+    if metadata.len == 0 {
+        return Box::into_raw(Box::new(CMetadata {
+            keys: ptr::null_mut(),
+            values: ptr::null_mut(),
+            len: 0,
+        }));
+    }
+
+    let copy = |strings: *mut *mut c_char| -> *mut *mut c_char {
+        let copied: Box<[*mut c_char]> = (0..metadata.len)
+            .map(|i| {
+                unsafe { CStr::from_ptr(*strings.add(i)) }
+                    .to_owned()
+                    .into_raw()
+            })
+            .collect();
+        Box::into_raw(copied) as *mut *mut c_char
+    };
+    Box::into_raw(Box::new(CMetadata {
+        keys: copy(metadata.keys),
+        values: copy(metadata.values),
+        len: metadata.len,
+    }))
+}
+
+/// Returns a new metadata structure holding a copy of each entry of
+/// `metadata` whose key starts with `prefix`, such as `"dc:"` for the Dublin
+/// Core fields. Matching is case-sensitive. Entries keep their order, and an
+/// empty prefix copies every entry. Returns NULL if `metadata` or `prefix` is
+/// NULL or `prefix` is not valid UTF-8.
+/// The result must be freed with `extractous_metadata_free`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_metadata_filter_by_prefix(
+    metadata: *const CMetadata,
+    prefix: *const c_char,
+) -> *mut CMetadata {
+    if metadata.is_null() || prefix.is_null() {
+        return ptr::null_mut();
+    }
+    let Ok(prefix) = unsafe { CStr::from_ptr(prefix) }.to_str() else {
+        return ptr::null_mut();
+    };
+    let metadata = unsafe { &*metadata };
+    let matching: Vec<usize> = (0..metadata.len)
+        .filter(|&i| {
+            unsafe { CStr::from_ptr(*metadata.keys.add(i)) }
+                .to_bytes()
+                .starts_with(prefix.as_bytes())
+        })
+        .collect();
+    if matching.is_empty() {
+        return Box::into_raw(Box::new(CMetadata {
+            keys: ptr::null_mut(),
+            values: ptr::null_mut(),
+            len: 0,
+        }));
+    }
+
+    let copy = |strings: *mut *mut c_char| -> *mut *mut c_char {
+        let copied: Box<[*mut c_char]> = matching
+            .iter()
+            .map(|&i| {
+                unsafe { CStr::from_ptr(*strings.add(i)) }
+                    .to_owned()
+                    .into_raw()
+            })
+            .collect();
+        Box::into_raw(copied) as *mut *mut c_char
+    };
+    Box::into_raw(Box::new(CMetadata {
+        keys: copy(metadata.keys),
+        values: copy(metadata.values),
+        len: matching.len(),
+    }))
+}
+
 /// Frees a metadata structure and all associated memory.
 #[unsafe(no_mangle)]
 pub unsafe extern "C" fn extractous_metadata_free(metadata: *mut CMetadata) {
@@ -0,0 +1,244 @@
+//! A small reader for RFC 822 / MIME email messages, enough to pull out
+//! their attachments. The core extractor parses attachments for their text
+//! only and never hands back their bytes.
+
+/// A decoded attachment of an email message.
+pub(crate) struct Attachment {
+    /// The file name from `Content-Disposition` or `Content-Type`.
+    pub(crate) name: Option<String>,
+    /// The base `Content-Type`, lowercased, without parameters.
+    pub(crate) mime: String,
+    pub(crate) data: Vec<u8>,
+}
+
+/// Header names of which at least two must start a file for it to be taken as
+/// an email message.
+const MESSAGE_HEADERS: [&str; 7] = [
+    "from",
+    "to",
+    "subject",
+    "date",
+    "message-id",
+    "mime-version",
+    "received",
+];
+
+/// Returns true if `head` starts with a header block typical of an email
+/// message.
+pub(crate) fn looks_like_message(head: &[u8]) -> bool {
+    let (headers, _) = split_part(head);
+    let known = headers
+        .iter()
+        .filter(|(name, _)| MESSAGE_HEADERS.contains(&name.as_str()))
+        .count();
+    known >= 2
+}
+
+/// Returns the attachments of an email message in document order.
+///
+/// A part is an attachment if it has `Content-Disposition: attachment` or a
+/// file name, or is an attached message. The message text, in `text/plain`
+/// and `text/html` parts without a file name, is not. Base64 and
+/// quoted-printable bodies are decoded. Encoded words (RFC 2047) in file
+/// names are left as they are.
+pub(crate) fn attachments(message: &[u8]) -> Vec<Attachment> {
+    let mut found = Vec::new();
+    collect(message, 0, &mut found);
+    found
+}
+
+/// Nested multiparts deeper than this are not searched.
+const MAX_DEPTH: usize = 16;
+
+fn collect(part: &[u8], depth: usize, found: &mut Vec<Attachment>) {
+    let (headers, body) = split_part(part);
+    let header = |name: &str| {
+        headers
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.as_str())
+    };
+    let content_type = header("content-type").unwrap_or("text/plain");
+    let mime = content_type
+        .split(';')
+        .next()
+        .unwrap_or("")
+        .trim()
+        .to_ascii_lowercase();
+    let disposition = header("content-disposition").unwrap_or("");
+
+    if mime.starts_with("multipart/") {
+        if depth < MAX_DEPTH
+            && let Some(boundary) = param(content_type, "boundary")
+        {
+            for child in multipart_parts(body, &boundary) {
+                collect(child, depth + 1, found);
+            }
+        }
+        return;
+    }
+    if depth == 0 {
+        // The top level of a single-part message is its text.
+        return;
+    }
+
+    let name = param(disposition, "filename").or_else(|| param(content_type, "name"));
+    let is_attachment = disposition
+        .split(';')
+        .next()
+        .is_some_and(|d| d.trim().eq_ignore_ascii_case("attachment"))
+        || name.is_some()
+        || mime == "message/rfc822";
+    if !is_attachment {
+        return;
+    }
+    let encoding = header("content-transfer-encoding")
+        .unwrap_or("")
+        .trim()
+        .to_ascii_lowercase();
+    let data = match encoding.as_str() {
+        "base64" => decode_base64(body),
+        "quoted-printable" => decode_quoted_printable(body),
+        _ => body.to_vec(),
+    };
+    found.push(Attachment { name, mime, data });
+}
+
+/// Splits a part into its unfolded headers, with lowercased names, and body.
+fn split_part(part: &[u8]) -> (Vec<(String, String)>, &[u8]) {
+    let mut headers: Vec<(String, String)> = Vec::new();
+    let mut pos = 0;
+    while pos < part.len() {
+        let end = part[pos..]
+            .iter()
+            .position(|&b| b == b'\n')
+            .map_or(part.len(), |i| pos + i + 1);
+        let line = String::from_utf8_lossy(&part[pos..end]);
+        let line = line.trim_end_matches(['\r', '\n']);
+        if line.is_empty() {
+            return (headers, &part[end..]);
+        }
+        if line.starts_with([' ', '\t']) {
+            if let Some((_, value)) = headers.last_mut() {
+                value.push(' ');
+                value.push_str(line.trim());
+            }
+        } else if let Some((name, value)) = line.split_once(':') {
+            headers.push((name.trim().to_ascii_lowercase(), value.trim().to_string()));
+        } else {
+            // Not a header block after all.
+            return (headers, &part[pos..]);
+        }
+        pos = end;
+    }
+    (headers, &part[part.len()..])
+}
+
+/// Returns the value of parameter `key` in a header value such as
+/// `attachment; filename="report.pdf"`.
+fn param(value: &str, key: &str) -> Option<String> {
+    value.split(';').skip(1).find_map(|p| {
+        let (k, v) = p.split_once('=')?;
+        if !k.trim().eq_ignore_ascii_case(key) {
+            return None;
+        }
+        let v = v.trim();
+        let v = v
+            .strip_prefix('"')
+            .and_then(|v| v.strip_suffix('"'))
+            .unwrap_or(v);
+        (!v.is_empty()).then(|| v.to_string())
+    })
+}
+
+/// Splits a multipart body on its boundary lines, dropping the preamble and
+/// epilogue.
+fn multipart_parts<'a>(body: &'a [u8], boundary: &str) -> Vec<&'a [u8]> {
+    let delimiter = format!("--{}", boundary);
+    let mut parts = Vec::new();
+    let mut start: Option<usize> = None;
+    let mut pos = 0;
+    while pos < body.len() {
+        let end = body[pos..]
+            .iter()
+            .position(|&b| b == b'\n')
+            .map_or(body.len(), |i| pos + i + 1);
+        let line = body[pos..end].trim_ascii_end();
+        if let Some(rest) = line.strip_prefix(delimiter.as_bytes()) {
+            if let Some(start) = start {
+                parts.push(strip_line_break(&body[start..pos]));
+            }
+            if rest.starts_with(b"--") {
+                return parts;
+            }
+            start = Some(end);
+        }
+        pos = end;
+    }
+    if let Some(start) = start {
+        parts.push(&body[start..]);
+    }
+    parts
+}
+
+/// Removes the line break that belongs to the boundary following a part.
+fn strip_line_break(part: &[u8]) -> &[u8] {
+    let part = part.strip_suffix(b"\n").unwrap_or(part);
+    part.strip_suffix(b"\r").unwrap_or(part)
+}
+
+fn decode_base64(input: &[u8]) -> Vec<u8> {
+    let value = |c: u8| match c {
+        b'A'..=b'Z' => Some(c - b'A'),
+        b'a'..=b'z' => Some(c - b'a' + 26),
+        b'0'..=b'9' => Some(c - b'0' + 52),
+        b'+' => Some(62),
+        b'/' => Some(63),
+        _ => None,
+    };
+    let mut out = Vec::with_capacity(input.len() * 3 / 4);
+    let mut acc: u32 = 0;
+    let mut bits = 0;
+    for &c in input {
+        if c == b'=' {
+            break;
+        }
+        let Some(v) = value(c) else {
+            continue;
+        };
+        acc = (acc << 6) | v as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((acc >> bits) as u8);
+        }
+    }
+    out
+}
+
+fn decode_quoted_printable(input: &[u8]) -> Vec<u8> {
+    let hex = |c: u8| (c as char).to_digit(16).map(|d| d as u8);
+    let mut out = Vec::with_capacity(input.len());
+    let mut i = 0;
+    while i < input.len() {
+        let rest = &input[i + 1..];
+        if input[i] != b'=' {
+            out.push(input[i]);
+            i += 1;
+        } else if rest.is_empty() || rest.starts_with(b"\r\n") {
+            // A soft line break, the last one without its line ending.
+            i += 3;
+        } else if rest.starts_with(b"\n") {
+            i += 2;
+        } else if let [h, l, ..] = rest
+            && let (Some(h), Some(l)) = (hex(*h), hex(*l))
+        {
+            out.push(h << 4 | l);
+            i += 3;
+        } else {
+            out.push(b'=');
+            i += 1;
+        }
+    }
+    out
+}
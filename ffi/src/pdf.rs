@@ -0,0 +1,163 @@
+//! Typed PDF properties read from the metadata Tika reports for a PDF, and
+//! checks of the file structure that Tika does not report.
+
+use crate::detect;
+use crate::errors::*;
+use crate::extractor::{FileInput, extractous_string_free};
+use crate::types::*;
+use std::collections::HashMap;
+use std::ffi::CString;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::os::raw::{c_char, c_int};
+use std::ptr;
+
+/// A linearization dictionary must be the first object of the file, within
+/// its first kilobyte.
+const LINEARIZED_SCAN: usize = 1024;
+
+/// The `startxref` keyword must be within the last kilobyte of the file.
+const STARTXREF_SCAN: u64 = 1024;
+
+/// Fills `*out_info` with the page count, version, encryption flag and
+/// linearized flag of the PDF at `path`.
+///
+/// The page count comes from Tika's `xmpTPg:NPages`, the version from
+/// `pdf:PDFVersion` or else the file header, and the encryption flag from
+/// `pdf:encrypted`. Tika does not report linearization, so the flag is set
+/// when the file starts with a linearization dictionary. Files that are not
+/// PDFs return `ERR_UNSUPPORTED_FORMAT`.
+///
+/// `*out_info` is the caller's; the strings it holds must be released with
+/// `extractous_pdf_info_free`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_extractor_extract_pdf_info(
+    handle: *mut CExtractor,
+    path: *const c_char,
+    out_info: *mut CPdfInfo,
+) -> c_int {
+    if handle.is_null() || path.is_null() || out_info.is_null() {
+        return ERR_NULL_POINTER;
+    }
+    let input = match unsafe { FileInput::open(handle, path) } {
+        Ok(input) => input,
+        Err(code) => return code,
+    };
+    let path_str = input.path;
+
+    let head = match detect::read_head(path_str) {
+        Ok(head) => head,
+        Err(e) => {
+            return record_ffi_error(ERR_IO_ERROR, format!("cannot read {}: {}", path_str, e));
+        }
+    };
+    if !head.starts_with(b"%PDF-") {
+        return record_ffi_error(ERR_UNSUPPORTED_FORMAT, format!("{} is not a PDF", path_str));
+    }
+    let metadata = match input.extract_to_string(&input.state.inner) {
+        Ok((_, metadata)) => metadata,
+        Err(e) => return record_error(e),
+    };
+
+    let version = first(&metadata, "pdf:PDFVersion")
+        .map(str::to_string)
+        .or_else(|| header_version(&head));
+    unsafe {
+        *out_info = CPdfInfo {
+            page_count: first(&metadata, "xmpTPg:NPages")
+                .and_then(|n| n.trim().parse().ok())
+                .unwrap_or(-1),
+            pdf_version: version
+                .and_then(|v| CString::new(v).ok())
+                .map_or(ptr::null_mut(), |s| s.into_raw()),
+            encrypted: first(&metadata, "pdf:encrypted")
+                .is_some_and(|v| v.trim().eq_ignore_ascii_case("true"))
+                as c_int,
+            linearized: is_linearized(&head) as c_int,
+        };
+    }
+    ERR_OK
+}
+
+/// Frees the strings held by a `CPdfInfo` filled by
+/// `extractous_extractor_extract_pdf_info` and sets them to NULL. The struct
+/// itself is not freed.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_pdf_info_free(info: *mut CPdfInfo) {
+    if info.is_null() {
+        return;
+    }
+    unsafe {
+        extractous_string_free((*info).pdf_version);
+        (*info).pdf_version = ptr::null_mut();
+    }
+}
+
+fn first<'a>(metadata: &'a HashMap<String, Vec<String>>, key: &str) -> Option<&'a str> {
+    metadata.get(key)?.first().map(String::as_str)
+}
+
+/// Reads the version from a `%PDF-1.7` header line.
+fn header_version(head: &[u8]) -> Option<String> {
+    let version: String = head[b"%PDF-".len()..]
+        .iter()
+        .take_while(|b| b.is_ascii_digit() || **b == b'.')
+        .map(|&b| b as char)
+        .collect();
+    (!version.is_empty()).then_some(version)
+}
+
+/// Returns true if the file starts with a linearization dictionary.
+fn is_linearized(head: &[u8]) -> bool {
+    let scan = &head[..head.len().min(LINEARIZED_SCAN)];
+    scan.windows(b"/Linearized".len())
+        .any(|w| w == b"/Linearized")
+}
+
+/// Returns true if the PDF at `path` has no `startxref` near its end, or if
+/// the offset it gives does not lead to a cross-reference table or stream.
+/// PDFBox then rebuilds the table by scanning the whole file, and says so
+/// only in its log.
+pub(crate) fn has_broken_xref(path: &str) -> std::io::Result<bool> {
+    let mut file = File::open(path)?;
+    let len = file.metadata()?.len();
+    let mut tail = Vec::new();
+    file.seek(SeekFrom::Start(len.saturating_sub(STARTXREF_SCAN)))?;
+    file.read_to_end(&mut tail)?;
+    let Some(at) = tail
+        .windows(b"startxref".len())
+        .rposition(|w| w == b"startxref")
+    else {
+        return Ok(true);
+    };
+    let digits: String = tail[at + b"startxref".len()..]
+        .iter()
+        .skip_while(|b| b.is_ascii_whitespace())
+        .take_while(|b| b.is_ascii_digit())
+        .map(|&b| b as char)
+        .collect();
+    let offset = match digits.parse::<u64>() {
+        Ok(offset) if offset < len => offset,
+        _ => return Ok(true),
+    };
+
+    let mut target = Vec::new();
+    file.seek(SeekFrom::Start(offset))?;
+    file.take(64).read_to_end(&mut target)?;
+    let target = target.trim_ascii_start();
+    if target.starts_with(b"xref") {
+        return Ok(false);
+    }
+    // A cross-reference stream is an indirect object: `12 0 obj`.
+    let mut words = target
+        .split(|b| b.is_ascii_whitespace())
+        .filter(|w| !w.is_empty());
+    let is_object = words
+        .next()
+        .is_some_and(|w| w.iter().all(u8::is_ascii_digit))
+        && words
+            .next()
+            .is_some_and(|w| w.iter().all(u8::is_ascii_digit))
+        && words.next().is_some_and(|w| w.starts_with(b"obj"));
+    Ok(!is_object)
+}
@@ -1,7 +1,170 @@
+use crate::archive::TempCopy;
 use crate::ecore::StreamReader as CoreStreamReader;
 use crate::errors::*;
 use crate::types::*;
 use std::io::Read;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// The reader behind a `CStreamReader` handle: the core stream plus any bytes
+/// taken from it by `extractous_stream_peek` that have not been read yet, and
+/// once a cursor is saved, the bytes read since then.
+pub(crate) struct StreamState {
+    /// The core stream, or `None` for the empty stream of a 0-byte input.
+    reader: Option<CoreStreamReader>,
+    peeked: Vec<u8>,
+    /// Offset of the first unread byte in `peeked`.
+    peeked_pos: usize,
+    /// Stream offset of the first byte in `peeked`.
+    peeked_start: u64,
+    /// Whether bytes are kept in `peeked` after they are read, so that a
+    /// saved cursor can be restored.
+    retain: bool,
+    /// Identifies the stream in the cursors saved from it.
+    id: u64,
+    /// The filtered copy of an archive the stream is parsed from, which the
+    /// parser reads as the stream is read.
+    _input: Option<TempCopy>,
+}
+
+/// A position saved by `extractous_stream_save_cursor`.
+struct StreamCursor {
+    stream: u64,
+    offset: u64,
+}
+
+impl StreamState {
+    /// Wraps a core stream in a new handle.
+    pub(crate) fn into_handle(reader: CoreStreamReader) -> *mut CStreamReader {
+        Self::new_handle(Some(reader), None)
+    }
+
+    /// Wraps a core stream parsed from `input` in a new handle, which keeps
+    /// the file until the stream is freed. `None` gives an empty stream.
+    pub(crate) fn into_file_handle(
+        reader: Option<CoreStreamReader>,
+        input: Option<TempCopy>,
+    ) -> *mut CStreamReader {
+        Self::new_handle(reader, input)
+    }
+
+    /// Creates a handle for a stream with no content.
+    pub(crate) fn empty_handle() -> *mut CStreamReader {
+        Self::new_handle(None, None)
+    }
+
+    fn new_handle(reader: Option<CoreStreamReader>, input: Option<TempCopy>) -> *mut CStreamReader {
+        static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+        let state = StreamState {
+            reader,
+            peeked: Vec::new(),
+            peeked_pos: 0,
+            peeked_start: 0,
+            retain: false,
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+            _input: input,
+        };
+        Box::into_raw(Box::new(state)) as *mut CStreamReader
+    }
+
+    /// Returns the stream offset of the next byte to read.
+    fn position(&self) -> u64 {
+        self.peeked_start + self.peeked_pos as u64
+    }
+
+    /// Drops the bytes of `peeked` that have been read, unless they are kept
+    /// for a cursor.
+    fn discard_read(&mut self) {
+        if self.retain || self.peeked_pos == 0 {
+            return;
+        }
+        self.peeked.drain(..self.peeked_pos);
+        self.peeked_start += self.peeked_pos as u64;
+        self.peeked_pos = 0;
+    }
+
+    fn read_inner(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        match self.reader.as_mut() {
+            Some(reader) => reader.read(buf),
+            None => Ok(0),
+        }
+    }
+
+    /// Makes at least `len` bytes available in the peek buffer, or as many as
+    /// remain before the end of the stream, and returns them.
+    fn fill_peeked(&mut self, len: usize) -> std::io::Result<&[u8]> {
+        self.discard_read();
+        let mut chunk = [0u8; 4096];
+        while self.peeked.len() - self.peeked_pos < len {
+            let want = (len - (self.peeked.len() - self.peeked_pos)).min(chunk.len());
+            match self.read_inner(&mut chunk[..want]) {
+                Ok(0) => break,
+                Ok(n) => self.peeked.extend_from_slice(&chunk[..n]),
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            }
+        }
+        let pending = &self.peeked[self.peeked_pos..];
+        Ok(&pending[..len.min(pending.len())])
+    }
+}
+
+impl Read for StreamState {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let pending = &self.peeked[self.peeked_pos..];
+        if pending.is_empty() {
+            let n = self.read_inner(buf)?;
+            if self.retain {
+                self.peeked.extend_from_slice(&buf[..n]);
+                self.peeked_pos += n;
+            } else {
+                self.peeked_start += n as u64;
+            }
+            return Ok(n);
+        }
+        let n = pending.len().min(buf.len());
+        buf[..n].copy_from_slice(&pending[..n]);
+        self.peeked_pos += n;
+        if self.peeked_pos == self.peeked.len() {
+            self.discard_read();
+        }
+        Ok(n)
+    }
+}
+
+/// Copies up to `buffer_size` upcoming bytes of the stream into `buffer`
+/// without consuming them, so the next reads return the same bytes again.
+///
+/// `*out_peeked` is set to the number of bytes copied, which is less than
+/// `buffer_size` only when the stream ends first. Peeked bytes are held in
+/// memory until read, so peeking far ahead costs as much memory as it reads.
+/// Peeking can be mixed freely with reads at any point in the stream.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_stream_peek(
+    handle: *mut CStreamReader,
+    buffer: *mut u8,
+    buffer_size: libc::size_t,
+    out_peeked: *mut libc::size_t,
+) -> libc::c_int {
+    if handle.is_null() || buffer.is_null() || out_peeked.is_null() {
+        return ERR_NULL_POINTER;
+    }
+    unsafe { *out_peeked = 0 };
+    if buffer_size == 0 {
+        return ERR_OK;
+    }
+
+    let state = unsafe { &mut *(handle as *mut StreamState) };
+    match state.fill_peeked(buffer_size) {
+        Ok(peeked) => {
+            unsafe {
+                std::ptr::copy_nonoverlapping(peeked.as_ptr(), buffer, peeked.len());
+                *out_peeked = peeked.len();
+            }
+            ERR_OK
+        }
+        Err(_) => ERR_IO_ERROR,
+    }
+}
 
 /// Reads data from a stream into a user-provided buffer.
 ///
@@ -24,7 +187,7 @@ pub unsafe extern "C" fn extractous_stream_read(
         return ERR_OK;
     }
 
-    let reader = unsafe { &mut *(handle as *mut CoreStreamReader) };
+    let reader = unsafe { &mut *(handle as *mut StreamState) };
     let buf_slice = unsafe { std::slice::from_raw_parts_mut(buffer, buffer_size) };
 
     match reader.read(buf_slice) {
@@ -58,7 +221,7 @@ pub unsafe extern "C" fn extractous_stream_read_exact(
 
     unsafe { *bytes_read = 0 };
 
-    let reader = unsafe { &mut *(handle as *mut CoreStreamReader) };
+    let reader = unsafe { &mut *(handle as *mut StreamState) };
     // slice representing the user-provided buffer
     let total_buf_slice = unsafe { std::slice::from_raw_parts_mut(buffer, buffer_size) };
 
@@ -103,7 +266,7 @@ pub unsafe extern "C" fn extractous_stream_read_all(
         return ERR_NULL_POINTER;
     }
 
-    let reader = unsafe { &mut *(handle as *mut CoreStreamReader) };
+    let reader = unsafe { &mut *(handle as *mut StreamState) };
     let mut data_vec = Vec::new();
 
     match reader.read_to_end(&mut data_vec) {
@@ -136,6 +299,65 @@ pub unsafe extern "C" fn extractous_buffer_free(buffer: *mut u8, size: libc::siz
 pub unsafe extern "C" fn extractous_stream_free(handle: *mut CStreamReader) {
     if !handle.is_null() {
         // Reconstruct the Box and let Rust's drop handler deallocate it.
-        let _ = unsafe { Box::from_raw(handle as *mut CoreStreamReader) };
+        let _ = unsafe { Box::from_raw(handle as *mut StreamState) };
+    }
+}
+
+/// Saves the current position of a stream, so that reading can go back to it
+/// later with `extractous_stream_restore_cursor`.
+///
+/// The core streams cannot seek, so once a cursor is saved, the stream keeps
+/// every byte read from the first saved cursor on in memory, until it is
+/// freed. The cursor records the byte offset. Returns NULL if `handle` is
+/// NULL. The cursor must be freed with `extractous_stream_cursor_free`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_stream_save_cursor(
+    handle: *mut CStreamReader,
+) -> *mut CStreamCursor {
+    if handle.is_null() {
+        return std::ptr::null_mut();
+    }
+    let state = unsafe { &mut *(handle as *mut StreamState) };
+    state.discard_read();
+    state.retain = true;
+    let cursor = StreamCursor {
+        stream: state.id,
+        offset: state.position(),
+    };
+    Box::into_raw(Box::new(cursor)) as *mut CStreamCursor
+}
+
+/// Moves a stream back, or forward, to a position saved with
+/// `extractous_stream_save_cursor`, so the following reads return the same
+/// bytes as they did from there.
+///
+/// Returns `ERR_NOT_SEEKABLE` if the cursor was saved from a different
+/// stream. The cursor stays valid and can be restored again.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_stream_restore_cursor(
+    handle: *mut CStreamReader,
+    cursor: *const CStreamCursor,
+) -> libc::c_int {
+    if handle.is_null() || cursor.is_null() {
+        return ERR_NULL_POINTER;
+    }
+    let state = unsafe { &mut *(handle as *mut StreamState) };
+    let cursor = unsafe { &*(cursor as *const StreamCursor) };
+    let end = state.peeked_start + state.peeked.len() as u64;
+    if cursor.stream != state.id || cursor.offset < state.peeked_start || cursor.offset > end {
+        return record_ffi_error(
+            ERR_NOT_SEEKABLE,
+            "the stream cannot return to a cursor saved from another stream",
+        );
+    }
+    state.peeked_pos = (cursor.offset - state.peeked_start) as usize;
+    ERR_OK
+}
+
+/// Frees a cursor returned by `extractous_stream_save_cursor`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_stream_cursor_free(cursor: *mut CStreamCursor) {
+    if !cursor.is_null() {
+        let _ = unsafe { Box::from_raw(cursor as *mut StreamCursor) };
     }
 }
@@ -0,0 +1,1379 @@
+//! Extraction APIs that expose the document structure from Tika's XHTML output.
+
+use crate::detect;
+use crate::ecore::{Extractor as CoreExtractor, PdfOcrStrategy, PdfParserConfig};
+use crate::errors::*;
+use crate::extractor::{
+    ExtractorState, FileInput, extractous_string_free, finish_content, finish_metadata,
+    strings_to_c,
+};
+use crate::lang;
+use crate::metadata::extractous_metadata_free;
+use crate::text;
+use crate::types::*;
+use crate::xhtml::{self, Event, Reader};
+use std::ffi::CString;
+use std::io::Read;
+use std::ops::RangeInclusive;
+use std::os::raw::{c_char, c_int, c_void};
+use std::path::Path;
+use std::ptr;
+
+/// Called for each opening tag with the element name and its attributes as
+/// parallel arrays of `attr_count` names and values.
+pub type CStartElementCallback = Option<
+    unsafe extern "C" fn(
+        ctx: *mut c_void,
+        name: *const c_char,
+        attr_names: *const *const c_char,
+        attr_values: *const *const c_char,
+        attr_count: libc::size_t,
+    ),
+>;
+
+/// Called for each closing tag with the element name.
+pub type CEndElementCallback = Option<unsafe extern "C" fn(ctx: *mut c_void, name: *const c_char)>;
+
+/// Called for each run of character data. `text` is null-terminated and
+/// `len` is its length in bytes.
+pub type CCharactersCallback =
+    Option<unsafe extern "C" fn(ctx: *mut c_void, text: *const c_char, len: libc::size_t)>;
+
+/// Called with each chunk of streamed output. Returning anything but `ERR_OK`
+/// stops the extraction.
+pub type CWriteCallback =
+    Option<unsafe extern "C" fn(ctx: *mut c_void, data: *const u8, len: libc::size_t) -> c_int>;
+
+/// Streamed XML is passed on once at least this many bytes are buffered.
+const XML_CHUNK_LEN: usize = 64 * 1024;
+
+/// Returns a copy of the handle's extractor configured for XML output.
+pub(crate) unsafe fn xml_extractor(handle: *const CExtractor) -> CoreExtractor {
+    let state = unsafe { &*(handle as *const ExtractorState) };
+    state.inner.clone().set_xml_output(true)
+}
+
+/// Extracts a local file and reports its XHTML structure through SAX-style
+/// callbacks, so callers get headings, paragraphs and tables without writing
+/// an XML parser.
+///
+/// The extraction runs in XML mode regardless of the handle's output format.
+/// Any callback may be NULL to skip those events. Strings passed to callbacks
+/// are only valid for the duration of the call.
+///
+/// Output metadata must be freed with `extractous_metadata_free`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_extractor_extract_file_sax(
+    handle: *mut CExtractor,
+    path: *const c_char,
+    start_elem_cb: CStartElementCallback,
+    end_elem_cb: CEndElementCallback,
+    chars_cb: CCharactersCallback,
+    ctx: *mut c_void,
+    out_metadata: *mut *mut CMetadata,
+) -> c_int {
+    if handle.is_null() || path.is_null() || out_metadata.is_null() {
+        return ERR_NULL_POINTER;
+    }
+    let input = match unsafe { FileInput::open(handle, path) } {
+        Ok(input) => input,
+        Err(code) => return code,
+    };
+
+    let extractor = unsafe { xml_extractor(handle) };
+    let (xml, metadata) = match input.extract_to_string(&extractor) {
+        Ok(result) => result,
+        Err(e) => return record_error(e),
+    };
+
+    for event in Reader::new(&xml) {
+        match event {
+            Event::Start { name, attrs } => {
+                let Some(cb) = start_elem_cb else { continue };
+                let c_name = c_string(name);
+                let names: Vec<CString> = attrs.iter().map(|(k, _)| c_string(k)).collect();
+                let values: Vec<CString> = attrs.iter().map(|(_, v)| c_string(v)).collect();
+                let name_ptrs: Vec<*const c_char> = names.iter().map(|s| s.as_ptr()).collect();
+                let value_ptrs: Vec<*const c_char> = values.iter().map(|s| s.as_ptr()).collect();
+                unsafe {
+                    cb(
+                        ctx,
+                        c_name.as_ptr(),
+                        name_ptrs.as_ptr(),
+                        value_ptrs.as_ptr(),
+                        attrs.len(),
+                    )
+                };
+            }
+            Event::End { name } => {
+                if let Some(cb) = end_elem_cb {
+                    unsafe { cb(ctx, c_string(name).as_ptr()) };
+                }
+            }
+            Event::Text(text) => {
+                if let Some(cb) = chars_cb {
+                    let len = text.len();
+                    let mut bytes = text.into_bytes();
+                    bytes.push(0);
+                    unsafe { cb(ctx, bytes.as_ptr() as *const c_char, len) };
+                }
+            }
+        }
+    }
+
+    unsafe { *out_metadata = finish_metadata(handle, metadata) };
+    ERR_OK
+}
+
+/// Extracts a local file in XML mode and passes the XML to `write_cb` in
+/// chunks as the parser produces it, so documents too large to hold as one
+/// string can be written out or parsed incrementally.
+///
+/// The extraction runs in XML mode regardless of the handle's output format,
+/// and the string post-processing options do not apply. Chunks end just
+/// after a `>` so that they break at element edges, except when a run of
+/// text is too long to buffer, when they end at a UTF-8 character boundary.
+/// Concatenated, the chunks form the complete document. If the callback
+/// returns anything but `ERR_OK`, extraction stops with `ERR_ABORTED`. An
+/// empty file gives no chunks and empty metadata.
+///
+/// Output metadata must be freed with `extractous_metadata_free`; it is NULL
+/// on failure.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_extractor_extract_file_xml_stream(
+    handle: *mut CExtractor,
+    path: *const c_char,
+    write_cb: CWriteCallback,
+    ctx: *mut c_void,
+    out_metadata: *mut *mut CMetadata,
+) -> c_int {
+    let Some(write_cb) = write_cb else {
+        return ERR_NULL_POINTER;
+    };
+    if handle.is_null() || path.is_null() || out_metadata.is_null() {
+        return ERR_NULL_POINTER;
+    }
+    unsafe { *out_metadata = ptr::null_mut() };
+    let input = match unsafe { FileInput::open(handle, path) } {
+        Ok(input) => input,
+        Err(code) => return code,
+    };
+
+    let extractor = unsafe { xml_extractor(handle) };
+    let (mut reader, metadata) = match input.extract_stream(&extractor) {
+        Ok((Some(reader), metadata)) => (reader, metadata),
+        Ok((None, metadata)) => {
+            unsafe { *out_metadata = finish_metadata(handle, metadata) };
+            return ERR_OK;
+        }
+        Err(e) => return record_error(e),
+    };
+    let write = |chunk: &[u8]| {
+        let rc = unsafe { write_cb(ctx, chunk.as_ptr(), chunk.len()) };
+        match rc {
+            ERR_OK => ERR_OK,
+            rc => record_ffi_error(ERR_ABORTED, format!("write callback returned {}", rc)),
+        }
+    };
+
+    let mut pending = Vec::with_capacity(2 * XML_CHUNK_LEN);
+    let mut buf = vec![0u8; XML_CHUNK_LEN];
+    loop {
+        let n = match reader.read(&mut buf) {
+            Ok(0) => break,
+            Ok(n) => n,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(e) => {
+                return record_ffi_error(
+                    ERR_IO_ERROR,
+                    format!("cannot read the XML of {}: {}", input.path, e),
+                );
+            }
+        };
+        pending.extend_from_slice(&buf[..n]);
+        if pending.len() < XML_CHUNK_LEN {
+            continue;
+        }
+        let end = match pending.iter().rposition(|&b| b == b'>') {
+            Some(i) => i + 1,
+            None => std::str::from_utf8(&pending).map_or_else(|e| e.valid_up_to(), |s| s.len()),
+        };
+        if end == 0 {
+            continue;
+        }
+        let rc = write(&pending[..end]);
+        if rc != ERR_OK {
+            return rc;
+        }
+        pending.drain(..end);
+    }
+    if !pending.is_empty() {
+        let rc = write(&pending);
+        if rc != ERR_OK {
+            return rc;
+        }
+    }
+
+    unsafe { *out_metadata = finish_metadata(handle, metadata) };
+    ERR_OK
+}
+
+/// Extracts only pages `start` to `end` (1-based, inclusive) of a local file
+/// into a string.
+///
+/// The core has no page selection, so the whole document is parsed in XML mode
+/// and the text of the `<div class="page">` elements Tika emits for paged
+/// formats such as PDF is kept for the selected pages. A range reaching past
+/// the last page is clamped to it, and `start` below 1 is treated as 1.
+/// Documents without page markup count as a single page. Returns
+/// `ERR_INVALID_CONFIG` if `end < start`.
+///
+/// Output strings must be freed with `extractous_string_free`.
+/// Output metadata must be freed with `extractous_metadata_free`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_extractor_extract_file_range(
+    handle: *mut CExtractor,
+    path: *const c_char,
+    start: c_int,
+    end: c_int,
+    out_content: *mut *mut c_char,
+    out_metadata: *mut *mut CMetadata,
+) -> c_int {
+    if handle.is_null() || path.is_null() || out_content.is_null() || out_metadata.is_null() {
+        return ERR_NULL_POINTER;
+    }
+    if end < start {
+        return ERR_INVALID_CONFIG;
+    }
+    let input = match unsafe { FileInput::open(handle, path) } {
+        Ok(input) => input,
+        Err(code) => return code,
+    };
+
+    let extractor = unsafe { xml_extractor(handle) };
+    let (xml, metadata) = match input.extract_to_string(&extractor) {
+        Ok(result) => result,
+        Err(e) => return record_error(e),
+    };
+    let first = start.max(1) as usize;
+    let last = end.max(0) as usize;
+    let content = unsafe { finish_content(handle, pages_text(&xml, first..=last)) };
+
+    unsafe {
+        *out_content = text::content_to_c(content);
+        *out_metadata = finish_metadata(handle, metadata);
+    }
+    ERR_OK
+}
+
+/// Collects the character data of the pages in `range` (1-based) from Tika's
+/// XHTML. A document with no page divs is treated as one page spanning the
+/// body.
+fn pages_text(xml: &str, range: RangeInclusive<usize>) -> String {
+    split_pages(xml)
+        .into_iter()
+        .enumerate()
+        .filter(|(i, _)| range.contains(&(i + 1)))
+        .map(|(_, page)| page)
+        .collect()
+}
+
+/// Splits the character data of Tika's XHTML into one string per
+/// `<div class="page">`. A document with no page divs is one page holding the
+/// text of the body.
+fn split_pages(xml: &str) -> Vec<String> {
+    let mut pages: Vec<String> = Vec::new();
+    let mut page_depth = 0;
+    let mut body_text = String::new();
+    let mut in_body = false;
+
+    for event in Reader::new(xml) {
+        match event {
+            Event::Start { name, attrs } => {
+                if page_depth > 0 {
+                    page_depth += 1;
+                } else if name == "div" && attrs.iter().any(|(k, v)| *k == "class" && v == "page") {
+                    pages.push(String::new());
+                    page_depth = 1;
+                } else if name == "body" {
+                    in_body = true;
+                }
+            }
+            Event::End { name } => {
+                if page_depth > 0 {
+                    page_depth -= 1;
+                } else if name == "body" {
+                    in_body = false;
+                }
+            }
+            Event::Text(t) => {
+                if page_depth > 0 {
+                    if let Some(page) = pages.last_mut() {
+                        page.push_str(&t);
+                    }
+                } else if in_body {
+                    body_text.push_str(&t);
+                }
+            }
+        }
+    }
+
+    if pages.is_empty() {
+        pages.push(body_text);
+    }
+    pages
+}
+
+/// Extracts a local file into one result per page, for page-level indexing
+/// and search highlighting.
+///
+/// The whole document is parsed in XML mode and split at the
+/// `<div class="page">` elements Tika emits for paged formats such as PDF.
+/// A document without page markup is a single page. The core reports
+/// metadata for the whole document only, so each page's metadata is a copy
+/// of the document's. `*out_count` is at least 1 on success. The array must
+/// be freed with `extractous_pages_free`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_extractor_extract_file_per_page(
+    handle: *mut CExtractor,
+    path: *const c_char,
+    out_pages: *mut *mut CPageResult,
+    out_count: *mut libc::size_t,
+) -> c_int {
+    if handle.is_null() || path.is_null() || out_pages.is_null() || out_count.is_null() {
+        return ERR_NULL_POINTER;
+    }
+    let input = match unsafe { FileInput::open(handle, path) } {
+        Ok(input) => input,
+        Err(code) => return code,
+    };
+
+    let extractor = unsafe { xml_extractor(handle) };
+    let (xml, metadata) = match input.extract_to_string(&extractor) {
+        Ok(result) => result,
+        Err(e) => return record_error(e),
+    };
+    let c_pages: Box<[CPageResult]> = split_pages(&xml)
+        .into_iter()
+        .enumerate()
+        .map(|(i, content)| CPageResult {
+            page: (i + 1) as c_int,
+            content: text::content_to_c(unsafe { finish_content(handle, content) }),
+            metadata: unsafe { finish_metadata(handle, metadata.clone()) },
+        })
+        .collect();
+
+    unsafe {
+        *out_count = c_pages.len();
+        *out_pages = Box::into_raw(c_pages) as *mut CPageResult;
+    }
+    ERR_OK
+}
+
+/// Frees an array of `count` pages returned by
+/// `extractous_extractor_extract_file_per_page`, along with their strings and
+/// metadata.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_pages_free(pages: *mut CPageResult, count: libc::size_t) {
+    if pages.is_null() {
+        return;
+    }
+    let pages = unsafe { Box::from_raw(ptr::slice_from_raw_parts_mut(pages, count)) };
+    for page in pages.iter() {
+        unsafe {
+            extractous_string_free(page.content);
+            extractous_metadata_free(page.metadata);
+        }
+    }
+}
+
+/// A page with fewer non-whitespace characters than this is taken to have no
+/// text layer.
+const TEXT_PAGE_MIN_CHARS: usize = 10;
+
+/// Classifies a local file as a digital document with a text layer, a scan
+/// without one, or a mix of both, so callers can choose an OCR strategy
+/// before extracting.
+///
+/// `*out_kind` is one of the `DOC_KIND_*` constants. Image files are
+/// `DOC_KIND_SCANNED` without being parsed. Other files are parsed in XML mode
+/// with PDF OCR turned off for this call, and each page of Tika's page markup
+/// with fewer than 10 non-whitespace characters counts as scanned: a document
+/// is `DOC_KIND_SCANNED` if every page is, `DOC_KIND_DIGITAL` if none is and
+/// `DOC_KIND_MIXED` otherwise. This is a heuristic: a blank page counts as
+/// scanned, and a scan with a hidden OCR text layer counts as digital.
+/// Documents without page markup are `DOC_KIND_DIGITAL` if they have any
+/// text and `DOC_KIND_UNKNOWN` otherwise, as are empty files.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_extractor_classify_file(
+    handle: *mut CExtractor,
+    path: *const c_char,
+    out_kind: *mut c_int,
+) -> c_int {
+    if handle.is_null() || path.is_null() || out_kind.is_null() {
+        return ERR_NULL_POINTER;
+    }
+    let input = match unsafe { FileInput::open(handle, path) } {
+        Ok(input) => input,
+        Err(code) => return code,
+    };
+    let head = match detect::read_head(input.path) {
+        Ok(head) => head,
+        Err(e) => return record_ffi_error(ERR_IO_ERROR, e.to_string()),
+    };
+    if head.is_empty() {
+        unsafe { *out_kind = DOC_KIND_UNKNOWN };
+        return ERR_OK;
+    }
+    if detect::sniff_mime(&head).starts_with("image/") {
+        unsafe { *out_kind = DOC_KIND_SCANNED };
+        return ERR_OK;
+    }
+
+    let extractor = unsafe { xml_extractor(handle) }
+        .set_pdf_config(PdfParserConfig::new().set_ocr_strategy(PdfOcrStrategy::NO_OCR));
+    let xml = match input.extract_to_string(&extractor) {
+        Ok((xml, _)) => xml,
+        Err(e) => return record_error(e),
+    };
+    unsafe { *out_kind = classify_pages(&xml) };
+    ERR_OK
+}
+
+/// Classifies a document from the text on each page of Tika's XHTML.
+fn classify_pages(xml: &str) -> c_int {
+    let mut page_chars: Vec<usize> = Vec::new();
+    let mut page_depth = 0;
+    let mut body_chars = 0;
+    let mut in_body = false;
+
+    for event in Reader::new(xml) {
+        match event {
+            Event::Start { name, attrs } => {
+                if page_depth > 0 {
+                    page_depth += 1;
+                } else if name == "div" && attrs.iter().any(|(k, v)| *k == "class" && v == "page") {
+                    page_chars.push(0);
+                    page_depth = 1;
+                } else if name == "body" {
+                    in_body = true;
+                }
+            }
+            Event::End { name } => {
+                if page_depth > 0 {
+                    page_depth -= 1;
+                } else if name == "body" {
+                    in_body = false;
+                }
+            }
+            Event::Text(t) => {
+                let chars = t.chars().filter(|c| !c.is_whitespace()).count();
+                if page_depth > 0 {
+                    if let Some(page) = page_chars.last_mut() {
+                        *page += chars;
+                    }
+                } else if in_body {
+                    body_chars += chars;
+                }
+            }
+        }
+    }
+
+    if page_chars.is_empty() {
+        return if body_chars > 0 {
+            DOC_KIND_DIGITAL
+        } else {
+            DOC_KIND_UNKNOWN
+        };
+    }
+    let scanned = page_chars
+        .iter()
+        .filter(|&&chars| chars < TEXT_PAGE_MIN_CHARS)
+        .count();
+    if scanned == 0 {
+        DOC_KIND_DIGITAL
+    } else if scanned == page_chars.len() {
+        DOC_KIND_SCANNED
+    } else {
+        DOC_KIND_MIXED
+    }
+}
+
+/// Extracts a local file into its body text and its footnote and endnote
+/// text, as two strings.
+///
+/// The whole document is parsed in XML mode and the text inside elements
+/// whose `class` marks a footnote or endnote, such as `footnote` or
+/// `endnotes`, goes to `*out_footnotes`; the rest of the body goes to
+/// `*out_body`. Only some of Tika's parsers mark notes this way. When a
+/// document has no such markup, all of its text is in `*out_body`, including
+/// any note text the parser wrote inline, and `*out_footnotes` is empty.
+///
+/// Output strings must be freed with `extractous_string_free`.
+/// Output metadata must be freed with `extractous_metadata_free`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_extractor_extract_file_structured_parts(
+    handle: *mut CExtractor,
+    path: *const c_char,
+    out_body: *mut *mut c_char,
+    out_footnotes: *mut *mut c_char,
+    out_metadata: *mut *mut CMetadata,
+) -> c_int {
+    if handle.is_null()
+        || path.is_null()
+        || out_body.is_null()
+        || out_footnotes.is_null()
+        || out_metadata.is_null()
+    {
+        return ERR_NULL_POINTER;
+    }
+    let input = match unsafe { FileInput::open(handle, path) } {
+        Ok(input) => input,
+        Err(code) => return code,
+    };
+
+    let extractor = unsafe { xml_extractor(handle) };
+    let (xml, metadata) = match input.extract_to_string(&extractor) {
+        Ok(result) => result,
+        Err(e) => return record_error(e),
+    };
+    let (body, notes) = split_notes(&xml);
+
+    unsafe {
+        *out_body = text::content_to_c(finish_content(handle, body));
+        *out_footnotes = text::content_to_c(finish_content(handle, notes));
+        *out_metadata = finish_metadata(handle, metadata);
+    }
+    ERR_OK
+}
+
+/// Extracts a local file once in XML mode and returns both the XHTML and a
+/// plain-text rendition of it, for callers that want the text for display
+/// and the markup for structure without extracting twice.
+///
+/// The text is the character data of the XHTML body, which matches what
+/// text mode returns apart from, at most, differences in whitespace. The
+/// string post-processing options apply to each output as for its format,
+/// whatever the handle's output format.
+///
+/// Output strings must be freed with `extractous_string_free`.
+/// Output metadata must be freed with `extractous_metadata_free`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_extractor_extract_file_dual(
+    handle: *mut CExtractor,
+    path: *const c_char,
+    out_text: *mut *mut c_char,
+    out_xhtml: *mut *mut c_char,
+    out_metadata: *mut *mut CMetadata,
+) -> c_int {
+    if handle.is_null()
+        || path.is_null()
+        || out_text.is_null()
+        || out_xhtml.is_null()
+        || out_metadata.is_null()
+    {
+        return ERR_NULL_POINTER;
+    }
+    let input = match unsafe { FileInput::open(handle, path) } {
+        Ok(input) => input,
+        Err(code) => return code,
+    };
+
+    let extractor = unsafe { xml_extractor(handle) };
+    let (xml, metadata) = match input.extract_to_string(&extractor) {
+        Ok(result) => result,
+        Err(e) => return record_error(e),
+    };
+    let state = unsafe { &*(handle as *const ExtractorState) };
+    let plain = state
+        .finish_content_as(xhtml::body_text(&xml), OUTPUT_TEXT)
+        .0;
+    let xml = state.finish_content_as(xml, OUTPUT_XML).0;
+
+    unsafe {
+        *out_text = text::content_to_c(plain);
+        *out_xhtml = text::content_to_c(xml);
+        *out_metadata = finish_metadata(handle, metadata);
+    }
+    ERR_OK
+}
+
+/// Returns true if a `class` attribute marks a footnote or endnote region.
+fn is_note_class(class: &str) -> bool {
+    class
+        .split_ascii_whitespace()
+        .any(|c| c.starts_with("footnote") || c.starts_with("endnote"))
+}
+
+/// Splits the body text of Tika's XHTML into the text outside and inside
+/// footnote and endnote regions.
+fn split_notes(xml: &str) -> (String, String) {
+    let mut body = String::new();
+    let mut notes = String::new();
+    let mut in_body = false;
+    let mut note_depth = 0;
+
+    for event in Reader::new(xml) {
+        match event {
+            Event::Start { name, attrs } => {
+                if note_depth > 0 {
+                    note_depth += 1;
+                } else if name == "body" {
+                    in_body = true;
+                } else if in_body && attrs.iter().any(|(k, v)| *k == "class" && is_note_class(v)) {
+                    note_depth = 1;
+                }
+            }
+            Event::End { name } => {
+                if note_depth > 0 {
+                    note_depth -= 1;
+                } else if name == "body" {
+                    in_body = false;
+                }
+            }
+            Event::Text(t) => {
+                if note_depth > 0 {
+                    notes.push_str(&t);
+                } else if in_body {
+                    body.push_str(&t);
+                }
+            }
+        }
+    }
+    (body, notes)
+}
+
+/// Lists the names of the embedded resources, such as email attachments and
+/// OLE objects, that the parser found in a local file.
+///
+/// Names come from the markers Tika leaves in its XHTML for each embedded
+/// document: the heading of a `package-entry` div or the `id` of an `embedded`
+/// div. Unnamed resources are skipped, and only resources the core parses
+/// recursively are seen. The array must be freed with
+/// `extractous_string_array_free`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_extractor_list_embedded_file(
+    handle: *mut CExtractor,
+    path: *const c_char,
+    out_names: *mut *mut *mut c_char,
+    out_count: *mut libc::size_t,
+) -> c_int {
+    if handle.is_null() || path.is_null() || out_names.is_null() || out_count.is_null() {
+        return ERR_NULL_POINTER;
+    }
+    let input = match unsafe { FileInput::open(handle, path) } {
+        Ok(input) => input,
+        Err(code) => return code,
+    };
+
+    let extractor = unsafe { xml_extractor(handle) };
+    let xml = match input.extract_to_string(&extractor) {
+        Ok((xml, _)) => xml,
+        Err(e) => return record_error(e),
+    };
+    unsafe { strings_to_c(embedded_names(&xml), out_names, out_count) };
+    ERR_OK
+}
+
+/// Collects embedded resource names from Tika's XHTML markers.
+fn embedded_names(xml: &str) -> Vec<String> {
+    let mut names = Vec::new();
+    // Set after a package-entry div opens, until its first child element.
+    let mut awaiting_heading = false;
+    let mut heading: Option<String> = None;
+
+    for event in Reader::new(xml) {
+        match event {
+            Event::Start { name, attrs } => {
+                let class = attrs.iter().find(|(k, _)| *k == "class").map(|(_, v)| v);
+                if heading.is_some() {
+                    continue;
+                }
+                if awaiting_heading && name == "h1" {
+                    heading = Some(String::new());
+                }
+                awaiting_heading = false;
+                match class.map(String::as_str) {
+                    Some("package-entry") if name == "div" => awaiting_heading = true,
+                    Some("embedded") if name == "div" => {
+                        if let Some((_, id)) = attrs.iter().find(|(k, _)| *k == "id") {
+                            names.push(id.clone());
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            Event::End { name: "h1" } => {
+                if let Some(h) = heading.take() {
+                    let h = h.trim();
+                    if !h.is_empty() {
+                        names.push(h.to_string());
+                    }
+                }
+            }
+            Event::End { .. } => {}
+            Event::Text(t) => {
+                if let Some(h) = heading.as_mut() {
+                    h.push_str(&t);
+                }
+            }
+        }
+    }
+    names
+}
+
+/// Extracts a local file into one segment per document: the file itself
+/// first, followed by each embedded resource, such as an archive entry or an
+/// email attachment, in document order.
+///
+/// Embedded resources are found from the markers Tika leaves in its XHTML, as
+/// for `extractous_extractor_list_embedded_file`, and the text inside a
+/// resource belongs to its segment only; text nested in a deeper resource goes
+/// to that resource. The first segment is named after the file and carries
+/// the extraction's metadata. The core reports metadata for the whole
+/// extraction only, so an embedded segment's metadata holds just its
+/// `resourceName` when the resource is named, and the `language` of its text
+/// when `extractous_extractor_set_segment_language_mut` is enabled.
+///
+/// `*out_count` is at least 1 on success. The array must be freed with
+/// `extractous_segments_free`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_extractor_extract_file_segmented(
+    handle: *mut CExtractor,
+    path: *const c_char,
+    out_segments: *mut *mut CSegment,
+    out_count: *mut libc::size_t,
+) -> c_int {
+    if handle.is_null() || path.is_null() || out_segments.is_null() || out_count.is_null() {
+        return ERR_NULL_POINTER;
+    }
+    let input = match unsafe { FileInput::open(handle, path) } {
+        Ok(input) => input,
+        Err(code) => return code,
+    };
+
+    let extractor = unsafe { xml_extractor(handle) };
+    let (xml, metadata) = match input.extract_to_string(&extractor) {
+        Ok(result) => result,
+        Err(e) => return record_error(e),
+    };
+    let mut segments = split_segments(&xml);
+    segments[0].name = Path::new(input.path)
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned());
+
+    let segment_language = unsafe { &*(handle as *const ExtractorState) }.segment_language;
+    let mut top_metadata = Some(metadata);
+    let c_segments: Box<[CSegment]> = segments
+        .into_iter()
+        .map(|segment| {
+            let mut metadata = top_metadata.take().unwrap_or_else(|| {
+                segment
+                    .name
+                    .iter()
+                    .map(|name| ("resourceName".to_string(), vec![name.clone()]))
+                    .collect()
+            });
+            let content = unsafe { finish_content(handle, segment.content) };
+            if segment_language && let Some((code, _)) = lang::detect_language(&content) {
+                metadata.insert("language".to_string(), vec![code.to_string()]);
+            }
+            CSegment {
+                name: segment
+                    .name
+                    .map_or(ptr::null_mut(), |name| c_string(&name).into_raw()),
+                content: text::content_to_c(content),
+                metadata: unsafe { finish_metadata(handle, metadata) },
+            }
+        })
+        .collect();
+
+    unsafe {
+        *out_count = c_segments.len();
+        *out_segments = Box::into_raw(c_segments) as *mut CSegment;
+    }
+    ERR_OK
+}
+
+/// Frees an array of `count` segments returned by
+/// `extractous_extractor_extract_file_segmented`, along with their strings and
+/// metadata.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_segments_free(segments: *mut CSegment, count: libc::size_t) {
+    if segments.is_null() {
+        return;
+    }
+    let segments = unsafe { Box::from_raw(ptr::slice_from_raw_parts_mut(segments, count)) };
+    for segment in segments.iter() {
+        unsafe {
+            extractous_string_free(segment.name);
+            extractous_string_free(segment.content);
+            extractous_metadata_free(segment.metadata);
+        }
+    }
+}
+
+/// A document's name and text, collected from Tika's XHTML.
+struct Segment {
+    name: Option<String>,
+    content: String,
+}
+
+/// Splits the body text of Tika's XHTML at its embedded resource markers.
+/// The first segment holds the text outside any embedded resource.
+fn split_segments(xml: &str) -> Vec<Segment> {
+    let mut segments = vec![Segment {
+        name: None,
+        content: String::new(),
+    }];
+    // Embedded resources being read, as their segment index and div depth.
+    let mut open: Vec<(usize, usize)> = Vec::new();
+    let mut div_depth = 0;
+    let mut in_body = false;
+    // Set after a package-entry div opens, until its first child element.
+    let mut awaiting_heading = false;
+    let mut heading: Option<String> = None;
+
+    for event in Reader::new(xml) {
+        match event {
+            Event::Start { name, attrs } => {
+                if awaiting_heading && name == "h1" {
+                    awaiting_heading = false;
+                    heading = Some(String::new());
+                    continue;
+                }
+                awaiting_heading = false;
+                match name {
+                    "body" => in_body = true,
+                    "div" => {
+                        div_depth += 1;
+                        let class = attrs.iter().find(|(k, _)| *k == "class").map(|(_, v)| v);
+                        let id = attrs.iter().find(|(k, _)| *k == "id").map(|(_, v)| v);
+                        let name = match class.map(String::as_str) {
+                            Some("package-entry") => {
+                                awaiting_heading = true;
+                                None
+                            }
+                            Some("embedded") => id.cloned(),
+                            _ => continue,
+                        };
+                        segments.push(Segment {
+                            name,
+                            content: String::new(),
+                        });
+                        open.push((segments.len() - 1, div_depth));
+                    }
+                    _ => {}
+                }
+            }
+            Event::End { name } => match name {
+                "h1" if heading.is_some() => {
+                    let h = heading.take().unwrap_or_default();
+                    let h = h.trim();
+                    if let Some(&(index, _)) = open.last() {
+                        segments[index].name = (!h.is_empty()).then(|| h.to_string());
+                    }
+                }
+                "div" => {
+                    if open.last().is_some_and(|&(_, depth)| depth == div_depth) {
+                        open.pop();
+                    }
+                    div_depth = div_depth.saturating_sub(1);
+                }
+                "body" => in_body = false,
+                _ => {}
+            },
+            Event::Text(t) => {
+                if let Some(h) = heading.as_mut() {
+                    h.push_str(&t);
+                } else if in_body {
+                    let index = open.last().map_or(0, |&(index, _)| index);
+                    segments[index].content.push_str(&t);
+                }
+            }
+        }
+    }
+    segments
+}
+
+/// Extracts the tables of a local file, such as the sheets of a spreadsheet,
+/// as grids of cell strings.
+///
+/// The tables are read from the `<table>` elements of Tika's XHTML, in
+/// document order; a table nested in a cell is kept as text of that cell. Each
+/// table is named after the closest `<h1>` heading before it, which for
+/// spreadsheets is the sheet name. Short rows are padded with empty cells to
+/// the width of the longest. A merged cell's text appears once, in its top-left
+/// position, and the other positions it covers are empty: spreadsheets are
+/// written that way by Tika, and `colspan` and `rowspan` are expanded the same
+/// way for other formats. Cell text is trimmed.
+///
+/// The array must be freed with `extractous_tables_free`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_extractor_extract_file_tables(
+    handle: *mut CExtractor,
+    path: *const c_char,
+    out_tables: *mut *mut CTable,
+    out_count: *mut libc::size_t,
+) -> c_int {
+    if handle.is_null() || path.is_null() || out_tables.is_null() || out_count.is_null() {
+        return ERR_NULL_POINTER;
+    }
+    let input = match unsafe { FileInput::open(handle, path) } {
+        Ok(input) => input,
+        Err(code) => return code,
+    };
+
+    let extractor = unsafe { xml_extractor(handle) };
+    let xml = match input.extract_to_string(&extractor) {
+        Ok((xml, _)) => xml,
+        Err(e) => return record_error(e),
+    };
+    let c_tables: Box<[CTable]> = read_tables(&xml)
+        .into_iter()
+        .map(|table| {
+            let columns = table.rows.iter().map(Vec::len).max().unwrap_or(0);
+            let cells: Box<[*mut c_char]> = table
+                .rows
+                .iter()
+                .flat_map(|row| (0..columns).map(move |c| row.get(c).map_or("", String::as_str)))
+                .map(|cell| c_string(cell).into_raw())
+                .collect();
+            CTable {
+                name: table
+                    .name
+                    .map_or(ptr::null_mut(), |name| c_string(&name).into_raw()),
+                cells: Box::into_raw(cells) as *mut *mut c_char,
+                rows: table.rows.len(),
+                columns,
+            }
+        })
+        .collect();
+
+    unsafe {
+        *out_count = c_tables.len();
+        *out_tables = Box::into_raw(c_tables) as *mut CTable;
+    }
+    ERR_OK
+}
+
+/// Frees an array of `count` tables returned by
+/// `extractous_extractor_extract_file_tables`, along with their strings.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_tables_free(tables: *mut CTable, count: libc::size_t) {
+    if tables.is_null() {
+        return;
+    }
+    let tables = unsafe { Box::from_raw(ptr::slice_from_raw_parts_mut(tables, count)) };
+    for table in tables.iter() {
+        unsafe {
+            extractous_string_free(table.name);
+            let cells = Box::from_raw(ptr::slice_from_raw_parts_mut(
+                table.cells,
+                table.rows * table.columns,
+            ));
+            for &cell in cells.iter() {
+                extractous_string_free(cell);
+            }
+        }
+    }
+}
+
+/// Extracts the outline of a local file, for navigation such as a table of
+/// contents.
+///
+/// The outline is read from Tika's XHTML. For a PDF it is the bookmark tree,
+/// which Tika writes as nested lists after the pages, with each bookmark's
+/// nesting depth as its level. Tika does not report where a bookmark points,
+/// so its page is the first page, no earlier than the previous bookmark's,
+/// whose text contains its title, or 0 if there is none. Other documents are
+/// outlined by their `<h1>` to `<h6>` headings, with the heading's rank as
+/// its level and its page only known for paged formats. Headings of embedded
+/// resources are not included. A document without bookmarks or headings has
+/// an empty outline: `*out_count` is 0.
+///
+/// The array must be freed with `extractous_outline_free`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_extractor_extract_file_outline(
+    handle: *mut CExtractor,
+    path: *const c_char,
+    out_entries: *mut *mut COutlineEntry,
+    out_count: *mut libc::size_t,
+) -> c_int {
+    if handle.is_null() || path.is_null() || out_entries.is_null() || out_count.is_null() {
+        return ERR_NULL_POINTER;
+    }
+    let input = match unsafe { FileInput::open(handle, path) } {
+        Ok(input) => input,
+        Err(code) => return code,
+    };
+
+    let extractor = unsafe { xml_extractor(handle) };
+    let xml = match input.extract_to_string(&extractor) {
+        Ok((xml, _)) => xml,
+        Err(e) => return record_error(e),
+    };
+    let c_entries: Box<[COutlineEntry]> = read_outline(&xml)
+        .into_iter()
+        .map(|entry| COutlineEntry {
+            title: c_string(&entry.title).into_raw(),
+            level: entry.level as c_int,
+            page: entry.page as c_int,
+        })
+        .collect();
+
+    unsafe {
+        *out_count = c_entries.len();
+        *out_entries = Box::into_raw(c_entries) as *mut COutlineEntry;
+    }
+    ERR_OK
+}
+
+/// Frees an array of `count` entries returned by
+/// `extractous_extractor_extract_file_outline`, along with their titles.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_outline_free(entries: *mut COutlineEntry, count: libc::size_t) {
+    if entries.is_null() {
+        return;
+    }
+    let entries = unsafe { Box::from_raw(ptr::slice_from_raw_parts_mut(entries, count)) };
+    for entry in entries.iter() {
+        unsafe { extractous_string_free(entry.title) };
+    }
+}
+
+/// Extracts the text runs of a local file with their bounding boxes, for
+/// layout-aware processing such as reading invoices.
+///
+/// Requires `extractous_extractor_set_include_coordinates_mut`, without which
+/// `ERR_INVALID_CONFIG` is returned. The core extractor reports text and its
+/// structure but not where it sits on the page, for PDFs and OCR alike, so
+/// this currently returns `ERR_UNSUPPORTED_FEATURE` for every document
+/// rather than runs without positions. `*out_runs` is then NULL and
+/// `*out_count` 0.
+///
+/// The array must be freed with `extractous_text_runs_free`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_extractor_extract_file_positioned(
+    handle: *mut CExtractor,
+    path: *const c_char,
+    out_runs: *mut *mut CTextRun,
+    out_count: *mut libc::size_t,
+) -> c_int {
+    if handle.is_null() || path.is_null() || out_runs.is_null() || out_count.is_null() {
+        return ERR_NULL_POINTER;
+    }
+    unsafe {
+        *out_runs = ptr::null_mut();
+        *out_count = 0;
+    }
+    if !unsafe { &*(handle as *const ExtractorState) }.include_coordinates {
+        return record_ffi_error(
+            ERR_INVALID_CONFIG,
+            "positioned extraction is disabled; enable it with \
+             extractous_extractor_set_include_coordinates_mut",
+        );
+    }
+    let input = match unsafe { FileInput::open(handle, path) } {
+        Ok(input) => input,
+        Err(code) => return code,
+    };
+    record_ffi_error(
+        ERR_UNSUPPORTED_FEATURE,
+        format!("no text coordinates are available for {}", input.path),
+    )
+}
+
+/// Frees an array of `count` text runs returned by
+/// `extractous_extractor_extract_file_positioned`, along with their strings.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn extractous_text_runs_free(runs: *mut CTextRun, count: libc::size_t) {
+    if runs.is_null() {
+        return;
+    }
+    let runs = unsafe { Box::from_raw(ptr::slice_from_raw_parts_mut(runs, count)) };
+    for run in runs.iter() {
+        unsafe { extractous_string_free(run.text) };
+    }
+}
+
+/// A table's heading and rows of cell text, collected from Tika's XHTML.
+struct Table {
+    name: Option<String>,
+    rows: Vec<Vec<String>>,
+}
+
+/// A table being read, with the state needed to place its cells.
+struct TableBuilder {
+    table: Table,
+    /// The row being read, if inside a `<tr>`.
+    row: Option<Vec<String>>,
+    /// The text, column span and row span of the cell being read.
+    cell: Option<(String, usize, usize)>,
+    /// How many rows below the current one each column stays covered by a
+    /// cell with a row span.
+    covered: Vec<usize>,
+}
+
+impl TableBuilder {
+    /// Pads the current row with empty cells up to `column`, where the next
+    /// cell starts, filling in positions covered from rows above.
+    fn skip_covered(row: &mut Vec<String>, covered: &mut [usize]) {
+        while covered.get(row.len()).is_some_and(|&rows| rows > 0) {
+            covered[row.len()] -= 1;
+            row.push(String::new());
+        }
+    }
+
+    fn end_cell(&mut self) {
+        let (Some(row), Some((text, colspan, rowspan))) = (self.row.as_mut(), self.cell.take())
+        else {
+            return;
+        };
+        Self::skip_covered(row, &mut self.covered);
+        let start = row.len();
+        row.push(text.trim().to_string());
+        row.extend(std::iter::repeat_n(String::new(), colspan - 1));
+        if self.covered.len() < row.len() {
+            self.covered.resize(row.len(), 0);
+        }
+        for rows in &mut self.covered[start..row.len()] {
+            *rows = rowspan - 1;
+        }
+    }
+
+    fn end_row(&mut self) {
+        self.end_cell();
+        if let Some(mut row) = self.row.take() {
+            // Positions after the last cell may still be covered from above.
+            while let Some(column) = (row.len()..self.covered.len()).find(|&c| self.covered[c] > 0)
+            {
+                row.resize(column, String::new());
+                Self::skip_covered(&mut row, &mut self.covered);
+            }
+            self.table.rows.push(row);
+        }
+    }
+}
+
+/// Collects the top-level tables in Tika's XHTML.
+fn read_tables(xml: &str) -> Vec<Table> {
+    let mut tables = Vec::new();
+    let mut builder: Option<TableBuilder> = None;
+    // Depth of tables nested inside the table being read.
+    let mut nested = 0;
+    let mut heading: Option<String> = None;
+    let mut last_heading: Option<String> = None;
+
+    for event in Reader::new(xml) {
+        match event {
+            Event::Start { name, attrs } => {
+                let Some(b) = builder.as_mut() else {
+                    match name {
+                        "h1" => heading = Some(String::new()),
+                        "table" => {
+                            builder = Some(TableBuilder {
+                                table: Table {
+                                    name: last_heading.take(),
+                                    rows: Vec::new(),
+                                },
+                                row: None,
+                                cell: None,
+                                covered: Vec::new(),
+                            });
+                        }
+                        _ => {}
+                    }
+                    continue;
+                };
+                match name {
+                    "table" => nested += 1,
+                    _ if nested > 0 => {}
+                    "tr" => {
+                        b.end_row();
+                        b.row = Some(Vec::new());
+                    }
+                    "td" | "th" => {
+                        b.end_cell();
+                        if b.row.is_none() {
+                            b.row = Some(Vec::new());
+                        }
+                        let span = |key: &str| {
+                            attrs
+                                .iter()
+                                .find(|(k, _)| *k == key)
+                                .and_then(|(_, v)| v.trim().parse::<usize>().ok())
+                                .unwrap_or(1)
+                                .clamp(1, 1000)
+                        };
+                        b.cell = Some((String::new(), span("colspan"), span("rowspan")));
+                    }
+                    _ => {}
+                }
+            }
+            Event::End { name } => {
+                if let Some(b) = builder.as_mut() {
+                    match name {
+                        "table" if nested > 0 => nested -= 1,
+                        "table" => {
+                            b.end_row();
+                            tables.extend(builder.take().map(|b| b.table));
+                        }
+                        _ if nested > 0 => {}
+                        "td" | "th" => b.end_cell(),
+                        "tr" => b.end_row(),
+                        _ => {}
+                    }
+                } else if name == "h1"
+                    && let Some(h) = heading.take()
+                {
+                    let h = h.trim();
+                    last_heading = (!h.is_empty()).then(|| h.to_string());
+                }
+            }
+            Event::Text(t) => {
+                if let Some((text, _, _)) = builder.as_mut().and_then(|b| b.cell.as_mut()) {
+                    text.push_str(&t);
+                } else if let Some(h) = heading.as_mut() {
+                    h.push_str(&t);
+                }
+            }
+        }
+    }
+    tables
+}
+
+/// An outline entry collected from Tika's XHTML.
+struct OutlineEntry {
+    title: String,
+    level: usize,
+    /// The 1-based page, or 0 if it is not known.
+    page: usize,
+}
+
+/// The kinds of `<div>` that matter to the outline.
+#[derive(PartialEq)]
+enum OutlineDiv {
+    Page,
+    Embedded,
+    Other,
+}
+
+/// Collects the outline of Tika's XHTML: the bookmark lists outside the
+/// page divs of a paged document, or if there are none the headings of the
+/// body outside embedded resources.
+fn read_outline(xml: &str) -> Vec<OutlineEntry> {
+    let mut bookmarks = Vec::new();
+    let mut headings = Vec::new();
+    let mut pages: Vec<String> = Vec::new();
+    let mut divs: Vec<OutlineDiv> = Vec::new();
+    let mut in_body = false;
+    // Depth of the bookmark lists being read.
+    let mut list_depth = 0;
+    // The entry being read, with the element that closes it.
+    let mut entry: Option<(OutlineEntry, &str)> = None;
+
+    for event in Reader::new(xml) {
+        match event {
+            Event::Start { name, attrs } => {
+                let in_page = divs.contains(&OutlineDiv::Page);
+                let in_embedded = divs.contains(&OutlineDiv::Embedded);
+                match name {
+                    "body" => in_body = true,
+                    "div" => {
+                        let class = attrs.iter().find(|(k, _)| *k == "class").map(|(_, v)| v);
+                        let div = match class.map(String::as_str) {
+                            Some("page") => {
+                                pages.push(String::new());
+                                OutlineDiv::Page
+                            }
+                            Some("embedded" | "package-entry") => OutlineDiv::Embedded,
+                            _ => OutlineDiv::Other,
+                        };
+                        divs.push(div);
+                    }
+                    "ul" if list_depth > 0
+                        || (in_body && !in_page && !in_embedded && !pages.is_empty()) =>
+                    {
+                        list_depth += 1;
+                    }
+                    "li" if list_depth > 0 => {
+                        entry = Some((
+                            OutlineEntry {
+                                title: String::new(),
+                                level: list_depth,
+                                page: 0,
+                            },
+                            name,
+                        ));
+                    }
+                    "h1" | "h2" | "h3" | "h4" | "h5" | "h6"
+                        if in_body && !in_embedded && list_depth == 0 =>
+                    {
+                        entry = Some((
+                            OutlineEntry {
+                                title: String::new(),
+                                level: usize::from(name.as_bytes()[1] - b'0'),
+                                page: if in_page { pages.len() } else { 0 },
+                            },
+                            name,
+                        ));
+                    }
+                    _ => {}
+                }
+            }
+            Event::End { name } => match name {
+                "body" => in_body = false,
+                "div" => {
+                    divs.pop();
+                }
+                "ul" if list_depth > 0 => list_depth -= 1,
+                _ => {
+                    let Some((mut e, _)) = entry.take_if(|(_, end)| *end == name) else {
+                        continue;
+                    };
+                    e.title = e.title.split_whitespace().collect::<Vec<_>>().join(" ");
+                    if e.title.is_empty() {
+                        continue;
+                    }
+                    if name == "li" {
+                        bookmarks.push(e);
+                    } else {
+                        headings.push(e);
+                    }
+                }
+            },
+            Event::Text(t) => {
+                if let Some((e, _)) = entry.as_mut() {
+                    e.title.push_str(&t);
+                }
+                if divs.contains(&OutlineDiv::Page)
+                    && let Some(page) = pages.last_mut()
+                {
+                    page.push_str(&t);
+                }
+            }
+        }
+    }
+
+    if bookmarks.is_empty() {
+        return headings;
+    }
+    let pages: Vec<String> = pages
+        .iter()
+        .map(|page| page.split_whitespace().collect::<Vec<_>>().join(" "))
+        .collect();
+    let mut from = 0;
+    for bookmark in &mut bookmarks {
+        if let Some(i) = (from..pages.len()).find(|&i| pages[i].contains(&bookmark.title)) {
+            bookmark.page = i + 1;
+            from = i;
+        }
+    }
+    bookmarks
+}
+
+/// Converts names and attribute values, dropping any interior NUL bytes.
+fn c_string(s: &str) -> CString {
+    CString::new(s.replace('\0', "")).unwrap_or_default()
+}
@@ -0,0 +1,307 @@
+//! Post-processing applied to extracted text before it is returned to callers.
+
+use crate::errors::*;
+use crate::types::*;
+use std::ffi::CString;
+use std::os::raw::{c_char, c_int};
+use std::ptr;
+use std::sync::atomic::{AtomicI32, Ordering};
+use unicode_bidi::{BidiClass, BidiInfo, Level, bidi_class};
+use unicode_normalization::UnicodeNormalization;
+
+/// How NUL characters in string output are handled, as a `NUL_POLICY_*` value.
+static NUL_BYTE_POLICY: AtomicI32 = AtomicI32::new(NUL_POLICY_ERROR);
+
+/// Sets how string output containing NUL characters, which cannot be passed
+/// to C as a null-terminated string, is handled. The setting is global and
+/// applies to later extractions.
+///
+/// - `NUL_POLICY_ERROR` (the default) returns NULL content, as earlier
+///   versions did.
+/// - `NUL_POLICY_REPLACE` replaces each NUL with U+FFFD.
+/// - `NUL_POLICY_TRUNCATE` cuts the content at the first NUL.
+///
+/// Returns `ERR_INVALID_ENUM` for an unknown policy.
+#[unsafe(no_mangle)]
+pub extern "C" fn extractous_set_nul_byte_policy(policy: c_int) -> c_int {
+    if !(NUL_POLICY_ERROR..=NUL_POLICY_TRUNCATE).contains(&policy) {
+        return ERR_INVALID_ENUM;
+    }
+    NUL_BYTE_POLICY.store(policy, Ordering::Relaxed);
+    ERR_OK
+}
+
+/// Hands extracted content to C as an owned string, applying the NUL policy.
+/// Returns NULL if the content contains a NUL under `NUL_POLICY_ERROR`.
+pub(crate) fn content_to_c(content: String) -> *mut c_char {
+    content_to_c_checked(content).0
+}
+
+/// Like `content_to_c`, also returning true if the content was cut at a NUL.
+pub(crate) fn content_to_c_checked(content: String) -> (*mut c_char, bool) {
+    let Some((content, cut)) = apply_nul_policy(content) else {
+        return (ptr::null_mut(), false);
+    };
+    let ptr = CString::new(content).map_or(ptr::null_mut(), |s| s.into_raw());
+    (ptr, cut)
+}
+
+/// Applies the NUL policy to content bound for C, also returning true if it
+/// was cut at a NUL. Returns `None` if the content contains a NUL under
+/// `NUL_POLICY_ERROR`.
+pub(crate) fn apply_nul_policy(mut content: String) -> Option<(String, bool)> {
+    let mut cut = false;
+    if let Some(nul) = content.find('\0') {
+        match NUL_BYTE_POLICY.load(Ordering::Relaxed) {
+            NUL_POLICY_REPLACE => content = content.replace('\0', "\u{FFFD}"),
+            NUL_POLICY_TRUNCATE => {
+                content.truncate(nul);
+                cut = true;
+            }
+            _ => return None,
+        }
+    }
+    Some((content, cut))
+}
+
+/// Returns true for the C0 control characters removed by sanitization:
+/// U+0000–U+0008, U+000B, U+000C and U+000E–U+001F. Tab, line feed and
+/// carriage return are kept.
+fn is_removed_control(c: char) -> bool {
+    c.is_ascii_control() && !matches!(c, '\t' | '\n' | '\r' | '\x7f')
+}
+
+/// Removes C0 control characters other than tab, line feed and carriage return.
+/// DEL and the C1 controls are left untouched.
+pub(crate) fn strip_control_chars(content: String) -> String {
+    if !content.chars().any(is_removed_control) {
+        return content;
+    }
+    content
+        .chars()
+        .filter(|&c| !is_removed_control(c))
+        .collect()
+}
+
+/// Collapses each run of whitespace to a single newline if it contains a line
+/// break, or to a single space otherwise, and trims both ends. Whitespace is
+/// any character with the Unicode `White_Space` property.
+pub(crate) fn collapse_whitespace(content: String) -> String {
+    let mut out = String::with_capacity(content.len());
+    let mut run: Option<char> = None;
+    for c in content.chars() {
+        if c.is_whitespace() {
+            let breaks = matches!(
+                c,
+                '\n' | '\r' | '\u{0B}' | '\u{0C}' | '\u{85}' | '\u{2028}' | '\u{2029}'
+            );
+            run = Some(if breaks || run == Some('\n') {
+                '\n'
+            } else {
+                ' '
+            });
+        } else {
+            if let Some(sep) = run.take()
+                && !out.is_empty()
+            {
+                out.push(sep);
+            }
+            out.push(c);
+        }
+    }
+    out
+}
+
+/// Shortens `content` to at most `max_bytes` bytes without splitting a UTF-8
+/// sequence, returning true if anything was cut.
+pub(crate) fn truncate_to_bytes(content: &mut String, max_bytes: usize) -> bool {
+    if content.len() <= max_bytes {
+        return false;
+    }
+    let mut end = max_bytes;
+    while !content.is_char_boundary(end) {
+        end -= 1;
+    }
+    content.truncate(end);
+    true
+}
+
+/// Returns at most `limit` characters of `content` starting at character
+/// `offset`, clamped to the end of the content.
+pub(crate) fn char_window(content: &str, offset: usize, limit: usize) -> String {
+    content.chars().skip(offset).take(limit).collect()
+}
+
+/// Converts `content` to one of the `NORM_*` Unicode normalization forms.
+/// `NORM_NONE` and unknown forms return it unchanged.
+pub(crate) fn normalize(content: String, form: libc::c_int) -> String {
+    match form {
+        NORM_NFC => content.nfc().collect(),
+        NORM_NFD => content.nfd().collect(),
+        NORM_NFKC => content.nfkc().collect(),
+        NORM_NFKD => content.nfkd().collect(),
+        _ => content,
+    }
+}
+
+/// Code point ranges that characters of string output are kept or removed by.
+pub(crate) struct CharFilter {
+    /// Inclusive ranges of code points.
+    pub(crate) ranges: Vec<(u32, u32)>,
+    /// Whether only characters in `ranges` are kept, rather than removed.
+    pub(crate) allow: bool,
+}
+
+impl CharFilter {
+    /// Removes the characters the filter does not let through.
+    pub(crate) fn apply(&self, content: String) -> String {
+        let keep = |c: char| {
+            let listed = self
+                .ranges
+                .iter()
+                .any(|&(start, end)| (start..=end).contains(&(c as u32)));
+            listed == self.allow
+        };
+        if content.chars().all(keep) {
+            return content;
+        }
+        content.chars().filter(|&c| keep(c)).collect()
+    }
+}
+
+/// Rewrites every `\r\n`, lone `\r` and lone `\n` to the line ending of a
+/// `LINE_ENDING_*` mode. `LINE_ENDING_PRESERVE` and unknown modes return the
+/// content unchanged.
+pub(crate) fn normalize_line_endings(content: String, mode: libc::c_int) -> String {
+    let ending = match mode {
+        LINE_ENDING_LF => "\n",
+        LINE_ENDING_CRLF => "\r\n",
+        _ => return content,
+    };
+    if !content.contains('\r') && (mode == LINE_ENDING_LF || !content.contains('\n')) {
+        return content;
+    }
+    let mut out = String::with_capacity(content.len());
+    let mut chars = content.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\r' => {
+                chars.next_if_eq(&'\n');
+                out.push_str(ending);
+            }
+            '\n' => out.push_str(ending),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// Reorders each line of `content` that holds right-to-left text with the
+/// Unicode Bidirectional Algorithm: from visual to logical order for
+/// `BIDI_LOGICAL`, and from logical to visual order for `BIDI_VISUAL`.
+/// `BIDI_PRESERVE` and unknown modes return the content unchanged.
+pub(crate) fn reorder_bidi(content: String, mode: c_int) -> String {
+    if !matches!(mode, BIDI_LOGICAL | BIDI_VISUAL) || !content.chars().any(is_rtl) {
+        return content;
+    }
+    let mut out = String::with_capacity(content.len());
+    for line in content.split_inclusive('\n') {
+        let text = line.trim_end_matches(['\r', '\n']);
+        if !text.chars().any(is_rtl) {
+            out.push_str(text);
+        } else if mode == BIDI_VISUAL {
+            out.push_str(&to_visual_order(text));
+        } else {
+            out.push_str(&to_logical_order(text));
+        }
+        out.push_str(&line[text.len()..]);
+    }
+    out
+}
+
+/// Reorders a line in logical order for display. Its direction is that of
+/// its first strong character, the first one read.
+fn to_visual_order(text: &str) -> String {
+    let info = BidiInfo::new(text, None);
+    info.paragraphs
+        .iter()
+        .map(|para| info.reorder_line(para, para.range.clone()))
+        .collect()
+}
+
+/// Reorders a line in visual order into the order it is read in, undoing
+/// `to_visual_order`. Its direction is that of its last strong character,
+/// which is the first one read in a right-to-left line. Display order
+/// reverses the runs at each level from the highest down, so the runs are
+/// reversed back from the lowest up.
+fn to_logical_order(text: &str) -> String {
+    let rtl = text
+        .chars()
+        .rev()
+        .map(bidi_class)
+        .find(|class| matches!(class, BidiClass::L | BidiClass::R | BidiClass::AL))
+        .is_some_and(|class| class != BidiClass::L);
+    let base = if rtl { Level::rtl() } else { Level::ltr() };
+    let info = BidiInfo::new(text, Some(base));
+    let levels = info.reordered_levels_per_char(&info.paragraphs[0], 0..text.len());
+    let mut chars: Vec<(char, u8)> = text
+        .chars()
+        .zip(levels.iter().map(|level| level.number()))
+        .collect();
+    let lowest_odd = chars.iter().map(|(_, level)| *level).min().unwrap_or(0) | 1;
+    let highest = chars.iter().map(|(_, level)| *level).max().unwrap_or(0);
+    for level in lowest_odd..=highest {
+        let mut start = 0;
+        while start < chars.len() {
+            let run = chars[start..]
+                .iter()
+                .take_while(|(_, l)| *l >= level)
+                .count();
+            chars[start..start + run].reverse();
+            start += run.max(1);
+        }
+    }
+    chars.into_iter().map(|(c, _)| c).collect()
+}
+
+/// Returns true for characters of right-to-left scripts such as Hebrew and
+/// Arabic.
+fn is_rtl(c: char) -> bool {
+    matches!(bidi_class(c), BidiClass::R | BidiClass::AL)
+}
+
+/// Returns true for punctuation that ends a sentence.
+fn is_sentence_end(c: char) -> bool {
+    matches!(
+        c,
+        '.' | '!' | '?' | '\u{2026}' | '\u{3002}' | '\u{FF01}' | '\u{FF1F}'
+    )
+}
+
+/// Counts sentences as runs of text closed by sentence-ending punctuation
+/// that is followed by whitespace or the end, allowing for closing quotes and
+/// brackets in between. Text after the last such run counts as one more
+/// sentence. Abbreviations such as "e.g." followed by a space end a sentence.
+pub(crate) fn sentence_count(content: &str) -> usize {
+    let mut count = 0;
+    let mut open = false;
+    let mut chars = content.chars().peekable();
+    while let Some(c) = chars.next() {
+        if is_sentence_end(c) {
+            while chars
+                .next_if(|&n| {
+                    is_sentence_end(n)
+                        || matches!(n, '"' | '\'' | ')' | ']' | '\u{201D}' | '\u{2019}')
+                })
+                .is_some()
+            {}
+            if open && chars.peek().is_none_or(|n| n.is_whitespace()) {
+                count += 1;
+                open = false;
+            }
+        } else if !c.is_whitespace() {
+            open = true;
+        }
+    }
+    count + open as usize
+}
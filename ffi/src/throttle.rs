@@ -0,0 +1,157 @@
+//! A process-wide limit on the number of extractions running at once.
+//!
+//! Each extraction holds a JVM thread and the parser's state until it
+//! returns, so callers running many of them in parallel, over one handle or
+//! many, can exhaust memory. Every extraction takes a slot here before it
+//! calls into the core library.
+
+use crate::errors::*;
+use std::cell::Cell;
+use std::os::raw::c_int;
+use std::sync::{Condvar, Mutex, MutexGuard};
+use std::time::Duration;
+
+struct Slots {
+    /// The most extractions allowed to run at once; 0 means no limit.
+    limit: usize,
+    running: usize,
+    /// Whether an extraction over the limit waits for a slot, rather than
+    /// failing with `ERR_BUSY`.
+    wait: bool,
+    /// Whether any extraction has taken a slot yet.
+    started: bool,
+}
+
+static SLOTS: Mutex<Slots> = Mutex::new(Slots {
+    limit: 0,
+    running: 0,
+    wait: true,
+    started: false,
+});
+static SLOT_FREED: Condvar = Condvar::new();
+
+thread_local! {
+    /// Whether this thread holds a slot, so that an extraction made while
+    /// running another, as when one API is built on another, does not wait
+    /// for itself.
+    static HOLDS_SLOT: Cell<bool> = const { Cell::new(false) };
+}
+
+fn slots() -> MutexGuard<'static, Slots> {
+    SLOTS.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// A slot taken by a running extraction, given back when dropped.
+pub(crate) struct Permit {
+    counted: bool,
+    /// Whether the permit was handed to another thread, which must not
+    /// clear its own `HOLDS_SLOT` when the permit is dropped there.
+    detached: bool,
+}
+
+impl Permit {
+    /// Hands the slot over to a worker thread, which holds it until it drops
+    /// the returned permit, even if this thread stops waiting for it first.
+    pub(crate) fn detach(mut self) -> Permit {
+        if self.counted {
+            HOLDS_SLOT.set(false);
+        }
+        let counted = std::mem::replace(&mut self.counted, false);
+        Permit {
+            counted,
+            detached: true,
+        }
+    }
+}
+
+impl Drop for Permit {
+    fn drop(&mut self) {
+        if !self.counted {
+            return;
+        }
+        if !self.detached {
+            HOLDS_SLOT.set(false);
+        }
+        slots().running -= 1;
+        SLOT_FREED.notify_one();
+    }
+}
+
+/// Takes a slot for an extraction, waiting for one to be freed while the
+/// limit is reached. Fails with `ERR_BUSY` instead of waiting when waiting
+/// is turned off.
+pub(crate) fn acquire() -> Result<Permit, c_int> {
+    acquire_while(|| Ok(None))
+}
+
+/// Like `acquire`, asking `keep_waiting` before each wait for a slot whether
+/// to go on. It returns the longest time to wait before it is asked again,
+/// `None` to wait for a slot however long it takes, or the code to fail with.
+pub(crate) fn acquire_while(
+    mut keep_waiting: impl FnMut() -> Result<Option<Duration>, c_int>,
+) -> Result<Permit, c_int> {
+    if HOLDS_SLOT.get() {
+        return Ok(Permit {
+            counted: false,
+            detached: false,
+        });
+    }
+    let mut slots = slots();
+    while slots.limit > 0 && slots.running >= slots.limit {
+        if !slots.wait {
+            return Err(record_ffi_error(
+                ERR_BUSY,
+                format!("{} extractions are already running", slots.running),
+            ));
+        }
+        slots = match keep_waiting()? {
+            Some(timeout) => {
+                SLOT_FREED
+                    .wait_timeout(slots, timeout)
+                    .unwrap_or_else(|e| e.into_inner())
+                    .0
+            }
+            None => SLOT_FREED.wait(slots).unwrap_or_else(|e| e.into_inner()),
+        };
+    }
+    slots.running += 1;
+    slots.started = true;
+    HOLDS_SLOT.set(true);
+    Ok(Permit {
+        counted: true,
+        detached: false,
+    })
+}
+
+/// Returns true once any extraction has started in this process.
+pub(crate) fn has_started() -> bool {
+    slots().started
+}
+
+/// Sets the most extractions that may run at once across all handles and
+/// threads. 0, the default, means no limit; negative values are ignored.
+///
+/// An extraction over the limit waits for another to finish, or fails with
+/// `ERR_BUSY` when `extractous_set_wait_for_extraction_slot` has turned
+/// waiting off. Lowering the limit does not interrupt extractions already
+/// running. For stream outputs the limit covers starting the extraction,
+/// not reading the stream. An extraction abandoned by its caller, after a
+/// deadline passed or a token was cancelled, keeps its slot until its worker
+/// finishes.
+#[unsafe(no_mangle)]
+pub extern "C" fn extractous_set_max_concurrent_extractions(limit: c_int) {
+    if limit < 0 {
+        return;
+    }
+    slots().limit = limit as usize;
+    SLOT_FREED.notify_all();
+}
+
+/// Sets whether an extraction over the limit set with
+/// `extractous_set_max_concurrent_extractions` waits for a slot, the
+/// default, or fails at once with `ERR_BUSY`.
+#[unsafe(no_mangle)]
+pub extern "C" fn extractous_set_wait_for_extraction_slot(wait: bool) {
+    slots().wait = wait;
+    SLOT_FREED.notify_all();
+}
@@ -9,6 +9,14 @@ pub struct CStreamReader {
     _private: [u8; 0],
 }
 #[repr(C)]
+pub struct CStreamCursor {
+    _private: [u8; 0],
+}
+#[repr(C)]
+pub struct CCancelToken {
+    _private: [u8; 0],
+}
+#[repr(C)]
 pub struct CPdfParserConfig {
     _private: [u8; 0],
 }
@@ -31,10 +39,177 @@ pub struct CMetadata {
     pub len: libc::size_t,
 }
 
+/// A borrowed, read-only segment of a scatter/gather input.
+#[repr(C)]
+pub struct CIoSlice {
+    /// Pointer to the first byte of the segment
+    pub ptr: *const u8,
+    /// The number of bytes in the segment
+    pub len: libc::size_t,
+}
+
+/// The content of one document within a segmented extraction.
+#[repr(C)]
+pub struct CSegment {
+    /// The document's file or resource name, or NULL if it has none
+    pub name: *mut c_char,
+    /// The document's text content
+    pub content: *mut c_char,
+    /// The document's metadata
+    pub metadata: *mut CMetadata,
+}
+
+/// The text of one page of a document.
+#[repr(C)]
+pub struct CPageResult {
+    /// The 1-based page number
+    pub page: c_int,
+    /// The page's text content
+    pub content: *mut c_char,
+    /// The document's metadata
+    pub metadata: *mut CMetadata,
+}
+
+/// An entry of a document's outline, such as a PDF bookmark or a heading.
+#[repr(C)]
+pub struct COutlineEntry {
+    /// The entry's title
+    pub title: *mut c_char,
+    /// The 1-based nesting depth of a bookmark, or the rank of a heading
+    pub level: c_int,
+    /// The 1-based page the entry is on, or 0 if it is not known
+    pub page: c_int,
+}
+
+/// A table found in a document, as a grid of cell strings.
+#[repr(C)]
+pub struct CTable {
+    /// The heading before the table, such as a spreadsheet's sheet name, or
+    /// NULL if it has none
+    pub name: *mut c_char,
+    /// Row-major array of `rows * columns` null-terminated cell strings
+    pub cells: *mut *mut c_char,
+    /// The number of rows
+    pub rows: libc::size_t,
+    /// The number of columns, the length of the longest row
+    pub columns: libc::size_t,
+}
+
+/// A run of text with its bounding box on the page, in points from the
+/// page's top-left corner.
+#[repr(C)]
+pub struct CTextRun {
+    /// The run's text
+    pub text: *mut c_char,
+    /// The 1-based page number
+    pub page: c_int,
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+/// An inclusive range of Unicode code points.
+#[repr(C)]
+pub struct CCharRange {
+    pub start: u32,
+    pub end: u32,
+}
+
+/// Statistics of extracted text.
+#[repr(C)]
+pub struct CContentAnalysis {
+    /// The number of runs of non-whitespace characters
+    pub word_count: libc::size_t,
+    /// The number of Unicode scalar values
+    pub char_count: libc::size_t,
+    /// The number of sentences, counting trailing text without final
+    /// punctuation as one
+    pub sentence_count: libc::size_t,
+    /// `word_count` divided by `READING_WORDS_PER_MINUTE`
+    pub reading_minutes: f64,
+}
+
+/// Properties of a PDF document.
+#[repr(C)]
+pub struct CPdfInfo {
+    /// The number of pages, or -1 if unknown
+    pub page_count: c_int,
+    /// The PDF version, such as `1.7`, or NULL if unknown
+    pub pdf_version: *mut c_char,
+    /// 1 if the document is encrypted, 0 otherwise
+    pub encrypted: c_int,
+    /// 1 if the document is linearized for fast web view, 0 otherwise
+    pub linearized: c_int,
+}
+
+/// An image embedded in a document and referenced from its extracted HTML.
+#[repr(C)]
+pub struct CImage {
+    /// The reference used in the HTML's `src` attributes, such as `cid:image0`
+    pub cid: *mut c_char,
+    /// The image's file name within the document
+    pub name: *mut c_char,
+    /// The image's MIME type
+    pub mime: *mut c_char,
+    /// The image bytes
+    pub data: *mut u8,
+    /// The number of bytes in `data`
+    pub len: libc::size_t,
+}
+
 pub const CHARSET_UTF_8: c_int = 0;
 pub const CHARSET_US_ASCII: c_int = 1;
 pub const CHARSET_UTF_16BE: c_int = 3;
 
+pub const OUTPUT_TEXT: c_int = 0;
+pub const OUTPUT_XML: c_int = 1;
+
+pub const HASH_SHA256: c_int = 0;
+pub const HASH_BLAKE3: c_int = 1;
+
+pub const NORM_NONE: c_int = 0;
+pub const NORM_NFC: c_int = 1;
+pub const NORM_NFD: c_int = 2;
+pub const NORM_NFKC: c_int = 3;
+pub const NORM_NFKD: c_int = 4;
+
+pub const LINE_ENDING_PRESERVE: c_int = 0;
+pub const LINE_ENDING_LF: c_int = 1;
+pub const LINE_ENDING_CRLF: c_int = 2;
+
+pub const BIDI_PRESERVE: c_int = 0;
+pub const BIDI_LOGICAL: c_int = 1;
+pub const BIDI_VISUAL: c_int = 2;
+
+pub const CHAR_FILTER_DENY: c_int = 0;
+pub const CHAR_FILTER_ALLOW: c_int = 1;
+
+pub const EMPTY_NONE: c_int = 0;
+pub const EMPTY_NO_TEXT_LAYER: c_int = 1;
+pub const EMPTY_UNSUPPORTED_BODY: c_int = 2;
+pub const EMPTY_OCR_DISABLED: c_int = 3;
+
+pub const DOC_KIND_UNKNOWN: c_int = 0;
+pub const DOC_KIND_DIGITAL: c_int = 1;
+pub const DOC_KIND_SCANNED: c_int = 2;
+pub const DOC_KIND_MIXED: c_int = 3;
+
+pub const TRUNC_NONE: c_int = 0;
+pub const TRUNC_MAX_CHARS: c_int = 1;
+pub const TRUNC_MAX_BYTES: c_int = 2;
+pub const TRUNC_NUL: c_int = 3;
+
+/// The reading speed behind `CContentAnalysis::reading_minutes`.
+pub const READING_WORDS_PER_MINUTE: c_int = 200;
+
+pub const NUL_POLICY_ERROR: c_int = 0;
+pub const NUL_POLICY_REPLACE: c_int = 1;
+pub const NUL_POLICY_TRUNCATE: c_int = 2;
+
+pub const LOG_INFO: c_int = 1;
+pub const LOG_WARN: c_int = 2;
+
 pub const PDF_OCR_STRATEGY_NO_OCR: c_int = 0;
 pub const PDF_OCR_STRATEGY_OCR_ONLY: c_int = 1;
 pub const PDF_OCR_STRATEGY_OCR_AND_TEXT_EXTRACTION: c_int = 2;
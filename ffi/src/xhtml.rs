@@ -0,0 +1,218 @@
+//! A minimal reader for the XHTML that Tika produces in XML output mode.
+//!
+//! It is not a general XML parser: it understands elements, attributes,
+//! character data, the predefined and numeric entities, and skips comments,
+//! processing instructions and doctype declarations. That is all Tika emits.
+
+/// A single event produced while reading an XHTML document.
+#[derive(Debug, PartialEq)]
+pub(crate) enum Event<'a> {
+    /// An opening tag. Self-closing tags produce a `Start` followed by an `End`.
+    Start {
+        name: &'a str,
+        attrs: Vec<(&'a str, String)>,
+    },
+    /// A closing tag.
+    End { name: &'a str },
+    /// Character data with entities decoded.
+    Text(String),
+}
+
+/// Iterates over the events of an XHTML document.
+pub(crate) struct Reader<'a> {
+    src: &'a str,
+    pos: usize,
+    pending_end: Option<&'a str>,
+}
+
+impl<'a> Reader<'a> {
+    pub(crate) fn new(src: &'a str) -> Self {
+        Self {
+            src,
+            pos: 0,
+            pending_end: None,
+        }
+    }
+
+    /// Skips past `terminator`, or to the end of input if it is missing.
+    fn skip_past(&mut self, terminator: &str) {
+        match self.src[self.pos..].find(terminator) {
+            Some(i) => self.pos += i + terminator.len(),
+            None => self.pos = self.src.len(),
+        }
+    }
+
+    fn read_tag(&mut self) -> Option<Event<'a>> {
+        let rest = &self.src[self.pos..];
+        let end = match rest.find('>') {
+            Some(i) => i,
+            None => {
+                self.pos = self.src.len();
+                return None;
+            }
+        };
+        let tag = &rest[1..end];
+        self.pos += end + 1;
+
+        if let Some(name) = tag.strip_prefix('/') {
+            return Some(Event::End { name: name.trim() });
+        }
+
+        let (tag, self_closing) = match tag.strip_suffix('/') {
+            Some(t) => (t, true),
+            None => (tag, false),
+        };
+        let name_end = tag
+            .find(|c: char| c.is_ascii_whitespace())
+            .unwrap_or(tag.len());
+        let name = &tag[..name_end];
+        let attrs = parse_attrs(&tag[name_end..]);
+        if self_closing {
+            self.pending_end = Some(name);
+        }
+        Some(Event::Start { name, attrs })
+    }
+}
+
+impl<'a> Iterator for Reader<'a> {
+    type Item = Event<'a>;
+
+    fn next(&mut self) -> Option<Event<'a>> {
+        if let Some(name) = self.pending_end.take() {
+            return Some(Event::End { name });
+        }
+        while self.pos < self.src.len() {
+            let rest = &self.src[self.pos..];
+            if rest.starts_with("<!--") {
+                self.skip_past("-->");
+            } else if let Some(data) = rest.strip_prefix("<![CDATA[") {
+                let len = data.find("]]>").unwrap_or(data.len());
+                let text = data[..len].to_string();
+                self.pos += "<![CDATA[".len() + len;
+                self.skip_past("]]>");
+                if !text.is_empty() {
+                    return Some(Event::Text(text));
+                }
+            } else if rest.starts_with("<?") {
+                self.skip_past("?>");
+            } else if rest.starts_with("<!") {
+                self.skip_past(">");
+            } else if rest.starts_with('<') {
+                if let Some(event) = self.read_tag() {
+                    return Some(event);
+                }
+            } else {
+                let len = rest.find('<').unwrap_or(rest.len());
+                self.pos += len;
+                return Some(Event::Text(decode_entities(&rest[..len])));
+            }
+        }
+        None
+    }
+}
+
+/// Returns the markup between the `<body>` start and end tags, or None if
+/// the document has no body element.
+pub(crate) fn body_inner(xml: &str) -> Option<&str> {
+    let mut from = 0;
+    let open = loop {
+        let i = from + xml[from..].find("<body")?;
+        match xml[i + 5..].chars().next()? {
+            '>' | '/' => break i,
+            c if c.is_ascii_whitespace() => break i,
+            _ => from = i + 5,
+        }
+    };
+    let tag_end = open + xml[open..].find('>')?;
+    if xml[..tag_end].ends_with('/') {
+        return Some("");
+    }
+    let start = tag_end + 1;
+    let end = xml.rfind("</body>")?;
+    (end >= start).then(|| &xml[start..end])
+}
+
+/// Returns the character data inside the `<body>` element, which is the
+/// document's text as Tika renders it in text mode: the line breaks Tika
+/// writes between block elements are character data too.
+pub(crate) fn body_text(xml: &str) -> String {
+    let mut text = String::new();
+    let mut in_body = false;
+    for event in Reader::new(xml) {
+        match event {
+            Event::Start { name: "body", .. } => in_body = true,
+            Event::End { name: "body" } => in_body = false,
+            Event::Text(t) if in_body => text.push_str(&t),
+            _ => {}
+        }
+    }
+    text
+}
+
+/// Parses `name="value"` pairs from the inside of a tag.
+fn parse_attrs(mut s: &str) -> Vec<(&str, String)> {
+    let mut attrs = Vec::new();
+    loop {
+        s = s.trim_start();
+        let eq = match s.find('=') {
+            Some(i) => i,
+            None => break,
+        };
+        let name = s[..eq].trim();
+        let after = s[eq + 1..].trim_start();
+        let quote = match after.chars().next() {
+            Some(q @ ('"' | '\'')) => q,
+            _ => break,
+        };
+        let value_end = match after[1..].find(quote) {
+            Some(i) => i + 1,
+            None => break,
+        };
+        attrs.push((name, decode_entities(&after[1..value_end])));
+        s = &after[value_end + 1..];
+    }
+    attrs
+}
+
+/// Decodes the predefined XML entities and numeric character references.
+/// Unknown entities are kept as written.
+pub(crate) fn decode_entities(s: &str) -> String {
+    if !s.contains('&') {
+        return s.to_string();
+    }
+    let mut out = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+        let decoded = rest.find(';').and_then(|semi| {
+            let entity = &rest[1..semi];
+            let ch = match entity {
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "amp" => Some('&'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                _ => entity
+                    .strip_prefix("#x")
+                    .or_else(|| entity.strip_prefix("#X"))
+                    .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                    .or_else(|| entity.strip_prefix('#').and_then(|d| d.parse().ok()))
+                    .and_then(char::from_u32),
+            };
+            ch.map(|c| (c, semi + 1))
+        });
+        match decoded {
+            Some((ch, len)) => {
+                out.push(ch);
+                rest = &rest[len..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
@@ -98,12 +98,30 @@ type metadataWrapper struct {
 	ptr *C.struct_CMetadata
 }
 
+// defaultMetadataValueSeparator is the FFI layer's default separator between
+// the values of a multi-valued metadata key.
+const defaultMetadataValueSeparator = "\x1f"
+
+// metadataValueSeparator returns the separator the FFI layer currently uses
+// between the values of a multi-valued metadata key, which C callers may have
+// changed with extractous_set_metadata_value_separator.
+//
+// Internal use only.
+func metadataValueSeparator() string {
+	cSep := C.extractous_get_metadata_value_separator()
+	if cSep == nil {
+		return defaultMetadataValueSeparator
+	}
+	defer C.extractous_string_free(cSep)
+	return C.GoString(cSep)
+}
+
 // newMetadata converts C metadata to Go and sets up cleanup.
 //
 // This function:
 //  1. Converts C metadata structure to Go map
 //  2. Sets up a finalizer to free C resources
-//  3. Splits comma-separated values into slices
+//  3. Splits multi-valued entries on the current value separator into slices
 //  4. Trims whitespace from values
 //
 // Returns an empty Metadata map if the C pointer is nil.
@@ -128,13 +146,15 @@ func newMetadata(cMeta *C.struct_CMetadata) Metadata {
 	// Convert C arrays to Go slices
 	keys := unsafe.Slice(cMeta.keys, cMeta.len)
 	values := unsafe.Slice(cMeta.values, cMeta.len)
+	separator := metadataValueSeparator()
 
 	for i := 0; i < int(cMeta.len); i++ {
 		key := C.GoString(keys[i])
 		value := C.GoString(values[i])
 
-		// Values are comma-separated in C, split them into individual values
-		valueSlice := strings.Split(value, ",")
+		// Multiple values are joined with the value separator in C,
+		// split them into individual values
+		valueSlice := strings.Split(value, separator)
 		// Trim whitespace from each value
 		for j := range valueSlice {
 			valueSlice[j] = strings.TrimSpace(valueSlice[j])
@@ -8,7 +8,18 @@
 #include <stdio.h>
 #include <stdlib.h>
 #include <string.h>
+#include <ctype.h>
 #include <assert.h>
+#include <time.h>
+#include <pthread.h>
+#include <stdatomic.h>
+#include <poll.h>
+#include <unistd.h>
+#include <sys/socket.h>
+#include <sys/stat.h>
+#include <sys/wait.h>
+#include <netinet/in.h>
+#include <arpa/inet.h>
 #include "../../include/extractous.h"
 
 // Test result tracking
@@ -67,6 +78,301 @@ static int tests_failed = 0;
         return; \
     }
 
+// ============================================================================
+// Helpers
+// ============================================================================
+
+// Builds a path for a scratch file used by a single test.
+static void temp_path(char *buf, size_t size, const char *name) {
+    snprintf(buf, size, "/tmp/extractous_ffi_test_%s", name);
+}
+
+// Writes `len` bytes of `data` to `path`, returning 0 on success.
+static int write_file(const char *path, const void *data, size_t len) {
+    FILE *f = fopen(path, "wb");
+    if (f == NULL) {
+        return -1;
+    }
+    size_t written = fwrite(data, 1, len, f);
+    fclose(f);
+    return written == len ? 0 : -1;
+}
+
+// A file stored in a zip archive built by `write_zip`.
+struct zip_entry {
+    const char *name;
+    const char *data;
+};
+
+static uint32_t crc32_of(const uint8_t *data, size_t len) {
+    uint32_t crc = 0xFFFFFFFFu;
+    for (size_t i = 0; i < len; i++) {
+        crc ^= data[i];
+        for (int bit = 0; bit < 8; bit++) {
+            crc = (crc >> 1) ^ (0xEDB88320u & (0u - (crc & 1u)));
+        }
+    }
+    return ~crc;
+}
+
+static void put_u16(FILE *f, uint16_t v) {
+    fputc(v & 0xFF, f);
+    fputc(v >> 8, f);
+}
+
+static void put_u32(FILE *f, uint32_t v) {
+    put_u16(f, v & 0xFFFF);
+    put_u16(f, v >> 16);
+}
+
+// Writes an uncompressed zip archive of `count` entries to `path`,
+// returning 0 on success. Enough to build OOXML fixtures at runtime.
+// `lens` gives the length of each entry's data, or is NULL when all entries
+// are null-terminated text.
+static int write_zip_sized(const char *path, const struct zip_entry *entries,
+                           const size_t *lens, size_t count) {
+    FILE *f = fopen(path, "wb");
+    if (f == NULL) {
+        return -1;
+    }
+    uint32_t offsets[32];
+    for (size_t i = 0; i < count && i < 32; i++) {
+        size_t name_len = strlen(entries[i].name);
+        size_t data_len = lens != NULL ? lens[i] : strlen(entries[i].data);
+        offsets[i] = (uint32_t)ftell(f);
+        put_u32(f, 0x04034b50);
+        put_u16(f, 20);
+        put_u16(f, 0);
+        put_u16(f, 0);
+        put_u32(f, 0);
+        put_u32(f, crc32_of((const uint8_t *)entries[i].data, data_len));
+        put_u32(f, (uint32_t)data_len);
+        put_u32(f, (uint32_t)data_len);
+        put_u16(f, (uint16_t)name_len);
+        put_u16(f, 0);
+        fwrite(entries[i].name, 1, name_len, f);
+        fwrite(entries[i].data, 1, data_len, f);
+    }
+    uint32_t central = (uint32_t)ftell(f);
+    for (size_t i = 0; i < count && i < 32; i++) {
+        size_t name_len = strlen(entries[i].name);
+        size_t data_len = lens != NULL ? lens[i] : strlen(entries[i].data);
+        put_u32(f, 0x02014b50);
+        put_u16(f, 20);
+        put_u16(f, 20);
+        put_u16(f, 0);
+        put_u16(f, 0);
+        put_u32(f, 0);
+        put_u32(f, crc32_of((const uint8_t *)entries[i].data, data_len));
+        put_u32(f, (uint32_t)data_len);
+        put_u32(f, (uint32_t)data_len);
+        put_u16(f, (uint16_t)name_len);
+        put_u16(f, 0);
+        put_u16(f, 0);
+        put_u16(f, 0);
+        put_u16(f, 0);
+        put_u32(f, 0);
+        put_u32(f, offsets[i]);
+        fwrite(entries[i].name, 1, name_len, f);
+    }
+    uint32_t central_len = (uint32_t)ftell(f) - central;
+    put_u32(f, 0x06054b50);
+    put_u16(f, 0);
+    put_u16(f, 0);
+    put_u16(f, (uint16_t)count);
+    put_u16(f, (uint16_t)count);
+    put_u32(f, central_len);
+    put_u32(f, central);
+    put_u16(f, 0);
+    return fclose(f) == 0 ? 0 : -1;
+}
+
+// Writes an uncompressed zip archive of `count` text entries to `path`.
+static int write_zip(const char *path, const struct zip_entry *entries, size_t count) {
+    return write_zip_sized(path, entries, NULL, count);
+}
+
+// Writes a PDF with one page per entry in `pages`, each showing that text in
+// Windows-1252, returning 0 on success. A NULL entry gives a page holding
+// only an image, like a scan without a text layer. Enough to build
+// multi-page fixtures at runtime.
+static int write_pdf(const char *path, const char *const *pages, int count) {
+    FILE *f = fopen(path, "wb");
+    if (f == NULL || count > 16) {
+        if (f != NULL) {
+            fclose(f);
+        }
+        return -1;
+    }
+    long offsets[3 + 2 * 16];
+    int objects = 3 + 2 * count;
+    fputs("%PDF-1.4\n", f);
+    offsets[0] = ftell(f);
+    fputs("1 0 obj << /Type /Catalog /Pages 2 0 R >> endobj\n", f);
+    offsets[1] = ftell(f);
+    fputs("2 0 obj << /Type /Pages /Kids [", f);
+    for (int i = 0; i < count; i++) {
+        fprintf(f, " %d 0 R", 4 + 2 * i);
+    }
+    fprintf(f, " ] /Count %d >> endobj\n", count);
+    offsets[2] = ftell(f);
+    fputs("3 0 obj << /Type /Font /Subtype /Type1 /BaseFont /Helvetica "
+          "/Encoding /WinAnsiEncoding >> endobj\n", f);
+    for (int i = 0; i < count; i++) {
+        char stream[256];
+        int len = pages[i] == NULL
+                      ? snprintf(stream, sizeof(stream),
+                                 "q 612 0 0 792 0 0 cm BI /W 2 /H 2 /CS /G /BPC 8 ID "
+                                 "\x80\x40\x40\x80 EI Q")
+                      : snprintf(stream, sizeof(stream), "BT /F1 12 Tf 72 720 Td (%s) Tj ET",
+                                 pages[i]);
+        offsets[3 + 2 * i] = ftell(f);
+        fprintf(f,
+                "%d 0 obj << /Type /Page /Parent 2 0 R /MediaBox [0 0 612 792] "
+                "/Resources << /Font << /F1 3 0 R >> >> /Contents %d 0 R >> endobj\n",
+                4 + 2 * i, 5 + 2 * i);
+        offsets[4 + 2 * i] = ftell(f);
+        fprintf(f, "%d 0 obj << /Length %d >> stream\n%s\nendstream endobj\n", 5 + 2 * i, len, stream);
+    }
+    long xref = ftell(f);
+    fprintf(f, "xref\n0 %d\n0000000000 65535 f \n", objects + 1);
+    for (int i = 0; i < objects; i++) {
+        fprintf(f, "%010ld 00000 n \n", offsets[i]);
+    }
+    fprintf(f, "trailer << /Size %d /Root 1 0 R >>\nstartxref\n%ld\n%%%%EOF\n", objects + 1, xref);
+    return fclose(f) == 0 ? 0 : -1;
+}
+
+// A 1x1 transparent PNG.
+static const char TINY_PNG[] =
+    "\x89PNG\r\n\x1a\n\0\0\0\rIHDR\0\0\0\x01\0\0\0\x01\x08\x06\0\0\0\x1f\x15\xc4\x89"
+    "\0\0\0\rIDATx\x9c" "c\0\x01\0\0\x05\0\x01\r\n-\xb4\0\0\0\0IEND\xae" "B`\x82";
+#define TINY_PNG_LEN (sizeof(TINY_PNG) - 1)
+
+// Writes a DOCX whose text "Has a picture" is followed by `TINY_PNG` as
+// word/media/image1.png, or by nothing when `with_image` is 0.
+static int write_image_docx(const char *path, int with_image) {
+    struct zip_entry docx[] = {
+        {"[Content_Types].xml",
+         "<?xml version=\"1.0\"?><Types xmlns=\"http://schemas.openxmlformats.org/package/2006/content-types\">"
+         "<Default Extension=\"rels\" ContentType=\"application/vnd.openxmlformats-package.relationships+xml\"/>"
+         "<Default Extension=\"xml\" ContentType=\"application/xml\"/>"
+         "<Default Extension=\"png\" ContentType=\"image/png\"/>"
+         "<Override PartName=\"/word/document.xml\" ContentType=\"application/vnd.openxmlformats-officedocument.wordprocessingml.document.main+xml\"/>"
+         "</Types>"},
+        {"_rels/.rels",
+         "<?xml version=\"1.0\"?><Relationships xmlns=\"http://schemas.openxmlformats.org/package/2006/relationships\">"
+         "<Relationship Id=\"rId1\" Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument\" Target=\"word/document.xml\"/>"
+         "</Relationships>"},
+        {"word/_rels/document.xml.rels",
+         "<?xml version=\"1.0\"?><Relationships xmlns=\"http://schemas.openxmlformats.org/package/2006/relationships\">"
+         "<Relationship Id=\"rId1\" Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/image\" Target=\"media/image1.png\"/>"
+         "</Relationships>"},
+        {"word/document.xml",
+         "<?xml version=\"1.0\"?><w:document xmlns:w=\"http://schemas.openxmlformats.org/wordprocessingml/2006/main\" "
+         "xmlns:r=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships\" "
+         "xmlns:wp=\"http://schemas.openxmlformats.org/drawingml/2006/wordprocessingDrawing\" "
+         "xmlns:a=\"http://schemas.openxmlformats.org/drawingml/2006/main\" "
+         "xmlns:pic=\"http://schemas.openxmlformats.org/drawingml/2006/picture\"><w:body>"
+         "<w:p><w:r><w:t>Has a picture</w:t></w:r></w:p>"
+         "<w:p><w:r><w:drawing><wp:inline><wp:extent cx=\"9525\" cy=\"9525\"/><wp:docPr id=\"1\" name=\"Picture 1\"/>"
+         "<a:graphic><a:graphicData uri=\"http://schemas.openxmlformats.org/drawingml/2006/picture\"><pic:pic>"
+         "<pic:nvPicPr><pic:cNvPr id=\"0\" name=\"image1.png\"/><pic:cNvPicPr/></pic:nvPicPr>"
+         "<pic:blipFill><a:blip r:embed=\"rId1\"/></pic:blipFill><pic:spPr/>"
+         "</pic:pic></a:graphicData></a:graphic></wp:inline></w:drawing></w:r></w:p>"
+         "</w:body></w:document>"},
+        {"word/media/image1.png", TINY_PNG},
+    };
+    if (!with_image) {
+        docx[2].data = "<?xml version=\"1.0\"?><Relationships "
+                       "xmlns=\"http://schemas.openxmlformats.org/package/2006/relationships\"/>";
+        docx[3].data = "<?xml version=\"1.0\"?><w:document "
+                       "xmlns:w=\"http://schemas.openxmlformats.org/wordprocessingml/2006/main\">"
+                       "<w:body><w:p><w:r><w:t>Has a picture</w:t></w:r></w:p></w:body></w:document>";
+        return write_zip(path, docx, 4);
+    }
+    size_t lens[5];
+    for (size_t i = 0; i < 4; i++) {
+        lens[i] = strlen(docx[i].data);
+    }
+    lens[4] = TINY_PNG_LEN;
+    return write_zip_sized(path, docx, lens, 5);
+}
+
+// Returns 1 if the last error's debug report contains `needle`.
+static int last_debug_contains(const char *needle) {
+    char *debug = extractous_error_get_last_debug();
+    if (debug == NULL) {
+        return 0;
+    }
+    int found = strstr(debug, needle) != NULL;
+    extractous_string_free(debug);
+    return found;
+}
+
+// Starts a one-shot HTTP server on localhost that answers with `response`.
+// Returns the child pid and stores the port in `port`.
+static pid_t start_http_server(const char *response, int *port) {
+    int fd = socket(AF_INET, SOCK_STREAM, 0);
+    struct sockaddr_in addr = {0};
+    addr.sin_family = AF_INET;
+    addr.sin_addr.s_addr = htonl(INADDR_LOOPBACK);
+    addr.sin_port = 0;
+    socklen_t addr_len = sizeof(addr);
+    if (fd < 0 || bind(fd, (struct sockaddr *)&addr, sizeof(addr)) != 0 || listen(fd, 1) != 0 ||
+        getsockname(fd, (struct sockaddr *)&addr, &addr_len) != 0) {
+        return -1;
+    }
+    *port = ntohs(addr.sin_port);
+
+    pid_t pid = fork();
+    if (pid == 0) {
+        int conn = accept(fd, NULL, NULL);
+        char request[1024];
+        if (read(conn, request, sizeof(request)) >= 0) {
+            if (write(conn, response, strlen(response)) < 0) {
+                _exit(1);
+            }
+        }
+        close(conn);
+        _exit(0);
+    }
+    close(fd);
+    return pid;
+}
+
+// Returns the value for `key` in `metadata`, or NULL if absent.
+static const char *metadata_get(const struct CMetadata *metadata, const char *key) {
+    for (size_t i = 0; i < metadata->len; i++) {
+        if (strcmp(metadata->keys[i], key) == 0) {
+            return metadata->values[i];
+        }
+    }
+    return NULL;
+}
+
+// Serializes `metadata` as "key=value\n" lines into `buf`.
+static void metadata_serialize(const struct CMetadata *metadata, char *buf, size_t size) {
+    size_t used = 0;
+    buf[0] = '\0';
+    for (size_t i = 0; i < metadata->len && used < size; i++) {
+        used += snprintf(buf + used, size - used, "%s=%s\n", metadata->keys[i], metadata->values[i]);
+    }
+}
+
+// Returns 1 if `value` is a non-empty string of decimal digits.
+static int is_numeric(const char *value) {
+    if (value == NULL || *value == '\0') {
+        return 0;
+    }
+    for (; *value; value++) {
+        if (*value < '0' || *value > '9') {
+            return 0;
+        }
+    }
+    return 1;
+}
+
 // ============================================================================
 // Test: Extractor Lifecycle
 // ============================================================================
@@ -90,6 +396,13 @@ TEST(extractor_double_free) {
     // but this test just verifies it doesn't crash the suite
 }
 
+TEST(tika_version) {
+    const char *version = extractous_tika_version();
+    ASSERT_NOT_NULL(version, "version");
+    ASSERT_TRUE(strlen(version) > 0, "non-empty version");
+    ASSERT_TRUE(version == extractous_tika_version(), "static string");
+}
+
 // ============================================================================
 // Test: Configuration Functions
 // ============================================================================
@@ -158,6 +471,32 @@ TEST(extractor_chained_configuration) {
     extractous_extractor_free(e4);
 }
 
+TEST(extractor_output_format_roundtrip) {
+    struct CExtractor *extractor = extractous_extractor_new();
+    ASSERT_NOT_NULL(extractor, "extractor");
+
+    ASSERT_EQ(OUTPUT_TEXT, extractous_extractor_get_output_format(extractor), "default format");
+
+    int formats[] = {OUTPUT_XML, OUTPUT_TEXT};
+    for (size_t i = 0; i < sizeof(formats) / sizeof(formats[0]); i++) {
+        extractous_extractor_set_output_format_mut(extractor, formats[i]);
+        ASSERT_EQ(formats[i], extractous_extractor_get_output_format(extractor), "format after set");
+        ASSERT_TRUE(extractous_extractor_get_xml_output(extractor) == (formats[i] == OUTPUT_XML),
+                    "xml shim matches format");
+    }
+
+    // Unknown formats leave the current one in place
+    extractous_extractor_set_output_format_mut(extractor, 999);
+    ASSERT_EQ(OUTPUT_TEXT, extractous_extractor_get_output_format(extractor), "format after invalid");
+
+    extractous_extractor_set_xml_output_mut(extractor, true);
+    ASSERT_EQ(OUTPUT_XML, extractous_extractor_get_output_format(extractor), "format after xml setter");
+
+    ASSERT_EQ(ERR_NULL_POINTER, extractous_extractor_get_output_format(NULL), "null handle");
+
+    extractous_extractor_free(extractor);
+}
+
 // ============================================================================
 // Test: PDF Configuration
 // ============================================================================
@@ -226,6 +565,125 @@ TEST(office_config_set_extract_macros) {
     extractous_office_config_free(c2);
 }
 
+TEST(office_config_exclude_headers_and_footers) {
+    struct zip_entry docx[] = {
+        {"[Content_Types].xml",
+         "<?xml version=\"1.0\"?><Types xmlns=\"http://schemas.openxmlformats.org/package/2006/content-types\">"
+         "<Default Extension=\"rels\" ContentType=\"application/vnd.openxmlformats-package.relationships+xml\"/>"
+         "<Default Extension=\"xml\" ContentType=\"application/xml\"/>"
+         "<Override PartName=\"/word/document.xml\" ContentType=\"application/vnd.openxmlformats-officedocument.wordprocessingml.document.main+xml\"/>"
+         "<Override PartName=\"/word/footer1.xml\" ContentType=\"application/vnd.openxmlformats-officedocument.wordprocessingml.footer+xml\"/>"
+         "</Types>"},
+        {"_rels/.rels",
+         "<?xml version=\"1.0\"?><Relationships xmlns=\"http://schemas.openxmlformats.org/package/2006/relationships\">"
+         "<Relationship Id=\"rId1\" Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument\" Target=\"word/document.xml\"/>"
+         "</Relationships>"},
+        {"word/_rels/document.xml.rels",
+         "<?xml version=\"1.0\"?><Relationships xmlns=\"http://schemas.openxmlformats.org/package/2006/relationships\">"
+         "<Relationship Id=\"rId1\" Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/footer\" Target=\"footer1.xml\"/>"
+         "</Relationships>"},
+        {"word/document.xml",
+         "<?xml version=\"1.0\"?><w:document xmlns:w=\"http://schemas.openxmlformats.org/wordprocessingml/2006/main\" "
+         "xmlns:r=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships\"><w:body>"
+         "<w:p><w:r><w:t>First page body</w:t></w:r></w:p>"
+         "<w:p><w:r><w:br w:type=\"page\"/><w:t>Second page body</w:t></w:r></w:p>"
+         "<w:sectPr><w:footerReference w:type=\"default\" r:id=\"rId1\"/></w:sectPr>"
+         "</w:body></w:document>"},
+        {"word/footer1.xml",
+         "<?xml version=\"1.0\"?><w:ftr xmlns:w=\"http://schemas.openxmlformats.org/wordprocessingml/2006/main\">"
+         "<w:p><w:r><w:t>Running footer text</w:t></w:r></w:p></w:ftr>"},
+    };
+    char path[256];
+    temp_path(path, sizeof(path), "footer.docx");
+    ASSERT_EQ(0, write_zip(path, docx, sizeof(docx) / sizeof(docx[0])), "write fixture");
+
+    struct CExtractor *extractor = extractous_extractor_new();
+    ASSERT_NOT_NULL(extractor, "extractor");
+    char *content = NULL;
+    struct CMetadata *metadata = NULL;
+
+    int result = extractous_extractor_extract_file_to_string(extractor, path, &content, &metadata);
+    ASSERT_EQ(ERR_OK, result, "default result");
+    ASSERT_TRUE(strstr(content, "Second page body") != NULL, "body present");
+    ASSERT_TRUE(strstr(content, "Running footer text") != NULL, "footer included by default");
+    extractous_string_free(content);
+    extractous_metadata_free(metadata);
+
+    struct COfficeParserConfig *config = extractous_office_config_new();
+    ASSERT_NOT_NULL(config, "office config");
+    extractous_office_config_set_include_headers_and_footers(config, false);
+    extractous_extractor_set_office_config_mut(extractor, config);
+    result = extractous_extractor_extract_file_to_string(extractor, path, &content, &metadata);
+    ASSERT_EQ(ERR_OK, result, "excluded result");
+    ASSERT_TRUE(strstr(content, "Second page body") != NULL, "body still present");
+    ASSERT_TRUE(strstr(content, "Running footer text") == NULL, "footer omitted");
+    extractous_string_free(content);
+    extractous_metadata_free(metadata);
+
+    extractous_office_config_free(config);
+    extractous_extractor_free(extractor);
+    remove(path);
+}
+
+TEST(extract_xlsx_sheet_names) {
+    const char *sheet_xml =
+        "<?xml version=\"1.0\"?><worksheet xmlns=\"http://schemas.openxmlformats.org/spreadsheetml/2006/main\">"
+        "<sheetData><row r=\"1\"><c r=\"A1\" t=\"inlineStr\"><is><t>%s</t></is></c></row></sheetData></worksheet>";
+    char sheet1[512];
+    char sheet2[512];
+    snprintf(sheet1, sizeof(sheet1), sheet_xml, "First quarter revenue");
+    snprintf(sheet2, sizeof(sheet2), sheet_xml, "Second quarter revenue");
+    struct zip_entry xlsx[] = {
+        {"[Content_Types].xml",
+         "<?xml version=\"1.0\"?><Types xmlns=\"http://schemas.openxmlformats.org/package/2006/content-types\">"
+         "<Default Extension=\"rels\" ContentType=\"application/vnd.openxmlformats-package.relationships+xml\"/>"
+         "<Default Extension=\"xml\" ContentType=\"application/xml\"/>"
+         "<Override PartName=\"/xl/workbook.xml\" ContentType=\"application/vnd.openxmlformats-officedocument.spreadsheetml.sheet.main+xml\"/>"
+         "<Override PartName=\"/xl/worksheets/sheet1.xml\" ContentType=\"application/vnd.openxmlformats-officedocument.spreadsheetml.worksheet+xml\"/>"
+         "<Override PartName=\"/xl/worksheets/sheet2.xml\" ContentType=\"application/vnd.openxmlformats-officedocument.spreadsheetml.worksheet+xml\"/>"
+         "</Types>"},
+        {"_rels/.rels",
+         "<?xml version=\"1.0\"?><Relationships xmlns=\"http://schemas.openxmlformats.org/package/2006/relationships\">"
+         "<Relationship Id=\"rId1\" Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument\" Target=\"xl/workbook.xml\"/>"
+         "</Relationships>"},
+        {"xl/workbook.xml",
+         "<?xml version=\"1.0\"?><workbook xmlns=\"http://schemas.openxmlformats.org/spreadsheetml/2006/main\" "
+         "xmlns:r=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships\"><sheets>"
+         "<sheet name=\"Q1\" sheetId=\"1\" r:id=\"rId1\"/><sheet name=\"Q2\" sheetId=\"2\" r:id=\"rId2\"/>"
+         "</sheets></workbook>"},
+        {"xl/_rels/workbook.xml.rels",
+         "<?xml version=\"1.0\"?><Relationships xmlns=\"http://schemas.openxmlformats.org/package/2006/relationships\">"
+         "<Relationship Id=\"rId1\" Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/worksheet\" Target=\"worksheets/sheet1.xml\"/>"
+         "<Relationship Id=\"rId2\" Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/worksheet\" Target=\"worksheets/sheet2.xml\"/>"
+         "</Relationships>"},
+        {"xl/worksheets/sheet1.xml", sheet1},
+        {"xl/worksheets/sheet2.xml", sheet2},
+    };
+    char path[256];
+    temp_path(path, sizeof(path), "sheets.xlsx");
+    ASSERT_EQ(0, write_zip(path, xlsx, sizeof(xlsx) / sizeof(xlsx[0])), "write fixture");
+
+    struct CExtractor *extractor = extractous_extractor_new();
+    ASSERT_NOT_NULL(extractor, "extractor");
+    char *content = NULL;
+    struct CMetadata *metadata = NULL;
+
+    // Tika heads each sheet with its name, so no option is needed to get them.
+    int result = extractous_extractor_extract_file_to_string(extractor, path, &content, &metadata);
+    ASSERT_EQ(ERR_OK, result, "result");
+    const char *q1 = strstr(content, "Q1");
+    const char *q2 = strstr(content, "Q2");
+    const char *first = strstr(content, "First quarter revenue");
+    const char *second = strstr(content, "Second quarter revenue");
+    ASSERT_TRUE(q1 != NULL && q2 != NULL, "sheet names present");
+    ASSERT_TRUE(first != NULL && second != NULL, "cells present");
+    ASSERT_TRUE(q1 < first && first < q2 && q2 < second, "each sheet follows its name");
+    extractous_extraction_result_free(content, metadata);
+
+    extractous_extractor_free(extractor);
+    remove(path);
+}
+
 // ============================================================================
 // Test: OCR Configuration
 // ============================================================================
@@ -246,6 +704,24 @@ TEST(ocr_config_set_language) {
     extractous_ocr_config_free(c2);
 }
 
+TEST(ocr_config_set_apply_rotation) {
+    struct CTesseractOcrConfig *config = extractous_ocr_config_new();
+    ASSERT_NOT_NULL(config, "ocr_config");
+
+    extractous_ocr_config_set_apply_rotation(config, true);
+    extractous_ocr_config_set_apply_rotation(config, false);
+    // Should not crash
+    extractous_ocr_config_set_apply_rotation(NULL, true);
+
+    struct CExtractor *extractor = extractous_extractor_new();
+    ASSERT_NOT_NULL(extractor, "extractor");
+    extractous_ocr_config_set_apply_rotation(config, true);
+    extractous_extractor_set_ocr_config_mut(extractor, config);
+
+    extractous_ocr_config_free(config);
+    extractous_extractor_free(extractor);
+}
+
 // ============================================================================
 // Test: Error Handling
 // ============================================================================
@@ -274,6 +750,15 @@ TEST(extract_with_null_extractor) {
     );
     
     ASSERT_EQ(ERR_NULL_POINTER, result, "error code");
+
+    struct CStreamReader *reader = NULL;
+    result = extractous_extractor_extract_file(NULL, "test.txt", &reader, &metadata);
+    ASSERT_EQ(ERR_NULL_POINTER, result, "stream error code");
+    int repaired = 0;
+    result = extractous_extractor_extract_file_to_string_repaired(
+        NULL, "test.txt", &content, &metadata, &repaired
+    );
+    ASSERT_EQ(ERR_NULL_POINTER, result, "repaired error code");
 }
 
 TEST(extract_with_null_path) {
@@ -303,6 +788,40 @@ TEST(extract_with_null_output) {
     extractous_extractor_free(extractor);
 }
 
+TEST(error_message_static) {
+    const int codes[] = {ERR_OK, ERR_NULL_POINTER, ERR_IO_ERROR, ERR_METADATA_ABSENT, 12345};
+    for (size_t i = 0; i < sizeof(codes) / sizeof(codes[0]); i++) {
+        const char *first = extractous_error_message_static(codes[i]);
+        const char *second = extractous_error_message_static(codes[i]);
+        ASSERT_NOT_NULL(first, "static message");
+        ASSERT_TRUE(first == second, "pointer is stable");
+        char *allocated = extractous_error_message(codes[i]);
+        ASSERT_NOT_NULL(allocated, "allocated message");
+        ASSERT_TRUE(strcmp(first, allocated) == 0, "matches allocating version");
+        extractous_string_free(allocated);
+    }
+}
+
+TEST(error_get_last_category) {
+    extractous_error_clear_last();
+    ASSERT_TRUE(strcmp("none", extractous_error_get_last_category()) == 0, "no stored error");
+    ASSERT_TRUE(strcmp("io_error", extractous_error_category(ERR_IO_ERROR)) == 0, "category by code");
+
+    struct CExtractor *extractor = extractous_extractor_new();
+    ASSERT_NOT_NULL(extractor, "extractor");
+    char *content = NULL;
+    struct CMetadata *metadata = NULL;
+    int result = extractous_extractor_extract_url_to_string(
+        extractor, "http://extractous-test.invalid/doc.txt", &content, &metadata
+    );
+    ASSERT_EQ(ERR_IO_ERROR, result, "I/O error code");
+    ASSERT_TRUE(strcmp("io_error", extractous_error_get_last_category()) == 0, "stored category");
+
+    extractous_error_clear_last();
+    ASSERT_TRUE(strcmp("none", extractous_error_get_last_category()) == 0, "cleared");
+    extractous_extractor_free(extractor);
+}
+
 // ============================================================================
 // Test: String Memory Management
 // ============================================================================
@@ -321,6 +840,225 @@ TEST(metadata_free_null) {
     extractous_metadata_free(NULL);
 }
 
+TEST(metadata_value_separator) {
+    const char *text = "multi-valued metadata\n";
+    struct CExtractor *extractor = extractous_extractor_new();
+    ASSERT_NOT_NULL(extractor, "extractor");
+    char *content = NULL;
+    struct CMetadata *metadata = NULL;
+
+    char *separator = extractous_get_metadata_value_separator();
+    ASSERT_NOT_NULL(separator, "default separator");
+    ASSERT_TRUE(strcmp(separator, "\x1f") == 0, "unit separator by default");
+    extractous_string_free(separator);
+
+    ASSERT_EQ(ERR_OK, extractous_set_metadata_value_separator(" | "), "set custom separator");
+    separator = extractous_get_metadata_value_separator();
+    ASSERT_NOT_NULL(separator, "custom separator");
+    ASSERT_TRUE(strcmp(separator, " | ") == 0, "custom separator reported");
+    extractous_string_free(separator);
+    int result = extractous_extractor_extract_bytes_to_string(
+        extractor, (const uint8_t *)text, strlen(text), &content, &metadata
+    );
+    ASSERT_EQ(ERR_OK, result, "custom separator result");
+    const char *parsers = metadata_get(metadata, "X-TIKA:Parsed-By");
+    ASSERT_NOT_NULL(parsers, "X-TIKA:Parsed-By");
+    ASSERT_TRUE(strstr(parsers, " | ") != NULL, "values joined with custom separator");
+    extractous_string_free(content);
+    extractous_metadata_free(metadata);
+
+    // Restore the default so other tests see the documented behaviour
+    ASSERT_EQ(ERR_OK, extractous_set_metadata_value_separator("\x1f"), "restore default");
+    result = extractous_extractor_extract_bytes_to_string(
+        extractor, (const uint8_t *)text, strlen(text), &content, &metadata
+    );
+    ASSERT_EQ(ERR_OK, result, "default separator result");
+    parsers = metadata_get(metadata, "X-TIKA:Parsed-By");
+    ASSERT_NOT_NULL(parsers, "X-TIKA:Parsed-By");
+    ASSERT_TRUE(strchr(parsers, '\x1f') != NULL, "values joined with unit separator");
+    extractous_string_free(content);
+    extractous_metadata_free(metadata);
+
+    ASSERT_EQ(ERR_NULL_POINTER, extractous_set_metadata_value_separator(NULL), "null separator");
+    extractous_extractor_free(extractor);
+}
+
+TEST(metadata_get_all_values) {
+    const char *html = "<html><head><title>Authors</title>"
+                       "<meta name=\"author\" content=\"Ada Lovelace\">"
+                       "<meta name=\"author\" content=\"Charles Babbage\">"
+                       "</head><body>Co-authored document</body></html>";
+    char path[256];
+    temp_path(path, sizeof(path), "authors.html");
+    ASSERT_EQ(0, write_file(path, html, strlen(html)), "write fixture");
+
+    struct CExtractor *extractor = extractous_extractor_new();
+    ASSERT_NOT_NULL(extractor, "extractor");
+    char *content = NULL;
+    struct CMetadata *metadata = NULL;
+    int result = extractous_extractor_extract_file_to_string(extractor, path, &content, &metadata);
+    ASSERT_EQ(ERR_OK, result, "result");
+
+    char **values = NULL;
+    size_t count = 0;
+    result = extractous_metadata_get_all_values(metadata, "author", &values, &count);
+    ASSERT_EQ(ERR_OK, result, "author lookup");
+    ASSERT_EQ(2, (int)count, "two authors");
+    ASSERT_TRUE(strcmp(values[0], "Ada Lovelace") == 0, "first author");
+    ASSERT_TRUE(strcmp(values[1], "Charles Babbage") == 0, "second author");
+    extractous_string_array_free(values, count);
+
+    values = NULL;
+    count = 1;
+    result = extractous_metadata_get_all_values(metadata, "no-such-key", &values, &count);
+    ASSERT_EQ(ERR_OK, result, "absent key");
+    ASSERT_EQ(0, (int)count, "absent key count");
+    ASSERT_TRUE(values == NULL, "absent key array");
+
+    extractous_string_free(content);
+    extractous_metadata_free(metadata);
+    extractous_extractor_free(extractor);
+    remove(path);
+}
+
+TEST(metadata_clone) {
+    const char *text = "Clone metadata document.\n";
+    struct CExtractor *extractor = extractous_extractor_new();
+    ASSERT_NOT_NULL(extractor, "extractor");
+    char *content = NULL;
+    struct CMetadata *metadata = NULL;
+
+    int result = extractous_extractor_extract_bytes_to_string(
+        extractor, (const uint8_t *)text, strlen(text), &content, &metadata
+    );
+    ASSERT_EQ(ERR_OK, result, "result");
+    ASSERT_TRUE(metadata->len > 0, "metadata has entries");
+
+    // Serialize before freeing so the clone can be compared afterwards.
+    char expected[4096];
+    metadata_serialize(metadata, expected, sizeof(expected));
+    struct CMetadata *clone = extractous_metadata_clone(metadata);
+    ASSERT_NOT_NULL(clone, "clone");
+    ASSERT_TRUE(clone->keys != metadata->keys, "keys array is separate");
+    extractous_extraction_result_free(content, metadata);
+
+    char actual[4096];
+    metadata_serialize(clone, actual, sizeof(actual));
+    ASSERT_TRUE(strcmp(expected, actual) == 0, "clone intact after original is freed");
+    extractous_metadata_free(clone);
+
+    struct CMetadata empty = {NULL, NULL, 0};
+    struct CMetadata *empty_clone = extractous_metadata_clone(&empty);
+    ASSERT_NOT_NULL(empty_clone, "empty clone");
+    ASSERT_EQ(0, (int)empty_clone->len, "empty clone length");
+    extractous_metadata_free(empty_clone);
+    ASSERT_NULL(extractous_metadata_clone(NULL), "null clone");
+
+    extractous_extractor_free(extractor);
+}
+
+TEST(metadata_get_date_epoch) {
+    struct {
+        const char *value;
+        int64_t epoch;
+    } cases[] = {
+        {"2023-05-01T12:34:56Z", 1682944496},
+        {"2023-05-01T12:34:56.123Z", 1682944496},
+        {"2023-05-01T14:34:56+02:00", 1682944496},
+        {"2023-05-01T12:34:56", 1682944496},
+        {"2023-05-01", 1682899200},
+    };
+    for (size_t i = 0; i < sizeof(cases) / sizeof(cases[0]); i++) {
+        char *keys[] = {"dcterms:created", "Last-Modified"};
+        char *values[] = {(char *)cases[i].value, (char *)cases[i].value};
+        struct CMetadata metadata = {keys, values, 2};
+        int64_t created = 0;
+        int64_t modified = 0;
+        ASSERT_EQ(ERR_OK, extractous_metadata_get_created_epoch(&metadata, &created), cases[i].value);
+        ASSERT_TRUE(created == cases[i].epoch, cases[i].value);
+        ASSERT_EQ(ERR_OK, extractous_metadata_get_modified_epoch(&metadata, &modified), cases[i].value);
+        ASSERT_TRUE(modified == cases[i].epoch, cases[i].value);
+    }
+
+    char *keys[] = {"dcterms:created"};
+    char *values[] = {"sometime last spring"};
+    struct CMetadata unparseable = {keys, values, 1};
+    struct CMetadata empty = {NULL, NULL, 0};
+    int64_t epoch = 0;
+    ASSERT_EQ(ERR_METADATA_ABSENT, extractous_metadata_get_created_epoch(&unparseable, &epoch), "unparseable date");
+    ASSERT_EQ(ERR_METADATA_ABSENT, extractous_metadata_get_modified_epoch(&unparseable, &epoch), "missing date");
+    ASSERT_EQ(ERR_METADATA_ABSENT, extractous_metadata_get_created_epoch(&empty, &epoch), "empty metadata");
+    ASSERT_EQ(ERR_NULL_POINTER, extractous_metadata_get_created_epoch(NULL, &epoch), "null metadata");
+}
+
+TEST(metadata_get_page_count) {
+    const char *pages[] = {"Page one", "Page two", "Page three"};
+    char pdf_path[256], png_path[256];
+    temp_path(pdf_path, sizeof(pdf_path), "pages.pdf");
+    temp_path(png_path, sizeof(png_path), "single.png");
+    ASSERT_EQ(0, write_pdf(pdf_path, pages, 3), "write pdf fixture");
+    // A 1x1 grayscale PNG
+    static const uint8_t png[] =
+        "\x89PNG\r\n\x1a\n\x00\x00\x00\x0dIHDR\x00\x00\x00\x01\x00\x00\x00\x01\x08\x00\x00\x00\x00"
+        "\x3a\x7e\x9b\x55\x00\x00\x00\x0aIDAT\x78\x9c\x63\x60\x00\x00\x00\x02\x00\x01\x48\xaf\xa4\x71"
+        "\x00\x00\x00\x00IEND\xae\x42\x60\x82";
+    ASSERT_EQ(0, write_file(png_path, png, sizeof(png) - 1), "write png fixture");
+
+    struct CExtractor *extractor = extractous_extractor_new();
+    ASSERT_NOT_NULL(extractor, "extractor");
+    char *content = NULL;
+    struct CMetadata *metadata = NULL;
+    int count = -1;
+
+    int result = extractous_extractor_extract_file_to_string(extractor, pdf_path, &content, &metadata);
+    ASSERT_EQ(ERR_OK, result, "pdf result");
+    ASSERT_EQ(ERR_OK, extractous_metadata_get_page_count(metadata, &count), "pdf page count present");
+    ASSERT_EQ(3, count, "pdf page count");
+    extractous_string_free(content);
+    extractous_metadata_free(metadata);
+
+    result = extractous_extractor_extract_file_to_string(extractor, png_path, &content, &metadata);
+    ASSERT_EQ(ERR_OK, result, "png result");
+    ASSERT_EQ(ERR_METADATA_ABSENT, extractous_metadata_get_page_count(metadata, &count), "image has no page count");
+    extractous_string_free(content);
+    extractous_metadata_free(metadata);
+
+    ASSERT_EQ(ERR_NULL_POINTER, extractous_metadata_get_page_count(NULL, &count), "null metadata");
+    extractous_extractor_free(extractor);
+    remove(pdf_path);
+    remove(png_path);
+}
+
+TEST(metadata_get_parsed_by) {
+    const char *pages[] = {"Parsed by test"};
+    char path[256];
+    temp_path(path, sizeof(path), "parsed_by.pdf");
+    ASSERT_EQ(0, write_pdf(path, pages, 1), "write fixture");
+
+    struct CExtractor *extractor = extractous_extractor_new();
+    ASSERT_NOT_NULL(extractor, "extractor");
+    char *content = NULL;
+    struct CMetadata *metadata = NULL;
+    int result = extractous_extractor_extract_file_to_string(extractor, path, &content, &metadata);
+    ASSERT_EQ(ERR_OK, result, "result");
+
+    char **parsers = NULL;
+    size_t count = 0;
+    result = extractous_metadata_get_parsed_by(metadata, &parsers, &count);
+    ASSERT_EQ(ERR_OK, result, "parsed-by present");
+    int found_pdf = 0;
+    for (size_t i = 0; i < count; i++) {
+        found_pdf |= strcmp(parsers[i], "org.apache.tika.parser.pdf.PDFParser") == 0;
+    }
+    ASSERT_TRUE(found_pdf, "PDF parser in chain");
+    extractous_string_array_free(parsers, count);
+
+    extractous_string_free(content);
+    extractous_metadata_free(metadata);
+    extractous_extractor_free(extractor);
+    remove(path);
+}
+
 // ============================================================================
 // Test: URL Extraction Functions (if they exist)
 // ============================================================================
@@ -347,62 +1085,4457 @@ TEST(url_extraction_null_checks) {
     extractous_extractor_free(extractor);
 }
 
+TEST(url_extraction_failure_reasons) {
+    struct CExtractor *extractor = extractous_extractor_new();
+    ASSERT_NOT_NULL(extractor, "extractor");
+    char *content = NULL;
+    struct CMetadata *metadata = NULL;
+
+    int result = extractous_extractor_extract_url_to_string(
+        extractor, "http://extractous-test.invalid/doc.txt", &content, &metadata
+    );
+    ASSERT_EQ(ERR_IO_ERROR, result, "unresolvable host error code");
+    ASSERT_TRUE(last_debug_contains("dns_failure"), "unresolvable host reason");
+
+    int port = 0;
+    pid_t server = start_http_server(
+        "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n", &port
+    );
+    ASSERT_TRUE(server > 0, "start mock server");
+    char url[64];
+    snprintf(url, sizeof(url), "http://127.0.0.1:%d/missing.txt", port);
+    result = extractous_extractor_extract_url_to_string(extractor, url, &content, &metadata);
+    waitpid(server, NULL, 0);
+    ASSERT_EQ(ERR_IO_ERROR, result, "404 error code");
+    ASSERT_TRUE(last_debug_contains("http_404"), "404 reason");
+
+    server = start_http_server(
+        "HTTP/1.1 500 Internal Server Error\r\nContent-Length: 0\r\nConnection: close\r\n\r\n", &port
+    );
+    ASSERT_TRUE(server > 0, "start mock server");
+    snprintf(url, sizeof(url), "http://127.0.0.1:%d/page404.txt", port);
+    result = extractous_extractor_extract_url_to_string(extractor, url, &content, &metadata);
+    waitpid(server, NULL, 0);
+    ASSERT_TRUE(result != ERR_OK, "500 fails");
+    ASSERT_TRUE(!last_debug_contains("http_404"), "404 in the URL is not a 404");
+
+    // A port nothing listens on: bound to learn a free port, then released.
+    int fd = socket(AF_INET, SOCK_STREAM, 0);
+    struct sockaddr_in addr = {0};
+    addr.sin_family = AF_INET;
+    addr.sin_addr.s_addr = htonl(INADDR_LOOPBACK);
+    socklen_t addr_len = sizeof(addr);
+    ASSERT_TRUE(fd >= 0 && bind(fd, (struct sockaddr *)&addr, sizeof(addr)) == 0 &&
+                    getsockname(fd, (struct sockaddr *)&addr, &addr_len) == 0,
+                "reserve port");
+    close(fd);
+    snprintf(url, sizeof(url), "http://127.0.0.1:%d/doc.txt", ntohs(addr.sin_port));
+    result = extractous_extractor_extract_url_to_string(extractor, url, &content, &metadata);
+    ASSERT_EQ(ERR_IO_ERROR, result, "refused connection error code");
+    ASSERT_TRUE(last_debug_contains("connection_refused"), "refused connection reason");
+
+    extractous_extractor_free(extractor);
+}
+
 // ============================================================================
-// Test Runner
+// Test: File Extraction
 // ============================================================================
 
-void run_all_tests() {
-    printf("\n");
-    printf("========================================\n");
-    printf("  FFI Layer Tests for Extractous\n");
-    printf("========================================\n\n");
-    
-    // Lifecycle tests
-    printf(COLOR_YELLOW "--- Extractor Lifecycle ---\n" COLOR_RESET);
-    run_test_extractor_new();
-    run_test_extractor_free_null();
-    run_test_extractor_double_free();
-    
-    // Configuration tests
-    printf(COLOR_YELLOW "\n--- Configuration Functions ---\n" COLOR_RESET);
-    run_test_extractor_set_max_length();
-    run_test_extractor_set_encoding();
-    run_test_extractor_set_invalid_encoding();
-    run_test_extractor_set_xml_output();
-    run_test_extractor_chained_configuration();
-    
-    // PDF config tests
-    printf(COLOR_YELLOW "\n--- PDF Configuration ---\n" COLOR_RESET);
-    run_test_pdf_config_new();
-    run_test_pdf_config_set_ocr_strategy();
-    run_test_pdf_config_set_extract_inline_images();
-    run_test_extractor_set_pdf_config();
-    
-    // Office config tests
-    printf(COLOR_YELLOW "\n--- Office Configuration ---\n" COLOR_RESET);
-    run_test_office_config_new();
-    run_test_office_config_set_extract_macros();
-    
-    // OCR config tests
-    printf(COLOR_YELLOW "\n--- OCR Configuration ---\n" COLOR_RESET);
-    run_test_ocr_config_new();
-    run_test_ocr_config_set_language();
-    
-    // Error handling tests
-    printf(COLOR_YELLOW "\n--- Error Handling ---\n" COLOR_RESET);
-    run_test_error_message();
-    run_test_extract_with_null_extractor();
-    run_test_extract_with_null_path();
-    run_test_extract_with_null_output();
-    
-    // Memory management tests
-    printf(COLOR_YELLOW "\n--- Memory Management ---\n" COLOR_RESET);
-    run_test_string_free_null();
-    run_test_metadata_free_null();
-    
-    // URL extraction tests
-    printf(COLOR_YELLOW "\n--- URL Extraction ---\n" COLOR_RESET);
-    run_test_url_extraction_null_checks();
+TEST(extract_file_to_string_hashed) {
+    const char *text = "Hash this extracted text.\n";
+    char path[256], content_path[256];
+    temp_path(path, sizeof(path), "hashed.txt");
+    temp_path(content_path, sizeof(content_path), "hashed_content.txt");
+    ASSERT_EQ(0, write_file(path, text, strlen(text)), "write fixture");
+
+    struct CExtractor *extractor = extractous_extractor_new();
+    ASSERT_NOT_NULL(extractor, "extractor");
+    char *content = NULL;
+    char *hash = NULL;
+    struct CMetadata *metadata = NULL;
+
+    int result = extractous_extractor_extract_file_to_string_hashed(
+        extractor, path, HASH_SHA256, &content, &hash, &metadata
+    );
+    ASSERT_EQ(ERR_OK, result, "sha256 result");
+    ASSERT_NOT_NULL(hash, "hash");
+    ASSERT_EQ(64, (int)strlen(hash), "sha256 hex length");
+
+    // Compare against sha256sum over the returned content
+    ASSERT_EQ(0, write_file(content_path, content, strlen(content)), "write content");
+    char command[320], expected[65] = {0};
+    snprintf(command, sizeof(command), "sha256sum %s", content_path);
+    FILE *pipe = popen(command, "r");
+    ASSERT_NOT_NULL(pipe, "run sha256sum");
+    ASSERT_TRUE(fread(expected, 1, 64, pipe) == 64, "read sha256sum output");
+    pclose(pipe);
+    ASSERT_TRUE(strcmp(expected, hash) == 0, "matches sha256sum");
+    extractous_string_free(content);
+    extractous_string_free(hash);
+    extractous_metadata_free(metadata);
+
+    result = extractous_extractor_extract_file_to_string_hashed(
+        extractor, path, HASH_BLAKE3, &content, &hash, &metadata
+    );
+    ASSERT_EQ(ERR_OK, result, "blake3 result");
+    ASSERT_TRUE(strcmp(expected, hash) != 0, "blake3 differs from sha256");
+    extractous_string_free(content);
+    extractous_string_free(hash);
+    extractous_metadata_free(metadata);
+
+    result = extractous_extractor_extract_file_to_string_hashed(
+        extractor, path, 99, &content, &hash, &metadata
+    );
+    ASSERT_EQ(ERR_INVALID_CONFIG, result, "unknown algorithm");
+    ASSERT_TRUE(last_debug_contains("unknown hash algorithm 99"), "unknown algorithm recorded");
+
+    extractous_extractor_free(extractor);
+    remove(path);
+    remove(content_path);
+}
+
+TEST(extract_file_to_string_timed) {
+    const char *text = "Time this small extraction.\n";
+    char path[256];
+    temp_path(path, sizeof(path), "timed.txt");
+    ASSERT_EQ(0, write_file(path, text, strlen(text)), "write fixture");
+
+    struct CExtractor *extractor = extractous_extractor_new();
+    ASSERT_NOT_NULL(extractor, "extractor");
+    char *content = NULL;
+    struct CMetadata *metadata = NULL;
+    uint64_t elapsed_ms = 0;
+
+    int result = extractous_extractor_extract_file_to_string_timed(
+        extractor, path, &content, &metadata, &elapsed_ms
+    );
+    ASSERT_EQ(ERR_OK, result, "result");
+    ASSERT_TRUE(elapsed_ms > 0, "duration reported");
+    ASSERT_TRUE(elapsed_ms < 60000, "duration plausible for a small file");
+    extractous_string_free(content);
+    extractous_metadata_free(metadata);
+
+    result = extractous_extractor_extract_file_to_string_timed(extractor, path, &content, &metadata, NULL);
+    ASSERT_EQ(ERR_NULL_POINTER, result, "null elapsed out-param");
+
+    elapsed_ms = 123;
+    result = extractous_extractor_extract_file_to_string_timed(
+        extractor, "/nonexistent/timed.txt", &content, &metadata, &elapsed_ms
+    );
+    ASSERT_EQ(ERR_IO_ERROR, result, "missing file");
+    ASSERT_TRUE(elapsed_ms == 0, "no duration for a refused file");
+
+    extractous_extractor_free(extractor);
+    remove(path);
+}
+
+TEST(extract_file_with_past_deadline) {
+    const char *text = "Deadline test document.\n";
+    char path[256];
+    temp_path(path, sizeof(path), "deadline.txt");
+    ASSERT_EQ(0, write_file(path, text, strlen(text)), "write fixture");
+
+    struct CExtractor *extractor = extractous_extractor_new();
+    ASSERT_NOT_NULL(extractor, "extractor");
+    char *content = NULL;
+    struct CMetadata *metadata = NULL;
+
+    time_t started = time(NULL);
+    int result = extractous_extractor_extract_file_with_deadline(
+        extractor, path, 1000, &content, &metadata
+    );
+    ASSERT_EQ(ERR_TIMEOUT, result, "past deadline");
+    ASSERT_TRUE(time(NULL) - started <= 1, "returned immediately");
+    ASSERT_TRUE(strcmp("timeout", extractous_error_get_last_category()) == 0, "timeout category");
+
+    uint64_t deadline = (uint64_t)time(NULL) * 1000 + 60000;
+    result = extractous_extractor_extract_file_with_deadline(
+        extractor, path, deadline, &content, &metadata
+    );
+    ASSERT_EQ(ERR_OK, result, "future deadline");
+    ASSERT_TRUE(strstr(content, "Deadline test") != NULL, "content");
+    extractous_string_free(content);
+    extractous_metadata_free(metadata);
+
+    extractous_extractor_free(extractor);
+    remove(path);
+}
+
+static void *cancel_after_delay(void *token) {
+    usleep(50 * 1000);
+    extractous_cancel_token_cancel(token);
+    return NULL;
+}
+
+TEST(extract_file_cancellable) {
+    char path[256];
+    temp_path(path, sizeof(path), "cancel.txt");
+    FILE *f = fopen(path, "w");
+    ASSERT_NOT_NULL(f, "open fixture");
+    for (int i = 0; i < 400000; i++) {
+        fputs("A long line of text that keeps the extraction busy for a while.\n", f);
+    }
+    fclose(f);
+
+    struct CExtractor *extractor = extractous_extractor_new();
+    ASSERT_NOT_NULL(extractor, "extractor");
+    char *content = NULL;
+    struct CMetadata *metadata = NULL;
+
+    // Cancel mid-extraction from another thread
+    struct CCancelToken *token = extractous_cancel_token_new();
+    ASSERT_NOT_NULL(token, "token");
+    pthread_t canceller;
+    extractous_set_max_concurrent_extractions(1);
+    ASSERT_EQ(0, pthread_create(&canceller, NULL, cancel_after_delay, token), "start canceller");
+    int result = extractous_extractor_extract_file_cancellable(extractor, path, token, &content, &metadata);
+    pthread_join(canceller, NULL);
+    ASSERT_EQ(ERR_ABORTED, result, "cancelled mid-extraction");
+    ASSERT_TRUE(strcmp("aborted", extractous_error_get_last_category()) == 0, "aborted category");
+
+    // The stopping worker gives its slot back, so the next extraction gets it
+    char small_path[256];
+    temp_path(small_path, sizeof(small_path), "cancel_small.txt");
+    ASSERT_EQ(0, write_file(small_path, "small\n", 6), "write small fixture");
+    result = extractous_extractor_extract_file_to_string(extractor, small_path, &content, &metadata);
+    ASSERT_EQ(ERR_OK, result, "slot given back after cancellation");
+    extractous_extraction_result_free(content, metadata);
+    extractous_set_max_concurrent_extractions(0);
+    remove(small_path);
+
+    // An already-cancelled token aborts before starting
+    result = extractous_extractor_extract_file_cancellable(extractor, path, token, &content, &metadata);
+    ASSERT_EQ(ERR_ABORTED, result, "already cancelled");
+    extractous_cancel_token_free(token);
+
+    extractous_extractor_free(extractor);
+    remove(path);
+}
+
+TEST(extract_file_page_range) {
+    const char *pages[] = {"Alpha page", "Bravo page", "Charlie page", "Delta page", "Echo page"};
+    char path[256];
+    temp_path(path, sizeof(path), "range.pdf");
+    ASSERT_EQ(0, write_pdf(path, pages, 5), "write fixture");
+
+    struct CExtractor *extractor = extractous_extractor_new();
+    ASSERT_NOT_NULL(extractor, "extractor");
+    char *content = NULL;
+    struct CMetadata *metadata = NULL;
+
+    int result = extractous_extractor_extract_file_range(extractor, path, 2, 3, &content, &metadata);
+    ASSERT_EQ(ERR_OK, result, "range result");
+    ASSERT_TRUE(strstr(content, "Bravo page") != NULL, "page 2 present");
+    ASSERT_TRUE(strstr(content, "Charlie page") != NULL, "page 3 present");
+    ASSERT_TRUE(strstr(content, "Alpha page") == NULL, "page 1 absent");
+    ASSERT_TRUE(strstr(content, "Delta page") == NULL, "page 4 absent");
+    ASSERT_TRUE(strstr(content, "<div") == NULL, "plain text output");
+    extractous_string_free(content);
+    extractous_metadata_free(metadata);
+
+    // Ranges past the last page are clamped
+    result = extractous_extractor_extract_file_range(extractor, path, 5, 99, &content, &metadata);
+    ASSERT_EQ(ERR_OK, result, "clamped result");
+    ASSERT_TRUE(strstr(content, "Echo page") != NULL, "last page present");
+    ASSERT_TRUE(strstr(content, "Delta page") == NULL, "earlier page absent");
+    extractous_string_free(content);
+    extractous_metadata_free(metadata);
+
+    result = extractous_extractor_extract_file_range(extractor, path, 3, 2, &content, &metadata);
+    ASSERT_EQ(ERR_INVALID_CONFIG, result, "end before start");
+
+    extractous_extractor_free(extractor);
+    remove(path);
+}
+
+TEST(list_embedded_files) {
+    const char *eml =
+        "From: sender@example.com\r\n"
+        "To: recipient@example.com\r\n"
+        "Subject: Two attachments\r\n"
+        "MIME-Version: 1.0\r\n"
+        "Content-Type: multipart/mixed; boundary=\"BOUNDARY\"\r\n"
+        "\r\n"
+        "--BOUNDARY\r\n"
+        "Content-Type: text/plain\r\n"
+        "\r\n"
+        "See the attached files.\r\n"
+        "--BOUNDARY\r\n"
+        "Content-Type: text/plain; name=\"notes.txt\"\r\n"
+        "Content-Disposition: attachment; filename=\"notes.txt\"\r\n"
+        "\r\n"
+        "First attachment.\r\n"
+        "--BOUNDARY\r\n"
+        "Content-Type: text/csv; name=\"figures.csv\"\r\n"
+        "Content-Disposition: attachment; filename=\"figures.csv\"\r\n"
+        "\r\n"
+        "a,b\r\n1,2\r\n"
+        "--BOUNDARY--\r\n";
+    char path[256];
+    temp_path(path, sizeof(path), "attachments.eml");
+    ASSERT_EQ(0, write_file(path, eml, strlen(eml)), "write fixture");
+
+    struct CExtractor *extractor = extractous_extractor_new();
+    ASSERT_NOT_NULL(extractor, "extractor");
+    char **names = NULL;
+    size_t count = 0;
+
+    int result = extractous_extractor_list_embedded_file(extractor, path, &names, &count);
+    ASSERT_EQ(ERR_OK, result, "result");
+    int found_notes = 0, found_figures = 0;
+    for (size_t i = 0; i < count; i++) {
+        found_notes |= strcmp(names[i], "notes.txt") == 0;
+        found_figures |= strcmp(names[i], "figures.csv") == 0;
+    }
+    ASSERT_TRUE(found_notes, "first attachment listed");
+    ASSERT_TRUE(found_figures, "second attachment listed");
+    extractous_string_array_free(names, count);
+
+    extractous_extractor_free(extractor);
+    remove(path);
+}
+
+TEST(extract_file_to_string_bounded) {
+    // A JVM out-of-memory error cannot be triggered reliably here, so this
+    // covers the non-retry path and argument validation.
+    const char *text = "Bounded extraction document.\n";
+    char path[256];
+    temp_path(path, sizeof(path), "bounded.txt");
+    ASSERT_EQ(0, write_file(path, text, strlen(text)), "write fixture");
+
+    struct CExtractor *extractor = extractous_extractor_new();
+    ASSERT_NOT_NULL(extractor, "extractor");
+    extractous_extractor_set_extract_string_max_length_mut(extractor, 10);
+    char *content = NULL;
+    struct CMetadata *metadata = NULL;
+    int capped = -1;
+
+    int result = extractous_extractor_extract_file_to_string_bounded(
+        extractor, path, 5, &content, &metadata, &capped
+    );
+    ASSERT_EQ(ERR_OK, result, "result");
+    ASSERT_EQ(0, capped, "no retry without out-of-memory");
+    ASSERT_TRUE(strlen(content) <= 10, "handle limit still applies");
+    extractous_string_free(content);
+    extractous_metadata_free(metadata);
+
+    result = extractous_extractor_extract_file_to_string_bounded(
+        extractor, path, 0, &content, &metadata, &capped
+    );
+    ASSERT_EQ(ERR_INVALID_CONFIG, result, "non-positive hard cap");
+    ASSERT_TRUE(last_debug_contains("hard cap must be positive"), "debug names the problem");
+
+    extractous_extractor_free(extractor);
+    remove(path);
+}
+
+TEST(extract_file_empty_reason) {
+    // A page with no text layer stands in for a scanned, image-only PDF.
+    const char *pages[] = {""};
+    const char *text = "Some text.\n";
+    char pdf_path[256];
+    char txt_path[256];
+    temp_path(pdf_path, sizeof(pdf_path), "empty_reason.pdf");
+    temp_path(txt_path, sizeof(txt_path), "empty_reason.txt");
+    ASSERT_EQ(0, write_pdf(pdf_path, pages, 1), "write pdf fixture");
+    ASSERT_EQ(0, write_file(txt_path, text, strlen(text)), "write text fixture");
+
+    struct CExtractor *extractor = extractous_extractor_new();
+    ASSERT_NOT_NULL(extractor, "extractor");
+    struct CPdfParserConfig *pdf_config = extractous_pdf_config_new();
+    ASSERT_NOT_NULL(pdf_config, "pdf config");
+    extractous_pdf_config_set_ocr_strategy(pdf_config, PDF_OCR_STRATEGY_NO_OCR);
+    extractous_extractor_set_pdf_config_mut(extractor, pdf_config);
+    char *content = NULL;
+    struct CMetadata *metadata = NULL;
+    int reason = -1;
+
+    int result = extractous_extractor_extract_file_to_string_with_empty_reason(
+        extractor, pdf_path, &content, &metadata, &reason
+    );
+    ASSERT_EQ(ERR_OK, result, "pdf result");
+    ASSERT_EQ(EMPTY_OCR_DISABLED, reason, "image-only pdf without ocr");
+    extractous_string_free(content);
+    extractous_metadata_free(metadata);
+
+    result = extractous_extractor_extract_file_to_string_with_empty_reason(
+        extractor, txt_path, &content, &metadata, &reason
+    );
+    ASSERT_EQ(ERR_OK, result, "text result");
+    ASSERT_EQ(EMPTY_NONE, reason, "text has content");
+    extractous_string_free(content);
+    extractous_metadata_free(metadata);
+
+    extractous_pdf_config_free(pdf_config);
+    extractous_extractor_free(extractor);
+    remove(pdf_path);
+    remove(txt_path);
+}
+
+TEST(extract_file_max_output_bytes) {
+    // 60 three-byte characters, so a 100-byte cap falls inside the 34th.
+    char text[256] = "";
+    for (int i = 0; i < 60; i++) {
+        strcat(text, "\xE2\x82\xAC");
+    }
+    char path[256];
+    temp_path(path, sizeof(path), "max_output_bytes.txt");
+    ASSERT_EQ(0, write_file(path, text, strlen(text)), "write fixture");
+
+    struct CExtractor *extractor = extractous_extractor_new();
+    ASSERT_NOT_NULL(extractor, "extractor");
+    extractous_extractor_set_max_output_bytes_mut(extractor, 100);
+    char *content = NULL;
+    struct CMetadata *metadata = NULL;
+    int truncated = -1;
+
+    int result = extractous_extractor_extract_file_to_string_ex(
+        extractor, path, &content, &metadata, &truncated
+    );
+    ASSERT_EQ(ERR_OK, result, "result");
+    ASSERT_NOT_NULL(content, "content");
+    size_t len = strlen(content);
+    ASSERT_TRUE(len <= 100, "content within byte cap");
+    ASSERT_TRUE(len > 0 && len % 3 == 0, "no split multibyte sequence");
+    ASSERT_EQ(0, memcmp(content, text, len), "content is a prefix");
+    ASSERT_EQ(1, truncated, "truncation reported");
+    extractous_string_free(content);
+    extractous_metadata_free(metadata);
+
+    extractous_extractor_free(extractor);
+    remove(path);
+}
+
+TEST(extraction_result_free) {
+    const char *text = "Result free document.\n";
+    char path[256];
+    temp_path(path, sizeof(path), "result_free.txt");
+    ASSERT_EQ(0, write_file(path, text, strlen(text)), "write fixture");
+
+    struct CExtractor *extractor = extractous_extractor_new();
+    ASSERT_NOT_NULL(extractor, "extractor");
+    char *content = NULL;
+    struct CMetadata *metadata = NULL;
+
+    int result = extractous_extractor_extract_file_to_string(extractor, path, &content, &metadata);
+    ASSERT_EQ(ERR_OK, result, "result");
+    ASSERT_NOT_NULL(content, "content");
+    ASSERT_NOT_NULL(metadata, "metadata");
+    extractous_extraction_result_free(content, metadata);
+
+    // NULLs for either or both are ignored, so repeated cleanup is safe.
+    extractous_extraction_result_free(NULL, NULL);
+    extractous_extraction_result_free(NULL, NULL);
+    char *only_content = NULL;
+    struct CMetadata *only_metadata = NULL;
+    result = extractous_extractor_extract_file_to_string(extractor, path, &only_content, &only_metadata);
+    ASSERT_EQ(ERR_OK, result, "second result");
+    extractous_extraction_result_free(only_content, NULL);
+    extractous_extraction_result_free(NULL, only_metadata);
+
+    extractous_extractor_free(extractor);
+    remove(path);
+}
+
+TEST(extract_file_ocr_info) {
+    // A page without a text layer stands in for a scan; OCR_ONLY sends every
+    // page to Tesseract regardless.
+    const char *scanned_pages[] = {""};
+    const char *digital_pages[] = {"Digital text layer"};
+    char scanned_path[256];
+    char digital_path[256];
+    temp_path(scanned_path, sizeof(scanned_path), "ocr_scanned.pdf");
+    temp_path(digital_path, sizeof(digital_path), "ocr_digital.pdf");
+    ASSERT_EQ(0, write_pdf(scanned_path, scanned_pages, 1), "write scanned fixture");
+    ASSERT_EQ(0, write_pdf(digital_path, digital_pages, 1), "write digital fixture");
+
+    struct CExtractor *extractor = extractous_extractor_new();
+    ASSERT_NOT_NULL(extractor, "extractor");
+    struct CPdfParserConfig *pdf_config = extractous_pdf_config_new();
+    ASSERT_NOT_NULL(pdf_config, "pdf config");
+    char *content = NULL;
+    struct CMetadata *metadata = NULL;
+    int ocr_used = -1;
+
+    extractous_pdf_config_set_ocr_strategy(pdf_config, PDF_OCR_STRATEGY_NO_OCR);
+    extractous_extractor_set_pdf_config_mut(extractor, pdf_config);
+    int result = extractous_extractor_extract_file_to_string_ocrinfo(
+        extractor, digital_path, &content, &metadata, &ocr_used
+    );
+    ASSERT_EQ(ERR_OK, result, "digital result");
+    ASSERT_EQ(0, ocr_used, "no ocr on digital pdf");
+    extractous_extraction_result_free(content, metadata);
+
+    if (system("command -v tesseract > /dev/null 2>&1") == 0) {
+        extractous_pdf_config_set_ocr_strategy(pdf_config, PDF_OCR_STRATEGY_OCR_ONLY);
+        extractous_extractor_set_pdf_config_mut(extractor, pdf_config);
+        result = extractous_extractor_extract_file_to_string_ocrinfo(
+            extractor, scanned_path, &content, &metadata, &ocr_used
+        );
+        ASSERT_EQ(ERR_OK, result, "scanned result");
+        ASSERT_EQ(1, ocr_used, "ocr on scanned pdf");
+        extractous_extraction_result_free(content, metadata);
+    } else {
+        printf(COLOR_YELLOW "         tesseract not installed, OCR_ONLY case skipped\n" COLOR_RESET);
+    }
+
+    extractous_pdf_config_free(pdf_config);
+    extractous_extractor_free(extractor);
+    remove(scanned_path);
+    remove(digital_path);
+}
+
+TEST(extract_file_as_format) {
+    const char *text = "Per-call format document.\n";
+    char path[256];
+    temp_path(path, sizeof(path), "as_format.txt");
+    ASSERT_EQ(0, write_file(path, text, strlen(text)), "write fixture");
+
+    struct CExtractor *extractor = extractous_extractor_new();
+    ASSERT_NOT_NULL(extractor, "extractor");
+    char *content = NULL;
+    struct CMetadata *metadata = NULL;
+
+    int result = extractous_extractor_extract_file_as_format(
+        extractor, path, OUTPUT_TEXT, &content, &metadata
+    );
+    ASSERT_EQ(ERR_OK, result, "text result");
+    ASSERT_TRUE(strstr(content, "Per-call format document.") != NULL, "text content");
+    ASSERT_TRUE(strstr(content, "<body") == NULL, "text has no markup");
+    extractous_extraction_result_free(content, metadata);
+
+    result = extractous_extractor_extract_file_as_format(
+        extractor, path, OUTPUT_XML, &content, &metadata
+    );
+    ASSERT_EQ(ERR_OK, result, "xml result");
+    ASSERT_TRUE(strstr(content, "<body") != NULL, "xml has markup");
+    ASSERT_TRUE(strstr(content, "Per-call format document.") != NULL, "xml content");
+    extractous_extraction_result_free(content, metadata);
+
+    ASSERT_EQ(OUTPUT_TEXT, extractous_extractor_get_output_format(extractor), "default unchanged");
+    result = extractous_extractor_extract_file_as_format(extractor, path, 7, &content, &metadata);
+    ASSERT_EQ(ERR_INVALID_ENUM, result, "unknown format");
+
+    extractous_extractor_free(extractor);
+    remove(path);
+}
+
+TEST(extract_file_root_dir) {
+    const char *text = "Root directory document.\n";
+    char root[256];
+    char inside[256];
+    char outside[256];
+    char link[256];
+    char traversal[512];
+    temp_path(root, sizeof(root), "root");
+    temp_path(outside, sizeof(outside), "outside_root.txt");
+    snprintf(inside, sizeof(inside), "%s/inside.txt", root);
+    snprintf(link, sizeof(link), "%s/link.txt", root);
+    snprintf(traversal, sizeof(traversal), "%s/../extractous_ffi_test_outside_root.txt", root);
+    mkdir(root, 0700);
+    ASSERT_EQ(0, write_file(inside, text, strlen(text)), "write inside fixture");
+    ASSERT_EQ(0, write_file(outside, text, strlen(text)), "write outside fixture");
+    unlink(link);
+    ASSERT_EQ(0, symlink(outside, link), "create symlink");
+
+    struct CExtractor *extractor = extractous_extractor_new();
+    ASSERT_NOT_NULL(extractor, "extractor");
+    char *content = NULL;
+    struct CMetadata *metadata = NULL;
+
+    // Without a root every path is accepted.
+    int result = extractous_extractor_extract_file_to_string(extractor, traversal, &content, &metadata);
+    ASSERT_EQ(ERR_OK, result, "traversal without root");
+    extractous_extraction_result_free(content, metadata);
+
+    extractous_extractor_set_root_dir_mut(extractor, root);
+    result = extractous_extractor_extract_file_to_string(extractor, inside, &content, &metadata);
+    ASSERT_EQ(ERR_OK, result, "path inside root");
+    extractous_extraction_result_free(content, metadata);
+
+    result = extractous_extractor_extract_file_to_string(extractor, link, &content, &metadata);
+    ASSERT_EQ(ERR_IO_ERROR, result, "symlink outside root");
+    ASSERT_TRUE(last_debug_contains("path escapes root"), "symlink debug message");
+
+    result = extractous_extractor_extract_file_to_string(extractor, traversal, &content, &metadata);
+    ASSERT_EQ(ERR_IO_ERROR, result, "traversal outside root");
+    ASSERT_TRUE(last_debug_contains("path escapes root"), "traversal debug message");
+
+    // Without a root, a link is only rejected when links are not followed.
+    extractous_extractor_set_root_dir_mut(extractor, NULL);
+    extractous_extractor_set_follow_symlinks_mut(extractor, false);
+    result = extractous_extractor_extract_file_to_string(extractor, link, &content, &metadata);
+    ASSERT_EQ(ERR_IO_ERROR, result, "symlink not followed");
+
+    extractous_extractor_free(extractor);
+    unlink(link);
+    remove(inside);
+    remove(outside);
+    rmdir(root);
+}
+
+TEST(extract_file_xml_body_only) {
+    const char *text = "Body only document.\n";
+    char path[256];
+    temp_path(path, sizeof(path), "xml_body_only.txt");
+    ASSERT_EQ(0, write_file(path, text, strlen(text)), "write fixture");
+
+    struct CExtractor *extractor = extractous_extractor_new();
+    ASSERT_NOT_NULL(extractor, "extractor");
+    extractous_extractor_set_output_format_mut(extractor, OUTPUT_XML);
+    char *content = NULL;
+    struct CMetadata *metadata = NULL;
+
+    int result = extractous_extractor_extract_file_to_string(extractor, path, &content, &metadata);
+    ASSERT_EQ(ERR_OK, result, "full document result");
+    ASSERT_TRUE(strstr(content, "<head") != NULL, "head present by default");
+    ASSERT_TRUE(strstr(content, "<body") != NULL, "body tag present by default");
+    extractous_extraction_result_free(content, metadata);
+
+    extractous_extractor_set_xml_body_only_mut(extractor, true);
+    result = extractous_extractor_extract_file_to_string(extractor, path, &content, &metadata);
+    ASSERT_EQ(ERR_OK, result, "body only result");
+    ASSERT_TRUE(strstr(content, "<head") == NULL, "head removed");
+    ASSERT_TRUE(strstr(content, "<body") == NULL, "body tag removed");
+    ASSERT_TRUE(strstr(content, "Body only document.") != NULL, "body content kept");
+    extractous_extraction_result_free(content, metadata);
+
+    extractous_extractor_free(extractor);
+    remove(path);
+}
+
+TEST(extract_file_tables) {
+    struct zip_entry xlsx[] = {
+        {"[Content_Types].xml",
+         "<?xml version=\"1.0\"?><Types xmlns=\"http://schemas.openxmlformats.org/package/2006/content-types\">"
+         "<Default Extension=\"rels\" ContentType=\"application/vnd.openxmlformats-package.relationships+xml\"/>"
+         "<Default Extension=\"xml\" ContentType=\"application/xml\"/>"
+         "<Override PartName=\"/xl/workbook.xml\" ContentType=\"application/vnd.openxmlformats-officedocument.spreadsheetml.sheet.main+xml\"/>"
+         "<Override PartName=\"/xl/worksheets/sheet1.xml\" ContentType=\"application/vnd.openxmlformats-officedocument.spreadsheetml.worksheet+xml\"/>"
+         "</Types>"},
+        {"_rels/.rels",
+         "<?xml version=\"1.0\"?><Relationships xmlns=\"http://schemas.openxmlformats.org/package/2006/relationships\">"
+         "<Relationship Id=\"rId1\" Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument\" Target=\"xl/workbook.xml\"/>"
+         "</Relationships>"},
+        {"xl/workbook.xml",
+         "<?xml version=\"1.0\"?><workbook xmlns=\"http://schemas.openxmlformats.org/spreadsheetml/2006/main\" "
+         "xmlns:r=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships\"><sheets>"
+         "<sheet name=\"Prices\" sheetId=\"1\" r:id=\"rId1\"/></sheets></workbook>"},
+        {"xl/_rels/workbook.xml.rels",
+         "<?xml version=\"1.0\"?><Relationships xmlns=\"http://schemas.openxmlformats.org/package/2006/relationships\">"
+         "<Relationship Id=\"rId1\" Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/worksheet\" Target=\"worksheets/sheet1.xml\"/>"
+         "</Relationships>"},
+        {"xl/worksheets/sheet1.xml",
+         "<?xml version=\"1.0\"?><worksheet xmlns=\"http://schemas.openxmlformats.org/spreadsheetml/2006/main\"><sheetData>"
+         "<row r=\"1\"><c r=\"A1\" t=\"inlineStr\"><is><t>Fruit</t></is></c><c r=\"B1\" t=\"inlineStr\"><is><t>Price</t></is></c></row>"
+         "<row r=\"2\"><c r=\"A2\" t=\"inlineStr\"><is><t>Apple</t></is></c><c r=\"B2\"><v>3</v></c></row>"
+         "<row r=\"3\"><c r=\"A3\" t=\"inlineStr\"><is><t>Pear</t></is></c><c r=\"B3\"><v>5</v></c></row>"
+         "</sheetData></worksheet>"},
+    };
+    char path[256];
+    temp_path(path, sizeof(path), "tables.xlsx");
+    ASSERT_EQ(0, write_zip(path, xlsx, sizeof(xlsx) / sizeof(xlsx[0])), "write fixture");
+
+    struct CExtractor *extractor = extractous_extractor_new();
+    ASSERT_NOT_NULL(extractor, "extractor");
+    struct CTable *tables = NULL;
+    size_t count = 0;
+
+    int result = extractous_extractor_extract_file_tables(extractor, path, &tables, &count);
+    ASSERT_EQ(ERR_OK, result, "result");
+    ASSERT_EQ(1, (int)count, "one table per sheet");
+    ASSERT_TRUE(tables[0].name != NULL && strcmp(tables[0].name, "Prices") == 0, "named after the sheet");
+    ASSERT_EQ(3, (int)tables[0].rows, "rows");
+    ASSERT_EQ(2, (int)tables[0].columns, "columns");
+    ASSERT_TRUE(strcmp(tables[0].cells[0], "Fruit") == 0, "header cell");
+    ASSERT_TRUE(strcmp(tables[0].cells[1 * 2 + 0], "Apple") == 0, "row 2, column 1");
+    ASSERT_TRUE(strcmp(tables[0].cells[2 * 2 + 1], "5") == 0, "row 3, column 2");
+    extractous_tables_free(tables, count);
+
+    result = extractous_extractor_extract_file_tables(extractor, path, NULL, &count);
+    ASSERT_EQ(ERR_NULL_POINTER, result, "null out-param");
+
+    extractous_extractor_free(extractor);
+    remove(path);
+}
+
+TEST(extract_file_segmented) {
+    const struct zip_entry entries[] = {
+        {"alpha.txt", "Alpha document\n"},
+        {"beta.txt", "Beta document\n"},
+        {"gamma.txt", "Gamma document\n"},
+    };
+    char path[256];
+    temp_path(path, sizeof(path), "segmented.zip");
+    ASSERT_EQ(0, write_zip(path, entries, 3), "write fixture");
+
+    struct CExtractor *extractor = extractous_extractor_new();
+    ASSERT_NOT_NULL(extractor, "extractor");
+    struct CSegment *segments = NULL;
+    size_t count = 0;
+
+    int result = extractous_extractor_extract_file_segmented(extractor, path, &segments, &count);
+    ASSERT_EQ(ERR_OK, result, "result");
+    ASSERT_NOT_NULL(segments, "segments");
+    // Segment 0 is the archive itself, followed by one segment per entry.
+    ASSERT_EQ(4, (int)count, "segment count");
+    ASSERT_TRUE(strcmp(segments[0].name, "extractous_ffi_test_segmented.zip") == 0, "top-level name");
+    ASSERT_NOT_NULL(metadata_get(segments[0].metadata, "Content-Type"), "top-level metadata");
+    for (int i = 0; i < 3; i++) {
+        const struct CSegment *segment = &segments[i + 1];
+        ASSERT_NOT_NULL(segment->name, "entry name");
+        ASSERT_TRUE(strcmp(segment->name, entries[i].name) == 0, "entry name matches");
+        ASSERT_TRUE(strstr(segment->content, entries[i].data) != NULL, "entry content");
+        for (int j = 0; j < 3; j++) {
+            if (j != i) {
+                ASSERT_TRUE(strstr(segment->content, entries[j].data) == NULL, "content is independent");
+            }
+        }
+    }
+    extractous_segments_free(segments, count);
+    extractous_segments_free(NULL, 0);
+
+    extractous_extractor_free(extractor);
+    remove(path);
+}
+
+// Counts the accented Latin letters (U+00C0 to U+00FF) in UTF-8 text.
+static int count_accented(const char *text) {
+    int count = 0;
+    for (const unsigned char *p = (const unsigned char *)text; *p; p++) {
+        if (*p == 0xC3) {
+            count++;
+        }
+    }
+    return count;
+}
+
+TEST(extract_file_ocr_lang) {
+    if (system("tesseract --list-langs 2>/dev/null | grep -qx fra") != 0) {
+        printf(COLOR_YELLOW "         tesseract with French data not installed, skipped\n" COLOR_RESET);
+        return;
+    }
+    // Rendered and sent through OCR as if it were a scanned page.
+    const char *pages[] = {"Le gar\xe7on \xe9tait d\xe9j\xe0 tr\xe8s f\xe2" "ch\xe9 contre l'\xe9l\xe8ve."};
+    char path[256];
+    temp_path(path, sizeof(path), "ocr_french.pdf");
+    ASSERT_EQ(0, write_pdf(path, pages, 1), "write fixture");
+
+    struct CExtractor *extractor = extractous_extractor_new();
+    ASSERT_NOT_NULL(extractor, "extractor");
+    struct CPdfParserConfig *pdf_config = extractous_pdf_config_new();
+    extractous_pdf_config_set_ocr_strategy(pdf_config, PDF_OCR_STRATEGY_OCR_ONLY);
+    extractous_extractor_set_pdf_config_mut(extractor, pdf_config);
+    struct CTesseractOcrConfig *ocr_config = extractous_ocr_config_new();
+    extractous_ocr_config_set_language(ocr_config, "eng");
+    extractous_extractor_set_ocr_config_mut(extractor, ocr_config);
+
+    char *english = NULL;
+    char *french = NULL;
+    char *after = NULL;
+    struct CMetadata *metadata = NULL;
+
+    int result = extractous_extractor_extract_file_ocr_lang(extractor, path, NULL, &english, &metadata);
+    ASSERT_EQ(ERR_OK, result, "configured language");
+    extractous_metadata_free(metadata);
+
+    result = extractous_extractor_extract_file_ocr_lang(extractor, path, "fra", &french, &metadata);
+    ASSERT_EQ(ERR_OK, result, "french override");
+    extractous_metadata_free(metadata);
+    ASSERT_NOT_NULL(strstr(french, "d\xc3\xa9j\xc3\xa0"), "french words recognised");
+    ASSERT_TRUE(count_accented(french) > count_accented(english), "more accents kept");
+
+    result = extractous_extractor_extract_file_to_string(extractor, path, &after, &metadata);
+    ASSERT_EQ(ERR_OK, result, "after override");
+    extractous_metadata_free(metadata);
+    ASSERT_TRUE(strcmp(after, english) == 0, "stored config unchanged");
+
+    extractous_string_free(english);
+    extractous_string_free(french);
+    extractous_string_free(after);
+    extractous_ocr_config_free(ocr_config);
+    extractous_pdf_config_free(pdf_config);
+    extractous_extractor_free(extractor);
+    remove(path);
+}
+
+TEST(extract_file_ocr_dpi) {
+    if (system("tesseract --version >/dev/null 2>&1") != 0) {
+        printf(COLOR_YELLOW "  SKIP: tesseract not installed\n" COLOR_RESET);
+        return;
+    }
+    const char *pages[] = {"The quick brown fox jumps over the lazy dog."};
+    char path[256];
+    temp_path(path, sizeof(path), "ocr_dpi.pdf");
+    ASSERT_EQ(0, write_pdf(path, pages, 1), "write fixture");
+
+    struct CExtractor *extractor = extractous_extractor_new();
+    ASSERT_NOT_NULL(extractor, "extractor");
+    struct CPdfParserConfig *pdf_config = extractous_pdf_config_new();
+    extractous_pdf_config_set_ocr_strategy(pdf_config, PDF_OCR_STRATEGY_OCR_ONLY);
+    extractous_extractor_set_pdf_config_mut(extractor, pdf_config);
+
+    char *before = NULL;
+    char *low = NULL;
+    char *high = NULL;
+    char *after = NULL;
+    struct CMetadata *metadata = NULL;
+
+    int result = extractous_extractor_extract_file_to_string(extractor, path, &before, &metadata);
+    ASSERT_EQ(ERR_OK, result, "configured dpi");
+    extractous_metadata_free(metadata);
+
+    result = extractous_extractor_extract_file_ocr_dpi(extractor, path, 30, &low, &metadata);
+    ASSERT_EQ(ERR_OK, result, "low dpi");
+    extractous_metadata_free(metadata);
+
+    result = extractous_extractor_extract_file_ocr_dpi(extractor, path, 300, &high, &metadata);
+    ASSERT_EQ(ERR_OK, result, "high dpi");
+    extractous_metadata_free(metadata);
+    ASSERT_NOT_NULL(strstr(high, "fox"), "text recovered at high dpi");
+    ASSERT_TRUE(strlen(high) >= strlen(low), "high dpi recovers at least as much");
+
+    metadata = NULL;
+    result = extractous_extractor_extract_file_ocr_dpi(extractor, path, 0, &after, &metadata);
+    ASSERT_EQ(ERR_INVALID_CONFIG, result, "zero dpi rejected");
+    ASSERT_TRUE(metadata == NULL, "no metadata on error");
+
+    result = extractous_extractor_extract_file_to_string(extractor, path, &after, &metadata);
+    ASSERT_EQ(ERR_OK, result, "after override");
+    extractous_metadata_free(metadata);
+    ASSERT_TRUE(strcmp(after, before) == 0, "stored config unchanged");
+
+    extractous_string_free(before);
+    extractous_string_free(low);
+    extractous_string_free(high);
+    extractous_string_free(after);
+    extractous_pdf_config_free(pdf_config);
+    extractous_extractor_free(extractor);
+    remove(path);
+}
+
+TEST(set_temp_dir) {
+    char dir[256];
+    char not_dir[256];
+    temp_path(dir, sizeof(dir), "tmpdir");
+    temp_path(not_dir, sizeof(not_dir), "tmpdir_file");
+    mkdir(dir, 0700);
+    ASSERT_EQ(0, write_file(not_dir, "x", 1), "write fixture");
+    const char *text = "Text parsed after setting the temp dir\n";
+
+    ASSERT_EQ(ERR_IO_ERROR, extractous_set_temp_dir("/nonexistent/extractous/tmp"), "missing dir");
+    ASSERT_EQ(ERR_IO_ERROR, extractous_set_temp_dir(not_dir), "file instead of dir");
+    ASSERT_EQ(ERR_IO_ERROR, extractous_set_temp_dir("/proc"), "read-only dir");
+    ASSERT_TRUE(last_debug_contains("not usable"), "debug names the problem");
+    ASSERT_EQ(ERR_NULL_POINTER, extractous_set_temp_dir(NULL), "null path");
+
+    const char *previous = getenv("TMPDIR");
+    ASSERT_EQ(ERR_OK, extractous_set_temp_dir(dir), "writable dir");
+    ASSERT_TRUE(getenv("TMPDIR") == previous, "environment left alone");
+
+    struct CExtractor *extractor = extractous_extractor_new();
+    ASSERT_NOT_NULL(extractor, "extractor");
+    char *content = NULL;
+    struct CMetadata *metadata = NULL;
+    int result = extractous_extractor_extract_bytes_to_string(
+        extractor, (const uint8_t *)text, strlen(text), &content, &metadata
+    );
+    ASSERT_EQ(ERR_OK, result, "extraction with custom temp dir");
+    extractous_extraction_result_free(content, metadata);
+    extractous_extractor_free(extractor);
+
+    ASSERT_EQ(ERR_INVALID_CONFIG, extractous_set_temp_dir(dir), "fixed once extraction started");
+    ASSERT_TRUE(last_debug_contains("started"), "debug explains why");
+
+    // The directory stays: later tests write their intermediate files there
+    remove(not_dir);
+}
+
+TEST(detect_mime_type_confidence) {
+    const char *pages[] = {"Detected"};
+    const char *text = "just some words, nothing distinctive\n";
+    char pdf_path[256];
+    char txt_path[256];
+    temp_path(pdf_path, sizeof(pdf_path), "detect.pdf");
+    temp_path(txt_path, sizeof(txt_path), "detect.dat");
+    ASSERT_EQ(0, write_pdf(pdf_path, pages, 1), "write pdf fixture");
+    ASSERT_EQ(0, write_file(txt_path, text, strlen(text)), "write text fixture");
+
+    struct CExtractor *extractor = extractous_extractor_new();
+    ASSERT_NOT_NULL(extractor, "extractor");
+    char *mime = NULL;
+    int pdf_confidence = -1;
+    int txt_confidence = -1;
+
+    int result = extractous_extractor_detect_mime_type_file_ex(extractor, pdf_path, &mime, &pdf_confidence);
+    ASSERT_EQ(ERR_OK, result, "pdf result");
+    ASSERT_TRUE(strcmp(mime, "application/pdf") == 0, "pdf detected");
+    ASSERT_TRUE(pdf_confidence >= 90, "pdf confidence is high");
+    extractous_string_free(mime);
+
+    result = extractous_extractor_detect_mime_type_file_ex(extractor, txt_path, &mime, &txt_confidence);
+    ASSERT_EQ(ERR_OK, result, "text result");
+    ASSERT_TRUE(strcmp(mime, "text/plain") == 0, "text detected");
+    ASSERT_TRUE(txt_confidence < pdf_confidence, "text confidence is lower");
+    extractous_string_free(mime);
+
+    result = extractous_extractor_detect_mime_type_file_ex(
+        extractor, "/nonexistent/detect.pdf", &mime, &txt_confidence
+    );
+    ASSERT_EQ(ERR_IO_ERROR, result, "missing file");
+
+    extractous_extractor_free(extractor);
+    remove(pdf_path);
+    remove(txt_path);
+}
+
+TEST(extract_file_to_string_stats) {
+    // Five words; "café" and "naïve" each hold one two-byte character, and the
+    // words are separated by a space, a no-break space and a line feed.
+    const char *text = "caf\xC3\xA9 na\xC3\xAFve one\xC2\xA0two\nthree";
+    char path[256];
+    temp_path(path, sizeof(path), "stats.txt");
+    ASSERT_EQ(0, write_file(path, text, strlen(text)), "write fixture");
+
+    struct CExtractor *extractor = extractous_extractor_new();
+    ASSERT_NOT_NULL(extractor, "extractor");
+    char *content = NULL;
+    struct CMetadata *metadata = NULL;
+    size_t chars = 0;
+    size_t words = 0;
+
+    int result = extractous_extractor_extract_file_to_string_stats(
+        extractor, path, &content, &metadata, &chars, &words
+    );
+    ASSERT_EQ(ERR_OK, result, "result");
+    ASSERT_EQ(5, (int)words, "word count");
+    // The counts describe the returned content, which may gain a trailing
+    // line feed, so compare against the content's own character count.
+    size_t expected_chars = 0;
+    for (const unsigned char *p = (const unsigned char *)content; *p; p++) {
+        if ((*p & 0xC0) != 0x80) {
+            expected_chars++;
+        }
+    }
+    ASSERT_EQ((int)expected_chars, (int)chars, "char count matches content");
+    ASSERT_TRUE(chars >= 24 && chars < strlen(content), "multibyte characters counted once");
+    extractous_extraction_result_free(content, metadata);
+
+    extractous_extractor_free(extractor);
+    remove(path);
+}
+
+TEST(extract_file_fallback_mime) {
+    // The control bytes make up too much of the input for it to pass as text,
+    // and without an extension the parser cannot identify it at all.
+    const char *csv = "\x01\x02\x03\x04\x05\x06\x07\x08\n"
+                      "name,city\nalice,paris\nbob,rome\n";
+    char path[256];
+    temp_path(path, sizeof(path), "fallback_csv");
+    ASSERT_EQ(0, write_file(path, csv, strlen(csv)), "write fixture");
+
+    struct CExtractor *extractor = extractous_extractor_new();
+    ASSERT_NOT_NULL(extractor, "extractor");
+    char *content = NULL;
+    struct CMetadata *metadata = NULL;
+
+    int result = extractous_extractor_extract_file_to_string(extractor, path, &content, &metadata);
+    ASSERT_EQ(ERR_OK, result, "result without fallback");
+    const char *mime = metadata_get(metadata, "Content-Type");
+    ASSERT_TRUE(mime != NULL && strstr(mime, "application/octet-stream") != NULL, "undetected");
+    ASSERT_NULL(strstr(content, "alice"), "nothing recovered without fallback");
+    extractous_extraction_result_free(content, metadata);
+
+    extractous_extractor_set_fallback_mime_mut(extractor, "text/csv");
+    result = extractous_extractor_extract_file_to_string(extractor, path, &content, &metadata);
+    ASSERT_EQ(ERR_OK, result, "result with fallback");
+    ASSERT_NOT_NULL(strstr(content, "alice"), "content recovered with fallback");
+    mime = metadata_get(metadata, "Content-Type");
+    ASSERT_TRUE(mime != NULL && strstr(mime, "text/csv") != NULL, "parsed as CSV");
+    const char *name = metadata_get(metadata, "resourceName");
+    ASSERT_TRUE(name != NULL && strcmp(name, "extractous_ffi_test_fallback_csv") == 0,
+                "resourceName is the caller's file, not the retried copy");
+    extractous_extraction_result_free(content, metadata);
+
+    int truncated = 0;
+    result = extractous_extractor_extract_file_to_string_ex(extractor, path, &content, &metadata, &truncated);
+    ASSERT_EQ(ERR_OK, result, "result from another entry point");
+    ASSERT_NOT_NULL(strstr(content, "alice"), "recovered by other entry points");
+    extractous_extraction_result_free(content, metadata);
+
+    char *json = NULL;
+    size_t json_len = 0;
+    result = extractous_extractor_extract_file_to_json(extractor, path, &json, &json_len);
+    ASSERT_EQ(ERR_OK, result, "JSON result");
+    ASSERT_NOT_NULL(strstr(json, "alice"), "recovered as JSON");
+    ASSERT_NOT_NULL(strstr(json, "\"Content-Type\":[\"text/csv"), "JSON reports the fallback");
+    extractous_string_free(json);
+
+    const uint8_t *bytes = (const uint8_t *)csv;
+    result = extractous_extractor_extract_bytes_to_string_ex(extractor, bytes, strlen(csv), &content,
+                                                             &metadata, &truncated);
+    ASSERT_EQ(ERR_OK, result, "bytes_ex result");
+    ASSERT_NOT_NULL(strstr(content, "alice"), "recovered from bytes_ex");
+    extractous_extraction_result_free(content, metadata);
+
+    char *detected = NULL;
+    result = extractous_extractor_extract_bytes_to_string_detect(extractor, bytes, strlen(csv), &content,
+                                                                 &metadata, &detected);
+    ASSERT_EQ(ERR_OK, result, "bytes_detect result");
+    ASSERT_NOT_NULL(strstr(content, "alice"), "recovered from bytes_detect");
+    ASSERT_TRUE(detected != NULL && strstr(detected, "text/csv") != NULL, "detect reports the fallback");
+    extractous_string_free(detected);
+    extractous_extraction_result_free(content, metadata);
+
+    struct CIoSlice iov[2] = {{bytes, 10}, {bytes + 10, strlen(csv) - 10}};
+    result = extractous_extractor_extract_iovec_to_string(extractor, iov, 2, &content, &metadata);
+    ASSERT_EQ(ERR_OK, result, "iovec result");
+    ASSERT_NOT_NULL(strstr(content, "alice"), "recovered from iovec");
+    extractous_extraction_result_free(content, metadata);
+
+    struct CStreamReader *reader = NULL;
+    result = extractous_extractor_extract_file(extractor, path, &reader, &metadata);
+    ASSERT_EQ(ERR_OK, result, "stream result");
+    mime = metadata_get(metadata, "Content-Type");
+    ASSERT_TRUE(mime != NULL && strstr(mime, "text/csv") != NULL, "stream reports the fallback");
+    extractous_stream_free(reader);
+    extractous_metadata_free(metadata);
+
+    extractous_extractor_set_fallback_mime_mut(extractor, NULL);
+    result = extractous_extractor_extract_file_to_string(extractor, path, &content, &metadata);
+    ASSERT_EQ(ERR_OK, result, "result after clearing");
+    ASSERT_NULL(strstr(content, "alice"), "NULL clears the fallback");
+    extractous_extraction_result_free(content, metadata);
+
+    extractous_extractor_free(extractor);
+    remove(path);
+}
+
+static double monotonic_seconds(void) {
+    struct timespec ts;
+    clock_gettime(CLOCK_MONOTONIC, &ts);
+    return (double)ts.tv_sec + (double)ts.tv_nsec / 1e9;
+}
+
+TEST(extract_file_metadata_only) {
+    // A large body makes the skipped work measurable.
+    const size_t size = 16 * 1024 * 1024;
+    char *body = malloc(size);
+    ASSERT_NOT_NULL(body, "allocate body");
+    for (size_t i = 0; i < size; i++) {
+        body[i] = (i % 64 == 63) ? '\n' : 'a' + (char)(i % 26);
+    }
+    char path[256];
+    temp_path(path, sizeof(path), "metadata_only.txt");
+    ASSERT_EQ(0, write_file(path, body, size), "write fixture");
+    free(body);
+
+    struct CExtractor *extractor = extractous_extractor_new();
+    ASSERT_NOT_NULL(extractor, "extractor");
+    extractous_extractor_set_extract_string_max_length_mut(extractor, -1);
+
+    struct CMetadata *metadata = NULL;
+    double start = monotonic_seconds();
+    int result = extractous_extractor_extract_file_metadata_only(extractor, path, &metadata);
+    double metadata_only_time = monotonic_seconds() - start;
+    ASSERT_EQ(ERR_OK, result, "result");
+    ASSERT_NOT_NULL(metadata, "metadata");
+    const char *mime = metadata_get(metadata, "Content-Type");
+    ASSERT_TRUE(mime != NULL && strstr(mime, "text/plain") != NULL, "content type populated");
+    ASSERT_NOT_NULL(metadata_get(metadata, "Content-Length"), "content length populated");
+    extractous_metadata_free(metadata);
+
+    char *content = NULL;
+    start = monotonic_seconds();
+    result = extractous_extractor_extract_file_to_string(extractor, path, &content, &metadata);
+    double full_time = monotonic_seconds() - start;
+    ASSERT_EQ(ERR_OK, result, "full result");
+    ASSERT_TRUE(strlen(content) >= size, "full extraction reads the whole body");
+    extractous_extraction_result_free(content, metadata);
+    ASSERT_TRUE(metadata_only_time < full_time, "body not processed for metadata only");
+
+    result = extractous_extractor_extract_file_metadata_only(extractor, path, NULL);
+    ASSERT_EQ(ERR_NULL_POINTER, result, "null out-param");
+
+    extractous_extractor_free(extractor);
+    remove(path);
+}
+
+TEST(extract_file_corrupt_reports_java_exception) {
+    // A PDF header followed by garbage: no objects, no cross-reference table.
+    const char *pdf = "%PDF-1.7\n\x93\x8c\x8b\x9e garbage where objects belong\n";
+    char path[256];
+    temp_path(path, sizeof(path), "corrupt.pdf");
+    ASSERT_EQ(0, write_file(path, pdf, strlen(pdf)), "write fixture");
+
+    struct CExtractor *extractor = extractous_extractor_new();
+    ASSERT_NOT_NULL(extractor, "extractor");
+    char *content = NULL;
+    struct CMetadata *metadata = NULL;
+
+    int result = extractous_extractor_extract_file_to_string(extractor, path, &content, &metadata);
+    ASSERT_EQ(ERR_EXTRACTION_FAILED, result, "corrupt file fails");
+    char *debug = extractous_error_get_last_debug();
+    ASSERT_NOT_NULL(debug, "debug report");
+    ASSERT_NOT_NULL(strstr(debug, "java_exception: "), "report is structured");
+    ASSERT_NOT_NULL(strstr(debug, "org.apache.tika.exception.TikaException"), "names the Tika exception class");
+    extractous_string_free(debug);
+
+    extractous_extractor_free(extractor);
+    remove(path);
+}
+
+TEST(extract_first_image) {
+    char path[256];
+    temp_path(path, sizeof(path), "image.docx");
+    ASSERT_EQ(0, write_image_docx(path, 1), "write fixture");
+
+    struct CExtractor *extractor = extractous_extractor_new();
+    ASSERT_NOT_NULL(extractor, "extractor");
+    uint8_t *image = NULL;
+    size_t image_len = 0;
+    char *mime = NULL;
+
+    int result = extractous_extractor_extract_first_image(extractor, path, &image, &image_len, &mime);
+    ASSERT_EQ(ERR_OK, result, "result");
+    ASSERT_EQ((int)TINY_PNG_LEN, (int)image_len, "image length");
+    ASSERT_EQ(0, memcmp(image, TINY_PNG, TINY_PNG_LEN), "image bytes");
+    ASSERT_TRUE(mime != NULL && strcmp(mime, "image/png") == 0, "image MIME type");
+    extractous_buffer_free(image, image_len);
+    extractous_string_free(mime);
+
+    // The same document without its picture.
+    ASSERT_EQ(0, write_image_docx(path, 0), "write fixture without image");
+    result = extractous_extractor_extract_first_image(extractor, path, &image, &image_len, &mime);
+    ASSERT_EQ(ERR_NO_IMAGE, result, "no image");
+
+    extractous_extractor_free(extractor);
+    remove(path);
+}
+
+TEST(extract_file_html_with_images) {
+    char path[256];
+    temp_path(path, sizeof(path), "html_images.docx");
+    ASSERT_EQ(0, write_image_docx(path, 1), "write fixture");
+
+    struct CExtractor *extractor = extractous_extractor_new();
+    ASSERT_NOT_NULL(extractor, "extractor");
+    char *html = NULL;
+    struct CImage *images = NULL;
+    size_t image_count = 0;
+    struct CMetadata *metadata = NULL;
+
+    int result = extractous_extractor_extract_file_html_with_images(
+        extractor, path, &html, &images, &image_count, &metadata
+    );
+    ASSERT_EQ(ERR_OK, result, "result");
+    ASSERT_EQ(1, (int)image_count, "one image");
+    ASSERT_TRUE(strcmp(images[0].cid, "cid:image0") == 0, "sequential cid");
+    ASSERT_TRUE(strcmp(images[0].name, "image1.png") == 0, "image name");
+    ASSERT_TRUE(strcmp(images[0].mime, "image/png") == 0, "image MIME type");
+    ASSERT_EQ((int)TINY_PNG_LEN, (int)images[0].len, "image length");
+    ASSERT_EQ(0, memcmp(images[0].data, TINY_PNG, TINY_PNG_LEN), "image bytes");
+    ASSERT_NOT_NULL(strstr(html, "src=\"cid:image0\""), "html references the image");
+    ASSERT_NULL(strstr(html, "embedded:image1.png"), "reference rewritten");
+    ASSERT_NOT_NULL(strstr(html, "Has a picture"), "text kept");
+    extractous_images_free(images, image_count);
+    extractous_string_free(html);
+    extractous_metadata_free(metadata);
+
+    extractous_extractor_free(extractor);
+    remove(path);
+}
+
+TEST(extract_file_collapse_whitespace) {
+    const char *pages[] = {"Heavily      spaced      words", "Second      page"};
+    char path[256];
+    temp_path(path, sizeof(path), "spaced.pdf");
+    ASSERT_EQ(0, write_pdf(path, pages, 2), "write fixture");
+
+    struct CExtractor *extractor = extractous_extractor_new();
+    ASSERT_NOT_NULL(extractor, "extractor");
+    char *content = NULL;
+    struct CMetadata *metadata = NULL;
+
+    int result = extractous_extractor_extract_file_to_string(extractor, path, &content, &metadata);
+    ASSERT_EQ(ERR_OK, result, "default result");
+    ASSERT_TRUE(strstr(content, "  ") != NULL || strstr(content, "\n\n") != NULL,
+                "layout whitespace kept by default");
+    extractous_extraction_result_free(content, metadata);
+
+    extractous_extractor_set_collapse_whitespace_mut(extractor, true);
+    result = extractous_extractor_extract_file_to_string(extractor, path, &content, &metadata);
+    ASSERT_EQ(ERR_OK, result, "collapsed result");
+    ASSERT_NULL(strstr(content, "  "), "no runs of spaces");
+    ASSERT_NULL(strstr(content, "\n\n"), "no runs of newlines");
+    ASSERT_NOT_NULL(strstr(content, "Heavily spaced words"), "words separated by one space");
+    const char *second = strstr(content, "Second page");
+    ASSERT_TRUE(second != NULL && second > content && second[-1] == '\n', "page boundary kept as a newline");
+    size_t len = strlen(content);
+    ASSERT_TRUE(len > 0 && content[0] != ' ' && content[0] != '\n' && content[len - 1] != '\n',
+                "trimmed");
+    extractous_extraction_result_free(content, metadata);
+
+    extractous_extractor_free(extractor);
+    remove(path);
+}
+
+TEST(extract_file_to_string_lang) {
+    const char *english =
+        "The quick brown fox jumps over the lazy dog. This sentence is written in plain English, "
+        "and it should be easy for any language detector to recognise with confidence.\n";
+    char english_path[256];
+    char short_path[256];
+    temp_path(english_path, sizeof(english_path), "lang_english.txt");
+    temp_path(short_path, sizeof(short_path), "lang_short.txt");
+    ASSERT_EQ(0, write_file(english_path, english, strlen(english)), "write english fixture");
+    ASSERT_EQ(0, write_file(short_path, "abc", 3), "write short fixture");
+
+    struct CExtractor *extractor = extractous_extractor_new();
+    ASSERT_NOT_NULL(extractor, "extractor");
+    char *content = NULL;
+    struct CMetadata *metadata = NULL;
+    char *language = NULL;
+    int confidence = -1;
+
+    int result = extractous_extractor_extract_file_to_string_lang(
+        extractor, english_path, &content, &metadata, &language, &confidence
+    );
+    ASSERT_EQ(ERR_OK, result, "english result");
+    ASSERT_TRUE(language != NULL && strcmp(language, "en") == 0, "detected as english");
+    ASSERT_TRUE(confidence >= 80 && confidence <= 100, "high confidence");
+    extractous_string_free(language);
+    extractous_extraction_result_free(content, metadata);
+
+    result = extractous_extractor_extract_file_to_string_lang(
+        extractor, short_path, &content, &metadata, &language, &confidence
+    );
+    ASSERT_EQ(ERR_OK, result, "short result");
+    ASSERT_NULL(language, "too short to classify");
+    ASSERT_EQ(0, confidence, "no confidence");
+    extractous_extraction_result_free(content, metadata);
+
+    extractous_extractor_free(extractor);
+    remove(english_path);
+    remove(short_path);
+}
+
+TEST(extract_file_window) {
+    const char *text = "Line one of the document.\nL\xC3\xADne two, with an accent.\nLine three ends it.\n";
+    char path[256];
+    temp_path(path, sizeof(path), "window.txt");
+    ASSERT_EQ(0, write_file(path, text, strlen(text)), "write fixture");
+
+    struct CExtractor *extractor = extractous_extractor_new();
+    ASSERT_NOT_NULL(extractor, "extractor");
+    char *full = NULL;
+    struct CMetadata *metadata = NULL;
+    int result = extractous_extractor_extract_file_to_string(extractor, path, &full, &metadata);
+    ASSERT_EQ(ERR_OK, result, "full result");
+    extractous_metadata_free(metadata);
+
+    // Page through the content seven characters at a time.
+    char reassembled[512] = "";
+    size_t total = 0;
+    size_t windows = 0;
+    for (size_t offset = 0; windows == 0 || offset < total; offset += 7, windows++) {
+        char *window = NULL;
+        result = extractous_extractor_extract_file_window(
+            extractor, path, offset, 7, &window, &total, &metadata
+        );
+        ASSERT_EQ(ERR_OK, result, "window result");
+        ASSERT_TRUE(strlen(reassembled) + strlen(window) < sizeof(reassembled), "fits");
+        strcat(reassembled, window);
+        extractous_extraction_result_free(window, metadata);
+    }
+    ASSERT_TRUE(strcmp(reassembled, full) == 0, "windows reassemble the content");
+    ASSERT_TRUE(total < strlen(full), "total counts characters, not bytes");
+    ASSERT_EQ((int)((total + 6) / 7), (int)windows, "window count");
+
+    char *window = NULL;
+    result = extractous_extractor_extract_file_window(
+        extractor, path, total + 10, 7, &window, &total, &metadata
+    );
+    ASSERT_EQ(ERR_OK, result, "past the end");
+    ASSERT_TRUE(window != NULL && window[0] == '\0', "empty window past the end");
+    extractous_extraction_result_free(window, metadata);
+
+    extractous_string_free(full);
+    extractous_extractor_free(extractor);
+    remove(path);
+}
+
+struct log_capture {
+    int warnings;
+    char last[512];
+    int infos;
+    char last_info[512];
+};
+
+static void capture_log(void *ctx, int level, const char *message) {
+    struct log_capture *capture = ctx;
+    if (level == LOG_WARN) {
+        capture->warnings++;
+        snprintf(capture->last, sizeof(capture->last), "%s", message);
+    } else if (level == LOG_INFO) {
+        capture->infos++;
+        snprintf(capture->last_info, sizeof(capture->last_info), "%s", message);
+    }
+}
+
+TEST(extract_file_max_embedded_bytes) {
+    char path[256];
+    temp_path(path, sizeof(path), "embedded_limit.zip");
+    static char big[8192];
+    for (size_t i = 0; i + 10 <= sizeof(big); i += 10) {
+        memcpy(big + i, "oversized ", 10);
+    }
+    struct zip_entry entries[] = {
+        {"small.txt", "small entry text"},
+        {"big.txt", big},
+    };
+    size_t lens[] = {strlen(entries[0].data), sizeof(big)};
+    ASSERT_EQ(0, write_zip_sized(path, entries, lens, 2), "write fixture");
+
+    struct log_capture capture = {0};
+    extractous_set_log_callback(capture_log, &capture);
+    struct CExtractor *extractor = extractous_extractor_new();
+    ASSERT_NOT_NULL(extractor, "extractor");
+    char *content = NULL;
+    struct CMetadata *metadata = NULL;
+
+    int result = extractous_extractor_extract_file_to_string(extractor, path, &content, &metadata);
+    ASSERT_EQ(ERR_OK, result, "unlimited result");
+    ASSERT_NOT_NULL(strstr(content, "oversized"), "big entry without a limit");
+    ASSERT_EQ(0, capture.warnings, "no warnings without a limit");
+    extractous_string_free(content);
+    extractous_metadata_free(metadata);
+
+    extractous_extractor_set_max_embedded_bytes_mut(extractor, 1024);
+    result = extractous_extractor_extract_file_to_string(extractor, path, &content, &metadata);
+    ASSERT_EQ(ERR_OK, result, "limited result");
+    ASSERT_NOT_NULL(strstr(content, "small entry text"), "small entry kept");
+    ASSERT_NULL(strstr(content, "oversized"), "big entry skipped");
+    ASSERT_EQ(1, capture.warnings, "one warning");
+    ASSERT_NOT_NULL(strstr(capture.last, "big.txt"), "warning names the entry");
+    extractous_string_free(content);
+    extractous_metadata_free(metadata);
+
+    char *json = NULL;
+    size_t json_len = 0;
+    result = extractous_extractor_extract_file_to_json(extractor, path, &json, &json_len);
+    ASSERT_EQ(ERR_OK, result, "json result");
+    ASSERT_NULL(strstr(json, "oversized"), "big entry skipped by other entry points");
+    ASSERT_EQ(2, capture.warnings, "warned again");
+    extractous_string_free(json);
+
+    char docx[256];
+    temp_path(docx, sizeof(docx), "embedded_limit.docx");
+    ASSERT_EQ(0, write_image_docx(docx, 1), "write docx");
+    extractous_extractor_set_max_embedded_bytes_mut(extractor, 16);
+    result = extractous_extractor_extract_file_to_string(extractor, docx, &content, &metadata);
+    ASSERT_EQ(ERR_OK, result, "docx result");
+    ASSERT_NOT_NULL(strstr(content, "Has a picture"), "document parts are not limited");
+    ASSERT_EQ(2, capture.warnings, "no warning for document parts");
+    extractous_extraction_result_free(content, metadata);
+
+    extractous_set_log_callback(NULL, NULL);
+    extractous_extractor_free(extractor);
+    remove(path);
+    remove(docx);
+}
+
+TEST(extract_file_max_embedded_bytes_understated) {
+    char path[256];
+    temp_path(path, sizeof(path), "embedded_understated.zip");
+    static char big[8192];
+    for (size_t i = 0; i + 10 <= sizeof(big); i += 10) {
+        memcpy(big + i, "oversized ", 10);
+    }
+    struct zip_entry entries[] = {
+        {"small.txt", "small entry text"},
+        {"big.txt", big},
+    };
+    size_t lens[] = {strlen(entries[0].data), sizeof(big)};
+    ASSERT_EQ(0, write_zip_sized(path, entries, lens, 2), "write fixture");
+
+    // Declare big.txt as 10 bytes in its local and central headers.
+    long local = 30 + 9 + 16;
+    long central = local + 30 + 7 + (long)sizeof(big) + 46 + 9;
+    FILE *f = fopen(path, "r+b");
+    ASSERT_NOT_NULL(f, "open fixture");
+    fseek(f, local + 22, SEEK_SET);
+    put_u32(f, 10);
+    fseek(f, central + 24, SEEK_SET);
+    put_u32(f, 10);
+    fclose(f);
+
+    struct log_capture capture = {0};
+    extractous_set_log_callback(capture_log, &capture);
+    struct CExtractor *extractor = extractous_extractor_new();
+    ASSERT_NOT_NULL(extractor, "extractor");
+    extractous_extractor_set_max_embedded_bytes_mut(extractor, 1024);
+    char *content = NULL;
+    struct CMetadata *metadata = NULL;
+
+    int result = extractous_extractor_extract_file_to_string(extractor, path, &content, &metadata);
+    ASSERT_EQ(ERR_OK, result, "limited result");
+    ASSERT_NOT_NULL(strstr(content, "small entry text"), "small entry kept");
+    ASSERT_NULL(strstr(content, "oversized"), "understated entry skipped");
+    ASSERT_EQ(1, capture.warnings, "one warning");
+    extractous_extraction_result_free(content, metadata);
+
+    extractous_set_log_callback(NULL, NULL);
+    extractous_extractor_free(extractor);
+    remove(path);
+}
+
+TEST(compute_doc_id) {
+    char first[256], second[256], changed[256];
+    temp_path(first, sizeof(first), "doc_id_a.txt");
+    temp_path(second, sizeof(second), "doc_id_b.txt");
+    temp_path(changed, sizeof(changed), "doc_id_c.txt");
+    const char *text = "Same bytes in both files.";
+    ASSERT_EQ(0, write_file(first, text, strlen(text)), "write first");
+    ASSERT_EQ(0, write_file(second, text, strlen(text)), "write second");
+    ASSERT_EQ(0, write_file(changed, "Same bytes in both files!", strlen(text)), "write changed");
+
+    struct CExtractor *extractor = extractous_extractor_new();
+    ASSERT_NOT_NULL(extractor, "extractor");
+    char *first_id = extractous_extractor_compute_doc_id(extractor, first);
+    char *second_id = extractous_extractor_compute_doc_id(extractor, second);
+    char *changed_id = extractous_extractor_compute_doc_id(NULL, changed);
+    ASSERT_NOT_NULL(first_id, "first id");
+    ASSERT_NOT_NULL(second_id, "second id");
+    ASSERT_NOT_NULL(changed_id, "changed id");
+    ASSERT_EQ(64, (int)strlen(first_id), "hex SHA-256");
+    ASSERT_TRUE(strcmp(first_id, second_id) == 0, "identical files share an id");
+    ASSERT_TRUE(strcmp(first_id, changed_id) != 0, "modified file has a new id");
+    extractous_string_free(first_id);
+    extractous_string_free(second_id);
+    extractous_string_free(changed_id);
+
+    char missing[256];
+    temp_path(missing, sizeof(missing), "doc_id_missing.txt");
+    remove(missing);
+    ASSERT_NULL(extractous_extractor_compute_doc_id(extractor, missing), "missing file");
+    char *id = NULL;
+    ASSERT_EQ(ERR_IO_ERROR, extractous_extractor_compute_doc_id_checked(extractor, missing, &id),
+              "missing file code");
+    ASSERT_NULL(id, "no id");
+
+    extractous_extractor_free(extractor);
+    remove(first);
+    remove(second);
+    remove(changed);
+}
+
+TEST(extract_empty_input) {
+    char path[256];
+    temp_path(path, sizeof(path), "empty_input.pdf");
+    ASSERT_EQ(0, write_file(path, "", 0), "write fixture");
+
+    struct CExtractor *extractor = extractous_extractor_new();
+    ASSERT_NOT_NULL(extractor, "extractor");
+    char *content = NULL;
+    struct CMetadata *metadata = NULL;
+
+    int result = extractous_extractor_extract_file_to_string(extractor, path, &content, &metadata);
+    ASSERT_EQ(ERR_OK, result, "empty file");
+    ASSERT_TRUE(content != NULL && content[0] == '\0', "empty file content");
+    ASSERT_EQ(0, (int)metadata->len, "empty file metadata");
+    extractous_extraction_result_free(content, metadata);
+
+    const uint8_t byte = 0;
+    result = extractous_extractor_extract_bytes_to_string(extractor, &byte, 0, &content, &metadata);
+    ASSERT_EQ(ERR_OK, result, "empty bytes");
+    ASSERT_TRUE(content != NULL && content[0] == '\0', "empty bytes content");
+    ASSERT_EQ(0, (int)metadata->len, "empty bytes metadata");
+    extractous_extraction_result_free(content, metadata);
+
+    int truncated = 1;
+    result = extractous_extractor_extract_bytes_to_string_ex(extractor, &byte, 0, &content, &metadata, &truncated);
+    ASSERT_EQ(ERR_OK, result, "empty bytes_ex");
+    ASSERT_TRUE(content != NULL && content[0] == '\0', "empty bytes_ex content");
+    ASSERT_EQ(0, (int)metadata->len, "empty bytes_ex metadata");
+    ASSERT_EQ(0, truncated, "empty bytes_ex not truncated");
+    extractous_extraction_result_free(content, metadata);
+
+    char unset = 0;
+    char *mime = &unset;
+    result = extractous_extractor_extract_bytes_to_string_detect(extractor, &byte, 0, &content, &metadata, &mime);
+    ASSERT_EQ(ERR_OK, result, "empty bytes_detect");
+    ASSERT_TRUE(content != NULL && content[0] == '\0', "empty bytes_detect content");
+    ASSERT_EQ(0, (int)metadata->len, "empty bytes_detect metadata");
+    ASSERT_NULL(mime, "empty bytes_detect has no MIME type");
+    extractous_extraction_result_free(content, metadata);
+
+    struct CIoSlice iov[2] = {{&byte, 0}, {NULL, 0}};
+    result = extractous_extractor_extract_iovec_to_string(extractor, iov, 2, &content, &metadata);
+    ASSERT_EQ(ERR_OK, result, "empty iovec");
+    ASSERT_TRUE(content != NULL && content[0] == '\0', "empty iovec content");
+    ASSERT_EQ(0, (int)metadata->len, "empty iovec metadata");
+    extractous_extraction_result_free(content, metadata);
+
+    truncated = 1;
+    result = extractous_extractor_extract_file_to_string_ex(extractor, path, &content, &metadata, &truncated);
+    ASSERT_EQ(ERR_OK, result, "empty file, other entry points");
+    ASSERT_TRUE(content != NULL && content[0] == '\0', "other entry point content");
+    ASSERT_EQ(0, (int)metadata->len, "other entry point metadata");
+    extractous_extraction_result_free(content, metadata);
+
+    result = extractous_extractor_extract_file_as_format(extractor, path, OUTPUT_XML, &content, &metadata);
+    ASSERT_EQ(ERR_OK, result, "empty file as XML");
+    ASSERT_TRUE(content != NULL && content[0] == '\0', "empty XML content");
+    extractous_extraction_result_free(content, metadata);
+
+    struct CCancelToken *token = extractous_cancel_token_new();
+    result = extractous_extractor_extract_file_cancellable(extractor, path, token, &content, &metadata);
+    ASSERT_EQ(ERR_OK, result, "empty file on a worker");
+    ASSERT_TRUE(content != NULL && content[0] == '\0', "worker content");
+    ASSERT_EQ(0, (int)metadata->len, "worker metadata");
+    extractous_extraction_result_free(content, metadata);
+    extractous_cancel_token_free(token);
+
+    struct CStreamReader *reader = NULL;
+    uint8_t buffer[16];
+    size_t bytes_read = 1;
+    result = extractous_extractor_extract_file(extractor, path, &reader, &metadata);
+    ASSERT_EQ(ERR_OK, result, "empty file stream");
+    ASSERT_EQ(ERR_OK, extractous_stream_read(reader, buffer, sizeof(buffer), &bytes_read), "read");
+    ASSERT_EQ(0, (int)bytes_read, "stream at its end");
+    ASSERT_EQ(0, (int)metadata->len, "empty stream metadata");
+    extractous_stream_free(reader);
+    extractous_metadata_free(metadata);
+
+    bytes_read = 1;
+    result = extractous_extractor_extract_bytes(extractor, &byte, 0, &reader, &metadata);
+    ASSERT_EQ(ERR_OK, result, "empty bytes stream");
+    ASSERT_EQ(ERR_OK, extractous_stream_read(reader, buffer, sizeof(buffer), &bytes_read), "read bytes");
+    ASSERT_EQ(0, (int)bytes_read, "bytes stream at its end");
+    extractous_stream_free(reader);
+    extractous_metadata_free(metadata);
+
+    extractous_extractor_free(extractor);
+    remove(path);
+}
+
+TEST(extract_resource_name) {
+    const char *text = "Named resource document.";
+    char path[256];
+    temp_path(path, sizeof(path), "resource_name.txt");
+    ASSERT_EQ(0, write_file(path, text, strlen(text)), "write fixture");
+
+    struct CExtractor *extractor = extractous_extractor_new();
+    ASSERT_NOT_NULL(extractor, "extractor");
+    char *content = NULL;
+    struct CMetadata *metadata = NULL;
+
+    int result = extractous_extractor_extract_file_to_string(extractor, path, &content, &metadata);
+    ASSERT_EQ(ERR_OK, result, "file result");
+    const char *name = extractous_metadata_get_resource_name(metadata);
+    ASSERT_TRUE(name != NULL && strcmp(name, "resource_name.txt") == 0, "file basename");
+    extractous_extraction_result_free(content, metadata);
+
+    result = extractous_extractor_extract_bytes_to_string_named(
+        extractor, (const uint8_t *)text, strlen(text), "upload-42.txt", &content, &metadata
+    );
+    ASSERT_EQ(ERR_OK, result, "bytes result");
+    name = extractous_metadata_get_resource_name(metadata);
+    ASSERT_TRUE(name != NULL && strcmp(name, "upload-42.txt") == 0, "bytes name hint");
+    extractous_extraction_result_free(content, metadata);
+
+    struct CStreamReader *reader = NULL;
+    result = extractous_extractor_extract_bytes_named(
+        extractor, (const uint8_t *)text, strlen(text), "stream-7.txt", &reader, &metadata
+    );
+    ASSERT_EQ(ERR_OK, result, "stream result");
+    name = extractous_metadata_get_resource_name(metadata);
+    ASSERT_TRUE(name != NULL && strcmp(name, "stream-7.txt") == 0, "stream name hint");
+    extractous_stream_free(reader);
+    extractous_metadata_free(metadata);
+
+    struct CMetadata empty = {NULL, NULL, 0};
+    ASSERT_NULL(extractous_metadata_get_resource_name(&empty), "absent name");
+    ASSERT_NULL(extractous_metadata_get_resource_name(NULL), "null metadata");
+
+    extractous_extractor_free(extractor);
+    remove(path);
+}
+
+// Only exported by libraries built with the `fault-injection` feature.
+extern void extractous_debug_fail_next_jni_calls(int count) __attribute__((weak));
+
+TEST(extract_jni_retry) {
+    if (extractous_debug_fail_next_jni_calls == NULL) {
+        printf(COLOR_YELLOW "  SKIP: library built without fault-injection\n" COLOR_RESET);
+        return;
+    }
+    const char *text = "Retried document.";
+    struct CExtractor *extractor = extractous_extractor_new();
+    ASSERT_NOT_NULL(extractor, "extractor");
+    char *content = NULL;
+    struct CMetadata *metadata = NULL;
+
+    extractous_debug_fail_next_jni_calls(1);
+    int result = extractous_extractor_extract_bytes_to_string(
+        extractor, (const uint8_t *)text, strlen(text), &content, &metadata
+    );
+    ASSERT_EQ(ERR_EXTRACTION_FAILED, result, "no retry by default");
+
+    extractous_extractor_set_jni_retry_mut(extractor, 2);
+    extractous_debug_fail_next_jni_calls(1);
+    result = extractous_extractor_extract_bytes_to_string(
+        extractor, (const uint8_t *)text, strlen(text), &content, &metadata
+    );
+    ASSERT_EQ(ERR_OK, result, "succeeds on retry");
+    ASSERT_NOT_NULL(strstr(content, "Retried document."), "content");
+    extractous_extraction_result_free(content, metadata);
+
+    extractous_debug_fail_next_jni_calls(3);
+    result = extractous_extractor_extract_bytes_to_string(
+        extractor, (const uint8_t *)text, strlen(text), &content, &metadata
+    );
+    ASSERT_EQ(ERR_EXTRACTION_FAILED, result, "gives up after the retries");
+
+    char path[256];
+    temp_path(path, sizeof(path), "jni_retry.txt");
+    ASSERT_EQ(0, write_file(path, text, strlen(text)), "write fixture");
+    extractous_debug_fail_next_jni_calls(1);
+    result = extractous_extractor_extract_file_to_string(extractor, path, &content, &metadata);
+    ASSERT_EQ(ERR_OK, result, "file retried");
+    extractous_extraction_result_free(content, metadata);
+
+    uint64_t elapsed_ms = 0;
+    extractous_debug_fail_next_jni_calls(1);
+    result = extractous_extractor_extract_file_to_string_timed(
+        extractor, path, &content, &metadata, &elapsed_ms
+    );
+    ASSERT_EQ(ERR_OK, result, "timed retried");
+    ASSERT_TRUE(elapsed_ms >= 10, "duration covers the retry and its wait");
+    extractous_extraction_result_free(content, metadata);
+
+    extractous_debug_fail_next_jni_calls(1);
+    result = extractous_extractor_extract_file_as_format(extractor, path, OUTPUT_XML, &content, &metadata);
+    ASSERT_EQ(ERR_OK, result, "format override retried");
+    extractous_extraction_result_free(content, metadata);
+
+    uint8_t buf[256];
+    size_t len = 0;
+    extractous_debug_fail_next_jni_calls(1);
+    result = extractous_extractor_extract_file_into(extractor, path, buf, sizeof(buf), &len, &metadata);
+    ASSERT_EQ(ERR_OK, result, "caller buffer retried");
+    extractous_metadata_free(metadata);
+
+    uint64_t deadline = (uint64_t)time(NULL) * 1000 + 60000;
+    extractous_debug_fail_next_jni_calls(1);
+    result = extractous_extractor_extract_file_with_deadline(extractor, path, deadline, &content, &metadata);
+    ASSERT_EQ(ERR_OK, result, "deadline worker retried");
+    extractous_extraction_result_free(content, metadata);
+
+    struct COutlineEntry *entries = NULL;
+    size_t count = 0;
+    extractous_debug_fail_next_jni_calls(1);
+    result = extractous_extractor_extract_file_outline(extractor, path, &entries, &count);
+    ASSERT_EQ(ERR_OK, result, "structure retried");
+    extractous_outline_free(entries, count);
+    remove(path);
+
+    extractous_debug_fail_next_jni_calls(0);
+    extractous_extractor_free(extractor);
+}
+
+TEST(extract_embedded_at) {
+    const char *eml =
+        "From: sender@example.com\r\n"
+        "To: receiver@example.com\r\n"
+        "Subject: Two attachments\r\n"
+        "MIME-Version: 1.0\r\n"
+        "Content-Type: multipart/mixed; boundary=\"BOUNDARY\"\r\n"
+        "\r\n"
+        "--BOUNDARY\r\n"
+        "Content-Type: text/plain\r\n"
+        "\r\n"
+        "See the attached files.\r\n"
+        "--BOUNDARY\r\n"
+        "Content-Type: text/plain; name=\"notes.txt\"\r\n"
+        "Content-Disposition: attachment; filename=\"notes.txt\"\r\n"
+        "\r\n"
+        "First attachment.\r\n"
+        "--BOUNDARY\r\n"
+        "Content-Type: application/octet-stream; name=\"data.bin\"\r\n"
+        "Content-Disposition: attachment; filename=\"data.bin\"\r\n"
+        "Content-Transfer-Encoding: base64\r\n"
+        "\r\n"
+        "AAEC/f7/SGVsbG8=\r\n"
+        "--BOUNDARY--\r\n";
+    const uint8_t expected[] = {0x00, 0x01, 0x02, 0xfd, 0xfe, 0xff, 'H', 'e', 'l', 'l', 'o'};
+    char path[256];
+    temp_path(path, sizeof(path), "attachments.eml");
+    ASSERT_EQ(0, write_file(path, eml, strlen(eml)), "write fixture");
+
+    struct CExtractor *extractor = extractous_extractor_new();
+    ASSERT_NOT_NULL(extractor, "extractor");
+    uint8_t *bytes = NULL;
+    size_t len = 0;
+    char *mime = NULL;
+    struct CMetadata *metadata = NULL;
+
+    int result = extractous_extractor_extract_embedded_at(extractor, path, 1, &bytes, &len, &mime, &metadata);
+    ASSERT_EQ(ERR_OK, result, "result");
+    ASSERT_EQ((int)sizeof(expected), (int)len, "attachment length");
+    ASSERT_EQ(0, memcmp(bytes, expected, sizeof(expected)), "attachment bytes");
+    const char *name = extractous_metadata_get_resource_name(metadata);
+    ASSERT_TRUE(name != NULL && strcmp(name, "data.bin") == 0, "attachment name");
+    extractous_buffer_free(bytes, len);
+    extractous_string_free(mime);
+    extractous_metadata_free(metadata);
+
+    result = extractous_extractor_extract_embedded_at(extractor, path, 0, &bytes, &len, &mime, &metadata);
+    ASSERT_EQ(ERR_OK, result, "first attachment");
+    ASSERT_EQ((int)strlen("First attachment."), (int)len, "first attachment length");
+    ASSERT_EQ(0, memcmp(bytes, "First attachment.", len), "first attachment bytes");
+    ASSERT_TRUE(strcmp(mime, "text/plain") == 0, "first attachment MIME type");
+    extractous_buffer_free(bytes, len);
+    extractous_string_free(mime);
+    extractous_metadata_free(metadata);
+
+    result = extractous_extractor_extract_embedded_at(extractor, path, 2, &bytes, &len, &mime, &metadata);
+    ASSERT_EQ(ERR_INDEX_OUT_OF_RANGE, result, "out of range");
+
+    extractous_extractor_free(extractor);
+    remove(path);
+}
+
+// Writes the directory entry `name` of an OLE compound file into `entry`.
+static void ole_dir_entry(uint8_t *entry, const char *name, uint8_t type) {
+    size_t len = strlen(name);
+    for (size_t i = 0; i < len; i++) {
+        entry[i * 2] = (uint8_t)name[i];
+    }
+    entry[64] = (uint8_t)((len + 1) * 2);
+    entry[66] = type;
+}
+
+TEST(is_encrypted_file) {
+    char plain_pdf[256], encrypted_pdf[256], encrypted_xlsx[256];
+    temp_path(plain_pdf, sizeof(plain_pdf), "plain.pdf");
+    temp_path(encrypted_pdf, sizeof(encrypted_pdf), "encrypted.pdf");
+    temp_path(encrypted_xlsx, sizeof(encrypted_xlsx), "encrypted.xlsx");
+
+    const char *pages[] = {"Nothing to hide"};
+    ASSERT_EQ(0, write_pdf(plain_pdf, pages, 1), "write plain pdf");
+    // The check only reads the trailer, so the streams need not be encrypted.
+    const char *pdf =
+        "%PDF-1.4\n"
+        "1 0 obj << /Type /Catalog /Pages 2 0 R >> endobj\n"
+        "2 0 obj << /Type /Pages /Kids [] /Count 0 >> endobj\n"
+        "3 0 obj << /Filter /Standard /V 2 /R 3 /Length 128 /P -4 "
+        "/O (0123456789abcdef0123456789abcdef) /U (0123456789abcdef0123456789abcdef) >> endobj\n"
+        "trailer << /Size 4 /Root 1 0 R /Encrypt 3 0 R "
+        "/ID [<0123456789abcdef0123456789abcdef> <0123456789abcdef0123456789abcdef>] >>\n"
+        "%%EOF\n";
+    ASSERT_EQ(0, write_file(encrypted_pdf, pdf, strlen(pdf)), "write encrypted pdf");
+    // A password-protected XLSX is an OLE container with the encrypted
+    // package in a stream; only the header and directory are written here.
+    static uint8_t ole[1024];
+    memset(ole, 0, sizeof(ole));
+    memcpy(ole, "\xD0\xCF\x11\xE0\xA1\xB1\x1A\xE1", 8);
+    ole_dir_entry(ole + 512, "Root Entry", 5);
+    ole_dir_entry(ole + 640, "EncryptionInfo", 2);
+    ole_dir_entry(ole + 768, "EncryptedPackage", 2);
+    ASSERT_EQ(0, write_file(encrypted_xlsx, ole, sizeof(ole)), "write encrypted xlsx");
+
+    struct CExtractor *extractor = extractous_extractor_new();
+    ASSERT_NOT_NULL(extractor, "extractor");
+    int encrypted = -1;
+    ASSERT_EQ(ERR_OK, extractous_extractor_is_encrypted_file(extractor, encrypted_pdf, &encrypted), "encrypted pdf");
+    ASSERT_EQ(1, encrypted, "encrypted pdf detected");
+    ASSERT_EQ(ERR_OK, extractous_extractor_is_encrypted_file(extractor, plain_pdf, &encrypted), "plain pdf");
+    ASSERT_EQ(0, encrypted, "plain pdf not encrypted");
+    ASSERT_EQ(ERR_OK, extractous_extractor_is_encrypted_file(extractor, encrypted_xlsx, &encrypted), "encrypted xlsx");
+    ASSERT_EQ(1, encrypted, "encrypted xlsx detected");
+
+    extractous_extractor_free(extractor);
+    remove(plain_pdf);
+    remove(encrypted_pdf);
+    remove(encrypted_xlsx);
+}
+
+TEST(extract_max_metadata_entries) {
+    // An HTML page with 500 meta tags, each of which becomes an entry.
+    static char html[64 * 1024];
+    size_t len = (size_t)snprintf(html, sizeof(html), "<html><head><title>Many</title>");
+    for (int i = 0; i < 500; i++) {
+        len += (size_t)snprintf(html + len, sizeof(html) - len,
+                                "<meta name=\"custom-%03d\" content=\"value %d\">", i, i);
+    }
+    len += (size_t)snprintf(html + len, sizeof(html) - len, "</head><body><p>Body</p></body></html>");
+    char path[256];
+    temp_path(path, sizeof(path), "many_meta.html");
+    ASSERT_EQ(0, write_file(path, html, len), "write fixture");
+
+    struct log_capture capture = {0};
+    extractous_set_log_callback(capture_log, &capture);
+    struct CExtractor *extractor = extractous_extractor_new();
+    ASSERT_NOT_NULL(extractor, "extractor");
+    char *content = NULL;
+    struct CMetadata *metadata = NULL;
+
+    int result = extractous_extractor_extract_file_to_string(extractor, path, &content, &metadata);
+    ASSERT_EQ(ERR_OK, result, "unlimited result");
+    ASSERT_TRUE(metadata->len >= 500, "all entries without a limit");
+    size_t total = metadata->len;
+    ASSERT_EQ(0, capture.warnings, "nothing dropped without a limit");
+    extractous_extraction_result_free(content, metadata);
+
+    extractous_extractor_set_max_metadata_entries_mut(extractor, 50);
+    result = extractous_extractor_extract_file_to_string(extractor, path, &content, &metadata);
+    ASSERT_EQ(ERR_OK, result, "limited result");
+    ASSERT_EQ(50, (int)metadata->len, "entries capped");
+    ASSERT_EQ(1, capture.warnings, "drop logged");
+    char expected[64];
+    snprintf(expected, sizeof(expected), "dropped %d of %d", (int)total - 50, (int)total);
+    ASSERT_NOT_NULL(strstr(capture.last, expected), "drop count logged");
+    extractous_extraction_result_free(content, metadata);
+
+    char *json = NULL;
+    size_t json_len = 0;
+    result = extractous_extractor_extract_file_to_json(extractor, path, &json, &json_len);
+    ASSERT_EQ(ERR_OK, result, "limited JSON result");
+    int keys = 0;
+    for (const char *p = json; (p = strstr(p, "\":[")) != NULL; p++) {
+        keys++;
+    }
+    ASSERT_EQ(50, keys, "JSON entries capped");
+    ASSERT_EQ(2, capture.warnings, "JSON drop logged");
+    ASSERT_NOT_NULL(strstr(capture.last, expected), "JSON drop count logged");
+    extractous_string_free(json);
+
+    extractous_set_log_callback(NULL, NULL);
+    extractous_extractor_free(extractor);
+    remove(path);
+}
+
+TEST(extract_file_truncation_reason) {
+    static const char html[] = "<html><body><p>before\0after</p></body></html>";
+    const char *text = "A sentence long enough to be cut by every limit below.\n";
+    char txt_path[256];
+    char html_path[256];
+    temp_path(txt_path, sizeof(txt_path), "truncation_reason.txt");
+    temp_path(html_path, sizeof(html_path), "truncation_reason.html");
+    ASSERT_EQ(0, write_file(txt_path, text, strlen(text)), "write text fixture");
+    ASSERT_EQ(0, write_file(html_path, html, sizeof(html) - 1), "write html fixture");
+
+    struct CExtractor *extractor = extractous_extractor_new();
+    ASSERT_NOT_NULL(extractor, "extractor");
+    char *content = NULL;
+    struct CMetadata *metadata = NULL;
+    int reason = -1;
+
+    int result = extractous_extractor_extract_file_to_string_with_truncation_reason(
+        extractor, txt_path, &content, &metadata, &reason
+    );
+    ASSERT_EQ(ERR_OK, result, "plain result");
+    ASSERT_EQ(TRUNC_NONE, reason, "nothing cut");
+    extractous_extraction_result_free(content, metadata);
+
+    struct CExtractor *limited = extractous_extractor_new();
+    ASSERT_NOT_NULL(limited, "limited extractor");
+    extractous_extractor_set_extract_string_max_length_mut(limited, 10);
+    result = extractous_extractor_extract_file_to_string_with_truncation_reason(
+        limited, txt_path, &content, &metadata, &reason
+    );
+    ASSERT_EQ(ERR_OK, result, "max chars result");
+    ASSERT_EQ(TRUNC_MAX_CHARS, reason, "cut at max length");
+    extractous_extraction_result_free(content, metadata);
+    extractous_extractor_free(limited);
+
+    extractous_extractor_set_max_output_bytes_mut(extractor, 8);
+    result = extractous_extractor_extract_file_to_string_with_truncation_reason(
+        extractor, txt_path, &content, &metadata, &reason
+    );
+    ASSERT_EQ(ERR_OK, result, "max bytes result");
+    ASSERT_NOT_NULL(content, "max bytes content");
+    ASSERT_TRUE(strlen(content) <= 8, "content within byte cap");
+    ASSERT_EQ(TRUNC_MAX_BYTES, reason, "cut at byte cap");
+    extractous_extraction_result_free(content, metadata);
+    extractous_extractor_set_max_output_bytes_mut(extractor, 0);
+
+    ASSERT_EQ(ERR_OK, extractous_set_nul_byte_policy(NUL_POLICY_TRUNCATE), "set truncate");
+    result = extractous_extractor_extract_file_to_string_with_truncation_reason(
+        extractor, html_path, &content, &metadata, &reason
+    );
+    ASSERT_EQ(ERR_OK, result, "nul result");
+    ASSERT_NOT_NULL(content, "nul content");
+    ASSERT_TRUE(strstr(content, "after") == NULL, "text after nul cut");
+    ASSERT_EQ(TRUNC_NUL, reason, "cut at nul");
+    extractous_extraction_result_free(content, metadata);
+    ASSERT_EQ(ERR_OK, extractous_set_nul_byte_policy(NUL_POLICY_ERROR), "restore default");
+
+    ASSERT_EQ(
+        ERR_NULL_POINTER,
+        extractous_extractor_extract_file_to_string_with_truncation_reason(
+            extractor, txt_path, &content, &metadata, NULL
+        ),
+        "null reason"
+    );
+
+    extractous_extractor_free(extractor);
+    remove(txt_path);
+    remove(html_path);
+}
+
+TEST(extract_pdf_info) {
+    const char *pages[] = {"First page", "Second page", "Third page"};
+    char pdf_path[256];
+    char txt_path[256];
+    temp_path(pdf_path, sizeof(pdf_path), "pdf_info.pdf");
+    temp_path(txt_path, sizeof(txt_path), "pdf_info.txt");
+    ASSERT_EQ(0, write_pdf(pdf_path, pages, 3), "write pdf fixture");
+    ASSERT_EQ(0, write_file(txt_path, "not a pdf\n", 10), "write text fixture");
+
+    struct CExtractor *extractor = extractous_extractor_new();
+    ASSERT_NOT_NULL(extractor, "extractor");
+    struct CPdfInfo info;
+    memset(&info, 0, sizeof(info));
+
+    int result = extractous_extractor_extract_pdf_info(extractor, pdf_path, &info);
+    ASSERT_EQ(ERR_OK, result, "result");
+    ASSERT_EQ(3, info.page_count, "page count");
+    ASSERT_NOT_NULL(info.pdf_version, "version");
+    ASSERT_TRUE(strcmp(info.pdf_version, "1.4") == 0, "version from file");
+    ASSERT_EQ(0, info.encrypted, "not encrypted");
+    ASSERT_EQ(0, info.linearized, "not linearized");
+    extractous_pdf_info_free(&info);
+    ASSERT_NULL(info.pdf_version, "version released");
+    extractous_pdf_info_free(&info);
+
+    ASSERT_EQ(
+        ERR_UNSUPPORTED_FORMAT,
+        extractous_extractor_extract_pdf_info(extractor, txt_path, &info),
+        "not a pdf"
+    );
+    ASSERT_EQ(
+        ERR_NULL_POINTER,
+        extractous_extractor_extract_pdf_info(extractor, pdf_path, NULL),
+        "null info"
+    );
+
+    extractous_extractor_free(extractor);
+    remove(pdf_path);
+    remove(txt_path);
+}
+
+TEST(error_category_table) {
+    const int *codes = NULL;
+    const char *const *categories = NULL;
+    size_t count = 0;
+
+    ASSERT_EQ(ERR_OK, extractous_error_category_table(&codes, &categories, &count), "result");
+    ASSERT_NOT_NULL(codes, "codes");
+    ASSERT_NOT_NULL(categories, "categories");
+    ASSERT_EQ(21, (int)count, "every code listed");
+    for (size_t i = 0; i < count; i++) {
+        ASSERT_NOT_NULL(categories[i], "category");
+        ASSERT_TRUE(
+            strcmp(categories[i], extractous_error_category(codes[i])) == 0,
+            "table matches extractous_error_category"
+        );
+        ASSERT_TRUE(strcmp(categories[i], "unknown") != 0, "listed code is known");
+        for (size_t j = 0; j < i; j++) {
+            ASSERT_TRUE(codes[j] != codes[i], "codes are distinct");
+        }
+    }
+
+    const int *again = NULL;
+    ASSERT_EQ(ERR_OK, extractous_error_category_table(&again, &categories, &count), "second call");
+    ASSERT_TRUE(again == codes, "same static table");
+    ASSERT_EQ(
+        ERR_NULL_POINTER,
+        extractous_error_category_table(NULL, &categories, &count),
+        "null codes"
+    );
+}
+
+TEST(extract_file_positioned) {
+    const char *pages[] = {"Invoice total"};
+    char path[256];
+    temp_path(path, sizeof(path), "positioned.pdf");
+    ASSERT_EQ(0, write_pdf(path, pages, 1), "write fixture");
+
+    struct CExtractor *extractor = extractous_extractor_new();
+    ASSERT_NOT_NULL(extractor, "extractor");
+    struct CTextRun *runs = NULL;
+    size_t count = 0;
+
+    int result = extractous_extractor_extract_file_positioned(extractor, path, &runs, &count);
+    ASSERT_EQ(ERR_INVALID_CONFIG, result, "coordinates disabled");
+
+    // The core reports no text positions, so this fails loudly rather than
+    // returning runs without coordinates.
+    extractous_extractor_set_include_coordinates_mut(extractor, true);
+    result = extractous_extractor_extract_file_positioned(extractor, path, &runs, &count);
+    ASSERT_EQ(ERR_UNSUPPORTED_FEATURE, result, "no coordinates from core");
+    ASSERT_NULL(runs, "no runs");
+    ASSERT_EQ(0, (int)count, "no count");
+    ASSERT_TRUE(
+        strcmp("unsupported_feature", extractous_error_category(result)) == 0, "category"
+    );
+    extractous_text_runs_free(runs, count);
+
+    extractous_extractor_free(extractor);
+    remove(path);
+}
+
+TEST(extract_line_ending) {
+    const char *text = "one\r\ntwo\rthree\nfour\n";
+    char path[256];
+    temp_path(path, sizeof(path), "line_ending.txt");
+    ASSERT_EQ(0, write_file(path, text, strlen(text)), "write fixture");
+
+    struct CExtractor *extractor = extractous_extractor_new();
+    ASSERT_NOT_NULL(extractor, "extractor");
+    char *content = NULL;
+    struct CMetadata *metadata = NULL;
+
+    extractous_extractor_set_line_ending_mut(extractor, LINE_ENDING_LF);
+    int result = extractous_extractor_extract_file_to_string(extractor, path, &content, &metadata);
+    ASSERT_EQ(ERR_OK, result, "lf result");
+    ASSERT_NOT_NULL(content, "lf content");
+    ASSERT_NULL(strchr(content, '\r'), "no carriage returns");
+    ASSERT_TRUE(strstr(content, "one\ntwo\nthree\nfour") != NULL, "uniform line feeds");
+    extractous_extraction_result_free(content, metadata);
+
+    extractous_extractor_set_line_ending_mut(extractor, LINE_ENDING_CRLF);
+    result = extractous_extractor_extract_file_to_string(extractor, path, &content, &metadata);
+    ASSERT_EQ(ERR_OK, result, "crlf result");
+    ASSERT_NOT_NULL(content, "crlf content");
+    ASSERT_TRUE(strstr(content, "one\r\ntwo\r\nthree\r\nfour") != NULL, "uniform crlf");
+    for (const char *p = strchr(content, '\n'); p != NULL; p = strchr(p + 1, '\n')) {
+        ASSERT_TRUE(p > content && p[-1] == '\r', "every line feed follows a carriage return");
+    }
+    extractous_extraction_result_free(content, metadata);
+
+    extractous_extractor_free(extractor);
+    remove(path);
+}
+
+TEST(extract_file_to_utf16) {
+    // "Grüße 𝄞 日本", with a character outside the BMP.
+    const char *text = "Gr\xC3\xBC\xC3\x9F" "e \xF0\x9D\x84\x9E \xE6\x97\xA5\xE6\x9C\xAC\n";
+    static const uint16_t expected[] = {
+        'G', 'r', 0x00FC, 0x00DF, 'e', ' ', 0xD834, 0xDD1E, ' ', 0x65E5, 0x672C,
+    };
+    const size_t expected_len = sizeof(expected) / sizeof(expected[0]);
+    char path[256];
+    temp_path(path, sizeof(path), "utf16.txt");
+    ASSERT_EQ(0, write_file(path, text, strlen(text)), "write fixture");
+
+    struct CExtractor *extractor = extractous_extractor_new();
+    ASSERT_NOT_NULL(extractor, "extractor");
+    uint16_t *content = NULL;
+    size_t len = 0;
+    struct CMetadata *metadata = NULL;
+
+    int result = extractous_extractor_extract_file_to_utf16(
+        extractor, path, &content, &len, &metadata
+    );
+    ASSERT_EQ(ERR_OK, result, "result");
+    ASSERT_NOT_NULL(content, "content");
+    ASSERT_NOT_NULL(metadata, "metadata");
+    ASSERT_EQ(0, content[len], "terminated");
+    int found = 0;
+    for (size_t i = 0; !found && i + expected_len <= len; i++) {
+        found = memcmp(content + i, expected, sizeof(expected)) == 0;
+    }
+    ASSERT_TRUE(found, "matches reference encoding");
+    extractous_utf16_free(content, len);
+    extractous_metadata_free(metadata);
+
+    ASSERT_EQ(
+        ERR_NULL_POINTER,
+        extractous_extractor_extract_file_to_utf16(extractor, path, &content, NULL, &metadata),
+        "null length"
+    );
+
+    extractous_extractor_free(extractor);
+    remove(path);
+}
+
+TEST(extract_offline) {
+    // A listener the external entity points at; nothing should connect.
+    int listener = socket(AF_INET, SOCK_STREAM, 0);
+    struct sockaddr_in addr = {0};
+    addr.sin_family = AF_INET;
+    addr.sin_addr.s_addr = htonl(INADDR_LOOPBACK);
+    socklen_t addr_len = sizeof(addr);
+    ASSERT_TRUE(listener >= 0, "socket");
+    ASSERT_EQ(0, bind(listener, (struct sockaddr *)&addr, sizeof(addr)), "bind");
+    ASSERT_EQ(0, listen(listener, 1), "listen");
+    ASSERT_EQ(0, getsockname(listener, (struct sockaddr *)&addr, &addr_len), "getsockname");
+    int port = ntohs(addr.sin_port);
+
+    char xml[512];
+    snprintf(xml, sizeof(xml),
+             "<?xml version=\"1.0\"?>\n"
+             "<!DOCTYPE doc [<!ENTITY ext SYSTEM \"http://127.0.0.1:%d/entity\">]>\n"
+             "<doc><p>Offline document</p><p>&ext;</p></doc>\n",
+             port);
+    char path[256];
+    temp_path(path, sizeof(path), "offline.xml");
+    ASSERT_EQ(0, write_file(path, xml, strlen(xml)), "write fixture");
+
+    struct CExtractor *extractor = extractous_extractor_new();
+    ASSERT_NOT_NULL(extractor, "extractor");
+    extractous_extractor_set_offline_mut(extractor, true);
+    char *content = NULL;
+    struct CMetadata *metadata = NULL;
+
+    int result = extractous_extractor_extract_file_to_string(extractor, path, &content, &metadata);
+    ASSERT_EQ(ERR_INVALID_CONFIG, result, "external entity refused offline");
+    ASSERT_NULL(content, "no content");
+    ASSERT_TRUE(last_debug_contains("/entity"), "error names the url");
+    result = extractous_extractor_extract_bytes_to_string(
+        extractor, (const uint8_t *)xml, strlen(xml), &content, &metadata
+    );
+    ASSERT_EQ(ERR_INVALID_CONFIG, result, "external entity in bytes refused offline");
+
+    char zip_path[256];
+    temp_path(zip_path, sizeof(zip_path), "offline.zip");
+    struct zip_entry entries[] = {{"notes.txt", "plain notes"}, {"doc.xml", xml}};
+    ASSERT_EQ(0, write_zip(zip_path, entries, 2), "write zip fixture");
+    result = extractous_extractor_extract_file_to_string(extractor, zip_path, &content, &metadata);
+    ASSERT_EQ(ERR_INVALID_CONFIG, result, "external entity in archive refused offline");
+    ASSERT_TRUE(last_debug_contains("doc.xml"), "error names the entry");
+
+    struct pollfd pending = {.fd = listener, .events = POLLIN};
+    ASSERT_EQ(0, poll(&pending, 1, 200), "no connection to the entity url");
+
+    // Documents without network references still parse offline
+    const char *local = "<?xml version=\"1.0\"?>\n<doc><p>Offline document</p></doc>\n";
+    ASSERT_EQ(0, write_file(path, local, strlen(local)), "write local fixture");
+    result = extractous_extractor_extract_file_to_string(extractor, path, &content, &metadata);
+    ASSERT_EQ(ERR_OK, result, "local document parses offline");
+    ASSERT_TRUE(strstr(content, "Offline document") != NULL, "document text");
+    extractous_extraction_result_free(content, metadata);
+
+    char url[64];
+    snprintf(url, sizeof(url), "http://127.0.0.1:%d/document", port);
+    result = extractous_extractor_extract_url_to_string(extractor, url, &content, &metadata);
+    ASSERT_EQ(ERR_INVALID_CONFIG, result, "url refused offline");
+    ASSERT_EQ(0, poll(&pending, 1, 0), "no connection to the url");
+
+    close(listener);
+    extractous_extractor_free(extractor);
+    remove(path);
+    remove(zip_path);
+}
+
+TEST(extract_skip_embedded_mime_types) {
+    char path[256];
+    temp_path(path, sizeof(path), "skip_embedded.zip");
+    struct zip_entry entries[] = {
+        {"diagram.svg",
+         "<svg xmlns=\"http://www.w3.org/2000/svg\"><text x=\"0\" y=\"10\">svg label text</text></svg>"},
+        {"notes.txt", "plain notes text"},
+    };
+    ASSERT_EQ(0, write_zip(path, entries, 2), "write fixture");
+
+    struct log_capture capture = {0};
+    extractous_set_log_callback(capture_log, &capture);
+    struct CExtractor *extractor = extractous_extractor_new();
+    ASSERT_NOT_NULL(extractor, "extractor");
+    char *content = NULL;
+    struct CMetadata *metadata = NULL;
+
+    int result = extractous_extractor_extract_file_to_string(extractor, path, &content, &metadata);
+    ASSERT_EQ(ERR_OK, result, "unfiltered result");
+    ASSERT_NOT_NULL(strstr(content, "svg label text"), "image parsed by default");
+    ASSERT_EQ(0, capture.infos, "nothing skipped by default");
+    extractous_extraction_result_free(content, metadata);
+
+    const char *skipped[] = {"image/*"};
+    extractous_extractor_set_skip_embedded_mime_types_mut(extractor, skipped, 1);
+    result = extractous_extractor_extract_file_to_string(extractor, path, &content, &metadata);
+    ASSERT_EQ(ERR_OK, result, "filtered result");
+    ASSERT_NOT_NULL(strstr(content, "plain notes text"), "text entry parsed");
+    ASSERT_NULL(strstr(content, "svg label text"), "image entry not parsed");
+    ASSERT_EQ(1, capture.infos, "one entry skipped");
+    ASSERT_NOT_NULL(strstr(capture.last_info, "diagram.svg"), "skip names the entry");
+    const char *name = metadata_get(metadata, "resourceName");
+    ASSERT_TRUE(name != NULL && strcmp(name, "extractous_ffi_test_skip_embedded.zip") == 0,
+                "resourceName is the caller's file, not the filtered copy");
+    extractous_extraction_result_free(content, metadata);
+
+    char *json = NULL;
+    size_t json_len = 0;
+    result = extractous_extractor_extract_file_to_json(extractor, path, &json, &json_len);
+    ASSERT_EQ(ERR_OK, result, "json result");
+    ASSERT_NULL(strstr(json, "svg label text"), "image entry skipped by other entry points");
+    ASSERT_EQ(2, capture.infos, "skipped again");
+    extractous_string_free(json);
+
+    char docx[256];
+    temp_path(docx, sizeof(docx), "skip_embedded.docx");
+    ASSERT_EQ(0, write_image_docx(docx, 1), "write docx");
+    const char *document_parts[] = {"application/xml", "image/*"};
+    extractous_extractor_set_skip_embedded_mime_types_mut(extractor, document_parts, 2);
+    result = extractous_extractor_extract_file_to_string(extractor, docx, &content, &metadata);
+    ASSERT_EQ(ERR_OK, result, "docx result");
+    ASSERT_NOT_NULL(strstr(content, "Has a picture"), "document parts are not skipped");
+    ASSERT_EQ(2, capture.infos, "nothing skipped from the document");
+    extractous_extraction_result_free(content, metadata);
+    remove(docx);
+
+    extractous_extractor_set_skip_embedded_mime_types_mut(extractor, NULL, 0);
+    result = extractous_extractor_extract_file_to_string(extractor, path, &content, &metadata);
+    ASSERT_EQ(ERR_OK, result, "cleared result");
+    ASSERT_NOT_NULL(strstr(content, "svg label text"), "image parsed again");
+    extractous_extraction_result_free(content, metadata);
+
+    extractous_set_log_callback(NULL, NULL);
+    extractous_extractor_free(extractor);
+    remove(path);
+}
+
+TEST(extract_file_unreadable) {
+    char missing[256];
+    char locked[256];
+    temp_path(missing, sizeof(missing), "unreadable_missing.txt");
+    temp_path(locked, sizeof(locked), "unreadable_locked.txt");
+    remove(missing);
+    ASSERT_EQ(0, write_file(locked, "locked text", 11), "write fixture");
+
+    struct CExtractor *extractor = extractous_extractor_new();
+    ASSERT_NOT_NULL(extractor, "extractor");
+    char *content = NULL;
+    struct CMetadata *metadata = NULL;
+
+    int result = extractous_extractor_extract_file_to_string(extractor, missing, &content, &metadata);
+    ASSERT_EQ(ERR_IO_ERROR, result, "missing file");
+    ASSERT_TRUE(last_debug_contains("file not found"), "missing file debug message");
+    struct CStreamReader *reader = NULL;
+    result = extractous_extractor_extract_file(extractor, missing, &reader, &metadata);
+    ASSERT_EQ(ERR_IO_ERROR, result, "missing file stream");
+    ASSERT_TRUE(last_debug_contains("file not found"), "missing file stream debug message");
+
+    if (geteuid() == 0) {
+        printf(COLOR_YELLOW "  SKIP: permission check needs a non-root user\n" COLOR_RESET);
+    } else {
+        ASSERT_EQ(0, chmod(locked, 0), "remove permissions");
+        result = extractous_extractor_extract_file_to_string(extractor, locked, &content, &metadata);
+        ASSERT_EQ(ERR_IO_ERROR, result, "unreadable file");
+        ASSERT_TRUE(last_debug_contains("permission denied"), "permission debug message");
+        chmod(locked, 0600);
+    }
+
+    extractous_extractor_free(extractor);
+    remove(locked);
+}
+
+struct xml_sink {
+    char *data;
+    size_t len;
+    size_t cap;
+    int chunks;
+    int split_tags;
+    int stop_after;
+};
+
+static int collect_xml(void *ctx, const uint8_t *data, size_t len) {
+    struct xml_sink *sink = ctx;
+    if (sink->chunks > 0 && sink->data[sink->len - 1] != '>') {
+        sink->split_tags++;
+    }
+    if (sink->len + len + 1 > sink->cap) {
+        sink->cap = (sink->len + len + 1) * 2;
+        sink->data = realloc(sink->data, sink->cap);
+    }
+    memcpy(sink->data + sink->len, data, len);
+    sink->len += len;
+    sink->data[sink->len] = '\0';
+    sink->chunks++;
+    return sink->stop_after > 0 && sink->chunks >= sink->stop_after ? 1 : ERR_OK;
+}
+
+static size_t count_occurrences(const char *haystack, const char *needle) {
+    size_t count = 0;
+    for (const char *p = strstr(haystack, needle); p != NULL; p = strstr(p + 1, needle)) {
+        count++;
+    }
+    return count;
+}
+
+TEST(extract_file_xml_stream) {
+    enum { PARAGRAPHS = 4000 };
+    size_t body_cap = PARAGRAPHS * 80 + 1024;
+    char *body = malloc(body_cap);
+    ASSERT_NOT_NULL(body, "body buffer");
+    size_t used = snprintf(body, body_cap,
+                           "<?xml version=\"1.0\"?><w:document "
+                           "xmlns:w=\"http://schemas.openxmlformats.org/wordprocessingml/2006/main\"><w:body>");
+    for (int i = 0; i < PARAGRAPHS; i++) {
+        used += snprintf(body + used, body_cap - used,
+                         "<w:p><w:r><w:t>Paragraph %d of the streamed document</w:t></w:r></w:p>", i);
+    }
+    snprintf(body + used, body_cap - used, "</w:body></w:document>");
+    struct zip_entry docx[] = {
+        {"[Content_Types].xml",
+         "<?xml version=\"1.0\"?><Types xmlns=\"http://schemas.openxmlformats.org/package/2006/content-types\">"
+         "<Default Extension=\"rels\" ContentType=\"application/vnd.openxmlformats-package.relationships+xml\"/>"
+         "<Default Extension=\"xml\" ContentType=\"application/xml\"/>"
+         "<Override PartName=\"/word/document.xml\" ContentType=\"application/vnd.openxmlformats-officedocument.wordprocessingml.document.main+xml\"/>"
+         "</Types>"},
+        {"_rels/.rels",
+         "<?xml version=\"1.0\"?><Relationships xmlns=\"http://schemas.openxmlformats.org/package/2006/relationships\">"
+         "<Relationship Id=\"rId1\" Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument\" Target=\"word/document.xml\"/>"
+         "</Relationships>"},
+        {"word/document.xml", body},
+    };
+    char path[256];
+    temp_path(path, sizeof(path), "xml_stream.docx");
+    ASSERT_EQ(0, write_zip(path, docx, 3), "write fixture");
+    free(body);
+
+    struct CExtractor *extractor = extractous_extractor_new();
+    ASSERT_NOT_NULL(extractor, "extractor");
+    struct CMetadata *metadata = NULL;
+    struct xml_sink sink = {0};
+
+    int result = extractous_extractor_extract_file_xml_stream(
+        extractor, path, collect_xml, &sink, &metadata
+    );
+    ASSERT_EQ(ERR_OK, result, "result");
+    ASSERT_NOT_NULL(metadata, "metadata");
+    ASSERT_NOT_NULL(sink.data, "xml");
+    ASSERT_TRUE(sink.chunks > 1, "streamed in several chunks");
+    ASSERT_EQ(0, sink.split_tags, "chunks end at element edges");
+    ASSERT_TRUE(strstr(sink.data, "<html") != NULL, "xml document");
+    ASSERT_TRUE(strstr(sink.data, "Paragraph 0 of") != NULL, "first paragraph");
+    ASSERT_TRUE(strstr(sink.data, "Paragraph 3999 of") != NULL, "last paragraph");
+    ASSERT_TRUE(count_occurrences(sink.data, "<p") == count_occurrences(sink.data, "</p>"),
+                "paragraphs balanced");
+    const char *end = sink.data + sink.len;
+    while (end > sink.data && (end[-1] == '\n' || end[-1] == ' ')) {
+        end--;
+    }
+    ASSERT_TRUE(end - sink.data >= 7 && memcmp(end - 7, "</html>", 7) == 0, "document closed");
+    extractous_metadata_free(metadata);
+    free(sink.data);
+
+    struct xml_sink stopping = {.stop_after = 1};
+    result = extractous_extractor_extract_file_xml_stream(
+        extractor, path, collect_xml, &stopping, &metadata
+    );
+    ASSERT_EQ(ERR_ABORTED, result, "callback stops extraction");
+    ASSERT_EQ(1, stopping.chunks, "no chunks after stopping");
+    ASSERT_NULL(metadata, "no metadata on failure");
+    free(stopping.data);
+
+    extractous_extractor_free(extractor);
+    remove(path);
+}
+
+TEST(extract_dedupe_embedded) {
+    char path[256];
+    temp_path(path, sizeof(path), "dedupe_embedded.zip");
+    struct zip_entry entries[] = {
+        {"report.txt", "quarterly duplicate report"},
+        {"copy/report.txt", "quarterly duplicate report"},
+        {"other.txt", "distinct other file"},
+        {"report.csv", "quarterly duplicate report\n"},
+    };
+    ASSERT_EQ(0, write_zip(path, entries, 4), "write fixture");
+
+    struct log_capture capture = {0};
+    extractous_set_log_callback(capture_log, &capture);
+    struct CExtractor *extractor = extractous_extractor_new();
+    ASSERT_NOT_NULL(extractor, "extractor");
+    char *content = NULL;
+    struct CMetadata *metadata = NULL;
+
+    int result = extractous_extractor_extract_file_to_string(extractor, path, &content, &metadata);
+    ASSERT_EQ(ERR_OK, result, "default result");
+    ASSERT_EQ(3, (int)count_occurrences(content, "quarterly duplicate report"), "every copy by default");
+    extractous_extraction_result_free(content, metadata);
+
+    extractous_extractor_set_dedupe_embedded_mut(extractor, true);
+    result = extractous_extractor_extract_file_to_string(extractor, path, &content, &metadata);
+    ASSERT_EQ(ERR_OK, result, "dedupe result");
+    ASSERT_EQ(2, (int)count_occurrences(content, "quarterly duplicate report"), "identical bytes parsed once");
+    ASSERT_NOT_NULL(strstr(content, "distinct other file"), "distinct entry kept");
+    ASSERT_EQ(1, capture.infos, "dedupe logged");
+    ASSERT_NOT_NULL(strstr(capture.last_info, "1 duplicate"), "dedupe count logged");
+    extractous_extraction_result_free(content, metadata);
+
+    char *json = NULL;
+    size_t json_len = 0;
+    result = extractous_extractor_extract_file_to_json(extractor, path, &json, &json_len);
+    ASSERT_EQ(ERR_OK, result, "json result");
+    ASSERT_EQ(2, (int)count_occurrences(json, "quarterly duplicate report"), "deduped by other entry points");
+    ASSERT_EQ(2, capture.infos, "dedupe logged again");
+    extractous_string_free(json);
+
+    extractous_set_log_callback(NULL, NULL);
+    extractous_extractor_free(extractor);
+    remove(path);
+}
+
+TEST(extract_file_to_string_analysis) {
+    const char *text = "The quick brown fox jumps over the lazy dog. It barked! Did it really? Yes.\n";
+    char path[256];
+    temp_path(path, sizeof(path), "analysis.txt");
+    ASSERT_EQ(0, write_file(path, text, strlen(text)), "write fixture");
+
+    struct CExtractor *extractor = extractous_extractor_new();
+    ASSERT_NOT_NULL(extractor, "extractor");
+    char *content = NULL;
+    struct CMetadata *metadata = NULL;
+    struct CContentAnalysis analysis;
+    memset(&analysis, 0xff, sizeof(analysis));
+
+    int result = extractous_extractor_extract_file_to_string_analysis(
+        extractor, path, &content, &analysis, &metadata
+    );
+    ASSERT_EQ(ERR_OK, result, "result");
+    ASSERT_NOT_NULL(content, "content");
+    ASSERT_EQ(15, (int)analysis.word_count, "word count");
+    ASSERT_EQ((int)strlen(content), (int)analysis.char_count, "character count");
+    ASSERT_EQ(4, (int)analysis.sentence_count, "sentence count");
+    double expected_minutes = 15.0 / READING_WORDS_PER_MINUTE;
+    ASSERT_TRUE(analysis.reading_minutes > expected_minutes - 1e-9 &&
+                analysis.reading_minutes < expected_minutes + 1e-9, "reading minutes");
+    extractous_extraction_result_free(content, metadata);
+
+    ASSERT_EQ(
+        ERR_NULL_POINTER,
+        extractous_extractor_extract_file_to_string_analysis(extractor, path, &content, NULL, &metadata),
+        "null analysis"
+    );
+
+    extractous_extractor_free(extractor);
+    remove(path);
+}
+
+TEST(extract_file_negotiated) {
+    static const char latin[] =
+        "<html><head><meta charset=\"utf-8\"></head><body><p>Caf\xc3\xa9 \xe2\x82\xac" "5</p></body></html>";
+    static const char cjk[] =
+        "<html><head><meta charset=\"utf-8\"></head><body><p>\xe4\xb8\xad\xe6\x96\x87</p></body></html>";
+    char latin_path[256];
+    char cjk_path[256];
+    temp_path(latin_path, sizeof(latin_path), "negotiated_latin.html");
+    temp_path(cjk_path, sizeof(cjk_path), "negotiated_cjk.html");
+    ASSERT_EQ(0, write_file(latin_path, latin, strlen(latin)), "write fixture");
+    ASSERT_EQ(0, write_file(cjk_path, cjk, strlen(cjk)), "write fixture");
+
+    struct CExtractor *extractor = extractous_extractor_new();
+    ASSERT_NOT_NULL(extractor, "extractor");
+    char *content = NULL;
+    char *encoding = NULL;
+    struct CMetadata *metadata = NULL;
+
+    int result = extractous_extractor_extract_file_negotiated(
+        extractor, latin_path, "windows-1252, utf-8", &content, &encoding, &metadata);
+    ASSERT_EQ(ERR_OK, result, "representable in 1252");
+    ASSERT_TRUE(strcmp(encoding, "windows-1252") == 0, "1252 chosen");
+    ASSERT_NOT_NULL(strstr(content, "Caf\xe9 \x80" "5"), "transcoded to 1252");
+    extractous_string_free(content);
+    extractous_string_free(encoding);
+    extractous_metadata_free(metadata);
+
+    result = extractous_extractor_extract_file_negotiated(
+        extractor, cjk_path, "windows-1252, utf-8", &content, &encoding, &metadata);
+    ASSERT_EQ(ERR_OK, result, "not representable in 1252");
+    ASSERT_TRUE(strcmp(encoding, "UTF-8") == 0, "falls back to utf-8");
+    ASSERT_NOT_NULL(strstr(content, "\xe4\xb8\xad\xe6\x96\x87"), "utf-8 content");
+    extractous_string_free(content);
+    extractous_string_free(encoding);
+    extractous_metadata_free(metadata);
+
+    content = NULL;
+    encoding = NULL;
+    metadata = NULL;
+    result = extractous_extractor_extract_file_negotiated(
+        extractor, latin_path, "us-ascii", &content, &encoding, &metadata);
+    ASSERT_EQ(ERR_INVALID_CONFIG, result, "nothing acceptable can represent it");
+    ASSERT_TRUE(content == NULL && encoding == NULL && metadata == NULL, "no output");
+
+    result = extractous_extractor_extract_file_negotiated(
+        extractor, latin_path, "klingon", &content, &encoding, &metadata);
+    ASSERT_EQ(ERR_INVALID_CONFIG, result, "no supported charset");
+
+    extractous_extractor_free(extractor);
+    remove(latin_path);
+    remove(cjk_path);
+}
+
+TEST(extract_file_structured_parts) {
+    struct zip_entry docx[] = {
+        {"[Content_Types].xml",
+         "<?xml version=\"1.0\"?><Types xmlns=\"http://schemas.openxmlformats.org/package/2006/content-types\">"
+         "<Default Extension=\"rels\" ContentType=\"application/vnd.openxmlformats-package.relationships+xml\"/>"
+         "<Default Extension=\"xml\" ContentType=\"application/xml\"/>"
+         "<Override PartName=\"/word/document.xml\" ContentType=\"application/vnd.openxmlformats-officedocument.wordprocessingml.document.main+xml\"/>"
+         "<Override PartName=\"/word/footnotes.xml\" ContentType=\"application/vnd.openxmlformats-officedocument.wordprocessingml.footnotes+xml\"/>"
+         "</Types>"},
+        {"_rels/.rels",
+         "<?xml version=\"1.0\"?><Relationships xmlns=\"http://schemas.openxmlformats.org/package/2006/relationships\">"
+         "<Relationship Id=\"rId1\" Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument\" Target=\"word/document.xml\"/>"
+         "</Relationships>"},
+        {"word/_rels/document.xml.rels",
+         "<?xml version=\"1.0\"?><Relationships xmlns=\"http://schemas.openxmlformats.org/package/2006/relationships\">"
+         "<Relationship Id=\"rId2\" Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/footnotes\" Target=\"footnotes.xml\"/>"
+         "</Relationships>"},
+        {"word/document.xml",
+         "<?xml version=\"1.0\"?><w:document xmlns:w=\"http://schemas.openxmlformats.org/wordprocessingml/2006/main\"><w:body>"
+         "<w:p><w:r><w:t>The court found for the plaintiff.</w:t></w:r>"
+         "<w:r><w:footnoteReference w:id=\"1\"/></w:r></w:p>"
+         "</w:body></w:document>"},
+        {"word/footnotes.xml",
+         "<?xml version=\"1.0\"?><w:footnotes xmlns:w=\"http://schemas.openxmlformats.org/wordprocessingml/2006/main\">"
+         "<w:footnote w:id=\"1\"><w:p><w:r><w:t>See the appellate ruling.</w:t></w:r></w:p></w:footnote>"
+         "</w:footnotes>"},
+    };
+    char path[256];
+    temp_path(path, sizeof(path), "structured_parts.docx");
+    ASSERT_EQ(0, write_zip(path, docx, 5), "write fixture");
+
+    struct CExtractor *extractor = extractous_extractor_new();
+    ASSERT_NOT_NULL(extractor, "extractor");
+    char *body = NULL;
+    char *footnotes = NULL;
+    struct CMetadata *metadata = NULL;
+
+    int result = extractous_extractor_extract_file_structured_parts(extractor, path, &body,
+                                                                    &footnotes, &metadata);
+    ASSERT_EQ(ERR_OK, result, "result");
+    ASSERT_NOT_NULL(body, "body");
+    ASSERT_NOT_NULL(footnotes, "footnotes");
+    ASSERT_NOT_NULL(metadata, "metadata");
+    ASSERT_NOT_NULL(strstr(body, "found for the plaintiff"), "body text in body");
+    ASSERT_NULL(strstr(footnotes, "found for the plaintiff"), "body text not in footnotes");
+    if (strstr(footnotes, "appellate ruling") != NULL) {
+        ASSERT_NULL(strstr(body, "appellate ruling"), "footnote text only in footnotes");
+    } else {
+        // Without note markup everything stays in the body.
+        ASSERT_NOT_NULL(strstr(body, "appellate ruling"), "footnote text kept in body");
+        ASSERT_EQ(0, (int)strlen(footnotes), "no footnotes without markup");
+    }
+    extractous_string_free(body);
+    extractous_string_free(footnotes);
+    extractous_metadata_free(metadata);
+
+    result = extractous_extractor_extract_file_structured_parts(extractor, path, &body, NULL,
+                                                                &metadata);
+    ASSERT_EQ(ERR_NULL_POINTER, result, "null footnotes");
+
+    extractous_extractor_free(extractor);
+    remove(path);
+}
+
+// Returns true if `a` and `b` are equal once all whitespace is removed.
+static int same_ignoring_space(const char *a, const char *b) {
+    for (;;) {
+        while (isspace((unsigned char)*a)) {
+            a++;
+        }
+        while (isspace((unsigned char)*b)) {
+            b++;
+        }
+        if (*a != *b) {
+            return 0;
+        }
+        if (*a == '\0') {
+            return 1;
+        }
+        a++;
+        b++;
+    }
+}
+
+TEST(extract_file_dual) {
+    static const char html[] =
+        "<html><head><title>Dual</title></head><body>"
+        "<h1>Quarterly report</h1><p>Revenue grew &amp; costs fell.</p><p>Second paragraph.</p>"
+        "</body></html>";
+    char path[256];
+    temp_path(path, sizeof(path), "dual.html");
+    ASSERT_EQ(0, write_file(path, html, strlen(html)), "write fixture");
+
+    struct CExtractor *extractor = extractous_extractor_new();
+    ASSERT_NOT_NULL(extractor, "extractor");
+    char *plain = NULL;
+    char *text = NULL;
+    char *xhtml = NULL;
+    struct CMetadata *metadata = NULL;
+
+    int result = extractous_extractor_extract_file_to_string(extractor, path, &plain, &metadata);
+    ASSERT_EQ(ERR_OK, result, "plain extraction");
+    extractous_metadata_free(metadata);
+
+    result = extractous_extractor_extract_file_dual(extractor, path, &text, &xhtml, &metadata);
+    ASSERT_EQ(ERR_OK, result, "dual extraction");
+    ASSERT_NOT_NULL(metadata, "metadata");
+    ASSERT_TRUE(same_ignoring_space(text, plain), "text matches text mode");
+    ASSERT_NOT_NULL(strstr(text, "Revenue grew & costs fell."), "entities decoded");
+    ASSERT_NULL(strstr(text, "<p"), "no markup in text");
+    ASSERT_NOT_NULL(strstr(xhtml, "<html"), "xhtml document");
+    ASSERT_NOT_NULL(strstr(xhtml, "&amp;"), "xhtml escaped");
+    ASSERT_EQ((int)count_occurrences(xhtml, "<p"), (int)count_occurrences(xhtml, "</p>"),
+              "paragraphs balanced");
+    ASSERT_TRUE(count_occurrences(xhtml, "<body") == 1 && count_occurrences(xhtml, "</body>") == 1,
+                "one body");
+    ASSERT_NOT_NULL(strstr(xhtml, "</html>"), "document closed");
+
+    extractous_string_free(plain);
+    extractous_string_free(text);
+    extractous_string_free(xhtml);
+    extractous_metadata_free(metadata);
+    extractous_extractor_free(extractor);
+    remove(path);
+}
+
+TEST(extract_char_filter) {
+    // U+E001 and U+F8FF from the private use area, as a bad font leaves them
+    const char *text = "Gar\xee\x80\x81" "bled \xef\xa3\xbfglyphs\n";
+    struct CExtractor *extractor = extractous_extractor_new();
+    ASSERT_NOT_NULL(extractor, "extractor");
+    char *content = NULL;
+    struct CMetadata *metadata = NULL;
+
+    int result = extractous_extractor_extract_bytes_to_string(
+        extractor, (const uint8_t *)text, strlen(text), &content, &metadata
+    );
+    ASSERT_EQ(ERR_OK, result, "unfiltered result");
+    ASSERT_NOT_NULL(strstr(content, "\xee\x80\x81"), "kept by default");
+    extractous_string_free(content);
+    extractous_metadata_free(metadata);
+
+    struct CCharRange private_use = {0xE000, 0xF8FF};
+    extractous_extractor_set_char_filter_mut(extractor, &private_use, 1, CHAR_FILTER_DENY);
+    result = extractous_extractor_extract_bytes_to_string(
+        extractor, (const uint8_t *)text, strlen(text), &content, &metadata
+    );
+    ASSERT_EQ(ERR_OK, result, "deny result");
+    ASSERT_NOT_NULL(strstr(content, "Garbled glyphs"), "normal text remains");
+    ASSERT_NULL(strstr(content, "\xee\x80\x81"), "U+E001 removed");
+    ASSERT_NULL(strstr(content, "\xef\xa3\xbf"), "U+F8FF removed");
+    extractous_string_free(content);
+    extractous_metadata_free(metadata);
+
+    struct CCharRange ascii[] = {{'a', 'z'}, {' ', ' '}};
+    extractous_extractor_set_char_filter_mut(extractor, ascii, 2, CHAR_FILTER_ALLOW);
+    result = extractous_extractor_extract_bytes_to_string(
+        extractor, (const uint8_t *)text, strlen(text), &content, &metadata
+    );
+    ASSERT_EQ(ERR_OK, result, "allow result");
+    ASSERT_TRUE(strcmp(content, "arbled glyphs") == 0, "only allowed characters kept");
+    extractous_string_free(content);
+    extractous_metadata_free(metadata);
+
+    extractous_extractor_set_char_filter_mut(extractor, NULL, 0, CHAR_FILTER_DENY);
+    result = extractous_extractor_extract_bytes_to_string(
+        extractor, (const uint8_t *)text, strlen(text), &content, &metadata
+    );
+    ASSERT_EQ(ERR_OK, result, "cleared result");
+    ASSERT_NOT_NULL(strstr(content, "\xef\xa3\xbf"), "filter cleared");
+    extractous_string_free(content);
+    extractous_metadata_free(metadata);
+
+    extractous_extractor_free(extractor);
+}
+
+TEST(classify_file) {
+    const char *digital_pages[] = {"An ordinary page of typeset text.", "And a second one like it."};
+    const char *scanned_pages[] = {NULL, NULL};
+    const char *mixed_pages[] = {"A typeset cover page for the scan.", NULL};
+    char digital[256];
+    char scanned[256];
+    char mixed[256];
+    char image[256];
+    temp_path(digital, sizeof(digital), "classify_digital.pdf");
+    temp_path(scanned, sizeof(scanned), "classify_scanned.pdf");
+    temp_path(mixed, sizeof(mixed), "classify_mixed.pdf");
+    temp_path(image, sizeof(image), "classify_image.png");
+    ASSERT_EQ(0, write_pdf(digital, digital_pages, 2), "write fixture");
+    ASSERT_EQ(0, write_pdf(scanned, scanned_pages, 2), "write fixture");
+    ASSERT_EQ(0, write_pdf(mixed, mixed_pages, 2), "write fixture");
+    ASSERT_EQ(0, write_file(image, TINY_PNG, TINY_PNG_LEN), "write fixture");
+
+    struct CExtractor *extractor = extractous_extractor_new();
+    ASSERT_NOT_NULL(extractor, "extractor");
+    int kind = -1;
+
+    ASSERT_EQ(ERR_OK, extractous_extractor_classify_file(extractor, digital, &kind), "digital");
+    ASSERT_EQ(DOC_KIND_DIGITAL, kind, "digital kind");
+    ASSERT_EQ(ERR_OK, extractous_extractor_classify_file(extractor, scanned, &kind), "scanned");
+    ASSERT_EQ(DOC_KIND_SCANNED, kind, "scanned kind");
+    ASSERT_EQ(ERR_OK, extractous_extractor_classify_file(extractor, mixed, &kind), "mixed");
+    ASSERT_EQ(DOC_KIND_MIXED, kind, "mixed kind");
+    ASSERT_EQ(ERR_OK, extractous_extractor_classify_file(extractor, image, &kind), "image");
+    ASSERT_EQ(DOC_KIND_SCANNED, kind, "image kind");
+    ASSERT_EQ(ERR_NULL_POINTER, extractous_extractor_classify_file(extractor, digital, NULL),
+              "null kind");
+
+    extractous_extractor_free(extractor);
+    remove(digital);
+    remove(scanned);
+    remove(mixed);
+    remove(image);
+}
+
+// Metadata entries collected by `collect_metadata`.
+struct meta_sink {
+    char keys[64][128];
+    char values[64][512];
+    size_t count;
+    // Stop after this many entries; 0 never stops.
+    size_t stop_after;
+};
+
+static int collect_metadata(void *ctx, const char *key, const char *value) {
+    struct meta_sink *sink = ctx;
+    if (sink->count < 64) {
+        snprintf(sink->keys[sink->count], sizeof(sink->keys[0]), "%s", key);
+        snprintf(sink->values[sink->count], sizeof(sink->values[0]), "%s", value);
+    }
+    sink->count++;
+    return sink->stop_after > 0 && sink->count >= sink->stop_after ? ERR_ABORTED : ERR_OK;
+}
+
+TEST(extract_file_meta_cb) {
+    static const char html[] =
+        "<html><head><title>Callback metadata</title>"
+        "<meta name=\"author\" content=\"A. Writer\"></head>"
+        "<body><p>Body of the callback document.</p></body></html>";
+    char path[256];
+    temp_path(path, sizeof(path), "meta_cb.html");
+    ASSERT_EQ(0, write_file(path, html, strlen(html)), "write fixture");
+
+    struct CExtractor *extractor = extractous_extractor_new();
+    ASSERT_NOT_NULL(extractor, "extractor");
+    char *expected_content = NULL;
+    struct CMetadata *metadata = NULL;
+    int result = extractous_extractor_extract_file_to_string(extractor, path, &expected_content,
+                                                             &metadata);
+    ASSERT_EQ(ERR_OK, result, "array result");
+
+    static struct meta_sink sink;
+    memset(&sink, 0, sizeof(sink));
+    char *content = NULL;
+    result = extractous_extractor_extract_file_meta_cb(extractor, path, collect_metadata, &sink,
+                                                       &content);
+    ASSERT_EQ(ERR_OK, result, "callback result");
+    ASSERT_TRUE(strcmp(content, expected_content) == 0, "same content");
+    ASSERT_EQ((int)metadata->len, (int)sink.count, "one call per entry");
+    ASSERT_TRUE(sink.count > 2 && sink.count <= 64, "entries collected");
+    for (size_t i = 0; i < sink.count; i++) {
+        ASSERT_TRUE(strcmp(sink.keys[i], metadata->keys[i]) == 0, "same key in same order");
+        // Values longer than the sink's buffer are cut short there.
+        ASSERT_TRUE(strncmp(sink.values[i], metadata->values[i], sizeof(sink.values[0]) - 1) == 0,
+                    "same value");
+    }
+    extractous_string_free(content);
+
+    static struct meta_sink stopping;
+    memset(&stopping, 0, sizeof(stopping));
+    stopping.stop_after = 1;
+    content = NULL;
+    result = extractous_extractor_extract_file_meta_cb(extractor, path, collect_metadata,
+                                                       &stopping, &content);
+    ASSERT_EQ(ERR_ABORTED, result, "callback stops");
+    ASSERT_EQ(1, (int)stopping.count, "no entries after stopping");
+    ASSERT_NULL(content, "no content after stopping");
+
+    result = extractous_extractor_extract_file_meta_cb(extractor, path, NULL, NULL, &content);
+    ASSERT_EQ(ERR_NULL_POINTER, result, "null callback");
+
+    extractous_string_free(expected_content);
+    extractous_metadata_free(metadata);
+    extractous_extractor_free(extractor);
+    remove(path);
+}
+
+TEST(extract_strict_utf8) {
+    // Declared UTF-8, with a stray 0xFF byte that cannot be decoded
+    static const char html[] =
+        "<html><head><meta charset=\"utf-8\"></head><body><p>Bad \xff byte</p></body></html>";
+    static const char valid[] =
+        "<html><head><meta charset=\"utf-8\"></head><body><p>Good caf\xc3\xa9</p></body></html>";
+    struct CExtractor *extractor = extractous_extractor_new();
+    ASSERT_NOT_NULL(extractor, "extractor");
+    char *content = NULL;
+    struct CMetadata *metadata = NULL;
+
+    int result = extractous_extractor_extract_bytes_to_string(
+        extractor, (const uint8_t *)html, strlen(html), &content, &metadata
+    );
+    ASSERT_EQ(ERR_OK, result, "lenient result");
+    ASSERT_NOT_NULL(strstr(content, "Bad \xef\xbf\xbd byte"), "replacement character");
+    extractous_string_free(content);
+    extractous_metadata_free(metadata);
+
+    extractous_extractor_set_strict_utf8_mut(extractor, true);
+    content = NULL;
+    metadata = NULL;
+    result = extractous_extractor_extract_bytes_to_string(
+        extractor, (const uint8_t *)html, strlen(html), &content, &metadata
+    );
+    ASSERT_EQ(ERR_INVALID_UTF8, result, "strict result");
+    ASSERT_NULL(content, "no content");
+    ASSERT_NULL(metadata, "no metadata");
+
+    struct CStreamReader *reader = NULL;
+    result = extractous_extractor_extract_bytes(
+        extractor, (const uint8_t *)html, strlen(html), &reader, &metadata
+    );
+    ASSERT_EQ(ERR_INVALID_UTF8, result, "strict stream result");
+    ASSERT_NULL(reader, "no stream");
+
+    result = extractous_extractor_extract_bytes_to_string(
+        extractor, (const uint8_t *)valid, strlen(valid), &content, &metadata
+    );
+    ASSERT_EQ(ERR_OK, result, "valid input under strict");
+    ASSERT_NOT_NULL(strstr(content, "Good caf\xc3\xa9"), "valid content");
+    extractous_string_free(content);
+    extractous_metadata_free(metadata);
+
+    extractous_extractor_free(extractor);
+}
+
+TEST(extract_file_per_page) {
+    const char *pages[] = {"Alpha page", "Bravo page", "Charlie page"};
+    char path[256];
+    temp_path(path, sizeof(path), "per_page.pdf");
+    ASSERT_EQ(0, write_pdf(path, pages, 3), "write fixture");
+
+    struct CExtractor *extractor = extractous_extractor_new();
+    ASSERT_NOT_NULL(extractor, "extractor");
+    struct CPageResult *results = NULL;
+    size_t count = 0;
+
+    int result = extractous_extractor_extract_file_per_page(extractor, path, &results, &count);
+    ASSERT_EQ(ERR_OK, result, "result");
+    ASSERT_NOT_NULL(results, "pages");
+    ASSERT_EQ(3, (int)count, "page count");
+    for (int i = 0; i < 3; i++) {
+        ASSERT_EQ(i + 1, results[i].page, "page number");
+        ASSERT_TRUE(strstr(results[i].content, pages[i]) != NULL, "page text");
+        for (int j = 0; j < 3; j++) {
+            if (j != i) {
+                ASSERT_TRUE(strstr(results[i].content, pages[j]) == NULL, "other pages absent");
+            }
+        }
+        ASSERT_NOT_NULL(metadata_get(results[i].metadata, "Content-Type"), "page metadata");
+    }
+    extractous_pages_free(results, count);
+    extractous_pages_free(NULL, 0);
+
+    extractous_extractor_free(extractor);
+    remove(path);
+}
+
+TEST(extract_file_segmented_language) {
+    const struct zip_entry entries[] = {
+        {"english.txt", "The quick brown fox jumps over the lazy dog while the children "
+                        "are playing in the garden behind the old house.\n"},
+        {"german.txt", "Der schnelle braune Fuchs springt \xc3\xbc" "ber den faulen Hund, "
+                       "w\xc3\xa4hrend die Kinder im Garten hinter dem alten Haus spielen.\n"},
+    };
+    char path[256];
+    temp_path(path, sizeof(path), "segmented_language.zip");
+    ASSERT_EQ(0, write_zip(path, entries, 2), "write fixture");
+
+    struct CExtractor *extractor = extractous_extractor_new();
+    ASSERT_NOT_NULL(extractor, "extractor");
+    struct CSegment *segments = NULL;
+    size_t count = 0;
+
+    int result = extractous_extractor_extract_file_segmented(extractor, path, &segments, &count);
+    ASSERT_EQ(ERR_OK, result, "result without detection");
+    ASSERT_EQ(3, (int)count, "segment count");
+    ASSERT_TRUE(metadata_get(segments[1].metadata, "language") == NULL, "off by default");
+    extractous_segments_free(segments, count);
+
+    extractous_extractor_set_segment_language_mut(extractor, true);
+    result = extractous_extractor_extract_file_segmented(extractor, path, &segments, &count);
+    ASSERT_EQ(ERR_OK, result, "result with detection");
+    ASSERT_EQ(3, (int)count, "segment count");
+    ASSERT_TRUE(strcmp(segments[1].name, "english.txt") == 0, "english segment");
+    const char *english = metadata_get(segments[1].metadata, "language");
+    ASSERT_NOT_NULL(english, "english language");
+    ASSERT_TRUE(strcmp(english, "en") == 0, "english detected");
+    ASSERT_TRUE(strcmp(segments[2].name, "german.txt") == 0, "german segment");
+    const char *german = metadata_get(segments[2].metadata, "language");
+    ASSERT_NOT_NULL(german, "german language");
+    ASSERT_TRUE(strcmp(german, "de") == 0, "german detected");
+    extractous_segments_free(segments, count);
+
+    extractous_extractor_free(extractor);
+    remove(path);
+}
+
+TEST(metadata_filter_by_prefix) {
+    static const char html[] =
+        "<html><head><title>Prefixed</title>"
+        "<meta name=\"dc:creator\" content=\"Ada\"></head>"
+        "<body><p>Dublin Core fields only.</p></body></html>";
+    char path[256];
+    temp_path(path, sizeof(path), "prefix.html");
+    ASSERT_EQ(0, write_file(path, html, strlen(html)), "write fixture");
+
+    struct CExtractor *extractor = extractous_extractor_new();
+    ASSERT_NOT_NULL(extractor, "extractor");
+    char *content = NULL;
+    struct CMetadata *metadata = NULL;
+    int result = extractous_extractor_extract_file_to_string(extractor, path, &content, &metadata);
+    ASSERT_EQ(ERR_OK, result, "result");
+    ASSERT_NOT_NULL(metadata_get(metadata, "Content-Type"), "unfiltered has Content-Type");
+
+    struct CMetadata *dc = extractous_metadata_filter_by_prefix(metadata, "dc:");
+    ASSERT_NOT_NULL(dc, "filtered metadata");
+    ASSERT_TRUE(dc->len > 0, "dc: entries kept");
+    ASSERT_TRUE(dc->len < metadata->len, "other entries dropped");
+    for (size_t i = 0; i < dc->len; i++) {
+        ASSERT_TRUE(strncmp(dc->keys[i], "dc:", 3) == 0, "only dc: keys");
+    }
+    ASSERT_NOT_NULL(metadata_get(dc, "dc:title"), "dc:title kept");
+    ASSERT_TRUE(metadata_get(dc, "Content-Type") == NULL, "Content-Type absent");
+    extractous_metadata_free(dc);
+
+    struct CMetadata *upper = extractous_metadata_filter_by_prefix(metadata, "DC:");
+    ASSERT_NOT_NULL(upper, "case-sensitive result");
+    ASSERT_EQ(0, (int)upper->len, "prefix is case-sensitive");
+    extractous_metadata_free(upper);
+    ASSERT_TRUE(extractous_metadata_filter_by_prefix(NULL, "dc:") == NULL, "null metadata");
+    ASSERT_TRUE(extractous_metadata_filter_by_prefix(metadata, NULL) == NULL, "null prefix");
+
+    extractous_string_free(content);
+    extractous_metadata_free(metadata);
+    extractous_extractor_free(extractor);
+    remove(path);
+}
+
+static int chain_contains(char **chain, size_t count, const char *mime) {
+    for (size_t i = 0; i < count; i++) {
+        if (strcmp(chain[i], mime) == 0) {
+            return 1;
+        }
+    }
+    return 0;
+}
+
+TEST(mime_supertypes) {
+    char **chain = NULL;
+    size_t count = 0;
+
+    int result = extractous_mime_supertypes(
+        "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet", &chain, &count);
+    ASSERT_EQ(ERR_OK, result, "xlsx result");
+    ASSERT_TRUE(count >= 2, "xlsx chain length");
+    ASSERT_TRUE(chain_contains(chain, count, "application/x-tika-ooxml"), "xlsx is OOXML");
+    ASSERT_TRUE(chain_contains(chain, count, "application/zip"), "xlsx is a ZIP");
+    ASSERT_TRUE(strcmp(chain[count - 1], "application/octet-stream") == 0, "xlsx root");
+    extractous_string_array_free(chain, count);
+
+    result = extractous_mime_supertypes("Application/VND.MS-Excel; charset=binary", &chain, &count);
+    ASSERT_EQ(ERR_OK, result, "xls result");
+    ASSERT_TRUE(strcmp(chain[0], "application/x-tika-msoffice") == 0, "xls is MS Office");
+    ASSERT_TRUE(strcmp(chain[count - 1], "application/octet-stream") == 0, "xls root");
+    extractous_string_array_free(chain, count);
+
+    result = extractous_mime_supertypes("text/html", &chain, &count);
+    ASSERT_EQ(ERR_OK, result, "html result");
+    ASSERT_EQ(2, (int)count, "html chain length");
+    ASSERT_TRUE(strcmp(chain[0], "text/plain") == 0, "html is text");
+    extractous_string_array_free(chain, count);
+
+    result = extractous_mime_supertypes("application/octet-stream", &chain, &count);
+    ASSERT_EQ(ERR_OK, result, "root result");
+    ASSERT_EQ(0, (int)count, "root has no supertypes");
+    ASSERT_TRUE(chain == NULL, "empty chain");
+
+    ASSERT_EQ(ERR_NULL_POINTER, extractous_mime_supertypes(NULL, &chain, &count), "null mime");
+}
+
+static atomic_int running_extractions;
+static atomic_int peak_extractions;
+static atomic_int hold_extraction;
+
+// Counts the extraction as running while its first metadata entry is
+// reported, holding it there for a moment, or for as long as
+// `hold_extraction` is set.
+static int track_concurrency(void *ctx, const char *key, const char *value) {
+    (void)key;
+    (void)value;
+    int *entries = ctx;
+    if ((*entries)++ > 0) {
+        return 0;
+    }
+    int now = atomic_fetch_add(&running_extractions, 1) + 1;
+    int peak = atomic_load(&peak_extractions);
+    while (now > peak && !atomic_compare_exchange_weak(&peak_extractions, &peak, now)) {
+    }
+    usleep(50 * 1000);
+    while (atomic_load(&hold_extraction)) {
+        usleep(5 * 1000);
+    }
+    atomic_fetch_sub(&running_extractions, 1);
+    return 0;
+}
+
+struct concurrent_job {
+    const char *path;
+    int result;
+};
+
+static void *run_tracked_extraction(void *arg) {
+    struct concurrent_job *job = arg;
+    struct CExtractor *extractor = extractous_extractor_new();
+    int entries = 0;
+    char *content = NULL;
+    job->result = extractous_extractor_extract_file_meta_cb(extractor, job->path, track_concurrency,
+                                                            &entries, &content);
+    extractous_string_free(content);
+    extractous_extractor_free(extractor);
+    return NULL;
+}
+
+TEST(max_concurrent_extractions) {
+    static const char text[] = "Throttled extraction test document.\n";
+    char path[256];
+    temp_path(path, sizeof(path), "throttle.txt");
+    ASSERT_EQ(0, write_file(path, text, strlen(text)), "write fixture");
+
+    enum { LIMIT = 2, JOBS = 6 };
+    atomic_store(&running_extractions, 0);
+    atomic_store(&peak_extractions, 0);
+    extractous_set_max_concurrent_extractions(LIMIT);
+    pthread_t threads[JOBS];
+    struct concurrent_job jobs[JOBS];
+    for (int i = 0; i < JOBS; i++) {
+        jobs[i].path = path;
+        jobs[i].result = -1;
+        ASSERT_EQ(0, pthread_create(&threads[i], NULL, run_tracked_extraction, &jobs[i]), "start job");
+    }
+    for (int i = 0; i < JOBS; i++) {
+        pthread_join(threads[i], NULL);
+        ASSERT_EQ(ERR_OK, jobs[i].result, "job result");
+    }
+    ASSERT_TRUE(atomic_load(&peak_extractions) >= 1, "jobs ran");
+    ASSERT_TRUE(atomic_load(&peak_extractions) <= LIMIT, "limit respected");
+
+    // With waiting turned off, an extraction over the limit fails at once.
+    extractous_set_max_concurrent_extractions(1);
+    extractous_set_wait_for_extraction_slot(false);
+    atomic_store(&hold_extraction, 1);
+    struct concurrent_job holder = {path, -1};
+    pthread_t holder_thread;
+    ASSERT_EQ(0, pthread_create(&holder_thread, NULL, run_tracked_extraction, &holder), "start holder");
+    while (atomic_load(&running_extractions) == 0) {
+        usleep(5 * 1000);
+    }
+    struct CExtractor *extractor = extractous_extractor_new();
+    ASSERT_NOT_NULL(extractor, "extractor");
+    char *content = NULL;
+    struct CMetadata *metadata = NULL;
+    int result = extractous_extractor_extract_file_to_string(extractor, path, &content, &metadata);
+    ASSERT_EQ(ERR_BUSY, result, "busy while the slot is held");
+    ASSERT_TRUE(strcmp(extractous_error_category(result), "busy") == 0, "busy category");
+
+    // A deadline bounds the wait for the slot
+    extractous_set_wait_for_extraction_slot(true);
+    result = extractous_extractor_extract_file_with_deadline(extractor, path, 1000, &content, &metadata);
+    ASSERT_EQ(ERR_TIMEOUT, result, "past deadline does not queue");
+    struct timespec ts;
+    clock_gettime(CLOCK_REALTIME, &ts);
+    uint64_t deadline = (uint64_t)ts.tv_sec * 1000 + ts.tv_nsec / 1000000 + 100;
+    time_t queued = time(NULL);
+    result = extractous_extractor_extract_file_with_deadline(extractor, path, deadline, &content, &metadata);
+    ASSERT_EQ(ERR_TIMEOUT, result, "deadline passes while queued");
+    ASSERT_TRUE(time(NULL) - queued <= 2, "gave up at the deadline");
+    ASSERT_TRUE(last_debug_contains("waiting for an extraction slot"), "debug names the wait");
+
+    // So does cancelling the token
+    struct CCancelToken *token = extractous_cancel_token_new();
+    ASSERT_NOT_NULL(token, "token");
+    pthread_t canceller;
+    ASSERT_EQ(0, pthread_create(&canceller, NULL, cancel_after_delay, token), "start canceller");
+    result = extractous_extractor_extract_file_cancellable(extractor, path, token, &content, &metadata);
+    pthread_join(canceller, NULL);
+    ASSERT_EQ(ERR_ABORTED, result, "cancelled while queued");
+    ASSERT_TRUE(last_debug_contains("waiting for an extraction slot"), "debug names the wait");
+    extractous_cancel_token_free(token);
+    atomic_store(&hold_extraction, 0);
+    pthread_join(holder_thread, NULL);
+    ASSERT_EQ(ERR_OK, holder.result, "holder result");
+
+    result = extractous_extractor_extract_file_to_string(extractor, path, &content, &metadata);
+    ASSERT_EQ(ERR_OK, result, "slot free again");
+    extractous_string_free(content);
+    extractous_metadata_free(metadata);
+
+    extractous_set_wait_for_extraction_slot(true);
+    extractous_set_max_concurrent_extractions(0);
+    extractous_extractor_free(extractor);
+    remove(path);
+}
+
+TEST(abandoned_worker_keeps_slot) {
+    // A long text document, so the parse outlasts a short deadline
+    size_t len = 32 * 1024 * 1024;
+    char *text = malloc(len);
+    ASSERT_NOT_NULL(text, "text");
+    for (size_t i = 0; i < len; i++) {
+        text[i] = (i % 64 == 63) ? '\n' : 'a' + (i % 26);
+    }
+    char path[256];
+    char small_path[256];
+    temp_path(path, sizeof(path), "abandoned.txt");
+    temp_path(small_path, sizeof(small_path), "abandoned_small.txt");
+    ASSERT_EQ(0, write_file(path, text, len), "write fixture");
+    ASSERT_EQ(0, write_file(small_path, "small\n", 6), "write small fixture");
+    free(text);
+
+    struct CExtractor *extractor = extractous_extractor_new();
+    ASSERT_NOT_NULL(extractor, "extractor");
+    extractous_extractor_set_extract_string_max_length_mut(extractor, -1);
+    char *content = NULL;
+    struct CMetadata *metadata = NULL;
+    extractous_set_max_concurrent_extractions(1);
+    extractous_set_wait_for_extraction_slot(false);
+
+    struct timespec ts;
+    clock_gettime(CLOCK_REALTIME, &ts);
+    uint64_t deadline = (uint64_t)ts.tv_sec * 1000 + ts.tv_nsec / 1000000 + 50;
+    int result = extractous_extractor_extract_file_with_deadline(
+        extractor, path, deadline, &content, &metadata
+    );
+    if (result == ERR_TIMEOUT) {
+        result = extractous_extractor_extract_file_to_string(extractor, small_path, &content, &metadata);
+        ASSERT_EQ(ERR_BUSY, result, "abandoned worker still holds the slot");
+
+        extractous_set_wait_for_extraction_slot(true);
+        result = extractous_extractor_extract_file_to_string(extractor, small_path, &content, &metadata);
+        ASSERT_EQ(ERR_OK, result, "slot freed when the worker finishes");
+        extractous_extraction_result_free(content, metadata);
+    } else {
+        printf(COLOR_YELLOW "  SKIP: parse finished before the deadline\n" COLOR_RESET);
+        if (result == ERR_OK) {
+            extractous_extraction_result_free(content, metadata);
+        }
+    }
+
+    extractous_set_wait_for_extraction_slot(true);
+    extractous_set_max_concurrent_extractions(0);
+    extractous_extractor_free(extractor);
+    remove(path);
+    remove(small_path);
+}
+
+TEST(extractor_config_to_json) {
+    struct CExtractor *extractor = extractous_extractor_new();
+    ASSERT_NOT_NULL(extractor, "extractor");
+    char *json = NULL;
+
+    ASSERT_EQ(ERR_OK, extractous_extractor_config_to_json(extractor, &json), "default result");
+    ASSERT_NOT_NULL(json, "default json");
+    ASSERT_TRUE(json[0] == '{' && json[strlen(json) - 1] == '}', "json object");
+    ASSERT_TRUE(strstr(json, "\"output_format\":0") != NULL, "default output format");
+    ASSERT_TRUE(strstr(json, "\"encoding\":0") != NULL, "default encoding");
+    ASSERT_TRUE(strstr(json, "\"root_dir\":null") != NULL, "no root dir");
+    extractous_string_free(json);
+
+    const char *types[] = {"application/pdf", "text/*"};
+    extractous_extractor_set_output_format_mut(extractor, OUTPUT_XML);
+    extractous_extractor_set_extract_string_max_length_mut(extractor, 1234);
+    extractous_extractor_set_encoding_mut(extractor, CHARSET_US_ASCII);
+    extractous_extractor_set_allowed_mime_types_mut(extractor, types, 2);
+    extractous_extractor_set_sanitize_output_mut(extractor, true);
+    extractous_extractor_set_max_output_bytes_mut(extractor, 4096);
+    extractous_extractor_set_fallback_mime_mut(extractor, "text/plain");
+    extractous_extractor_set_offline_mut(extractor, true);
+
+    json = NULL;
+    ASSERT_EQ(ERR_OK, extractous_extractor_config_to_json(extractor, &json), "configured result");
+    ASSERT_NOT_NULL(json, "configured json");
+    ASSERT_TRUE(strstr(json, "\"output_format\":1") != NULL, "output format");
+    ASSERT_TRUE(strstr(json, "\"extract_string_max_length\":1234") != NULL, "max length");
+    ASSERT_TRUE(strstr(json, "\"encoding\":1") != NULL, "encoding");
+    ASSERT_TRUE(strstr(json, "\"allowed_mime_types\":[\"application/pdf\",\"text/*\"]") != NULL,
+                "allowed MIME types");
+    ASSERT_TRUE(strstr(json, "\"sanitize_output\":true") != NULL, "sanitize");
+    ASSERT_TRUE(strstr(json, "\"max_output_bytes\":4096") != NULL, "max output bytes");
+    ASSERT_TRUE(strstr(json, "\"fallback_mime\":\"text/plain\"") != NULL, "fallback MIME type");
+    ASSERT_TRUE(strstr(json, "\"offline\":true") != NULL, "offline");
+    ASSERT_TRUE(strstr(json, "\"collapse_whitespace\":false") != NULL, "untouched setting");
+    extractous_string_free(json);
+
+    ASSERT_EQ(ERR_NULL_POINTER, extractous_extractor_config_to_json(NULL, &json), "null handle");
+    extractous_extractor_free(extractor);
+}
+
+static int realloc_calls;
+
+static void *counting_realloc(void *ptr, size_t new_size) {
+    realloc_calls++;
+    return realloc(ptr, new_size);
+}
+
+static void *failing_realloc(void *ptr, size_t new_size) {
+    (void)ptr;
+    (void)new_size;
+    return NULL;
+}
+
+TEST(extract_file_realloc) {
+    static const char html[] =
+        "<html><head><title>Realloc</title></head><body>"
+        "<p>Grown through the caller's allocator.</p><p>Second paragraph.</p>"
+        "</body></html>";
+    char path[256];
+    temp_path(path, sizeof(path), "realloc.html");
+    ASSERT_EQ(0, write_file(path, html, strlen(html)), "write fixture");
+
+    struct CExtractor *extractor = extractous_extractor_new();
+    ASSERT_NOT_NULL(extractor, "extractor");
+    char *expected = NULL;
+    struct CMetadata *metadata = NULL;
+    int result = extractous_extractor_extract_file_to_string(extractor, path, &expected, &metadata);
+    ASSERT_EQ(ERR_OK, result, "string result");
+    extractous_metadata_free(metadata);
+
+    uint8_t *buf = NULL;
+    size_t len = 0;
+    metadata = NULL;
+    result = extractous_extractor_extract_file_realloc(extractor, path, realloc, &buf, &len, &metadata);
+    ASSERT_EQ(ERR_OK, result, "realloc result");
+    ASSERT_NOT_NULL(buf, "buffer");
+    ASSERT_NOT_NULL(metadata, "metadata");
+    ASSERT_EQ((int)strlen((const char *)buf), (int)len, "null-terminated at out_len");
+    ASSERT_TRUE(same_ignoring_space((const char *)buf, expected), "same content as string");
+    free(buf);
+    extractous_metadata_free(metadata);
+    extractous_string_free(expected);
+
+    // Content larger than the first block is grown in several steps.
+    char big_path[256];
+    temp_path(big_path, sizeof(big_path), "realloc_big.txt");
+    FILE *f = fopen(big_path, "w");
+    ASSERT_NOT_NULL(f, "open big fixture");
+    for (int i = 0; i < 8000; i++) {
+        fprintf(f, "line %05d of the realloc document\n", i);
+    }
+    fclose(f);
+    realloc_calls = 0;
+    buf = NULL;
+    metadata = NULL;
+    result = extractous_extractor_extract_file_realloc(extractor, big_path, counting_realloc, &buf,
+                                                       &len, &metadata);
+    ASSERT_EQ(ERR_OK, result, "big result");
+    ASSERT_TRUE(len > 200000, "whole content read");
+    ASSERT_TRUE(realloc_calls > 1, "buffer grown");
+    ASSERT_TRUE(strstr((const char *)buf, "line 07999 of the realloc document") != NULL, "last line");
+    free(buf);
+    extractous_metadata_free(metadata);
+
+    buf = NULL;
+    metadata = NULL;
+    result = extractous_extractor_extract_file_realloc(extractor, path, failing_realloc, &buf, &len,
+                                                       &metadata);
+    ASSERT_EQ(ERR_OUT_OF_MEMORY, result, "allocation failure");
+    ASSERT_TRUE(buf == NULL, "nothing allocated");
+    ASSERT_TRUE(metadata == NULL, "no metadata on failure");
+    ASSERT_EQ(ERR_NULL_POINTER,
+              extractous_extractor_extract_file_realloc(extractor, path, NULL, &buf, &len, &metadata),
+              "null callback");
+
+    extractous_extractor_free(extractor);
+    remove(big_path);
+    remove(path);
+}
+
+// Rewrites the `startxref` offset of a PDF so that it no longer points at
+// the cross-reference table.
+static int break_pdf_xref(const char *path) {
+    FILE *f = fopen(path, "rb");
+    if (f == NULL) {
+        return -1;
+    }
+    static char data[64 * 1024];
+    size_t len = fread(data, 1, sizeof(data) - 1, f);
+    fclose(f);
+    data[len] = '\0';
+    char *startxref = strstr(data, "startxref\n");
+    if (startxref == NULL) {
+        return -1;
+    }
+    f = fopen(path, "wb");
+    if (f == NULL) {
+        return -1;
+    }
+    fwrite(data, 1, (size_t)(startxref - data), f);
+    fputs("startxref\n12\n%%EOF\n", f);
+    return fclose(f) == 0 ? 0 : -1;
+}
+
+TEST(extract_file_to_string_repaired) {
+    const char *pages[] = {"Recovered page text"};
+    char path[256];
+    temp_path(path, sizeof(path), "repaired.pdf");
+    ASSERT_EQ(0, write_pdf(path, pages, 1), "write fixture");
+
+    struct CExtractor *extractor = extractous_extractor_new();
+    ASSERT_NOT_NULL(extractor, "extractor");
+    char *content = NULL;
+    struct CMetadata *metadata = NULL;
+    int repaired = -1;
+
+    int result = extractous_extractor_extract_file_to_string_repaired(extractor, path, &content,
+                                                                      &metadata, &repaired);
+    ASSERT_EQ(ERR_OK, result, "intact result");
+    ASSERT_EQ(0, repaired, "intact PDF not repaired");
+    extractous_string_free(content);
+    extractous_metadata_free(metadata);
+
+    ASSERT_EQ(0, break_pdf_xref(path), "break xref");
+    content = NULL;
+    metadata = NULL;
+    repaired = -1;
+    result = extractous_extractor_extract_file_to_string_repaired(extractor, path, &content,
+                                                                  &metadata, &repaired);
+    ASSERT_EQ(ERR_OK, result, "damaged result");
+    ASSERT_EQ(1, repaired, "damaged PDF repaired");
+    ASSERT_TRUE(strstr(content, "Recovered page text") != NULL, "content still returned");
+    ASSERT_NOT_NULL(metadata, "metadata still returned");
+    extractous_string_free(content);
+    extractous_metadata_free(metadata);
+
+    extractous_extractor_free(extractor);
+    remove(path);
+}
+
+static int xor_transform(void *ctx, const uint8_t *in_ptr, size_t in_len, uint8_t **out_ptr,
+                         size_t *out_len) {
+    const char *key = ctx;
+    size_t key_len = strlen(key);
+    uint8_t *out = malloc(in_len);
+    if (out == NULL) {
+        return ERR_OUT_OF_MEMORY;
+    }
+    for (size_t i = 0; i < in_len; i++) {
+        out[i] = in_ptr[i] ^ (uint8_t)key[i % key_len];
+    }
+    *out_ptr = out;
+    *out_len = in_len;
+    return ERR_OK;
+}
+
+static int refusing_transform(void *ctx, const uint8_t *in_ptr, size_t in_len, uint8_t **out_ptr,
+                              size_t *out_len) {
+    (void)ctx;
+    (void)in_ptr;
+    (void)in_len;
+    (void)out_ptr;
+    (void)out_len;
+    return ERR_INVALID_CONFIG;
+}
+
+TEST(input_transform) {
+    static const char plaintext[] = "The decrypted ledger balances on the third of May.";
+    static char key[] = "k3y";
+    uint8_t blob[sizeof(plaintext) - 1];
+    for (size_t i = 0; i < sizeof(blob); i++) {
+        blob[i] = (uint8_t)plaintext[i] ^ (uint8_t)key[i % strlen(key)];
+    }
+
+    struct CExtractor *extractor = extractous_extractor_new();
+    ASSERT_NOT_NULL(extractor, "extractor");
+    extractous_extractor_set_input_transform_mut(extractor, xor_transform, key);
+    char *content = NULL;
+    struct CMetadata *metadata = NULL;
+    int result = extractous_extractor_extract_bytes_to_string(extractor, blob, sizeof(blob),
+                                                              &content, &metadata);
+    ASSERT_EQ(ERR_OK, result, "transformed result");
+    ASSERT_TRUE(strstr(content, plaintext) != NULL, "plaintext extracted");
+    extractous_string_free(content);
+    extractous_metadata_free(metadata);
+
+    // A failing transform aborts the extraction.
+    extractous_extractor_set_input_transform_mut(extractor, refusing_transform, NULL);
+    content = NULL;
+    metadata = NULL;
+    result = extractous_extractor_extract_bytes_to_string(extractor, blob, sizeof(blob), &content,
+                                                          &metadata);
+    ASSERT_EQ(ERR_ABORTED, result, "refused result");
+    ASSERT_TRUE(content == NULL, "no content on failure");
+
+    // Without a transform the ciphertext is parsed as it is.
+    extractous_extractor_set_input_transform_mut(extractor, NULL, NULL);
+    content = NULL;
+    metadata = NULL;
+    result = extractous_extractor_extract_bytes_to_string(extractor, blob, sizeof(blob), &content,
+                                                          &metadata);
+    if (result == ERR_OK) {
+        ASSERT_TRUE(strstr(content, plaintext) == NULL, "ciphertext not decrypted");
+        extractous_string_free(content);
+        extractous_metadata_free(metadata);
+    }
+
+    extractous_extractor_free(extractor);
+}
+
+TEST(detect_mime_by_filename) {
+    static const struct {
+        const char *filename;
+        const char *mime;
+    } cases[] = {
+        {"report.pdf", "application/pdf"},
+        {"/tmp/archive/REPORT.PDF", "application/pdf"},
+        {"notes.docx", "application/vnd.openxmlformats-officedocument.wordprocessingml.document"},
+        {"notes.md", "text/x-web-markdown"},
+        {"data.xyz", "application/octet-stream"},
+        {"README", "application/octet-stream"},
+    };
+    for (size_t i = 0; i < sizeof(cases) / sizeof(cases[0]); i++) {
+        char *mime = extractous_detect_mime_by_filename(cases[i].filename);
+        ASSERT_NOT_NULL(mime, cases[i].filename);
+        ASSERT_TRUE(strcmp(cases[i].mime, mime) == 0, cases[i].filename);
+        extractous_string_free(mime);
+    }
+    ASSERT_NULL(extractous_detect_mime_by_filename(NULL), "NULL filename");
+
+    // A type guessed from a name can be forced as a fallback, which needs an
+    // extension for it.
+    struct CExtractor *extractor = extractous_extractor_new();
+    ASSERT_NOT_NULL(extractor, "extractor");
+    extractous_extractor_set_fallback_mime_mut(extractor, "text/x-web-markdown");
+    char *json = NULL;
+    ASSERT_EQ(ERR_OK, extractous_extractor_config_to_json(extractor, &json), "config");
+    ASSERT_NOT_NULL(strstr(json, "\"fallback_mime\":\"text/x-web-markdown\""), "guessed type accepted");
+    extractous_string_free(json);
+    extractous_extractor_free(extractor);
+}
+
+// Writes a one-page PDF showing the Hebrew word "shalom" with its letters
+// drawn left to right in reading order, which PDF text extraction reads as
+// visual order and reverses.
+static int write_hebrew_pdf(const char *path) {
+    FILE *f = fopen(path, "wb");
+    if (f == NULL) {
+        return -1;
+    }
+    static const char stream[] = "BT /F1 12 Tf 72 720 Td (\x80\x81\x82\x83) Tj ET";
+    long offsets[5];
+    fputs("%PDF-1.4\n", f);
+    offsets[0] = ftell(f);
+    fputs("1 0 obj << /Type /Catalog /Pages 2 0 R >> endobj\n", f);
+    offsets[1] = ftell(f);
+    fputs("2 0 obj << /Type /Pages /Kids [ 4 0 R ] /Count 1 >> endobj\n", f);
+    offsets[2] = ftell(f);
+    fputs("3 0 obj << /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding << "
+          "/Type /Encoding /BaseEncoding /WinAnsiEncoding "
+          "/Differences [128 /shin /lamed /vav /finalmem] >> >> endobj\n", f);
+    offsets[3] = ftell(f);
+    fputs("4 0 obj << /Type /Page /Parent 2 0 R /MediaBox [0 0 612 792] "
+          "/Resources << /Font << /F1 3 0 R >> >> /Contents 5 0 R >> endobj\n", f);
+    offsets[4] = ftell(f);
+    fprintf(f, "5 0 obj << /Length %d >> stream\n%s\nendstream endobj\n",
+            (int)strlen(stream), stream);
+    long xref = ftell(f);
+    fputs("xref\n0 6\n0000000000 65535 f \n", f);
+    for (int i = 0; i < 5; i++) {
+        fprintf(f, "%010ld 00000 n \n", offsets[i]);
+    }
+    fprintf(f, "trailer << /Size 6 /Root 1 0 R >>\nstartxref\n%ld\n%%%%EOF\n", xref);
+    return fclose(f) == 0 ? 0 : -1;
+}
+
+TEST(bidi_mode) {
+    // "shalom" in reading order, and reversed as in visual order.
+    static const char logical[] = "\xd7\xa9\xd7\x9c\xd7\x95\xd7\x9d";
+    static const char visual[] = "\xd7\x9d\xd7\x95\xd7\x9c\xd7\xa9";
+    char path[256];
+    temp_path(path, sizeof(path), "hebrew.pdf");
+    ASSERT_EQ(0, write_hebrew_pdf(path), "write fixture");
+
+    struct CExtractor *extractor = extractous_extractor_new();
+    ASSERT_NOT_NULL(extractor, "extractor");
+    char *content = NULL;
+    struct CMetadata *metadata = NULL;
+    int result = extractous_extractor_extract_file_to_string(extractor, path, &content, &metadata);
+    ASSERT_EQ(ERR_OK, result, "preserve result");
+    int reversed = strstr(content, visual) != NULL;
+    extractous_string_free(content);
+    extractous_metadata_free(metadata);
+    if (!reversed) {
+        printf(COLOR_YELLOW "  SKIP: parser did not return the text in visual order\n" COLOR_RESET);
+        extractous_extractor_free(extractor);
+        remove(path);
+        return;
+    }
+
+    extractous_extractor_set_bidi_mode_mut(extractor, BIDI_LOGICAL);
+    content = NULL;
+    metadata = NULL;
+    result = extractous_extractor_extract_file_to_string(extractor, path, &content, &metadata);
+    ASSERT_EQ(ERR_OK, result, "logical result");
+    ASSERT_TRUE(strstr(content, logical) != NULL, "logical order");
+    ASSERT_TRUE(strstr(content, visual) == NULL, "visual order gone");
+    extractous_string_free(content);
+    extractous_metadata_free(metadata);
+
+    // Unknown modes are ignored.
+    extractous_extractor_set_bidi_mode_mut(extractor, 99);
+    content = NULL;
+    metadata = NULL;
+    result = extractous_extractor_extract_file_to_string(extractor, path, &content, &metadata);
+    ASSERT_EQ(ERR_OK, result, "after unknown mode");
+    ASSERT_TRUE(strstr(content, logical) != NULL, "mode kept");
+    extractous_string_free(content);
+    extractous_metadata_free(metadata);
+
+    extractous_extractor_free(extractor);
+    remove(path);
+}
+
+TEST(bidi_mode_conversions) {
+    // A right-to-left line starting with the Hebrew letters alef, bet and
+    // gimel and ending with a Latin word, in reading and in display order.
+    static const char logical[] = "\xd7\x90\xd7\x91\xd7\x92 abc";
+    static const char visual[] = "abc \xd7\x92\xd7\x91\xd7\x90";
+    char logical_path[256];
+    char visual_path[256];
+    temp_path(logical_path, sizeof(logical_path), "bidi_logical.txt");
+    temp_path(visual_path, sizeof(visual_path), "bidi_visual.txt");
+    ASSERT_EQ(0, write_file(logical_path, logical, strlen(logical)), "write logical fixture");
+    ASSERT_EQ(0, write_file(visual_path, visual, strlen(visual)), "write visual fixture");
+
+    struct CExtractor *extractor = extractous_extractor_new();
+    ASSERT_NOT_NULL(extractor, "extractor");
+    char *content = NULL;
+    struct CMetadata *metadata = NULL;
+
+    extractous_extractor_set_bidi_mode_mut(extractor, BIDI_VISUAL);
+    int result = extractous_extractor_extract_file_to_string(extractor, logical_path, &content, &metadata);
+    ASSERT_EQ(ERR_OK, result, "visual result");
+    ASSERT_NOT_NULL(strstr(content, visual), "logical line put in display order");
+    extractous_extraction_result_free(content, metadata);
+
+    extractous_extractor_set_bidi_mode_mut(extractor, BIDI_LOGICAL);
+    result = extractous_extractor_extract_file_to_string(extractor, visual_path, &content, &metadata);
+    ASSERT_EQ(ERR_OK, result, "logical result");
+    ASSERT_NOT_NULL(strstr(content, logical), "visual line put back in reading order");
+    extractous_extraction_result_free(content, metadata);
+
+    extractous_extractor_free(extractor);
+    remove(logical_path);
+    remove(visual_path);
+}
+
+// Writes a two-page PDF with the bookmarks "Chapter One", holding
+// "Section 1.1", on page 1, and "Chapter Two" on page 2.
+static int write_bookmarked_pdf(const char *path) {
+    FILE *f = fopen(path, "wb");
+    if (f == NULL) {
+        return -1;
+    }
+    static const char page_one[] = "BT /F1 12 Tf 72 720 Td (Chapter One) Tj 0 -20 Td (Section 1.1) Tj ET";
+    static const char page_two[] = "BT /F1 12 Tf 72 720 Td (Chapter Two) Tj ET";
+    char contents[2][160];
+    snprintf(contents[0], sizeof(contents[0]), "<< /Length %d >> stream\n%s\nendstream",
+             (int)strlen(page_one), page_one);
+    snprintf(contents[1], sizeof(contents[1]), "<< /Length %d >> stream\n%s\nendstream",
+             (int)strlen(page_two), page_two);
+    const char *objects[] = {
+        "<< /Type /Catalog /Pages 2 0 R /Outlines 8 0 R /PageMode /UseOutlines >>",
+        "<< /Type /Pages /Kids [ 4 0 R 6 0 R ] /Count 2 >>",
+        "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding /WinAnsiEncoding >>",
+        "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 612 792] "
+        "/Resources << /Font << /F1 3 0 R >> >> /Contents 5 0 R >>",
+        contents[0],
+        "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 612 792] "
+        "/Resources << /Font << /F1 3 0 R >> >> /Contents 7 0 R >>",
+        contents[1],
+        "<< /Type /Outlines /First 9 0 R /Last 11 0 R /Count 3 >>",
+        "<< /Title (Chapter One) /Parent 8 0 R /Next 11 0 R /First 10 0 R /Last 10 0 R "
+        "/Count 1 /Dest [4 0 R /Fit] >>",
+        "<< /Title (Section 1.1) /Parent 9 0 R /Dest [4 0 R /XYZ 72 700 0] >>",
+        "<< /Title (Chapter Two) /Parent 8 0 R /Prev 9 0 R /Dest [6 0 R /Fit] >>",
+    };
+    enum { COUNT = sizeof(objects) / sizeof(objects[0]) };
+    long offsets[COUNT];
+    fputs("%PDF-1.4\n", f);
+    for (int i = 0; i < COUNT; i++) {
+        offsets[i] = ftell(f);
+        fprintf(f, "%d 0 obj %s endobj\n", i + 1, objects[i]);
+    }
+    long xref = ftell(f);
+    fprintf(f, "xref\n0 %d\n0000000000 65535 f \n", COUNT + 1);
+    for (int i = 0; i < COUNT; i++) {
+        fprintf(f, "%010ld 00000 n \n", offsets[i]);
+    }
+    fprintf(f, "trailer << /Size %d /Root 1 0 R >>\nstartxref\n%ld\n%%%%EOF\n", COUNT + 1, xref);
+    return fclose(f) == 0 ? 0 : -1;
+}
+
+TEST(extract_file_outline) {
+    static const struct {
+        const char *title;
+        int level;
+        int page;
+    } expected[] = {
+        {"Chapter One", 1, 1},
+        {"Section 1.1", 2, 1},
+        {"Chapter Two", 1, 2},
+    };
+    char path[256];
+    temp_path(path, sizeof(path), "outline.pdf");
+    ASSERT_EQ(0, write_bookmarked_pdf(path), "write fixture");
+
+    struct CExtractor *extractor = extractous_extractor_new();
+    ASSERT_NOT_NULL(extractor, "extractor");
+    struct COutlineEntry *entries = NULL;
+    size_t count = 0;
+    int result = extractous_extractor_extract_file_outline(extractor, path, &entries, &count);
+    ASSERT_EQ(ERR_OK, result, "outline result");
+    ASSERT_EQ(3, (int)count, "entry count");
+    for (size_t i = 0; i < count; i++) {
+        ASSERT_TRUE(strcmp(expected[i].title, entries[i].title) == 0, expected[i].title);
+        ASSERT_EQ(expected[i].level, entries[i].level, "level");
+        ASSERT_EQ(expected[i].page, entries[i].page, "page");
+    }
+    extractous_outline_free(entries, count);
+
+    // A document without bookmarks or headings has an empty outline.
+    const char *pages[] = {"No bookmarks here"};
+    ASSERT_EQ(0, write_pdf(path, pages, 1), "write plain fixture");
+    entries = NULL;
+    count = 99;
+    result = extractous_extractor_extract_file_outline(extractor, path, &entries, &count);
+    ASSERT_EQ(ERR_OK, result, "plain result");
+    ASSERT_EQ(0, (int)count, "no entries");
+    extractous_outline_free(entries, count);
+
+    extractous_extractor_free(extractor);
+    remove(path);
+}
+
+TEST(extract_file_into_buffer) {
+    char path[256];
+    temp_path(path, sizeof(path), "into.txt");
+    const char *text = "Hello from a caller-owned buffer\n";
+    ASSERT_EQ(0, write_file(path, text, strlen(text)), "write fixture");
+
+    struct CExtractor *extractor = extractous_extractor_new();
+    ASSERT_NOT_NULL(extractor, "extractor");
+    struct CMetadata *metadata = NULL;
+    size_t needed = 0;
+
+    // Size query with no buffer
+    metadata = (struct CMetadata *)&needed;
+    int result = extractous_extractor_extract_file_into(extractor, path, NULL, 0, &needed, &metadata);
+    ASSERT_EQ(ERR_BUFFER_TOO_SMALL, result, "size query result");
+    ASSERT_TRUE(needed > 0, "required size reported");
+    ASSERT_NULL(metadata, "size query metadata");
+    char expected[64];
+    snprintf(expected, sizeof(expected), "content needs %zu bytes", needed);
+    ASSERT_TRUE(last_debug_contains(expected), "required size recorded");
+
+    // Exact fit
+    uint8_t *exact = malloc(needed);
+    ASSERT_NOT_NULL(exact, "exact buffer");
+    size_t len = 0;
+    result = extractous_extractor_extract_file_into(extractor, path, exact, needed, &len, &metadata);
+    ASSERT_EQ(ERR_OK, result, "exact fit result");
+    ASSERT_EQ((int)needed, (int)len, "exact fit length");
+    ASSERT_NOT_NULL(metadata, "exact fit metadata");
+    extractous_metadata_free(metadata);
+    metadata = NULL;
+
+    // Too small
+    len = 0;
+    result = extractous_extractor_extract_file_into(extractor, path, exact, needed - 1, &len, &metadata);
+    ASSERT_EQ(ERR_BUFFER_TOO_SMALL, result, "too small result");
+    ASSERT_EQ((int)needed, (int)len, "too small reports required size");
+    ASSERT_NULL(metadata, "too small metadata");
+
+    // Other failures clear the metadata too
+    metadata = (struct CMetadata *)&needed;
+    result = extractous_extractor_extract_file_into(extractor, "/nonexistent/into.txt", exact, needed,
+                                                    &len, &metadata);
+    ASSERT_EQ(ERR_IO_ERROR, result, "missing file result");
+    ASSERT_NULL(metadata, "missing file metadata");
+
+    // Oversized
+    uint8_t *big = malloc(needed + 64);
+    ASSERT_NOT_NULL(big, "big buffer");
+    result = extractous_extractor_extract_file_into(extractor, path, big, needed + 64, &len, &metadata);
+    ASSERT_EQ(ERR_OK, result, "oversized result");
+    ASSERT_EQ((int)needed, (int)len, "oversized length");
+    ASSERT_TRUE(memcmp(exact, big, len) == 0, "oversized content matches exact fit");
+    extractous_metadata_free(metadata);
+
+    free(exact);
+    free(big);
+    extractous_extractor_free(extractor);
+    remove(path);
+}
+
+TEST(extract_file_to_json) {
+    char path[256];
+    temp_path(path, sizeof(path), "json.txt");
+    const char *text = "Quoted \"json\" content\twith\\escapes\n";
+    ASSERT_EQ(0, write_file(path, text, strlen(text)), "write fixture");
+
+    struct CExtractor *extractor = extractous_extractor_new();
+    ASSERT_NOT_NULL(extractor, "extractor");
+
+    char *json = NULL;
+    size_t len = 0;
+    int result = extractous_extractor_extract_file_to_json(extractor, path, &json, &len);
+    ASSERT_EQ(ERR_OK, result, "result");
+    ASSERT_NOT_NULL(json, "json");
+    ASSERT_EQ((int)strlen(json), (int)len, "reported length");
+    ASSERT_TRUE(strncmp(json, "{\"content\":\"", 12) == 0, "content field first");
+    ASSERT_TRUE(strstr(json, "Quoted \\\"json\\\" content\\twith\\\\escapes") != NULL,
+                "content escaped");
+    ASSERT_TRUE(strstr(json, ",\"metadata\":{\"") != NULL, "metadata object populated");
+    ASSERT_TRUE(strstr(json, "\"Content-Type\":[") != NULL, "metadata values are arrays");
+    ASSERT_TRUE(json[len - 1] == '}', "document closed");
+
+    extractous_string_free(json);
+    extractous_extractor_free(extractor);
+    remove(path);
+}
+
+TEST(metadata_has_content_length) {
+    const char *text = "Content-Length should always be reported\n";
+    char path[256];
+    temp_path(path, sizeof(path), "length.txt");
+    ASSERT_EQ(0, write_file(path, text, strlen(text)), "write fixture");
+
+    struct CExtractor *extractor = extractous_extractor_new();
+    ASSERT_NOT_NULL(extractor, "extractor");
+    char *content = NULL;
+    struct CMetadata *metadata = NULL;
+
+    // File input
+    int result = extractous_extractor_extract_file_to_string(extractor, path, &content, &metadata);
+    ASSERT_EQ(ERR_OK, result, "file result");
+    ASSERT_TRUE(is_numeric(metadata_get(metadata, "Content-Length")), "file Content-Length");
+    extractous_string_free(content);
+    extractous_metadata_free(metadata);
+
+    // Bytes input
+    result = extractous_extractor_extract_bytes_to_string(
+        extractor, (const uint8_t *)text, strlen(text), &content, &metadata
+    );
+    ASSERT_EQ(ERR_OK, result, "bytes result");
+    ASSERT_TRUE(is_numeric(metadata_get(metadata, "Content-Length")), "bytes Content-Length");
+    extractous_string_free(content);
+    extractous_metadata_free(metadata);
+
+    // URL input
+    char response[256];
+    snprintf(response, sizeof(response),
+             "HTTP/1.1 200 OK\r\nContent-Type: text/plain\r\nContent-Length: %zu\r\n"
+             "Connection: close\r\n\r\n%s", strlen(text), text);
+    int port = 0;
+    pid_t server = start_http_server(response, &port);
+    ASSERT_TRUE(server > 0, "start mock server");
+    char url[64];
+    snprintf(url, sizeof(url), "http://127.0.0.1:%d/length.txt", port);
+    result = extractous_extractor_extract_url_to_string(extractor, url, &content, &metadata);
+    waitpid(server, NULL, 0);
+    ASSERT_EQ(ERR_OK, result, "url result");
+    ASSERT_TRUE(is_numeric(metadata_get(metadata, "Content-Length")), "url Content-Length");
+    extractous_string_free(content);
+    extractous_metadata_free(metadata);
+
+    // Without a declared length the size of a URL input is not known.
+    snprintf(response, sizeof(response),
+             "HTTP/1.1 200 OK\r\nContent-Type: text/plain\r\nConnection: close\r\n\r\n%s", text);
+    server = start_http_server(response, &port);
+    ASSERT_TRUE(server > 0, "start mock server");
+    snprintf(url, sizeof(url), "http://127.0.0.1:%d/undeclared.txt", port);
+    result = extractous_extractor_extract_url_to_string(extractor, url, &content, &metadata);
+    waitpid(server, NULL, 0);
+    ASSERT_EQ(ERR_OK, result, "undeclared length result");
+    ASSERT_NULL(metadata_get(metadata, "Content-Length"), "no url Content-Length without a header");
+    extractous_string_free(content);
+    extractous_metadata_free(metadata);
+
+    extractous_extractor_free(extractor);
+    remove(path);
+}
+
+TEST(metadata_deterministic_order) {
+    const char *html = "<html><head><title>Order</title><meta name=\"author\" content=\"A\">"
+                       "<meta name=\"keywords\" content=\"k\"></head><body>Body</body></html>";
+    char path[256];
+    temp_path(path, sizeof(path), "order.html");
+    ASSERT_EQ(0, write_file(path, html, strlen(html)), "write fixture");
+
+    struct CExtractor *extractor = extractous_extractor_new();
+    ASSERT_NOT_NULL(extractor, "extractor");
+    static char first[8192], second[8192];
+    char *content = NULL;
+    struct CMetadata *metadata = NULL;
+
+    int result = extractous_extractor_extract_file_to_string(extractor, path, &content, &metadata);
+    ASSERT_EQ(ERR_OK, result, "first result");
+    for (size_t i = 1; i < metadata->len; i++) {
+        ASSERT_TRUE(strcmp(metadata->keys[i - 1], metadata->keys[i]) < 0, "keys sorted");
+    }
+    metadata_serialize(metadata, first, sizeof(first));
+    extractous_string_free(content);
+    extractous_metadata_free(metadata);
+
+    result = extractous_extractor_extract_file_to_string(extractor, path, &content, &metadata);
+    ASSERT_EQ(ERR_OK, result, "second result");
+    metadata_serialize(metadata, second, sizeof(second));
+    extractous_string_free(content);
+    extractous_metadata_free(metadata);
+
+    ASSERT_TRUE(strcmp(first, second) == 0, "identical serialized metadata");
+    extractous_extractor_free(extractor);
+    remove(path);
+}
+
+// Records the element sequence reported by the SAX callbacks.
+struct sax_log {
+    char events[2048];
+    int depth;
+};
+
+static void sax_append(struct sax_log *log, const char *prefix, const char *name) {
+    size_t used = strlen(log->events);
+    snprintf(log->events + used, sizeof(log->events) - used, "%s%s ", prefix, name);
+}
+
+static void sax_start(void *ctx, const char *name, const char *const *attr_names,
+                      const char *const *attr_values, size_t attr_count) {
+    (void)attr_names;
+    (void)attr_values;
+    (void)attr_count;
+    struct sax_log *log = ctx;
+    log->depth++;
+    sax_append(log, "+", name);
+}
+
+static void sax_end(void *ctx, const char *name) {
+    struct sax_log *log = ctx;
+    log->depth--;
+    sax_append(log, "-", name);
+}
+
+static void sax_chars(void *ctx, const char *text, size_t len) {
+    struct sax_log *log = ctx;
+    if (len > 0 && strstr(text, "Introduction") != NULL) {
+        sax_append(log, "#", "Introduction");
+    }
+}
+
+TEST(extract_file_sax_events) {
+    char path[256];
+    temp_path(path, sizeof(path), "sax.html");
+    const char *html = "<html><head><title>Doc</title></head><body>"
+                       "<h1>Introduction</h1><p>First paragraph.</p></body></html>";
+    ASSERT_EQ(0, write_file(path, html, strlen(html)), "write fixture");
+
+    struct CExtractor *extractor = extractous_extractor_new();
+    ASSERT_NOT_NULL(extractor, "extractor");
+    struct sax_log log = {{0}, 0};
+    struct CMetadata *metadata = NULL;
+
+    int result = extractous_extractor_extract_file_sax(
+        extractor, path, sax_start, sax_end, sax_chars, &log, &metadata
+    );
+    ASSERT_EQ(ERR_OK, result, "result");
+    ASSERT_NOT_NULL(metadata, "metadata");
+    ASSERT_EQ(0, log.depth, "balanced elements");
+    ASSERT_TRUE(strstr(log.events, "+h1 #Introduction -h1 +p -p") != NULL, "heading then paragraph");
+    ASSERT_EQ(OUTPUT_TEXT, extractous_extractor_get_output_format(extractor), "handle format unchanged");
+
+    // Callbacks are optional
+    extractous_metadata_free(metadata);
+    result = extractous_extractor_extract_file_sax(extractor, path, NULL, NULL, NULL, NULL, &metadata);
+    ASSERT_EQ(ERR_OK, result, "result without callbacks");
+
+    extractous_metadata_free(metadata);
+    extractous_extractor_free(extractor);
+    remove(path);
+}
+
+TEST(extract_file_mime_allowlist) {
+    // Local file header and data of a stored "a.txt" entry
+    static const uint8_t zip[] = "PK\x03\x04\x14\x00\x00\x00\x00\x00\x00\x00\x21\x00"
+                                 "\xac\x2a\x93\xd8\x02\x00\x00\x00\x02\x00\x00\x00"
+                                 "\x05\x00\x00\x00a.txthi";
+    char path[256];
+    temp_path(path, sizeof(path), "allowlist.zip");
+    ASSERT_EQ(0, write_file(path, zip, sizeof(zip) - 1), "write fixture");
+
+    struct CExtractor *extractor = extractous_extractor_new();
+    ASSERT_NOT_NULL(extractor, "extractor");
+    const char *pdf_only[] = {"application/pdf"};
+    extractous_extractor_set_allowed_mime_types_mut(extractor, pdf_only, 1);
+
+    char *content = NULL;
+    struct CMetadata *metadata = NULL;
+    int result = extractous_extractor_extract_file_to_string(extractor, path, &content, &metadata);
+    ASSERT_EQ(ERR_MIME_NOT_ALLOWED, result, "zip rejected");
+    ASSERT_TRUE(last_debug_contains("application/zip"), "debug names detected type");
+
+    const char *text = "plain text document";
+    result = extractous_extractor_extract_bytes_to_string(
+        extractor, (const uint8_t *)text, strlen(text), &content, &metadata
+    );
+    ASSERT_EQ(ERR_MIME_NOT_ALLOWED, result, "text rejected");
+
+    const char *text_any[] = {"text/*"};
+    extractous_extractor_set_allowed_mime_types_mut(extractor, text_any, 1);
+    result = extractous_extractor_extract_bytes_to_string(
+        extractor, (const uint8_t *)text, strlen(text), &content, &metadata
+    );
+    ASSERT_EQ(ERR_OK, result, "text allowed by wildcard");
+    extractous_string_free(content);
+    extractous_metadata_free(metadata);
+
+    extractous_extractor_free(extractor);
+    remove(path);
+}
+
+// ============================================================================
+// Test: Byte Extraction
+// ============================================================================
+
+TEST(extract_bytes_reports_truncation) {
+    struct CExtractor *extractor = extractous_extractor_new();
+    ASSERT_NOT_NULL(extractor, "extractor");
+    extractous_extractor_set_extract_string_max_length_mut(extractor, 100);
+
+    size_t len = 64 * 1024;
+    uint8_t *data = malloc(len);
+    ASSERT_NOT_NULL(data, "data");
+    for (size_t i = 0; i < len; i++) {
+        data[i] = (i % 64 == 63) ? '\n' : 'a' + (i % 26);
+    }
+
+    char *content = NULL;
+    struct CMetadata *metadata = NULL;
+    int truncated = -1;
+    int result = extractous_extractor_extract_bytes_to_string_ex(
+        extractor, data, len, &content, &metadata, &truncated
+    );
+    free(data);
+    ASSERT_EQ(ERR_OK, result, "large document result");
+    ASSERT_EQ(1, truncated, "large document truncated flag");
+    ASSERT_EQ(100, (int)strlen(content), "large document cut to the limit");
+    extractous_string_free(content);
+    extractous_metadata_free(metadata);
+
+    const char *short_text = "short document";
+    truncated = -1;
+    result = extractous_extractor_extract_bytes_to_string_ex(
+        extractor, (const uint8_t *)short_text, strlen(short_text), &content, &metadata, &truncated
+    );
+    ASSERT_EQ(ERR_OK, result, "short document result");
+    ASSERT_EQ(0, truncated, "short document truncated flag");
+    int full_len = (int)strlen(content);
+    extractous_string_free(content);
+    extractous_metadata_free(metadata);
+
+    // Content exactly as long as the limit is complete; one shorter is cut
+    extractous_extractor_set_extract_string_max_length_mut(extractor, full_len);
+    result = extractous_extractor_extract_bytes_to_string_ex(
+        extractor, (const uint8_t *)short_text, strlen(short_text), &content, &metadata, &truncated
+    );
+    ASSERT_EQ(ERR_OK, result, "exact length result");
+    ASSERT_EQ(0, truncated, "exact length not truncated");
+    ASSERT_EQ(full_len, (int)strlen(content), "exact length content kept");
+    extractous_string_free(content);
+    extractous_metadata_free(metadata);
+
+    extractous_extractor_set_extract_string_max_length_mut(extractor, full_len - 1);
+    result = extractous_extractor_extract_bytes_to_string_ex(
+        extractor, (const uint8_t *)short_text, strlen(short_text), &content, &metadata, &truncated
+    );
+    ASSERT_EQ(ERR_OK, result, "one short result");
+    ASSERT_EQ(1, truncated, "one short truncated");
+    ASSERT_EQ(full_len - 1, (int)strlen(content), "one short content cut");
+    extractous_string_free(content);
+    extractous_metadata_free(metadata);
+
+    result = extractous_extractor_extract_bytes_to_string_ex(
+        extractor, (const uint8_t *)short_text, strlen(short_text), &content, &metadata, NULL
+    );
+    ASSERT_EQ(ERR_NULL_POINTER, result, "null truncated out-param");
+
+    extractous_extractor_free(extractor);
+}
+
+TEST(extract_bytes_detect_mime) {
+    const char *html = "<html><head><title>Detect</title></head><body><p>Hello</p></body></html>";
+    struct CExtractor *extractor = extractous_extractor_new();
+    ASSERT_NOT_NULL(extractor, "extractor");
+    char *content = NULL;
+    struct CMetadata *metadata = NULL;
+    char *mime = NULL;
+
+    int result = extractous_extractor_extract_bytes_to_string_detect(
+        extractor, (const uint8_t *)html, strlen(html), &content, &metadata, &mime
+    );
+    ASSERT_EQ(ERR_OK, result, "result");
+    ASSERT_NOT_NULL(mime, "mime");
+    const char *content_type = metadata_get(metadata, "Content-Type");
+    ASSERT_NOT_NULL(content_type, "Content-Type present");
+    ASSERT_TRUE(strcmp(mime, content_type) == 0, "mime matches Content-Type");
+    ASSERT_TRUE(strncmp(mime, "text/html", 9) == 0, "html detected");
+    extractous_string_free(mime);
+    extractous_extraction_result_free(content, metadata);
+
+    // A NULL out_mime is skipped.
+    result = extractous_extractor_extract_bytes_to_string_detect(
+        extractor, (const uint8_t *)html, strlen(html), &content, &metadata, NULL
+    );
+    ASSERT_EQ(ERR_OK, result, "result without mime");
+    extractous_extraction_result_free(content, metadata);
+
+    extractous_extractor_free(extractor);
+}
+
+TEST(extract_bytes_nul_byte_policy) {
+    static const char html[] = "<html><body><p>before\0after</p></body></html>";
+    const size_t html_len = sizeof(html) - 1;
+    struct CExtractor *extractor = extractous_extractor_new();
+    ASSERT_NOT_NULL(extractor, "extractor");
+    char *content = NULL;
+    struct CMetadata *metadata = NULL;
+
+    int result = extractous_extractor_extract_bytes_to_string(
+        extractor, (const uint8_t *)html, html_len, &content, &metadata
+    );
+    ASSERT_EQ(ERR_OK, result, "error policy result");
+    ASSERT_TRUE(content == NULL, "error policy returns no content");
+    extractous_extraction_result_free(content, metadata);
+
+    ASSERT_EQ(ERR_OK, extractous_set_nul_byte_policy(NUL_POLICY_REPLACE), "set replace");
+    result = extractous_extractor_extract_bytes_to_string(
+        extractor, (const uint8_t *)html, html_len, &content, &metadata
+    );
+    ASSERT_EQ(ERR_OK, result, "replace policy result");
+    ASSERT_NOT_NULL(content, "replace policy content");
+    ASSERT_TRUE(strstr(content, "before\xEF\xBF\xBD" "after") != NULL, "nul replaced");
+    extractous_extraction_result_free(content, metadata);
+
+    ASSERT_EQ(ERR_OK, extractous_set_nul_byte_policy(NUL_POLICY_TRUNCATE), "set truncate");
+    result = extractous_extractor_extract_bytes_to_string(
+        extractor, (const uint8_t *)html, html_len, &content, &metadata
+    );
+    ASSERT_EQ(ERR_OK, result, "truncate policy result");
+    ASSERT_NOT_NULL(content, "truncate policy content");
+    ASSERT_TRUE(strstr(content, "before") != NULL, "text before nul kept");
+    ASSERT_TRUE(strstr(content, "after") == NULL, "text after nul cut");
+    extractous_extraction_result_free(content, metadata);
+
+    ASSERT_EQ(ERR_INVALID_ENUM, extractous_set_nul_byte_policy(42), "unknown policy");
+    ASSERT_EQ(ERR_OK, extractous_set_nul_byte_policy(NUL_POLICY_ERROR), "restore default");
+    extractous_extractor_free(extractor);
+}
+
+TEST(stream_peek) {
+    const char *text = "Peekable stream content.\n";
+    struct CExtractor *extractor = extractous_extractor_new();
+    ASSERT_NOT_NULL(extractor, "extractor");
+    struct CStreamReader *reader = NULL;
+    struct CMetadata *metadata = NULL;
+
+    int result = extractous_extractor_extract_bytes(
+        extractor, (const uint8_t *)text, strlen(text), &reader, &metadata
+    );
+    ASSERT_EQ(ERR_OK, result, "result");
+    ASSERT_NOT_NULL(reader, "reader");
+
+    uint8_t peeked[4];
+    size_t peeked_len = 0;
+    ASSERT_EQ(ERR_OK, extractous_stream_peek(reader, peeked, sizeof(peeked), &peeked_len), "peek");
+    ASSERT_EQ(4, (int)peeked_len, "peeked length");
+    // A second peek sees the same bytes.
+    uint8_t again[4];
+    ASSERT_EQ(ERR_OK, extractous_stream_peek(reader, again, sizeof(again), &peeked_len), "peek again");
+    ASSERT_EQ(0, memcmp(peeked, again, sizeof(peeked)), "peek is repeatable");
+
+    char buffer[256];
+    size_t bytes_read = 0;
+    result = extractous_stream_read_exact(reader, (uint8_t *)buffer, sizeof(buffer) - 1, &bytes_read);
+    ASSERT_EQ(ERR_OK, result, "read");
+    ASSERT_TRUE(bytes_read >= sizeof(peeked), "read covers peeked bytes");
+    ASSERT_EQ(0, memcmp(buffer, peeked, sizeof(peeked)), "peeked bytes read first");
+    buffer[bytes_read] = '\0';
+    ASSERT_TRUE(strstr(buffer, "Peekable stream content.") != NULL, "full content read");
+
+    extractous_stream_free(reader);
+    extractous_metadata_free(metadata);
+    extractous_extractor_free(extractor);
+}
+
+TEST(stream_cursor) {
+    char text[4096];
+    size_t used = 0;
+    for (int i = 0; i < 100; i++) {
+        used += snprintf(text + used, sizeof(text) - used, "Line %d of the resumable stream.\n", i);
+    }
+    struct CExtractor *extractor = extractous_extractor_new();
+    ASSERT_NOT_NULL(extractor, "extractor");
+    struct CStreamReader *reader = NULL;
+    struct CStreamReader *other = NULL;
+    struct CMetadata *metadata = NULL;
+    struct CMetadata *other_metadata = NULL;
+
+    int result = extractous_extractor_extract_bytes(
+        extractor, (const uint8_t *)text, used, &reader, &metadata
+    );
+    ASSERT_EQ(ERR_OK, result, "result");
+
+    static uint8_t first[8192];
+    static uint8_t second[8192];
+    static uint8_t replay[8192];
+    size_t first_len = 0;
+    size_t second_len = 0;
+    size_t replay_len = 0;
+    ASSERT_EQ(ERR_OK, extractous_stream_read_exact(reader, first, used / 2, &first_len), "read half");
+    ASSERT_EQ((int)(used / 2), (int)first_len, "first half read");
+
+    struct CStreamCursor *cursor = extractous_stream_save_cursor(reader);
+    ASSERT_NOT_NULL(cursor, "cursor");
+    ASSERT_EQ(ERR_OK, extractous_stream_read_exact(reader, second, sizeof(second), &second_len),
+              "read rest");
+    ASSERT_TRUE(second_len > 0, "rest read");
+
+    ASSERT_EQ(ERR_OK, extractous_stream_restore_cursor(reader, cursor), "restore");
+    ASSERT_EQ(ERR_OK, extractous_stream_read_exact(reader, replay, sizeof(replay), &replay_len),
+              "read again");
+    ASSERT_EQ((int)second_len, (int)replay_len, "same length");
+    ASSERT_EQ(0, memcmp(second, replay, second_len), "same bytes");
+
+    // Restoring again works, and peeking mixes with it.
+    ASSERT_EQ(ERR_OK, extractous_stream_restore_cursor(reader, cursor), "restore again");
+    uint8_t peeked[16];
+    size_t peeked_len = 0;
+    ASSERT_EQ(ERR_OK, extractous_stream_peek(reader, peeked, sizeof(peeked), &peeked_len), "peek");
+    ASSERT_EQ(0, memcmp(peeked, second, peeked_len), "peek after restore");
+
+    result = extractous_extractor_extract_bytes(
+        extractor, (const uint8_t *)text, used, &other, &other_metadata
+    );
+    ASSERT_EQ(ERR_OK, result, "other result");
+    ASSERT_EQ(ERR_NOT_SEEKABLE, extractous_stream_restore_cursor(other, cursor), "foreign cursor");
+    ASSERT_EQ(ERR_NULL_POINTER, extractous_stream_restore_cursor(reader, NULL), "null cursor");
+    ASSERT_NULL(extractous_stream_save_cursor(NULL), "null stream");
+
+    extractous_stream_cursor_free(cursor);
+    extractous_stream_free(other);
+    extractous_stream_free(reader);
+    extractous_metadata_free(other_metadata);
+    extractous_metadata_free(metadata);
+    extractous_extractor_free(extractor);
+}
+
+TEST(extract_bytes_zero_copy) {
+    const char *text = "Borrowed buffer document.\n";
+    struct CExtractor *extractor = extractous_extractor_new();
+    ASSERT_NOT_NULL(extractor, "extractor");
+    char *copied = NULL;
+    char *borrowed = NULL;
+    struct CMetadata *copied_metadata = NULL;
+    struct CMetadata *borrowed_metadata = NULL;
+
+    int result = extractous_extractor_extract_bytes_to_string(
+        extractor, (const uint8_t *)text, strlen(text), &copied, &copied_metadata
+    );
+    ASSERT_EQ(ERR_OK, result, "bytes result");
+    result = extractous_extractor_extract_bytes_zero_copy(
+        extractor, (const uint8_t *)text, strlen(text), &borrowed, &borrowed_metadata
+    );
+    ASSERT_EQ(ERR_OK, result, "zero copy result");
+    ASSERT_TRUE(strcmp(copied, borrowed) == 0, "same content");
+    ASSERT_TRUE(strstr(borrowed, "Borrowed buffer document.") != NULL, "content");
+    extractous_extraction_result_free(copied, copied_metadata);
+    extractous_extraction_result_free(borrowed, borrowed_metadata);
+
+    result = extractous_extractor_extract_bytes_zero_copy(extractor, NULL, 0, &borrowed, &borrowed_metadata);
+    ASSERT_EQ(ERR_NULL_POINTER, result, "null data");
+    extractous_extractor_free(extractor);
+}
+
+TEST(extract_bytes_sanitize_output) {
+    const char *text = "page one\x0cpage two\x0bline\tcell\r\n";
+    struct CExtractor *extractor = extractous_extractor_new();
+    ASSERT_NOT_NULL(extractor, "extractor");
+    char *content = NULL;
+    struct CMetadata *metadata = NULL;
+
+    int result = extractous_extractor_extract_bytes_to_string(
+        extractor, (const uint8_t *)text, strlen(text), &content, &metadata
+    );
+    ASSERT_EQ(ERR_OK, result, "unsanitized result");
+    ASSERT_TRUE(strchr(content, '\x0c') != NULL, "form feed kept by default");
+    ASSERT_TRUE(strchr(content, '\x0b') != NULL, "vertical tab kept by default");
+    extractous_string_free(content);
+    extractous_metadata_free(metadata);
+
+    extractous_extractor_set_sanitize_output_mut(extractor, true);
+    result = extractous_extractor_extract_bytes_to_string(
+        extractor, (const uint8_t *)text, strlen(text), &content, &metadata
+    );
+    ASSERT_EQ(ERR_OK, result, "sanitized result");
+    ASSERT_TRUE(strchr(content, '\x0c') == NULL, "form feed stripped");
+    ASSERT_TRUE(strchr(content, '\x0b') == NULL, "vertical tab stripped");
+    ASSERT_TRUE(strchr(content, '\t') != NULL, "tab kept");
+    ASSERT_TRUE(strstr(content, "page onepage two") != NULL, "surrounding text intact");
+    extractous_string_free(content);
+    extractous_metadata_free(metadata);
+
+    extractous_extractor_free(extractor);
+}
+
+TEST(extract_bytes_unicode_normalization) {
+    // "Cafe" and "resume" with combining acute accents (decomposed form)
+    const char *text = "Cafe\xcc\x81 re\xcc\x81sume\xcc\x81\n";
+    struct CExtractor *extractor = extractous_extractor_new();
+    ASSERT_NOT_NULL(extractor, "extractor");
+    char *content = NULL;
+    struct CMetadata *metadata = NULL;
+
+    int result = extractous_extractor_extract_bytes_to_string(
+        extractor, (const uint8_t *)text, strlen(text), &content, &metadata
+    );
+    ASSERT_EQ(ERR_OK, result, "default result");
+    ASSERT_TRUE(strstr(content, "Cafe\xcc\x81") != NULL, "decomposed by default");
+    extractous_string_free(content);
+    extractous_metadata_free(metadata);
+
+    extractous_extractor_set_unicode_normalization_mut(extractor, NORM_NFC);
+    result = extractous_extractor_extract_bytes_to_string(
+        extractor, (const uint8_t *)text, strlen(text), &content, &metadata
+    );
+    ASSERT_EQ(ERR_OK, result, "NFC result");
+    ASSERT_TRUE(strstr(content, "Caf\xc3\xa9 r\xc3\xa9sum\xc3\xa9") != NULL, "composed under NFC");
+    ASSERT_TRUE(strstr(content, "\xcc\x81") == NULL, "no combining marks left");
+    extractous_string_free(content);
+    extractous_metadata_free(metadata);
+
+    extractous_extractor_free(extractor);
+}
+
+TEST(extract_iovec_matches_contiguous) {
+    const char *text = "First segment of the document. Second segment continues here. "
+                       "Third segment ends it.\n";
+    size_t len = strlen(text);
+    struct CExtractor *extractor = extractous_extractor_new();
+    ASSERT_NOT_NULL(extractor, "extractor");
+
+    char *expected = NULL;
+    struct CMetadata *metadata = NULL;
+    int result = extractous_extractor_extract_bytes_to_string(
+        extractor, (const uint8_t *)text, len, &expected, &metadata
+    );
+    ASSERT_EQ(ERR_OK, result, "contiguous result");
+    extractous_metadata_free(metadata);
+
+    struct CIoSlice iov[3] = {
+        {(const uint8_t *)text, 20},
+        {(const uint8_t *)text + 20, 30},
+        {(const uint8_t *)text + 50, len - 50},
+    };
+    char *content = NULL;
+    result = extractous_extractor_extract_iovec_to_string(extractor, iov, 3, &content, &metadata);
+    ASSERT_EQ(ERR_OK, result, "iovec result");
+    ASSERT_TRUE(strcmp(expected, content) == 0, "iovec content matches contiguous");
+    ASSERT_NULL(metadata_get(metadata, "resourceName"), "no name taken from the temp file");
+    char expected_len[32];
+    snprintf(expected_len, sizeof(expected_len), "%zu", len);
+    const char *content_len = metadata_get(metadata, "Content-Length");
+    ASSERT_TRUE(content_len != NULL && strcmp(content_len, expected_len) == 0, "iovec length");
+    extractous_string_free(content);
+    extractous_metadata_free(metadata);
+
+    struct CIoSlice bad[2] = {{(const uint8_t *)text, 10}, {NULL, 5}};
+    result = extractous_extractor_extract_iovec_to_string(extractor, bad, 2, &content, &metadata);
+    ASSERT_EQ(ERR_NULL_POINTER, result, "null slice with length");
+
+    extractous_string_free(expected);
+    extractous_extractor_free(extractor);
+}
+
+// ============================================================================
+// Test Runner
+// ============================================================================
+
+void run_all_tests() {
+    printf("\n");
+    printf("========================================\n");
+    printf("  FFI Layer Tests for Extractous\n");
+    printf("========================================\n\n");
+    
+    // Must run before any extraction, after which the temp dir is fixed
+    run_test_set_temp_dir();
+
+    // Lifecycle tests
+    printf(COLOR_YELLOW "--- Extractor Lifecycle ---\n" COLOR_RESET);
+    run_test_extractor_new();
+    run_test_extractor_free_null();
+    run_test_extractor_double_free();
+    run_test_tika_version();
+    
+    // Configuration tests
+    printf(COLOR_YELLOW "\n--- Configuration Functions ---\n" COLOR_RESET);
+    run_test_extractor_set_max_length();
+    run_test_extractor_set_encoding();
+    run_test_extractor_set_invalid_encoding();
+    run_test_extractor_set_xml_output();
+    run_test_extractor_chained_configuration();
+    run_test_extractor_output_format_roundtrip();
+    
+    // PDF config tests
+    printf(COLOR_YELLOW "\n--- PDF Configuration ---\n" COLOR_RESET);
+    run_test_pdf_config_new();
+    run_test_pdf_config_set_ocr_strategy();
+    run_test_pdf_config_set_extract_inline_images();
+    run_test_extractor_set_pdf_config();
+    
+    // Office config tests
+    printf(COLOR_YELLOW "\n--- Office Configuration ---\n" COLOR_RESET);
+    run_test_office_config_new();
+    run_test_office_config_set_extract_macros();
+    run_test_office_config_exclude_headers_and_footers();
+    run_test_extract_xlsx_sheet_names();
+    
+    // OCR config tests
+    printf(COLOR_YELLOW "\n--- OCR Configuration ---\n" COLOR_RESET);
+    run_test_ocr_config_new();
+    run_test_ocr_config_set_language();
+    run_test_ocr_config_set_apply_rotation();
+    
+    // Error handling tests
+    printf(COLOR_YELLOW "\n--- Error Handling ---\n" COLOR_RESET);
+    run_test_error_message();
+    run_test_extract_with_null_extractor();
+    run_test_extract_with_null_path();
+    run_test_extract_with_null_output();
+    run_test_error_message_static();
+    run_test_error_get_last_category();
+    
+    // Memory management tests
+    printf(COLOR_YELLOW "\n--- Memory Management ---\n" COLOR_RESET);
+    run_test_string_free_null();
+    run_test_metadata_free_null();
+    run_test_metadata_value_separator();
+    run_test_metadata_get_all_values();
+    run_test_metadata_clone();
+    run_test_metadata_get_date_epoch();
+    run_test_metadata_get_page_count();
+    run_test_metadata_get_parsed_by();
+    
+    // URL extraction tests
+    printf(COLOR_YELLOW "\n--- URL Extraction ---\n" COLOR_RESET);
+    run_test_url_extraction_null_checks();
+    run_test_url_extraction_failure_reasons();
+
+    // File extraction tests
+    printf(COLOR_YELLOW "\n--- File Extraction ---\n" COLOR_RESET);
+    run_test_extract_file_to_string_hashed();
+    run_test_extract_file_to_string_timed();
+    run_test_extract_file_with_past_deadline();
+    run_test_extract_file_cancellable();
+    run_test_extract_file_page_range();
+    run_test_list_embedded_files();
+    run_test_extract_file_to_string_bounded();
+    run_test_extract_file_empty_reason();
+    run_test_extract_file_max_output_bytes();
+    run_test_extraction_result_free();
+    run_test_extract_file_ocr_info();
+    run_test_extract_file_as_format();
+    run_test_extract_file_root_dir();
+    run_test_extract_file_xml_body_only();
+    run_test_extract_file_segmented();
+    run_test_extract_file_tables();
+    run_test_extract_file_ocr_lang();
+    run_test_extract_file_ocr_dpi();
+    run_test_detect_mime_type_confidence();
+    run_test_extract_file_to_string_stats();
+    run_test_extract_file_fallback_mime();
+    run_test_extract_file_metadata_only();
+    run_test_extract_file_corrupt_reports_java_exception();
+    run_test_extract_first_image();
+    run_test_extract_file_html_with_images();
+    run_test_extract_file_collapse_whitespace();
+    run_test_extract_file_to_string_lang();
+    run_test_extract_file_window();
+    run_test_extract_file_max_embedded_bytes();
+    run_test_extract_file_max_embedded_bytes_understated();
+    run_test_compute_doc_id();
+    run_test_extract_empty_input();
+    run_test_extract_resource_name();
+    run_test_extract_jni_retry();
+    run_test_extract_embedded_at();
+    run_test_is_encrypted_file();
+    run_test_extract_max_metadata_entries();
+    run_test_extract_file_truncation_reason();
+    run_test_extract_pdf_info();
+    run_test_error_category_table();
+    run_test_extract_file_positioned();
+    run_test_extract_line_ending();
+    run_test_extract_file_to_utf16();
+    run_test_extract_offline();
+    run_test_extract_skip_embedded_mime_types();
+    run_test_extract_file_unreadable();
+    run_test_extract_file_xml_stream();
+    run_test_extract_dedupe_embedded();
+    run_test_extract_file_to_string_analysis();
+    run_test_extract_file_negotiated();
+    run_test_extract_file_structured_parts();
+    run_test_extract_file_dual();
+    run_test_extract_char_filter();
+    run_test_classify_file();
+    run_test_extract_file_meta_cb();
+    run_test_extract_strict_utf8();
+    run_test_extract_file_per_page();
+    run_test_extract_file_segmented_language();
+    run_test_metadata_filter_by_prefix();
+    run_test_mime_supertypes();
+    run_test_max_concurrent_extractions();
+    run_test_abandoned_worker_keeps_slot();
+    run_test_extractor_config_to_json();
+    run_test_extract_file_realloc();
+    run_test_extract_file_to_string_repaired();
+    run_test_input_transform();
+    run_test_detect_mime_by_filename();
+    run_test_bidi_mode();
+    run_test_bidi_mode_conversions();
+    run_test_extract_file_outline();
+    run_test_extract_file_into_buffer();
+    run_test_extract_file_to_json();
+    run_test_metadata_has_content_length();
+    run_test_metadata_deterministic_order();
+    run_test_extract_file_sax_events();
+    run_test_extract_file_mime_allowlist();
+
+    // Byte extraction tests
+    printf(COLOR_YELLOW "\n--- Byte Extraction ---\n" COLOR_RESET);
+    run_test_extract_bytes_reports_truncation();
+    run_test_extract_bytes_detect_mime();
+    run_test_extract_bytes_nul_byte_policy();
+    run_test_stream_peek();
+    run_test_stream_cursor();
+    run_test_extract_bytes_zero_copy();
+    run_test_extract_bytes_sanitize_output();
+    run_test_extract_bytes_unicode_normalization();
+    run_test_extract_iovec_matches_contiguous();
     
     // Summary
     printf("\n");